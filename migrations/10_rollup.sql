@@ -0,0 +1,54 @@
+insert into property_type (name) values ('rollup'); -- 11
+
+create table rollup_aggregate(
+    id serial primary key,
+    name varchar(255) not null
+);
+
+insert into rollup_aggregate (name) values
+    ('Count'), ---------- 1
+    ('Sum'), ------------ 2
+    ('Average'), -------- 3
+    ('Min'), ------------ 4
+    ('Max'), ------------ 5
+    ('Percent True') ---- 6
+;
+
+-- A rollup aggregates `target_prop_id` over the pages linked through
+-- `relation_prop_id`. The relation can either belong to the rollup's own
+-- collection, or it can belong to another collection and point at this one,
+-- in which case the rollup aggregates over backlinks. Count does not need a
+-- target prop. Like formulas, rollup values are computed by the page list
+-- query and never stored.
+create table property_rollup(
+    prop_id int primary key references property(id) on delete cascade,
+    relation_prop_id int not null references property(id) on delete cascade,
+    target_prop_id int references property(id) on delete cascade,
+    aggregate_id int not null references rollup_aggregate(id)
+);
+
+insert into property (name, type_id, collection_id, "order")
+select e.name, 11, c.id, e.ord
+from collection c, (values
+    ('Tickets', 1),
+    ('% Completed', 2)
+) as e(name, ord)
+where c.name = 'Epics';
+
+insert into property_rollup
+    (prop_id, relation_prop_id, target_prop_id, aggregate_id)
+select p.id, rel.id, target.id, e.aggregate_id
+from
+    property p,
+    property rel,
+    (values
+        ('Tickets', null, 1),
+        ('% Completed', 'Completed', 6)
+    ) as e(name, target_name, aggregate_id)
+    left join property target
+        on target.name = e.target_name and target.collection_id = 1
+where
+    p.name = e.name
+    and p.type_id = 11
+    and rel.name = 'Epic'
+    and rel.collection_id = 1;
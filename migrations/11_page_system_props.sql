@@ -0,0 +1,30 @@
+-- Pages existing before this migration get the migration time, and no
+-- author.
+alter table page add column created_at timestamptz not null default now();
+alter table page add column updated_at timestamptz not null default now();
+alter table page add column created_by int references users(id) on delete set null;
+alter table page add column updated_by int references users(id) on delete set null;
+
+-- System props read their values from the columns above, rather than from
+-- a propval table.
+insert into property_type (name) values
+    ('created time'), ------ 12
+    ('last edited time'), -- 13
+    ('created by'), -------- 14
+    ('last edited by') ----- 15
+;
+
+-- Filters on the "created by" and "last edited by" props.
+create table filter_user(
+    id serial primary key,
+    type_id int not null references filter_type(id),
+    prop_id int unique not null references property(id) on delete cascade,
+    value int not null references users(id) on delete cascade
+);
+
+insert into property (name, type_id, collection_id, "order") values
+    ('Created', 12, 1, 12),
+    ('Last Edited', 13, 1, 13),
+    ('Created By', 14, 1, 14),
+    ('Last Edited By', 15, 1, 15)
+;
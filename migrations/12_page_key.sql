@@ -0,0 +1,37 @@
+-- Pages get human-readable keys like `NC-113`, made of their collection's
+-- prefix and a number which counts up separately for each collection.
+alter table collection add column key_prefix varchar(10);
+alter table collection add column next_page_number int not null default 1;
+alter table page add column number int;
+
+update collection set key_prefix = 'NC' where name = 'Default Collection';
+update collection set key_prefix = 'EPIC' where name = 'Epics';
+-- Prefixes are letters only, so that keys can be written without the dash,
+-- like `nc113`. Any other collections get a unique prefix spelled out from
+-- their ID, which can be changed later.
+update collection
+set key_prefix = 'C' || translate(id::text, '0123456789', 'ABCDEFGHIJ')
+where key_prefix is null;
+
+alter table collection alter column key_prefix set not null;
+alter table collection add constraint collection_key_prefix_unique
+    unique (key_prefix);
+
+-- Number the existing pages in the order they were created.
+update page set number = numbered.number
+from (
+    select id, row_number() over (
+        partition by collection_id order by id
+    ) as number
+    from page
+) as numbered
+where page.id = numbered.id;
+
+update collection set next_page_number = coalesce(
+    (select max(number) + 1 from page where page.collection_id = collection.id),
+    1
+);
+
+alter table page alter column number set not null;
+alter table page add constraint page_collection_number_unique
+    unique (collection_id, number);
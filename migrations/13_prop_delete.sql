@@ -0,0 +1,11 @@
+-- Props can now be deleted from the UI, so everything which refers to a prop
+-- needs to go along with it. This one was missed in the initial schema.
+alter table propval_datetime drop constraint propval_datetime_prop_id_fkey;
+alter table propval_datetime add constraint propval_datetime_prop_id_fkey
+    foreign key (prop_id) references property(id) on delete cascade;
+
+-- The sort is also reset in `Prop::delete`, but the sort type is meaningless
+-- without the prop, so this is just a backstop.
+alter table collection drop constraint collection_sort_by_prop_id_fkey;
+alter table collection add constraint collection_sort_by_prop_id_fkey
+    foreign key (sort_by_prop_id) references property(id) on delete set null;
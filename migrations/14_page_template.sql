@@ -0,0 +1,34 @@
+-- Templates pre-fill new pages with starter content and default propvals.
+create table page_template(
+    id serial primary key,
+    name varchar(255) not null,
+    content text not null default '',
+
+    collection_id int not null references collection(id) on delete cascade,
+    unique (collection_id, name)
+);
+
+-- Default propvals are stored as text, and parsed into the prop's type when
+-- a page is created from the template, using the same rules as changing the
+-- type of a prop. That way, templates survive their props changing type.
+create table page_template_propval(
+    value varchar(511) not null,
+
+    template_id int not null references page_template(id) on delete cascade,
+    prop_id int not null references property(id) on delete cascade,
+    primary key (template_id, prop_id)
+);
+
+insert into page_template (name, content, collection_id) values (
+    'Bug Report',
+    E'## Steps to Reproduce\n\n## Expected Behavior\n\n## Actual Behavior\n',
+    1
+);
+insert into page_template_propval (value, template_id, prop_id)
+select 'bug', t.id, p.id
+from page_template t, property p
+where t.name = 'Bug Report' and p.name = 'Tags' and p.collection_id = 1;
+insert into page_template_propval (value, template_id, prop_id)
+select 'false', t.id, p.id
+from page_template t, property p
+where t.name = 'Bug Report' and p.name = 'Completed' and p.collection_id = 1;
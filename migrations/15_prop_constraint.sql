@@ -0,0 +1,23 @@
+-- Rules which a prop's values must follow, checked whenever a propval is
+-- saved. Each column only applies to some types of props, and is ignored for
+-- the others; that way, constraints survive their prop changing type.
+create table property_constraint(
+    required boolean not null default false,
+    -- Bounds for int and float props.
+    min float8,
+    max float8,
+    -- Bounds for date and datetime props.
+    min_date date,
+    max_date date,
+    -- Rules for text props.
+    pattern varchar(255),
+    min_length int,
+    max_length int,
+    -- No two pages in the collection can share a value.
+    is_unique boolean not null default false,
+
+    prop_id int primary key references property(id) on delete cascade
+);
+
+insert into property_constraint (min, prop_id)
+select 0, id from property where name = 'Sprint Number' and collection_id = 1;
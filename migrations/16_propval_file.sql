@@ -0,0 +1,23 @@
+insert into property_type (name) values ('files'); -- 16
+
+-- One row per upload. The contents are kept outside of the database, by
+-- `file::storage::Storage`, under `stored_name`; the SHA-256 of the
+-- contents. Identical uploads share one stored copy, which is only deleted
+-- along with the last row referring to it.
+create table propval_file(
+    id serial primary key,
+    name varchar(255) not null,
+    stored_name varchar(64) not null,
+    mime_type varchar(255) not null,
+    size bigint not null,
+    created_at timestamptz not null default now(),
+
+    page_id int not null references page(id) on delete cascade,
+    prop_id int not null references property(id) on delete cascade
+);
+create index on propval_file (page_id, prop_id);
+create index on propval_file (stored_name);
+
+insert into property (name, type_id, collection_id, "order") values
+    ('Attachments', 16, 1, 16)
+;
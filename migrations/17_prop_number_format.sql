@@ -0,0 +1,13 @@
+-- How int and float props are displayed; see `number_format::models`.
+-- Values are always stored as plain numbers, so changing the format never
+-- touches them.
+alter table property
+    -- One of 'plain', 'percent', 'currency', 'fixed', or 'progress'.
+    add column number_format varchar(15) not null default 'plain',
+    -- Digits after the decimal point, for percent, currency, and fixed.
+    add column number_decimals smallint not null default 0,
+    -- An ISO 4217 code like 'USD', for currency.
+    add column currency_code varchar(3);
+
+update property set number_format = 'percent'
+where name = 'Percentage' and collection_id = 1;
@@ -0,0 +1,39 @@
+-- Filters are arranged in a tree of groups. Pages must match all of the
+-- filters and subgroups in an `and` group, or any of them in an `or` group.
+--
+-- Each collection has one root group, which is created the first time it is
+-- needed. Filters and groups without a parent belong to the root.
+create table filter_group(
+    id serial primary key,
+    -- 'and' or 'or'
+    conjunction varchar(3) not null default 'and',
+    collection_id int not null references collection(id) on delete cascade,
+    parent_id int references filter_group(id) on delete cascade
+);
+create unique index on filter_group (collection_id) where parent_id is null;
+create index on filter_group (parent_id);
+
+alter table filter_bool
+    add column group_id int references filter_group(id) on delete cascade;
+alter table filter_int
+    add column group_id int references filter_group(id) on delete cascade;
+alter table filter_int_range
+    add column group_id int references filter_group(id) on delete cascade;
+alter table filter_float
+    add column group_id int references filter_group(id) on delete cascade;
+alter table filter_float_range
+    add column group_id int references filter_group(id) on delete cascade;
+alter table filter_date
+    add column group_id int references filter_group(id) on delete cascade;
+alter table filter_date_range
+    add column group_id int references filter_group(id) on delete cascade;
+alter table filter_datetime
+    add column group_id int references filter_group(id) on delete cascade;
+alter table filter_datetime_range
+    add column group_id int references filter_group(id) on delete cascade;
+alter table filter_select
+    add column group_id int references filter_group(id) on delete cascade;
+alter table filter_relation
+    add column group_id int references filter_group(id) on delete cascade;
+alter table filter_user
+    add column group_id int references filter_group(id) on delete cascade;
@@ -0,0 +1,21 @@
+insert into filter_type (name) values
+    ('Does not Contain'), -- 9
+    ('Starts With'), ------- 10
+    ('Ends With'), --------- 11
+    ('Matches Regex') ------ 12
+;
+
+-- Filters on text props, and on page titles.
+create table filter_str(
+    id serial primary key,
+    type_id int not null references filter_type(id),
+    -- null for filters on the page title, which is not a prop.
+    prop_id int references property(id) on delete cascade,
+    collection_id int not null references collection(id) on delete cascade,
+    group_id int references filter_group(id) on delete cascade,
+    value varchar(511) not null,
+    case_sensitive boolean not null default false
+);
+-- Like other filters, there is only one per prop, and one on the title.
+create unique index on filter_str (prop_id);
+create unique index on filter_str (collection_id) where prop_id is null;
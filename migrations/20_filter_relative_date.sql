@@ -0,0 +1,23 @@
+insert into filter_type (name) values ('Is Within'); -- 13
+
+-- Filters like "this week" or "in the last 7 days" on date and datetime
+-- props. The period is stored by name and resolved when pages are listed,
+-- so that saved filters move along with the current date.
+create table filter_relative_date(
+    id serial primary key,
+    type_id int not null references filter_type(id),
+    prop_id int not null references property(id) on delete cascade,
+    group_id int references filter_group(id) on delete cascade,
+    period varchar(15) not null,
+    -- The `n` of "last n days"; 0 for periods which don't have one.
+    amount int not null default 0,
+    -- Whether the prop stores dates or datetimes, which are compared
+    -- differently.
+    value_type_id int not null references property_type(id)
+);
+-- Like other filters, there is only one per prop.
+create unique index on filter_relative_date (prop_id);
+
+-- The first day of the week for "this week" filters; an ISO weekday, where
+-- Monday is 1 and Sunday is 7.
+alter table users add column week_start smallint not null default 1;
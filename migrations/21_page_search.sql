@@ -0,0 +1,14 @@
+-- Full-text search over page titles and content. Titles and content live in
+-- separate tables, so each gets its own vector; titles are weighted higher
+-- so that they rank first when the two are combined.
+alter table page add column search_vector tsvector
+    generated always as (
+        setweight(to_tsvector('english', title), 'A')
+    ) stored;
+create index on page using gin (search_vector);
+
+alter table page_content add column search_vector tsvector
+    generated always as (
+        setweight(to_tsvector('english', content), 'B')
+    ) stored;
+create index on page_content using gin (search_vector);
@@ -0,0 +1,140 @@
+-- Every filter, whatever the type of the values it compares. This replaces
+-- the `filter_<type>` and `filter_<type>_range` tables, which needed new
+-- tables, and a new branch in every query over them, for each value type.
+--
+-- `value_type_id` says which of the value columns are used. Ranged filters
+-- keep their start in the value column, and their end in the matching
+-- `*_end` column. Selects, relations, and users keep foreign keys, so that
+-- deleting an option, page, or user still deletes the filters on it.
+create table filter(
+    id serial primary key,
+    type_id int not null references filter_type(id),
+    -- null for filters on the page title, which is not a prop.
+    prop_id int references property(id) on delete cascade,
+    collection_id int not null references collection(id) on delete cascade,
+    group_id int references filter_group(id) on delete cascade,
+    value_type_id int not null references property_type(id),
+    bool_value boolean,
+    int_value bigint,
+    int_end bigint,
+    float_value float,
+    float_end float,
+    date_value date,
+    date_end date,
+    datetime_value timestamp with time zone,
+    datetime_end timestamp with time zone,
+    text_value varchar(511),
+    case_sensitive boolean not null default false,
+    option_id int references property_option(id) on delete cascade,
+    page_id int references page(id) on delete cascade,
+    user_id int references users(id) on delete cascade,
+    -- "Is within" filters; a relative date period, like `last_days`, and
+    -- its amount.
+    period varchar(15),
+    amount int
+);
+create index on filter (collection_id);
+create index on filter (prop_id);
+-- Like filters on props, there is only one filter on the title.
+create unique index on filter (collection_id) where prop_id is null;
+
+insert into filter
+    (type_id, prop_id, collection_id, group_id, value_type_id, bool_value)
+select f.type_id, f.prop_id, p.collection_id, f.group_id, 1, f.value
+from filter_bool f join property p on p.id = f.prop_id;
+
+insert into filter
+    (type_id, prop_id, collection_id, group_id, value_type_id, int_value)
+select f.type_id, f.prop_id, p.collection_id, f.group_id, 2, f.value
+from filter_int f join property p on p.id = f.prop_id;
+
+insert into filter (
+    type_id, prop_id, collection_id, group_id, value_type_id,
+    int_value, int_end
+)
+select f.type_id, f.prop_id, p.collection_id, f.group_id, 2, f.start, f."end"
+from filter_int_range f join property p on p.id = f.prop_id;
+
+insert into filter
+    (type_id, prop_id, collection_id, group_id, value_type_id, float_value)
+select f.type_id, f.prop_id, p.collection_id, f.group_id, 3, f.value
+from filter_float f join property p on p.id = f.prop_id;
+
+insert into filter (
+    type_id, prop_id, collection_id, group_id, value_type_id,
+    float_value, float_end
+)
+select f.type_id, f.prop_id, p.collection_id, f.group_id, 3, f.start, f."end"
+from filter_float_range f join property p on p.id = f.prop_id;
+
+insert into filter
+    (type_id, prop_id, collection_id, group_id, value_type_id, date_value)
+select f.type_id, f.prop_id, p.collection_id, f.group_id, 6, f.value
+from filter_date f join property p on p.id = f.prop_id;
+
+insert into filter (
+    type_id, prop_id, collection_id, group_id, value_type_id,
+    date_value, date_end
+)
+select f.type_id, f.prop_id, p.collection_id, f.group_id, 6, f.start, f."end"
+from filter_date_range f join property p on p.id = f.prop_id;
+
+insert into filter
+    (type_id, prop_id, collection_id, group_id, value_type_id, datetime_value)
+select f.type_id, f.prop_id, p.collection_id, f.group_id, 7, f.value
+from filter_datetime f join property p on p.id = f.prop_id;
+
+insert into filter (
+    type_id, prop_id, collection_id, group_id, value_type_id,
+    datetime_value, datetime_end
+)
+select f.type_id, f.prop_id, p.collection_id, f.group_id, 7, f.start, f."end"
+from filter_datetime_range f join property p on p.id = f.prop_id;
+
+insert into filter
+    (type_id, prop_id, collection_id, group_id, value_type_id, option_id)
+select f.type_id, f.prop_id, p.collection_id, f.group_id, 8, f.value
+from filter_select f join property p on p.id = f.prop_id;
+
+insert into filter
+    (type_id, prop_id, collection_id, group_id, value_type_id, page_id)
+select f.type_id, f.prop_id, p.collection_id, f.group_id, 9, f.value
+from filter_relation f join property p on p.id = f.prop_id;
+
+insert into filter
+    (type_id, prop_id, collection_id, group_id, value_type_id, user_id)
+select f.type_id, f.prop_id, p.collection_id, f.group_id, 14, f.value
+from filter_user f join property p on p.id = f.prop_id;
+
+insert into filter (
+    type_id, prop_id, collection_id, group_id, value_type_id,
+    text_value, case_sensitive
+)
+select
+    f.type_id, f.prop_id, f.collection_id, f.group_id, 4,
+    f.value, f.case_sensitive
+from filter_str f;
+
+insert into filter (
+    type_id, prop_id, collection_id, group_id, value_type_id,
+    period, amount
+)
+select
+    f.type_id, f.prop_id, p.collection_id, f.group_id, f.value_type_id,
+    f.period, f.amount
+from filter_relative_date f join property p on p.id = f.prop_id;
+
+drop table filter_bool;
+drop table filter_int;
+drop table filter_int_range;
+drop table filter_float;
+drop table filter_float_range;
+drop table filter_date;
+drop table filter_date_range;
+drop table filter_datetime;
+drop table filter_datetime_range;
+drop table filter_select;
+drop table filter_relation;
+drop table filter_user;
+drop table filter_str;
+drop table filter_relative_date;
@@ -0,0 +1,2 @@
+-- Disabled filters stay in the filter toolbar, but don't filter any pages.
+alter table filter add column enabled boolean not null default true;
@@ -0,0 +1,56 @@
+-- Views are saved ways of looking at the pages of a collection. Each view
+-- has its own filters, sort, columns, and layout, so changing one view
+-- doesn't change the page list for everyone else.
+create table view(
+    id serial primary key,
+    name varchar(255) not null,
+    -- 'table' or 'list'
+    layout varchar(15) not null default 'table',
+    sort_by_prop_id int references property(id) on delete set null,
+    sort_type_id int references sort_type(id),
+
+    collection_id int not null references collection(id) on delete cascade,
+    unique (collection_id, name)
+);
+
+-- The columns of a view, in order. Props without a row here are visible,
+-- after the others, in the collection's prop order; that way, new props
+-- show up in every view.
+create table view_prop(
+    "order" int not null,
+    visible boolean not null default true,
+
+    view_id int not null references view(id) on delete cascade,
+    prop_id int not null references property(id) on delete cascade,
+    primary key (view_id, prop_id)
+);
+
+-- Every collection starts out with one view, which takes over its sort,
+-- filters, and filter groups.
+insert into view (name, collection_id, sort_by_prop_id, sort_type_id)
+select 'All Pages', id, sort_by_prop_id, sort_type_id
+from collection
+order by id;
+
+alter table collection drop column sort_by_prop_id;
+alter table collection drop column sort_type_id;
+
+-- Dropping `collection_id` also drops the indexes on it, including the
+-- unique indexes for the root group and the title filter, which are
+-- recreated per view.
+alter table filter_group
+    add column view_id int references view(id) on delete cascade;
+update filter_group g set view_id = v.id
+from view v where v.collection_id = g.collection_id;
+alter table filter_group alter column view_id set not null;
+alter table filter_group drop column collection_id;
+create unique index on filter_group (view_id) where parent_id is null;
+
+alter table filter
+    add column view_id int references view(id) on delete cascade;
+update filter f set view_id = v.id
+from view v where v.collection_id = f.collection_id;
+alter table filter alter column view_id set not null;
+alter table filter drop column collection_id;
+create index on filter (view_id);
+create unique index on filter (view_id) where prop_id is null;
@@ -0,0 +1,11 @@
+-- Every view has a root filter group from the moment it is created, so
+-- reading a view's filters never has to write. Views which haven't needed
+-- theirs yet get one now.
+insert into filter_group (view_id)
+select v.id
+from view v
+where not exists (
+    select 1 from filter_group g
+    where g.view_id = v.id and g.parent_id is null
+)
+order by v.id;
@@ -0,0 +1,3 @@
+insert into property (name, type_id, collection_id, "order") values
+    ('Owner', 4, 1, 6)
+;
@@ -0,0 +1,18 @@
+insert into property (name, type_id, collection_id, "order") values
+    ('Tags', 5, 1, 7)
+;
+
+-- A page should not carry the same tag twice.
+alter table propval_multistr__value
+    add constraint propval_multistr__value_unique
+    unique (propval_multistr_id, value);
+
+-- Every tag which has ever been used for a given prop. This is the
+-- vocabulary offered for autocomplete, and it deliberately outlives the
+-- propvals which introduced each tag.
+create table propval_multistr__vocab(
+    value varchar(511) not null,
+
+    prop_id int not null references property(id) on delete cascade,
+    primary key (prop_id, value)
+);
@@ -0,0 +1,22 @@
+insert into property (name, type_id, collection_id, "order") values
+    ('Deadline', 7, 1, 8)
+;
+
+-- IANA timezone name (i.e, `America/New_York`) used to display and accept
+-- datetimes for this user.
+alter table users add column timezone varchar(255) not null default 'UTC';
+
+create table filter_datetime(
+    id serial primary key,
+    type_id int not null references filter_type(id),
+    prop_id int not null references property(id) on delete cascade,
+    value timestamp with time zone not null
+);
+
+create table filter_datetime_range(
+    id serial primary key,
+    type_id int not null references filter_type(id),
+    prop_id int not null references property(id) on delete cascade,
+    start timestamp with time zone not null,
+    "end" timestamp with time zone not null
+);
@@ -0,0 +1,40 @@
+insert into property_type (name) values ('select'); -- 8
+
+-- The choices available to a select prop. `color` is one of the names in
+-- the palette at `models::SelectColor`.
+create table property_option(
+    id serial primary key,
+    name varchar(255) not null,
+    color varchar(31) not null,
+    "order" smallint not null,
+
+    prop_id int not null references property(id) on delete cascade,
+    unique (prop_id, name)
+);
+
+create table propval_select(
+    value int not null references property_option(id) on delete cascade,
+
+    page_id int not null references page(id) on delete cascade,
+    prop_id int not null references property(id) on delete cascade,
+    primary key (page_id, prop_id)
+);
+
+create table filter_select(
+    id serial primary key,
+    type_id int not null references filter_type(id),
+    prop_id int not null references property(id) on delete cascade,
+    value int not null references property_option(id) on delete cascade
+);
+
+insert into property (name, type_id, collection_id, "order") values
+    ('Status', 8, 1, 9)
+;
+insert into property_option (name, color, "order", prop_id)
+select o.name, o.color, o."order", p.id
+from property p, (values
+    ('Todo', 'gray', 1),
+    ('In Progress', 'blue', 2),
+    ('Done', 'green', 3)
+) as o(name, color, "order")
+where p.name = 'Status' and p.collection_id = 1;
@@ -0,0 +1,41 @@
+insert into property_type (name) values ('relation'); -- 9
+insert into filter_type (name) values ('Contains'); -- 8
+
+-- Every relation prop links to pages in exactly one target collection.
+create table property_relation(
+    prop_id int primary key references property(id) on delete cascade,
+    target_collection_id int not null references collection(id)
+);
+
+-- One row per link, so a page can relate to many pages and visa versa.
+create table propval_relation(
+    target_page_id int not null references page(id) on delete cascade,
+
+    page_id int not null references page(id) on delete cascade,
+    prop_id int not null references property(id) on delete cascade,
+    primary key (page_id, prop_id, target_page_id)
+);
+
+create table filter_relation(
+    id serial primary key,
+    type_id int not null references filter_type(id),
+    prop_id int not null references property(id) on delete cascade,
+    value int not null references page(id) on delete cascade
+);
+
+insert into collection (name) values ('Epics');
+insert into page (title, collection_id)
+select e.title, c.id
+from collection c, (values
+    ('Property types'),
+    ('Git integration')
+) as e(title)
+where c.name = 'Epics';
+
+insert into property (name, type_id, collection_id, "order") values
+    ('Epic', 9, 1, 10)
+;
+insert into property_relation (prop_id, target_collection_id)
+select p.id, c.id
+from property p, collection c
+where p.name = 'Epic' and p.collection_id = 1 and c.name = 'Epics';
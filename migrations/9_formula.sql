@@ -0,0 +1,18 @@
+insert into property_type (name) values ('formula'); -- 10
+
+-- `result_type_id` is determined by type-checking the expression whenever it
+-- is saved. Formula values are never stored; they're computed by the page
+-- list query.
+create table property_formula(
+    prop_id int primary key references property(id) on delete cascade,
+    expression text not null,
+    result_type_id int not null references property_type(id)
+);
+
+insert into property (name, type_id, collection_id, "order") values
+    ('Next Sprint', 10, 1, 11)
+;
+insert into property_formula (prop_id, expression, result_type_id)
+select p.id, 'prop("Sprint Number") + 1', 2
+from property p
+where p.name = 'Next Sprint' and p.collection_id = 1;
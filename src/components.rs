@@ -7,8 +7,11 @@
 #![allow(clippy::let_and_return)]
 
 use super::{models, routes::Route};
-use crate::filter::components as filter_component;
-use ammonia::clean;
+use crate::{
+    constraint, conversion, filter, filter::components as filter_component,
+    number_format, prop_val, template, view,
+};
+use ammonia::{clean, clean_text};
 use std::fmt::{Display, Write};
 
 #[cfg(feature = "live_reload")]
@@ -132,16 +135,31 @@ impl Component for Home {
     }
 }
 
+/// A collection, showing the pages of one of its views.
 pub struct Collection {
     pub id: i32,
     pub name: String,
+    pub view: view::models::View,
+    /// All views of the collection, including [Collection::view].
+    pub views: Vec<view::models::View>,
+    /// Filters the pages on top of, or in place of, the filters of the view.
+    pub adhoc: filter::models::AdHocQuery,
+    /// Why the ad-hoc query of the page could not be used, if it couldn't.
+    pub adhoc_error: Option<String>,
 }
 impl Component for Collection {
     fn render(&self) -> String {
         let id = self.id;
+        let view_id = self.view.id;
+        let view_tabs = view::components::ViewTabs {
+            collection_id: id,
+            active_view_id: view_id,
+            views: &self.views,
+        }
+        .render();
         let col_order = HoverIcon {
-            children: Box::new(ColumnOrderIcon { collection_id: id }),
-            tooltip_text: "Edit Column Order",
+            children: Box::new(ColumnOrderIcon { view_id }),
+            tooltip_text: "Edit View",
         }
         .render();
         let filter_icon = HoverIcon {
@@ -150,72 +168,187 @@ impl Component for Collection {
         }
         .render();
         let filter_toolbar_placeholder =
-            filter_component::FilterToolbarPlaceholder { collection_id: id }
-                .render();
+            filter_component::FilterToolbarPlaceholder { view_id }.render();
         let sort_icon = HoverIcon {
             children: Box::new(SortIcon {}),
             tooltip_text: "Configure Sorting",
         }
         .render();
         let sort_toolbar_placeholder =
-            SortToolbarPlaceholder { collection_id: id }.render();
+            SortToolbarPlaceholder { view_id }.render();
         let new_page_route = Route::CollectionNewPageForm(Some(id));
-        let list_page_route = Route::CollectionListPages(Some(id));
+        let list_page_route =
+            self.adhoc.append_to(&Route::ViewListPages(Some(view_id)));
+        let timezone_route = Route::UserTimezone;
+        let key_prefix_route = Route::CollectionKeyPrefix(Some(id));
+        let props_route = Route::CollectionProps(Some(id));
+        let templates_route = Route::CollectionTemplates(Some(id));
+        let export_route =
+            self.adhoc.append_to(&Route::ViewExport(Some(view_id)));
+        let adhoc_notice = if let Some(error) = &self.adhoc_error {
+            let error = clean(error);
+            format!(
+                r#"<p class="text-sm text-red-600 dark:text-red-400">The filters in this link could not be used: {error}</p>"#
+            )
+        } else if !self.adhoc.is_empty() {
+            r#"<p class="text-sm">These pages are filtered by the link. Open the filters to save or clear them.</p>"#.into()
+        } else {
+            "".into()
+        };
+        let search_route = Route::Search;
         let name = clean(&self.name);
         format!(
-            r#"
+            r##"
                 <h1 class="serif text-xl my-4">{name}</h1>
                 <a class="link" href="{new_page_route}">Create Page</a>
-                <div class="mt-2 flex">
+                <a class="link" href="{timezone_route}">Timezone</a>
+                <a class="link" href="{key_prefix_route}">Page Keys</a>
+                <a class="link" href="{props_route}">Props</a>
+                <a class="link" href="{templates_route}">Templates</a>
+                <a class="link" href="{export_route}" hx-boost="false" download>Export CSV</a>
+                <a class="link" href="{search_route}">Search All</a>
+                {view_tabs}
+                <div class="mt-2 flex gap-2 items-center">
                     {col_order} {filter_icon} {sort_icon}
+                    <input
+                        id="page-search"
+                        class="dark:text-black text-sm rounded"
+                        type="search"
+                        name="search"
+                        placeholder="Search pages"
+                        hx-get="{list_page_route}"
+                        hx-trigger="input changed delay:300ms, search"
+                        hx-target="#page-list"
+                    />
                 </div>
+                {adhoc_notice}
                 {filter_toolbar_placeholder}
                 {sort_toolbar_placeholder}
-                <main hx-trigger="load" hx-get="{list_page_route}">Loading Pages...</main>
-            "#
+                <main id="page-list" hx-trigger="load" hx-get="{list_page_route}">Loading Pages...</main>
+            "##
         )
     }
 }
 
+/// The pages of a view. `props` are the visible columns of the view, in
+/// order; see [view::models::View::get_visible_props].
 pub struct PageList<'a> {
     pub pages: &'a [models::Page],
     pub props: &'a [models::Prop],
-    pub collection_id: i32,
+    pub view: &'a view::models::View,
+    /// Kept when the pages are reloaded.
+    pub adhoc: &'a filter::models::AdHocQuery,
 }
 impl Component for PageList<'_> {
     fn render(&self) -> String {
-        let collection_id = self.collection_id;
+        let list_page_route = self
+            .adhoc
+            .append_to(&Route::ViewListPages(Some(self.view.id)));
         if self.pages.is_empty() {
-            let list_page_route =
-                Route::CollectionListPages(Some(collection_id));
             return format!(
-                r#"
+                r##"
                 <div
+                    id="page-list"
                     hx-get="{list_page_route}"
                     hx-trigger="reload-pages from:body"
+                    hx-include="#page-search"
                     >
                     <div>
-                        <p>No pages matching filters are available</p>
+                        <p>No pages matching filters or search are available</p>
                     </div>
                 </div>
-                "#
+                "##
             );
         };
+        if self.view.layout == view::models::Layout::List {
+            return PageListItems {
+                pages: self.pages,
+                list_page_route,
+            }
+            .render();
+        }
         let list = self.pages.iter().fold(String::new(), |mut str, page| {
                 let page_route = Route::Page(Some(page.id));
                 let title = clean(&page.title);
+                let key = page
+                    .key
+                    .as_ref()
+                    .map(|k| k.to_string())
+                    .unwrap_or_default();
                 let other_props = page
                     .props
                     .iter()
-                    .map(|p| match p {
+                    .zip(self.props)
+                    .map(|(p, prop)| match p {
+                        models::PvOrType::Pv(
+                            pv @ prop_val::models::PropVal {
+                                value: models::Value::Int(_) | models::Value::Float(_),
+                                ..
+                            },
+                        ) => number_format::components::NumberCell {
+                            propval: pv,
+                            format: &prop.number_format,
+                        }
+                        .render(),
                         models::PvOrType::Pv(pv) => pv.render(),
+                        // An empty tag list is already a usable editor, so
+                        // there's no need for the null button.
+                        models::PvOrType::Tp(models::ValueType::MultiStr, prop_id) => {
+                            prop_val::models::PropVal {
+                                page_id: page.id,
+                                prop_id: *prop_id,
+                                value: models::Value::MultiStr(vec![]),
+                            }
+                            .render()
+                        }
+                        models::PvOrType::Tp(models::ValueType::Relation, prop_id) => {
+                            prop_val::models::PropVal {
+                                page_id: page.id,
+                                prop_id: *prop_id,
+                                value: models::Value::Relation(vec![]),
+                            }
+                            .render()
+                        }
+                        models::PvOrType::Tp(models::ValueType::Files, prop_id) => {
+                            prop_val::models::PropVal {
+                                page_id: page.id,
+                                prop_id: *prop_id,
+                                value: models::Value::Files(vec![]),
+                            }
+                            .render()
+                        }
+                        // Formulas, rollups, and system props are read-only,
+                        // so there is nothing to create.
+                        models::PvOrType::Tp(
+                            models::ValueType::Formula
+                            | models::ValueType::Rollup
+                            | models::ValueType::CreatedTime
+                            | models::ValueType::LastEditedTime
+                            | models::ValueType::CreatedBy
+                            | models::ValueType::LastEditedBy,
+                            _,
+                        ) => {
+                            r#"<span class="justify-self-center"></span>"#.into()
+                        }
                         models::PvOrType::Tp(tp, prop_id) => {
                             NullPropvalButton {
                                 post_href: &match tp {
                                     models::ValueType::Int => Route::PageNewIntProp(Some((page.id, *prop_id))).as_string(),
                                     models::ValueType::Bool => Route::PageNewBoolProp(Some((page.id, *prop_id))).as_string(),
                                     models::ValueType::Float => Route::PageNewFloatProp(Some((page.id, *prop_id))).as_string(),
+                                    models::ValueType::Str => Route::PageNewStrProp(Some((page.id, *prop_id))).as_string(),
+                                    models::ValueType::MultiStr
+                                    | models::ValueType::Relation
+                                    | models::ValueType::Files
+                                    | models::ValueType::Formula
+                                    | models::ValueType::Rollup
+                                    | models::ValueType::CreatedTime
+                                    | models::ValueType::LastEditedTime
+                                    | models::ValueType::CreatedBy
+                                    | models::ValueType::LastEditedBy => unreachable!(),
                                     models::ValueType::Date => Route::PageNewDateProp(Some((page.id, *prop_id))).as_string(),
+                                    models::ValueType::DateTime => Route::PageNewDateTimeProp(Some((page.id, *prop_id))).as_string(),
+                                    models::ValueType::Select => Route::PageSelectProp(Some((page.id, *prop_id))).as_string(),
                                 }
                             }.render()
                         }
@@ -227,6 +360,7 @@ impl Component for PageList<'_> {
                 r#"
                     <div class="flex gap-2">
                         <a class="link" href="{page_route}">Edit</a>
+                        <span class="text-sm text-slate-500 dark:text-slate-400 whitespace-nowrap">{key}</span>
                         <div class="max-w-[50vw] sm:max-w-xs truncate">{title}</div>
                     </div>
                     {other_props}
@@ -250,42 +384,102 @@ impl Component for PageList<'_> {
             String::from(r#"<p class="text-center">Title</p>"#),
             |mut str, prop| {
                 let prop_name = clean(&prop.name);
-                str.push_str(&format!(
-                    r#"<p class="text-center">{prop_name}</p>"#
-                ));
+                // Computed props link to their editors.
+                let editor_route = match prop.type_id {
+                    models::ValueType::Formula => {
+                        Some(Route::PropFormula(Some(prop.id)))
+                    }
+                    models::ValueType::Rollup => {
+                        Some(Route::PropRollup(Some(prop.id)))
+                    }
+                    _ => None,
+                };
+                if let Some(editor_route) = editor_route {
+                    str.push_str(&format!(
+                        r#"<a class="link text-center" href="{editor_route}">{prop_name}</a>"#
+                    ));
+                } else {
+                    str.push_str(&format!(
+                        r#"<p class="text-center">{prop_name}</p>"#
+                    ));
+                }
                 str
             },
         );
         // "+ 1" because we're accountign for the leftmost column containing
         // the "edit" button and the page title.
         let column_count = self.props.len() + 1;
-        let list_page_route = Route::CollectionListPages(Some(collection_id));
         format!(
-            r#"
+            r##"
             <div
+                id="page-list"
                 hx-get="{list_page_route}"
                 hx-trigger="reload-pages from:body"
+                hx-include="#page-search"
                 class="mt-8 overflow-y-scroll grid gap-2"
                 style="grid-template-columns: repeat({column_count}, auto);"
                 >
                     {header}
                     {list}
             </div>
-            "#
+            "##
         )
     }
 }
 
+/// The [view::models::Layout::List] layout of a [PageList].
+struct PageListItems<'a> {
+    pages: &'a [models::Page],
+    list_page_route: String,
+}
+impl Component for PageListItems<'_> {
+    fn render(&self) -> String {
+        let list_page_route = &self.list_page_route;
+        let items = self.pages.iter().fold(String::new(), |mut str, page| {
+            let page_route = Route::Page(Some(page.id));
+            let title = clean(&page.title);
+            let key = page
+                .key
+                .as_ref()
+                .map(|k| k.to_string())
+                .unwrap_or_default();
+            let _ = write!(
+                str,
+                r#"
+                <li class="flex gap-2">
+                    <span class="text-sm text-slate-500 dark:text-slate-400 whitespace-nowrap">{key}</span>
+                    <a class="link truncate" href="{page_route}">{title}</a>
+                </li>
+                "#
+            );
+            str
+        });
+        format!(
+            r##"
+            <ul
+                id="page-list"
+                hx-get="{list_page_route}"
+                hx-trigger="reload-pages from:body"
+                hx-include="#page-search"
+                class="mt-8 flex flex-col gap-2"
+                >
+                    {items}
+            </ul>
+            "##
+        )
+    }
+}
+
+/// Links to the settings of a view, where its columns are chosen.
 struct ColumnOrderIcon {
-    collection_id: i32,
+    view_id: i32,
 }
 impl Component for ColumnOrderIcon {
     fn render(&self) -> String {
-        let cid = self.collection_id;
-        let prop_order_route = Route::CollectionChangePropOrder(Some(cid));
+        let view_route = Route::View(Some(self.view_id));
         format!(
             r#"
-            <a href="{prop_order_route}">
+            <a href="{view_route}">
                 <svg xmlns="http://www.w3.org/2000/svg" fill="none" viewBox="0 0 24 24" stroke-width="1.5" stroke="currentColor" class="w-6 h-6 rotate-90">
                   <path stroke-linecap="round" stroke-linejoin="round" d="M3.75 5.25h16.5m-16.5 4.5h16.5m-16.5 4.5h16.5m-16.5 4.5h16.5" />
                 </svg>
@@ -319,9 +513,16 @@ pub struct PageOverview<'a> {
 impl Component for PageOverview<'_> {
     fn render(&self) -> String {
         let collection_route = Route::Collection(Some(self.page.collection_id));
+        let key = match &self.page.key {
+            Some(key) => format!(
+                r#"<p class="text-sm text-slate-500 dark:text-slate-400">{key}</p>"#
+            ),
+            None => "".into(),
+        };
         let back_button = format!(
             r#"
                 <a class="block mb-2 link" href="{collection_route}">Back</a>
+                {key}
             "#,
         );
         [
@@ -423,6 +624,10 @@ pub struct NewPage {
     pub collection_id: i32,
     pub page_id: Option<i32>,
     pub title: Option<String>,
+    pub templates: Vec<template::models::PageTemplate>,
+    /// Why the page couldn't be created; say, because a default of the
+    /// template breaks a constraint.
+    pub error: Option<String>,
 }
 impl Component for NewPage {
     fn render(&self) -> String {
@@ -436,22 +641,55 @@ impl Component for NewPage {
         } else {
             "".to_string()
         };
+        let template_select = if self.templates.is_empty() {
+            "".to_string()
+        } else {
+            let options = self.templates.iter().fold(
+                String::from(r#"<option value="">Blank Page</option>"#),
+                |mut acc, t| {
+                    let id = t.id;
+                    let name = clean(&t.name);
+                    let _ =
+                        write!(acc, r#"<option value="{id}">{name}</option>"#);
+                    acc
+                },
+            );
+            format!(
+                r#"
+                <label for="template_id">Template</label>
+                <select class="dark:text-black" name="template_id" id="template_id">{options}</select>
+                "#
+            )
+        };
         let collection_route = Route::Collection(Some(self.collection_id));
         let back_button = format!(
             r#"
                 <a class="block mb-2 link" href="{collection_route}">Back</a>
             "#
         );
+        let error = match &self.error {
+            Some(error) => {
+                let error = clean(error);
+                format!(
+                    r#"<p class="text-red-600 dark:text-red-400">{error}</p>"#
+                )
+            }
+            None => "".into(),
+        };
         format!(
             r#"
+                <div hx-target="this" hx-swap="outerHTML">
                 {back_button}
                 <form hx-post="{collection_route}">
                     <h1 class="text-xl">New Page</h1>
                     <label for="title">Title</label>
                     <input class="rounded" type="text" name="title" id="title" value="{title}" />
+                    {template_select}
                     {page_id}
+                    {error}
                     <button class="dark:bg-slate-700 dark:text-white dark:hover:bg-slate-600 transition shadow hover:shadow-none rounded p-1 block">Save</button>
                 </form>
+                </div>
             "#,
         )
     }
@@ -521,10 +759,12 @@ impl Component for PropOrderForm {
             .join("\n");
 
         let collection_route = Route::Collection(Some(collection_id));
+        let props_route = Route::CollectionProps(Some(collection_id));
         format!(
             r#"
                 <div>
                     <a class="link" href="{collection_route}">Back</a>
+                    <a class="link" href="{props_route}">Manage Props</a>
                     <ol class="ml-4 list-decimal"">{list_items}</ol>
                 </div>
             "#
@@ -532,6 +772,198 @@ impl Component for PropOrderForm {
     }
 }
 
+/// Lists the collection's props, which can be renamed or deleted from here,
+/// along with a form to add new ones.
+pub struct PropManager<'a> {
+    pub collection_id: i32,
+    pub props: &'a [models::Prop],
+    pub new_prop_form: NewPropForm<'a>,
+    pub error: Option<&'a str>,
+}
+impl Component for PropManager<'_> {
+    fn render(&self) -> String {
+        let collection_route = Route::Collection(Some(self.collection_id));
+        let prop_order_route =
+            Route::CollectionChangePropOrder(Some(self.collection_id));
+        let props = self.props.iter().fold(String::new(), |mut acc, p| {
+            let name = clean_text(&p.name);
+            let type_name = p.type_id.get_display_name();
+            let prop_route = Route::Prop(Some(p.id));
+            let confirmation_route = Route::PropDeleteConfirmation(Some(p.id));
+            // Some props need more setup than a name and a type.
+            let editor_link = match p.type_id {
+                models::ValueType::Select => Some(Route::PropOptions(Some(p.id))),
+                models::ValueType::Formula => Some(Route::PropFormula(Some(p.id))),
+                models::ValueType::Rollup => Some(Route::PropRollup(Some(p.id))),
+                _ => None,
+            }
+            .map(|route| format!(r#"<a class="text-xs link" href="{route}">edit</a>"#))
+            .unwrap_or_default();
+            let type_link = if conversion::models::is_convertible(p.type_id) {
+                let type_route = Route::PropType(Some(p.id));
+                format!(r#"<a class="text-xs link" href="{type_route}">change type</a>"#)
+            } else {
+                "".into()
+            };
+            let format_link = match p.type_id {
+                models::ValueType::Int | models::ValueType::Float => {
+                    let format_route = Route::PropNumberFormat(Some(p.id));
+                    format!(r#"<a class="text-xs link" href="{format_route}">format</a>"#)
+                }
+                _ => "".into(),
+            };
+            let constraints_link = if constraint::models::is_constrainable(p.type_id) {
+                let constraints_route = Route::PropConstraints(Some(p.id));
+                format!(r#"<a class="text-xs link" href="{constraints_route}">constraints</a>"#)
+            } else {
+                "".into()
+            };
+            let _ = write!(
+                acc,
+                r#"
+                <li class="flex items-center gap-2">
+                    <form hx-post="{prop_route}" hx-trigger="change">
+                        <input class="dark:text-black" required maxlength="255" type="text" name="name" value="{name}" />
+                    </form>
+                    <span class="text-sm text-slate-500 dark:text-slate-400">{type_name}</span>
+                    {editor_link}
+                    {type_link}
+                    {format_link}
+                    {constraints_link}
+                    <button
+                        class="text-xs link"
+                        hx-get="{confirmation_route}"
+                        hx-target="closest li"
+                    >delete</button>
+                </li>
+                "#
+            );
+            acc
+        });
+        let error = match self.error {
+            Some(error) => {
+                let error = clean(error);
+                format!(
+                    r#"<p class="text-red-600 dark:text-red-400">{error}</p>"#
+                )
+            }
+            None => "".into(),
+        };
+        let new_prop_form = self.new_prop_form.render();
+        format!(
+            r#"
+            <div class="flex flex-col gap-2 max-w-md" hx-target="this" hx-swap="outerHTML">
+                <a class="link" href="{collection_route}">Back</a>
+                <a class="link" href="{prop_order_route}">Column Order</a>
+                <h1 class="text-xl">Props</h1>
+                {error}
+                <ul class="flex flex-col gap-1">{props}</ul>
+                {new_prop_form}
+            </div>
+            "#
+        )
+    }
+}
+
+pub struct NewPropForm<'a> {
+    pub collection_id: i32,
+    pub name: &'a str,
+    pub type_id: models::ValueType,
+    /// Choices for the collection which relation props link to.
+    pub collections: &'a [models::Collection],
+}
+impl Component for NewPropForm<'_> {
+    fn render(&self) -> String {
+        let props_route = Route::CollectionProps(Some(self.collection_id));
+        let form_route = Route::CollectionNewPropForm(Some(self.collection_id));
+        let name = clean_text(self.name);
+        let types = models::ValueType::all().iter().fold(
+            String::new(),
+            |mut acc, t| {
+                let type_id = t.get_int_repr();
+                let type_name = t.get_display_name();
+                let selected = if *t == self.type_id { "selected" } else { "" };
+                let _ = write!(
+                    acc,
+                    r#"<option {selected} value="{type_id}">{type_name}</option>"#
+                );
+                acc
+            },
+        );
+        let target_select = if self.type_id == models::ValueType::Relation {
+            let collections =
+                self.collections.iter().fold(String::new(), |mut acc, c| {
+                    let id = c.id;
+                    let name = clean_text(&c.name);
+                    let _ =
+                        write!(acc, r#"<option value="{id}">{name}</option>"#);
+                    acc
+                });
+            format!(
+                r#"
+                <label for="target_collection_id">Links To</label>
+                <select class="dark:text-black" id="target_collection_id" name="target_collection_id">{collections}</select>
+                "#
+            )
+        } else {
+            "".into()
+        };
+        format!(
+            r#"
+            <form class="flex flex-col gap-2" hx-post="{props_route}">
+                <h2 class="text-lg">New Prop</h2>
+                <label for="name">Name</label>
+                <input class="dark:text-black" required maxlength="255" type="text" id="name" name="name" value="{name}" />
+                <label for="type_id">Type</label>
+                <select
+                    class="dark:text-black"
+                    id="type_id"
+                    name="type_id"
+                    hx-get="{form_route}"
+                    hx-include="closest form"
+                    hx-target="closest form"
+                    hx-swap="outerHTML"
+                >{types}</select>
+                {target_select}
+                <button class="dark:bg-slate-700 w-36 dark:text-white dark:hover:bg-slate-600 transition shadow hover:shadow-none rounded p-1 block">Add Prop</button>
+            </form>
+            "#
+        )
+    }
+}
+
+/// Takes the place of a prop in [PropManager] until the deletion is
+/// confirmed or cancelled.
+pub struct PropDeleteConfirmation<'a> {
+    pub prop: &'a models::Prop,
+    pub value_count: i64,
+}
+impl Component for PropDeleteConfirmation<'_> {
+    fn render(&self) -> String {
+        let name = clean(&self.prop.name);
+        let prop_route = Route::Prop(Some(self.prop.id));
+        let props_route = Route::CollectionProps(Some(self.prop.collection_id));
+        let values = match self.value_count {
+            1 => "1 value".to_string(),
+            n => format!("{n} values"),
+        };
+        format!(
+            r#"
+            <li class="flex flex-col gap-1 p-2 border border-red-500 rounded">
+                <p>
+                    Delete {name}? {values} will be lost, along with any
+                    filters on this prop.
+                </p>
+                <div class="flex gap-2">
+                    <button class="text-xs link" hx-delete="{prop_route}">delete</button>
+                    <button class="text-xs link" hx-get="{props_route}">cancel</button>
+                </div>
+            </li>
+            "#
+        )
+    }
+}
+
 pub enum ChevronVariant {
     Open,
     Closed,
@@ -603,32 +1035,6 @@ impl Component for DeleteButton<'_> {
     }
 }
 
-pub struct Div<'a> {
-    pub class: &'a str,
-    pub hx_get: Option<&'a str>,
-    pub hx_trigger: Option<&'a str>,
-    pub children: Box<dyn Component>,
-}
-impl Component for Div<'_> {
-    fn render(&self) -> String {
-        let hx_get = if let Some(hxg) = self.hx_get {
-            clean(&format!(r#"hx-get="{hxg}""#))
-        } else {
-            "".into()
-        };
-        let hx_trigger = if let Some(hxt) = self.hx_trigger {
-            clean(&format!(r#"hx-trigger="{hxt}""#))
-        } else {
-            "".into()
-        };
-        let class = self.class;
-        let children = self.children.render();
-        format!(
-            r#"<div {hx_get} {hx_trigger} class="{class}">{children}</div>"#
-        )
-    }
-}
-
 pub struct SortIcon;
 impl Component for SortIcon {
     fn render(&self) -> String {
@@ -659,14 +1065,14 @@ impl Component for SortIcon {
 }
 
 pub struct SortToolbar<'a> {
-    pub collection_id: i32,
+    pub view_id: i32,
     pub default_selected_prop: Option<i32>,
     pub prop_choices: &'a [models::Prop],
     pub sort_type: Option<models::SortType>,
 }
 impl Component for SortToolbar<'_> {
     fn render(&self) -> String {
-        let collection_id = self.collection_id;
+        let view_id = self.view_id;
         let disable_sorting_option = if self.default_selected_prop.is_some() {
             r#"<option selected value="-1">-- Disable Sorting --</option>"#
         } else {
@@ -710,9 +1116,8 @@ impl Component for SortToolbar<'_> {
                    <option value="2">Descending</option>"#
             }
         };
-        let hide_toolbar =
-            Route::CollectionHideSortToolbar(Some(collection_id));
-        let sort_route = Route::CollectionSort(Some(collection_id));
+        let hide_toolbar = Route::ViewHideSortToolbar(Some(view_id));
+        let sort_route = Route::ViewSort(Some(view_id));
         format!(
             r#"<div
                 hx-get="{hide_toolbar}"
@@ -744,11 +1149,11 @@ impl Component for SortToolbar<'_> {
 }
 
 pub struct SortToolbarPlaceholder {
-    pub collection_id: i32,
+    pub view_id: i32,
 }
 impl Component for SortToolbarPlaceholder {
     fn render(&self) -> String {
-        let route = Route::CollectionShowSortToolbar(Some(self.collection_id));
+        let route = Route::ViewShowSortToolbar(Some(self.view_id));
         format!(
             r#"
             <div
@@ -761,11 +1166,11 @@ impl Component for SortToolbarPlaceholder {
 }
 
 pub struct SortOrderSavedConfirmation {
-    pub collection_id: i32,
+    pub view_id: i32,
 }
 impl Component for SortOrderSavedConfirmation {
     fn render(&self) -> String {
-        let route = Route::CollectionHideSortToolbar(Some(self.collection_id));
+        let route = Route::ViewHideSortToolbar(Some(self.view_id));
         format!(
             r##"
             <div
@@ -801,12 +1206,257 @@ impl Component for NullPropvalButton<'_> {
             r#"
             <button
                 hx-get="{post_href}"
+                hx-swap="outerHTML"
                 >--</button>
             "#
         )
     }
 }
 
+/// `<option>` elements to be swapped into a tag input's `<datalist>`.
+pub struct TagVocab<'a> {
+    pub tags: &'a [String],
+}
+impl Component for TagVocab<'_> {
+    fn render(&self) -> String {
+        self.tags.iter().fold(String::new(), |mut acc, tag| {
+            let tag = clean_text(tag);
+            let _ = write!(acc, r#"<option value="{tag}"></option>"#);
+            acc
+        })
+    }
+}
+
+/// Lets the user choose the timezone used to display and enter datetimes,
+/// and the first day of the week for relative date filters.
+pub struct TimezoneForm {
+    pub current: chrono_tz::Tz,
+    pub week_start: chrono::Weekday,
+}
+impl Component for TimezoneForm {
+    fn render(&self) -> String {
+        let timezone_route = Route::UserTimezone;
+        let options =
+            chrono_tz::TZ_VARIANTS
+                .iter()
+                .fold(String::new(), |mut acc, tz| {
+                    let name = tz.name();
+                    let selected =
+                        if *tz == self.current { "selected" } else { "" };
+                    let _ = write!(
+                        acc,
+                        r#"<option value="{name}" {selected}>{name}</option>"#
+                    );
+                    acc
+                });
+        let week_start_options = [
+            chrono::Weekday::Mon,
+            chrono::Weekday::Sat,
+            chrono::Weekday::Sun,
+        ]
+        .iter()
+        .fold(String::new(), |mut acc, day| {
+            let number = day.number_from_monday();
+            let selected = if *day == self.week_start {
+                "selected"
+            } else {
+                ""
+            };
+            let name = match day {
+                chrono::Weekday::Sat => "Saturday",
+                chrono::Weekday::Sun => "Sunday",
+                _ => "Monday",
+            };
+            let _ = write!(
+                acc,
+                r#"<option value="{number}" {selected}>{name}</option>"#
+            );
+            acc
+        });
+        format!(
+            r#"
+            <form class="flex flex-col gap-2 max-w-md" hx-post="{timezone_route}">
+                <h1 class="text-xl">Timezone</h1>
+                <p class="text-sm dark:text-slate-100">
+                    Dates and times are shown, and entered, in this timezone.
+                </p>
+                <select class="dark:text-black" id="timezone" name="timezone">{options}</select>
+                <label for="week_start">First day of the week</label>
+                <select class="dark:text-black" id="week_start" name="week_start">{week_start_options}</select>
+                <button class="dark:bg-slate-700 w-36 dark:text-white dark:hover:bg-slate-600 transition shadow hover:shadow-none rounded p-1 block">Save</button>
+            </form>
+            "#
+        )
+    }
+}
+
+pub struct KeyPrefixForm<'a> {
+    pub collection_id: i32,
+    pub current: &'a str,
+    pub error: Option<&'a str>,
+}
+impl Component for KeyPrefixForm<'_> {
+    fn render(&self) -> String {
+        let key_prefix_route =
+            Route::CollectionKeyPrefix(Some(self.collection_id));
+        let current = clean_text(self.current);
+        let error = match self.error {
+            Some(error) => {
+                let error = clean(error);
+                format!(
+                    r#"<p class="text-red-600 dark:text-red-400">{error}</p>"#
+                )
+            }
+            None => "".into(),
+        };
+        format!(
+            r#"
+            <form class="flex flex-col gap-2 max-w-md" hx-post="{key_prefix_route}" hx-swap="outerHTML">
+                <h1 class="text-xl">Page Keys</h1>
+                <p class="text-sm dark:text-slate-100">
+                    Pages in this collection get keys like {current}-113,
+                    which can be used to link to them from anywhere.
+                </p>
+                <label for="key_prefix">Prefix</label>
+                <input class="dark:text-black" id="key_prefix" name="key_prefix" type="text" value="{current}" />
+                {error}
+                <button class="dark:bg-slate-700 w-36 dark:text-white dark:hover:bg-slate-600 transition shadow hover:shadow-none rounded p-1 block">Save</button>
+            </form>
+            "#
+        )
+    }
+}
+
+/// `<option>` elements for each of a select prop's options.
+pub struct SelectOptionList<'a> {
+    pub options: &'a [models::SelectOption],
+    pub selected: Option<i32>,
+}
+impl Component for SelectOptionList<'_> {
+    fn render(&self) -> String {
+        self.options.iter().fold(String::new(), |mut acc, option| {
+            let id = option.id;
+            let name = clean_text(&option.name);
+            let selected = if Some(id) == self.selected {
+                "selected"
+            } else {
+                ""
+            };
+            let _ = write!(
+                acc,
+                r#"<option {selected} value="{id}">{name}</option>"#
+            );
+            acc
+        })
+    }
+}
+
+/// `<option>` elements for each page which a relation prop can link to.
+pub struct RelatedPageOptionList<'a> {
+    pub pages: &'a [models::RelatedPage],
+    pub selected: Option<i32>,
+}
+impl Component for RelatedPageOptionList<'_> {
+    fn render(&self) -> String {
+        self.pages.iter().fold(String::new(), |mut acc, page| {
+            let id = page.id;
+            let title = clean_text(&page.title);
+            let selected = if Some(id) == self.selected {
+                "selected"
+            } else {
+                ""
+            };
+            let _ = write!(
+                acc,
+                r#"<option {selected} value="{id}">{title}</option>"#
+            );
+            acc
+        })
+    }
+}
+
+/// `<option>` elements for each user, for filtering on people props.
+pub struct UserOptionList<'a> {
+    pub users: &'a [models::User],
+    pub selected: Option<i32>,
+}
+impl Component for UserOptionList<'_> {
+    fn render(&self) -> String {
+        self.users.iter().fold(String::new(), |mut acc, user| {
+            let id = user.id;
+            let username = clean_text(&user.username);
+            let selected = if Some(id) == self.selected {
+                "selected"
+            } else {
+                ""
+            };
+            let _ = write!(
+                acc,
+                r#"<option {selected} value="{id}">{username}</option>"#
+            );
+            acc
+        })
+    }
+}
+
+/// Lists a select prop's options, with buttons to delete them and a form to
+/// add new ones.
+pub struct SelectOptionEditor<'a> {
+    pub prop: &'a models::Prop,
+    pub options: &'a [models::SelectOption],
+}
+impl Component for SelectOptionEditor<'_> {
+    fn render(&self) -> String {
+        let prop_name = clean(&self.prop.name);
+        let options_route = Route::PropOptions(Some(self.prop.id));
+        let collection_route = Route::Collection(Some(self.prop.collection_id));
+        let options = self.options.iter().fold(String::new(), |mut acc, o| {
+            let name = clean(&o.name);
+            let color = prop_val::components::get_select_color_classes(o.color);
+            let delete_route = Route::PropOption(Some((o.prop_id, o.id)));
+            let _ = write!(
+                acc,
+                r#"
+                <li class="flex items-center gap-2">
+                    <span class="rounded-full px-2 text-sm {color}">{name}</span>
+                    <button
+                        class="text-xs link"
+                        hx-delete="{delete_route}"
+                        hx-confirm="Pages using this option will be cleared. Continue?"
+                    >delete</button>
+                </li>
+                "#
+            );
+            acc
+        });
+        let colors = models::SelectColor::ALL.iter().fold(
+            String::new(),
+            |mut acc, color| {
+                let name = color.get_name();
+                let _ =
+                    write!(acc, r#"<option value="{name}">{name}</option>"#);
+                acc
+            },
+        );
+        format!(
+            r#"
+            <div class="flex flex-col gap-2 max-w-md" hx-target="this" hx-swap="outerHTML">
+                <a class="link" href="{collection_route}">Back</a>
+                <h1 class="text-xl">Options for {prop_name}</h1>
+                <ul class="flex flex-col gap-1">{options}</ul>
+                <form class="flex flex-col gap-2" hx-post="{options_route}">
+                    <label for="name">Name</label>
+                    <input class="dark:text-black" required maxlength="255" type="text" id="name" name="name" />
+                    <label for="color">Color</label>
+                    <select class="dark:text-black" id="color" name="color">{colors}</select>
+                    <button class="dark:bg-slate-700 w-36 dark:text-white dark:hover:bg-slate-600 transition shadow hover:shadow-none rounded p-1 block">Add Option</button>
+                </form>
+            </div>
+            "#
+        )
+    }
+}
+
 pub struct LoginForm;
 impl Component for LoginForm {
     fn render(&self) -> String {
@@ -849,6 +1499,11 @@ impl Component for RegisterForm {
                     be your Captcha.
                 </p>
                 <input type="text" id="secret_word" name="secret_word" />
+                <input type="hidden" id="timezone" name="timezone" />
+                <script>
+                    document.getElementById("timezone").value =
+                        Intl.DateTimeFormat().resolvedOptions().timeZone;
+                </script>
                 <button class="dark:bg-slate-700 w-36 dark:text-white dark:hover:bg-slate-600 transition shadow hover:shadow-none rounded p-1 block">Sign Up</button>
             </form>
             "#
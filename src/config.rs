@@ -6,3 +6,8 @@
 /// max size for the set of props in a collection, and defer dealing with
 /// scaling beyond this limit until it is hit.
 pub const PROP_SET_MAX: usize = 5000;
+
+/// The largest request which can be sent to upload files. Uploads are held
+/// in memory while they are hashed and stored, so this also caps how much
+/// memory each upload can take.
+pub const UPLOAD_MAX_BYTES: usize = 25 * 1024 * 1024;
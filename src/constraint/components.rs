@@ -0,0 +1,148 @@
+use super::models::{Constraints, Rule};
+use crate::{
+    components::Component, models::Prop, prop_val::models::PropVal,
+    routes::Route,
+};
+use ammonia::{clean, clean_text};
+
+/// A propval input which was rejected by its prop's constraints, along with
+/// the reason. The input keeps the rejected value so that it can be fixed,
+/// and saving it again replaces this whole fragment.
+pub struct InvalidPropVal<'a> {
+    pub propval: &'a PropVal,
+    pub error: &'a str,
+}
+impl Component for InvalidPropVal<'_> {
+    fn render(&self) -> String {
+        let propval = self.propval.render();
+        let error = clean(self.error);
+        format!(
+            r#"
+            <div
+                class="flex flex-col items-center gap-1 justify-self-center"
+                hx-target="this"
+                hx-swap="outerHTML"
+            >
+                {propval}
+                <p class="text-xs text-red-600 dark:text-red-400">{error}</p>
+            </div>
+            "#
+        )
+    }
+}
+
+/// Form for the constraints of a prop. Only the constraints which apply to
+/// the prop's type are shown.
+pub struct ConstraintForm<'a> {
+    pub prop: &'a Prop,
+    pub constraints: &'a Constraints,
+    pub error: Option<&'a str>,
+    pub saved: bool,
+}
+impl Component for ConstraintForm<'_> {
+    fn render(&self) -> String {
+        let prop_name = clean(&self.prop.name);
+        let type_name = self.prop.type_id.get_display_name();
+        let constraints_route = Route::PropConstraints(Some(self.prop.id));
+        let props_route = Route::CollectionProps(Some(self.prop.collection_id));
+        let c = self.constraints;
+        let applies = |rule: Rule| rule.applies_to(self.prop.type_id);
+        let checked = |on: bool| if on { "checked" } else { "" };
+        let num = |n: Option<f64>| n.map(|n| n.to_string()).unwrap_or_default();
+        let mut fields = String::new();
+        if applies(Rule::Required) {
+            let required = checked(c.required);
+            fields.push_str(&format!(
+                r#"
+                <label class="flex items-center gap-2">
+                    <input type="checkbox" name="required" {required} />
+                    Required
+                </label>
+                "#
+            ));
+        }
+        if applies(Rule::Unique) {
+            let unique = checked(c.unique);
+            fields.push_str(&format!(
+                r#"
+                <label class="flex items-center gap-2">
+                    <input type="checkbox" name="unique" {unique} />
+                    No two pages can have the same value
+                </label>
+                "#
+            ));
+        }
+        if applies(Rule::Range) {
+            let min = num(c.min);
+            let max = num(c.max);
+            fields.push_str(&format!(
+                r#"
+                <label for="min">Minimum</label>
+                <input class="dark:text-black" type="number" step="any" id="min" name="min" value="{min}" />
+                <label for="max">Maximum</label>
+                <input class="dark:text-black" type="number" step="any" id="max" name="max" value="{max}" />
+                "#
+            ));
+        }
+        if applies(Rule::DateRange) {
+            let min_date =
+                c.min_date.map(|d| d.to_string()).unwrap_or_default();
+            let max_date =
+                c.max_date.map(|d| d.to_string()).unwrap_or_default();
+            fields.push_str(&format!(
+                r#"
+                <label for="min_date">Earliest Date</label>
+                <input class="dark:text-black" type="date" id="min_date" name="min_date" value="{min_date}" />
+                <label for="max_date">Latest Date</label>
+                <input class="dark:text-black" type="date" id="max_date" name="max_date" value="{max_date}" />
+                "#
+            ));
+        }
+        if applies(Rule::Text) {
+            let pattern = clean_text(c.pattern.as_deref().unwrap_or_default());
+            let min_length =
+                c.min_length.map(|l| l.to_string()).unwrap_or_default();
+            let max_length =
+                c.max_length.map(|l| l.to_string()).unwrap_or_default();
+            fields.push_str(&format!(
+                r#"
+                <label for="pattern">Pattern <span class="text-sm text-slate-500 dark:text-slate-400">regular expression</span></label>
+                <input class="dark:text-black" type="text" maxlength="255" id="pattern" name="pattern" value="{pattern}" placeholder="^[A-Z]+-\d+$" />
+                <label for="min_length">Minimum Length</label>
+                <input class="dark:text-black" type="number" min="0" id="min_length" name="min_length" value="{min_length}" />
+                <label for="max_length">Maximum Length</label>
+                <input class="dark:text-black" type="number" min="0" id="max_length" name="max_length" value="{max_length}" />
+                "#
+            ));
+        }
+        let error = match self.error {
+            Some(error) => {
+                let error = clean(error);
+                format!(
+                    r#"<p class="text-red-600 dark:text-red-400">{error}</p>"#
+                )
+            }
+            None => "".into(),
+        };
+        let saved = if self.saved {
+            r#"<p class="text-green-700 dark:text-green-400">Saved.</p>"#
+        } else {
+            ""
+        };
+        format!(
+            r#"
+            <div class="flex flex-col gap-2 max-w-md" hx-target="this" hx-swap="outerHTML">
+                <a class="link" href="{props_route}">Back</a>
+                <h1 class="text-xl">Constraints for {prop_name}</h1>
+                <p class="text-sm">{prop_name} is a {type_name} prop. Values which are already saved are not checked again.</p>
+                <form class="flex flex-col gap-2" hx-post="{constraints_route}">
+                    {fields}
+                    {error}
+                    {saved}
+                    <button class="dark:bg-slate-700 w-36 dark:text-white dark:hover:bg-slate-600 transition shadow hover:shadow-none rounded p-1 block">Save</button>
+                </form>
+            </div>
+            "#
+        )
+    }
+}
@@ -0,0 +1,211 @@
+use super::models::{Constraints, ValidationError};
+use crate::{models::Value, prop_val::models::PropVal};
+use anyhow::Result;
+use chrono::Utc;
+use sqlx::{query, query_as, PgConnection, PgPool};
+
+/// The constraints of a prop. Props without a `property_constraint` row
+/// don't have any.
+pub async fn get_constraints(db: &PgPool, prop_id: i32) -> Result<Constraints> {
+    get_constraints_in(&mut *db.acquire().await?, prop_id).await
+}
+
+/// Like [get_constraints], as part of a larger transaction.
+async fn get_constraints_in(
+    conn: &mut PgConnection,
+    prop_id: i32,
+) -> Result<Constraints> {
+    let constraints = query_as!(
+        Constraints,
+        r#"select
+            prop_id,
+            required,
+            min,
+            max,
+            min_date,
+            max_date,
+            pattern,
+            min_length,
+            max_length,
+            is_unique as "unique"
+        from property_constraint
+        where prop_id = $1"#,
+        prop_id
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    Ok(constraints.unwrap_or(Constraints {
+        prop_id,
+        ..Default::default()
+    }))
+}
+
+pub async fn save_constraints(
+    db: &PgPool,
+    constraints: &Constraints,
+) -> Result<()> {
+    query!(
+        "insert into property_constraint (
+            required,
+            min,
+            max,
+            min_date,
+            max_date,
+            pattern,
+            min_length,
+            max_length,
+            is_unique,
+            prop_id
+        ) values ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+        on conflict (prop_id) do update set
+            required = $1,
+            min = $2,
+            max = $3,
+            min_date = $4,
+            max_date = $5,
+            pattern = $6,
+            min_length = $7,
+            max_length = $8,
+            is_unique = $9",
+        constraints.required,
+        constraints.min,
+        constraints.max,
+        constraints.min_date,
+        constraints.max_date,
+        constraints.pattern,
+        constraints.min_length,
+        constraints.max_length,
+        constraints.unique,
+        constraints.prop_id
+    )
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+/// Whether a page other than `pv.page_id` already has the value of `pv`.
+/// Only values which [super::models::Rule::Unique] applies to are ever
+/// taken.
+async fn is_taken(conn: &mut PgConnection, pv: &PropVal) -> Result<bool> {
+    struct Qres {
+        taken: Option<bool>,
+    }
+    let res = match &pv.value {
+        Value::Int(val) => {
+            query_as!(
+                Qres,
+                "select exists(
+                    select 1 from propval_int
+                    where prop_id = $1 and page_id != $2 and value = $3
+                ) taken",
+                pv.prop_id,
+                pv.page_id,
+                val
+            )
+            .fetch_one(&mut *conn)
+            .await?
+        }
+        Value::Float(val) => {
+            query_as!(
+                Qres,
+                "select exists(
+                    select 1 from propval_float
+                    where prop_id = $1 and page_id != $2 and value = $3
+                ) taken",
+                pv.prop_id,
+                pv.page_id,
+                val
+            )
+            .fetch_one(&mut *conn)
+            .await?
+        }
+        Value::Str(val) => {
+            query_as!(
+                Qres,
+                "select exists(
+                    select 1 from propval_str
+                    where prop_id = $1 and page_id != $2 and value = $3
+                ) taken",
+                pv.prop_id,
+                pv.page_id,
+                val
+            )
+            .fetch_one(&mut *conn)
+            .await?
+        }
+        Value::Date(val) => {
+            query_as!(
+                Qres,
+                "select exists(
+                    select 1 from propval_date
+                    where prop_id = $1 and page_id != $2 and value = $3
+                ) taken",
+                pv.prop_id,
+                pv.page_id,
+                val
+            )
+            .fetch_one(&mut *conn)
+            .await?
+        }
+        Value::DateTime(val) => {
+            query_as!(
+                Qres,
+                "select exists(
+                    select 1 from propval_datetime
+                    where prop_id = $1 and page_id != $2 and value = $3
+                ) taken",
+                pv.prop_id,
+                pv.page_id,
+                val.with_timezone(&Utc)
+            )
+            .fetch_one(&mut *conn)
+            .await?
+        }
+        _ => Qres { taken: Some(false) },
+    };
+
+    Ok(res.taken.unwrap_or_default())
+}
+
+/// Check `pv` against the constraints of its prop. A value which breaks
+/// one of them fails with a [ValidationError]. This runs in the transaction
+/// which saves `pv`.
+///
+/// For unique props, the prop is locked until that transaction ends, so
+/// concurrent saves of the same value are checked one after the other.
+pub async fn validate(conn: &mut PgConnection, pv: &PropVal) -> Result<()> {
+    let constraints = get_constraints_in(&mut *conn, pv.prop_id).await?;
+    if let Err(e) = constraints.check(&pv.value) {
+        return Err(ValidationError(e).into());
+    }
+    if constraints.unique {
+        query!(
+            "select id from property where id = $1 for update",
+            pv.prop_id
+        )
+        .fetch_one(&mut *conn)
+        .await?;
+    }
+    if constraints.unique && is_taken(&mut *conn, pv).await? {
+        return Err(ValidationError(
+            "another page already has this value".into(),
+        )
+        .into());
+    }
+
+    Ok(())
+}
+
+/// Check that the value of `pv` can be cleared, which is only a problem for
+/// required props.
+pub async fn validate_delete(db: &PgPool, pv: &PropVal) -> Result<()> {
+    let constraints = get_constraints(db, pv.prop_id).await?;
+    // An unchecked checkbox still counts as a value.
+    if constraints.required && !matches!(pv.value, Value::Bool(_)) {
+        return Err(ValidationError("a value is required".into()).into());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,12 @@
+//! Constraints are rules which the values of a prop must follow; say, that
+//! a sprint number is never negative, or that every page has a due date.
+//! They are checked in [crate::db_ops::DbModel::save] for propvals, which
+//! fails with a [models::ValidationError] so that controllers can show the
+//! problem next to the input.
+//!
+//! Constraints only apply to new values. Values saved before a constraint
+//! was added, or by changing the type of a prop, are left alone.
+
+pub mod components;
+pub mod db_ops;
+pub mod models;
@@ -0,0 +1,265 @@
+use crate::models::{Value, ValueType};
+use chrono::NaiveDate;
+use regex::Regex;
+use std::fmt;
+
+/// Rules which the values of one prop must follow. Rules which don't apply
+/// to the type of a value are ignored; see [Rule::applies_to].
+#[derive(Debug, Clone, Default)]
+pub struct Constraints {
+    pub prop_id: i32,
+    pub required: bool,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub min_date: Option<NaiveDate>,
+    pub max_date: Option<NaiveDate>,
+    pub pattern: Option<String>,
+    pub min_length: Option<i32>,
+    pub max_length: Option<i32>,
+    pub unique: bool,
+}
+
+/// The kinds of constraints, for deciding which ones a prop can have.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Rule {
+    Required,
+    /// `min` and `max`
+    Range,
+    /// `min_date` and `max_date`
+    DateRange,
+    /// `pattern`, `min_length`, and `max_length`
+    Text,
+    Unique,
+}
+
+impl Rule {
+    pub fn applies_to(&self, ty: ValueType) -> bool {
+        match self {
+            // An unchecked checkbox is a value, so bools are never empty.
+            Self::Required => matches!(
+                ty,
+                ValueType::Int
+                    | ValueType::Float
+                    | ValueType::Str
+                    | ValueType::MultiStr
+                    | ValueType::Date
+                    | ValueType::DateTime
+                    | ValueType::Select
+                    | ValueType::Relation
+                    | ValueType::Files
+            ),
+            Self::Range => matches!(ty, ValueType::Int | ValueType::Float),
+            Self::DateRange => {
+                matches!(ty, ValueType::Date | ValueType::DateTime)
+            }
+            Self::Text => ty == ValueType::Str,
+            Self::Unique => matches!(
+                ty,
+                ValueType::Int
+                    | ValueType::Float
+                    | ValueType::Str
+                    | ValueType::Date
+                    | ValueType::DateTime
+            ),
+        }
+    }
+}
+
+/// Whether `ty` supports any constraints at all.
+pub fn is_constrainable(ty: ValueType) -> bool {
+    [
+        Rule::Required,
+        Rule::Range,
+        Rule::DateRange,
+        Rule::Text,
+        Rule::Unique,
+    ]
+    .iter()
+    .any(|r| r.applies_to(ty))
+}
+
+/// A value which breaks one of its prop's constraints. This is returned out
+/// of [crate::db_ops::DbModel::save] for propvals so that controllers can
+/// tell it apart from other errors, and show it next to the input.
+#[derive(Debug)]
+pub struct ValidationError(pub String);
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+impl std::error::Error for ValidationError {}
+
+/// Values with nothing in them. Saving these is how the UI clears a prop.
+pub fn is_empty(value: &Value) -> bool {
+    match value {
+        Value::Str(s) => s.trim().is_empty(),
+        Value::MultiStr(tags) => tags.is_empty(),
+        Value::Relation(pages) => pages.is_empty(),
+        Value::Files(files) => files.is_empty(),
+        _ => false,
+    }
+}
+
+impl Constraints {
+    /// Check `value` against every constraint except uniqueness, which
+    /// needs the database. The error explains what is wrong with the value.
+    pub fn check(&self, value: &Value) -> Result<(), String> {
+        if is_empty(value) {
+            return if self.required {
+                Err("a value is required".into())
+            } else {
+                Ok(())
+            };
+        }
+        match value {
+            Value::Int(v) => self.check_range(*v as f64),
+            Value::Float(v) => self.check_range(*v),
+            Value::Date(v) => self.check_date(*v),
+            Value::DateTime(v) => self.check_date(v.date_naive()),
+            Value::Str(v) => self.check_text(v),
+            _ => Ok(()),
+        }
+    }
+    fn check_range(&self, value: f64) -> Result<(), String> {
+        match (self.min, self.max) {
+            (Some(min), _) if value < min => {
+                Err(format!("must be at least {min}"))
+            }
+            (_, Some(max)) if value > max => {
+                Err(format!("must be at most {max}"))
+            }
+            _ => Ok(()),
+        }
+    }
+    fn check_date(&self, value: NaiveDate) -> Result<(), String> {
+        match (self.min_date, self.max_date) {
+            (Some(min), _) if value < min => {
+                Err(format!("must be on or after {min}"))
+            }
+            (_, Some(max)) if value > max => {
+                Err(format!("must be on or before {max}"))
+            }
+            _ => Ok(()),
+        }
+    }
+    fn check_text(&self, value: &str) -> Result<(), String> {
+        let len = value.chars().count() as i64;
+        if let Some(min) = self.min_length {
+            if len < min.into() {
+                return Err(format!("must be at least {min} characters"));
+            }
+        }
+        if let Some(max) = self.max_length {
+            if len > max.into() {
+                return Err(format!("must be at most {max} characters"));
+            }
+        }
+        if let Some(pattern) = &self.pattern {
+            // Patterns are checked when constraints are saved, so one which
+            // doesn't compile can only come from editing the database.
+            let re = Regex::new(pattern)
+                .map_err(|_| format!("the pattern {pattern} is invalid"))?;
+            if !re.is_match(value) {
+                return Err(format!("must match the pattern {pattern}"));
+            }
+        }
+
+        Ok(())
+    }
+    /// Check that the constraints make sense together. The error explains
+    /// what to fix.
+    pub fn validate(&self) -> Result<(), String> {
+        if [self.min, self.max]
+            .iter()
+            .flatten()
+            .any(|n| !n.is_finite())
+        {
+            return Err("bounds must be finite numbers".into());
+        }
+        if let (Some(min), Some(max)) = (self.min, self.max) {
+            if min > max {
+                return Err("the minimum is more than the maximum".into());
+            }
+        }
+        if let (Some(min), Some(max)) = (self.min_date, self.max_date) {
+            if min > max {
+                return Err("the earliest date is after the latest".into());
+            }
+        }
+        if let (Some(min), Some(max)) = (self.min_length, self.max_length) {
+            if min > max {
+                return Err(
+                    "the minimum length is more than the maximum".into()
+                );
+            }
+        }
+        if self.min_length.is_some_and(|l| l < 0)
+            || self.max_length.is_some_and(|l| l < 0)
+        {
+            return Err("lengths cannot be negative".into());
+        }
+        if let Some(pattern) = &self.pattern {
+            if let Err(e) = Regex::new(pattern) {
+                return Err(format!("the pattern is invalid: {e}"));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check() {
+        let constraints = Constraints {
+            required: true,
+            min: Some(0.0),
+            max: Some(10.0),
+            min_date: NaiveDate::from_ymd_opt(2024, 1, 1),
+            pattern: Some(r"^[A-Z]+-\d+$".into()),
+            max_length: Some(8),
+            ..Default::default()
+        };
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        let cases = [
+            (Value::Int(-1), Err("must be at least 0".to_string())),
+            (Value::Int(0), Ok(())),
+            (Value::Float(10.5), Err("must be at most 10".to_string())),
+            (
+                Value::Date(date(2023, 12, 31)),
+                Err("must be on or after 2024-01-01".to_string()),
+            ),
+            (Value::Date(date(2024, 1, 1)), Ok(())),
+            (
+                Value::Str("  ".into()),
+                Err("a value is required".to_string()),
+            ),
+            (Value::Str("NC-12".into()), Ok(())),
+            (
+                Value::Str("nc-12".into()),
+                Err(r"must match the pattern ^[A-Z]+-\d+$".to_string()),
+            ),
+            (
+                Value::Str("NC-123456".into()),
+                Err("must be at most 8 characters".to_string()),
+            ),
+            (
+                Value::MultiStr(vec![]),
+                Err("a value is required".to_string()),
+            ),
+            (Value::Bool(false), Ok(())),
+        ];
+        for (value, expected) in cases {
+            assert_eq!(constraints.check(&value), expected, "{value:?}");
+        }
+        let optional = Constraints {
+            pattern: Some("^x$".into()),
+            ..Default::default()
+        };
+        assert_eq!(optional.check(&Value::Str("".into())), Ok(()));
+    }
+}
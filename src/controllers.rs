@@ -1,15 +1,18 @@
 use super::{
-    auth, components, components::Component, db_ops, db_ops::DbModel,
-    errors::ServerError, filter, htmx, models, models::AppState, prop_val, pw,
-    routes::Route, session,
+    auth, components, components::Component, constraint, conversion, db_ops,
+    db_ops::DbModel, errors::ServerError, export, file, filter, formula, htmx,
+    models, models::AppState, number_format, prop_val, pw, rollup,
+    routes::Route, search, session, template, view,
 };
 use anyhow::Result;
 use axum::{
-    extract::{Path, Query, State},
+    body::StreamBody,
+    extract::{Multipart, Path, Query, State},
     http::{HeaderMap, HeaderValue, StatusCode},
-    response::IntoResponse,
+    response::{IntoResponse, Redirect},
     Form,
 };
+use chrono_tz::Tz;
 use futures::join;
 use serde::Deserialize;
 use std::collections::{HashMap, HashSet};
@@ -71,39 +74,287 @@ pub async fn get_htmx_js() -> impl IntoResponse {
     (headers, include_str!("./htmx-1.9.9.vendor.js"))
 }
 
-pub async fn get_collection(
-    State(AppState { db }): State<AppState>,
-    Path(id): Path<i32>,
-    headers: HeaderMap,
-) -> Result<impl IntoResponse, ServerError> {
-    let name = db_ops::get_collection_name(&db, id).await?;
+/// The collection, with the pages of `view` and tabs for its other views.
+/// The pages are filtered by the [filter::models::AdHocQuery] in `params`,
+/// if there is one.
+async fn render_collection(
+    db: &sqlx::PgPool,
+    headers: &HeaderMap,
+    view: view::models::View,
+    params: &[(String, String)],
+) -> Result<String> {
+    let id = view.collection_id;
+    let tz = get_user_tz(db, headers).await?;
+    // Links are often written by hand, so a mistake in the ad-hoc filters is
+    // shown on the page, which falls back to the filters of the view.
+    let adhoc = async {
+        let query = filter::models::AdHocQuery::parse(params)?;
+        resolve_adhoc_query(db, &view, &query, tz, get_user_id(headers)?)
+            .await?;
+        Ok::<_, anyhow::Error>(query)
+    }
+    .await;
+    let (adhoc, adhoc_error) = match adhoc {
+        Ok(query) => (query, None),
+        Err(e) => (Default::default(), Some(e.to_string())),
+    };
+    let list_query = view::db_ops::ListViewQuery { collection_id: id };
+    let (name, views) = join!(
+        db_ops::get_collection_name(db, id),
+        view::models::View::list(db, &list_query)
+    );
+    let name = name?;
+    let collection = components::Collection {
+        id,
+        name: name.clone(),
+        view,
+        views: views?,
+        adhoc,
+        adhoc_error,
+    };
+
     Ok(if headers.contains_key("Hx-Request") {
-        components::Collection { id, name }.render()
+        collection.render()
     } else {
         components::Page {
             title: &format!("Workspace ({name})"),
-            children: Box::new(components::Collection { id, name }),
+            children: Box::new(collection),
         }
         .render()
     })
 }
 
+pub async fn get_collection(
+    State(AppState { db }): State<AppState>,
+    Path(id): Path<i32>,
+    Query(params): Query<Vec<(String, String)>>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, ServerError> {
+    let view = view::db_ops::get_default_view(&db, id).await?;
+
+    Ok(render_collection(&db, &headers, view, &params).await?)
+}
+
+pub async fn get_collection_view(
+    State(AppState { db }): State<AppState>,
+    Path((collection_id, view_id)): Path<(i32, i32)>,
+    Query(params): Query<Vec<(String, String)>>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, ServerError> {
+    let view = view::models::View::get(
+        &db,
+        &view::db_ops::GetViewQuery { id: view_id },
+    )
+    .await?;
+    if view.collection_id != collection_id {
+        return Err(anyhow::anyhow!(
+            "view {view_id} is not in collection {collection_id}"
+        )
+        .into());
+    }
+
+    Ok(render_collection(&db, &headers, view, &params).await?)
+}
+
+/// The display timezone of the requesting user. Protected routes are behind
+/// [crate::middleware::auth], so the session should always be present.
+async fn get_user_tz(db: &sqlx::PgPool, headers: &HeaderMap) -> Result<Tz> {
+    db_ops::get_user_timezone(db, get_user_id(headers)?).await
+}
+
+/// The ID of the user making the request.
+fn get_user_id(headers: &HeaderMap) -> Result<i32> {
+    let session = session::Session::from_headers(headers)
+        .ok_or_else(|| anyhow::anyhow!("session is missing"))?;
+    Ok(session.user.id)
+}
+
+/// What relative date filters are resolved against for the requesting user;
+/// see [get_user_tz].
+async fn get_relative_date_ctx(
+    db: &sqlx::PgPool,
+    headers: &HeaderMap,
+) -> Result<filter::models::RelativeDateCtx> {
+    let session = session::Session::from_headers(headers)
+        .ok_or_else(|| anyhow::anyhow!("session is missing"))?;
+    let (tz, week_start) = join!(
+        db_ops::get_user_timezone(db, session.user.id),
+        db_ops::get_user_week_start(db, session.user.id)
+    );
+    Ok(filter::models::RelativeDateCtx::now(tz?, week_start?))
+}
+
+/// Look up the props and values of `query` in the collection of `view`.
+/// Values are written like those of filter forms; see [parse_filter_value].
+async fn resolve_adhoc_query(
+    db: &sqlx::PgPool,
+    view: &view::models::View,
+    query: &filter::models::AdHocQuery,
+    tz: Tz,
+    user_id: i32,
+) -> Result<filter::models::AdHocFilters> {
+    use filter::models::{FilterKind, FilterType, FilterValue};
+    let collection_id = view.collection_id;
+    let props = db_ops::get_prop_set(db, collection_id).await?;
+    let mut props =
+        with_computed_result_types(db, collection_id, props).await?;
+    // Pages can't be sorted by title, so the title is only a filter prop.
+    let sort = match &query.sort {
+        Some(sort) => {
+            let prop = filter::models::find_prop(&props, &sort.prop)?;
+            Some(models::ViewSort {
+                view_id: view.id,
+                prop_id: Some(prop.id),
+                r#type: Some(sort.r#type),
+            })
+        }
+        None => None,
+    };
+    props.push(filter::models::get_title_prop(collection_id));
+    let mut filters = vec![];
+    for adhoc in &query.filters {
+        let prop = filter::models::find_prop(&props, &adhoc.prop)?;
+        let kind = FilterKind::new(adhoc.r#type, prop.type_id)?;
+        let input = adhoc.value.as_str();
+        let value = async {
+            Ok::<_, anyhow::Error>(match kind {
+                _ if adhoc.r#type == FilterType::IsEmpty => {
+                    filter::db_ops::get_default_value(
+                        db,
+                        prop.id,
+                        adhoc.r#type,
+                        prop.type_id,
+                        user_id,
+                    )
+                    .await?
+                }
+                FilterKind::Single(models::ValueType::Str) => {
+                    if adhoc.r#type == FilterType::Matches {
+                        filter::db_ops::validate_regex(db, input).await?;
+                    }
+                    FilterValue::Text {
+                        value: input.to_string(),
+                        case_sensitive: false,
+                    }
+                }
+                FilterKind::Single(models::ValueType::Bool) => {
+                    FilterValue::Single(models::Value::Bool(input.parse()?))
+                }
+                FilterKind::Single(value_type) => FilterValue::Single(
+                    parse_filter_value(db, prop.id, value_type, input, tz)
+                        .await?,
+                ),
+                FilterKind::Range(value_type) => {
+                    let Some((start, end)) = input.split_once("..") else {
+                        anyhow::bail!("{input} should look like start..end");
+                    };
+                    let (start, end) = join!(
+                        parse_filter_value(db, prop.id, value_type, start, tz),
+                        parse_filter_value(db, prop.id, value_type, end, tz)
+                    );
+                    FilterValue::Range(start?, end?)
+                }
+                FilterKind::RelativeDate => {
+                    let (period, amount) = match input.split_once(':') {
+                        Some((period, amount)) => (period, amount.parse()?),
+                        None => (input, 0),
+                    };
+                    FilterValue::Relative {
+                        date: filter::models::RelativeDate::parse(
+                            period, amount,
+                        )?,
+                        value_type: prop.type_id,
+                    }
+                }
+            })
+        }
+        .await
+        .map_err(|e| {
+            anyhow::anyhow!(
+                "{input} is not a valid value for {}: {e}",
+                prop.name
+            )
+        })?;
+        filters.push(filter::models::Filter {
+            id: 0,
+            view_id: view.id,
+            prop_id: prop.id,
+            group_id: None,
+            r#type: adhoc.r#type,
+            value,
+            enabled: true,
+        });
+    }
+
+    Ok(filter::models::AdHocFilters {
+        filters,
+        sort,
+        replace: query.replace,
+    })
+}
+
+/// The ad-hoc filters of `query`, unless it is empty; see
+/// [resolve_adhoc_query].
+async fn get_adhoc_filters(
+    db: &sqlx::PgPool,
+    view: &view::models::View,
+    query: &filter::models::AdHocQuery,
+    tz: Tz,
+    user_id: i32,
+) -> Result<Option<filter::models::AdHocFilters>> {
+    if query.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(
+        resolve_adhoc_query(db, view, query, tz, user_id).await?,
+    ))
+}
+
 #[derive(Deserialize)]
 pub struct CpQuery {
     page: Option<i32>,
+    search: Option<String>,
 }
-pub async fn collection_pages(
+pub async fn view_pages(
+    headers: HeaderMap,
     State(AppState { db }): State<AppState>,
-    Query(CpQuery { page }): Query<CpQuery>,
-    Path(collection_id): Path<i32>,
+    Query(CpQuery { page, search }): Query<CpQuery>,
+    Query(params): Query<Vec<(String, String)>>,
+    Path(view_id): Path<i32>,
 ) -> Result<impl IntoResponse, ServerError> {
-    let (pages, props) =
-        db_ops::list_pages(&db, collection_id, page.unwrap_or(0)).await?;
+    let ctx = get_relative_date_ctx(&db, &headers).await?;
+    let view = view::models::View::get(
+        &db,
+        &view::db_ops::GetViewQuery { id: view_id },
+    )
+    .await?;
+    let adhoc_query = filter::models::AdHocQuery::parse(&params)?;
+    let adhoc = get_adhoc_filters(
+        &db,
+        &view,
+        &adhoc_query,
+        ctx.tz,
+        get_user_id(&headers)?,
+    )
+    .await?;
+    let (mut pages, props) = db_ops::list_pages(
+        &db,
+        &view,
+        Some(page.unwrap_or(0)),
+        &ctx,
+        search::models::normalize(search.as_deref()),
+        adhoc.as_ref(),
+    )
+    .await?;
+    for page in &mut pages {
+        page.localize(ctx.tz);
+    }
 
     Ok(components::PageList {
         pages: &pages,
         props: &props,
-        collection_id,
+        view: &view,
+        adhoc: &adhoc_query,
     }
     .render())
 }
@@ -134,1155 +385,1272 @@ pub async fn collection_prop_order(
     })
 }
 
-pub async fn new_bool_propval_form(
-    Path((page_id, prop_id)): Path<(i32, i32)>,
-) -> impl IntoResponse {
-    prop_val::models::PropVal {
-        page_id,
-        prop_id,
-        value: models::Value::Bool(false),
-    }
-    .render()
+async fn get_prop_manager_data(
+    db: &sqlx::PgPool,
+    collection_id: i32,
+) -> Result<(Vec<models::Prop>, Vec<models::Collection>)> {
+    let prop_query = db_ops::ListPropQuery {
+        collection_id: Some(collection_id),
+        order_in: None,
+        exact_ids: None,
+    };
+    let (props, collections) = join!(
+        models::Prop::list(db, &prop_query),
+        db_ops::list_collections(db)
+    );
+
+    Ok((props?, collections?))
 }
 
-#[derive(Deserialize)]
-pub struct PvbForm {
-    value: Option<String>,
+/// Every change on the prop management screen re-renders the whole thing.
+async fn render_prop_manager(
+    db: &sqlx::PgPool,
+    collection_id: i32,
+    error: Option<&str>,
+) -> Result<String> {
+    let (props, collections) = get_prop_manager_data(db, collection_id).await?;
+
+    Ok(components::PropManager {
+        collection_id,
+        props: &props,
+        new_prop_form: components::NewPropForm {
+            collection_id,
+            name: "",
+            type_id: models::ValueType::Str,
+            collections: &collections,
+        },
+        error,
+    }
+    .render())
 }
 
-pub async fn save_pv_bool(
+pub async fn get_prop_manager(
+    headers: HeaderMap,
     State(AppState { db }): State<AppState>,
-    Path((page_id, prop_id)): Path<(i32, i32)>,
-    Form(PvbForm { value }): Form<PvbForm>,
+    Path(collection_id): Path<i32>,
 ) -> Result<impl IntoResponse, ServerError> {
-    let pvb = prop_val::models::PropVal {
-        page_id,
-        prop_id,
-        value: models::Value::Bool(value.is_some()),
+    let (props, collections) =
+        get_prop_manager_data(&db, collection_id).await?;
+    let manager = components::PropManager {
+        collection_id,
+        props: &props,
+        new_prop_form: components::NewPropForm {
+            collection_id,
+            name: "",
+            type_id: models::ValueType::Str,
+            collections: &collections,
+        },
+        error: None,
     };
-    pvb.save(&db).await?;
-    Ok(pvb.render())
-}
 
-pub async fn new_int_propval_form(
-    Path((page_id, prop_id)): Path<(i32, i32)>,
-) -> impl IntoResponse {
-    prop_val::models::PropVal {
-        page_id,
-        prop_id,
-        value: models::Value::Int(0),
-    }
-    .render()
+    Ok(if headers.contains_key("Hx-Request") {
+        manager.render()
+    } else {
+        components::Page {
+            title: "Props",
+            children: Box::new(manager),
+        }
+        .render()
+    })
 }
 
 #[derive(Deserialize)]
-pub struct PvIntForm {
-    value: i64,
+pub struct NewPropFormQuery {
+    type_id: Option<i32>,
+    name: Option<String>,
 }
-pub async fn save_pv_int(
+pub async fn get_new_prop_form(
     State(AppState { db }): State<AppState>,
-    Path((page_id, prop_id)): Path<(i32, i32)>,
-    Form(PvIntForm { value }): Form<PvIntForm>,
+    Path(collection_id): Path<i32>,
+    Query(query): Query<NewPropFormQuery>,
 ) -> Result<impl IntoResponse, ServerError> {
-    let existing = prop_val::models::PropVal {
-        page_id,
-        prop_id,
-        value: models::Value::Int(value),
+    let Ok(type_id) =
+        query.type_id.map(models::ValueType::try_from).transpose()
+    else {
+        return Ok((StatusCode::BAD_REQUEST, "Invalid type").into_response());
     };
-    existing.save(&db).await?;
-    Ok(existing.render())
-}
+    let collections = db_ops::list_collections(&db).await?;
 
-pub async fn new_float_propval_form(
-    Path((page_id, prop_id)): Path<(i32, i32)>,
-) -> impl IntoResponse {
-    prop_val::models::PropVal {
-        page_id,
-        prop_id,
-        value: models::Value::Float(0.0),
+    Ok(components::NewPropForm {
+        collection_id,
+        name: query.name.as_deref().unwrap_or_default(),
+        type_id: type_id.unwrap_or(models::ValueType::Str),
+        collections: &collections,
     }
     .render()
+    .into_response())
 }
 
 #[derive(Deserialize)]
-pub struct PvFloatForm {
-    value: f64,
+pub struct NewPropForm {
+    name: String,
+    type_id: i32,
+    /// Only sent for relation props.
+    target_collection_id: Option<i32>,
 }
-pub async fn save_pv_float(
+pub async fn handle_new_prop_submit(
     State(AppState { db }): State<AppState>,
-    Path((page_id, prop_id)): Path<(i32, i32)>,
-    Form(PvFloatForm { value }): Form<PvFloatForm>,
+    Path(collection_id): Path<i32>,
+    Form(form): Form<NewPropForm>,
 ) -> Result<impl IntoResponse, ServerError> {
-    let pv = prop_val::models::PropVal {
-        page_id,
-        prop_id,
-        value: models::Value::Float(value),
+    let Ok(type_id) = models::ValueType::try_from(form.type_id) else {
+        return Ok((StatusCode::BAD_REQUEST, "Invalid type").into_response());
     };
-    pv.save(&db).await?;
-    Ok(pv.render())
+    let name = form.name.trim();
+    if name.is_empty() {
+        return Ok(render_prop_manager(
+            &db,
+            collection_id,
+            Some("props need a name"),
+        )
+        .await?
+        .into_response());
+    }
+    db_ops::create_prop(
+        &db,
+        collection_id,
+        name,
+        type_id,
+        form.target_collection_id,
+    )
+    .await?;
+
+    Ok(render_prop_manager(&db, collection_id, None)
+        .await?
+        .into_response())
 }
 
-pub async fn new_date_propval_form(
-    Path((page_id, prop_id)): Path<(i32, i32)>,
-) -> impl IntoResponse {
-    prop_val::models::PropVal {
-        page_id,
-        prop_id,
-        value: models::Value::Date(chrono::Local::now().date_naive()),
+#[derive(Deserialize)]
+pub struct PropRenameForm {
+    name: String,
+}
+pub async fn handle_prop_rename(
+    State(AppState { db }): State<AppState>,
+    Path(prop_id): Path<i32>,
+    Form(form): Form<PropRenameForm>,
+) -> Result<impl IntoResponse, ServerError> {
+    let mut prop =
+        models::Prop::get(&db, &db_ops::GetPropQuery { id: prop_id }).await?;
+    let name = form.name.trim();
+    if name.is_empty() {
+        return Ok(render_prop_manager(
+            &db,
+            prop.collection_id,
+            Some("props need a name"),
+        )
+        .await?);
     }
-    .render()
+    prop.name = name.to_string();
+    prop.save(&db).await?;
+
+    Ok(render_prop_manager(&db, prop.collection_id, None).await?)
+}
+
+pub async fn get_prop_delete_confirmation(
+    State(AppState { db }): State<AppState>,
+    Path(prop_id): Path<i32>,
+) -> Result<impl IntoResponse, ServerError> {
+    let prop_query = db_ops::GetPropQuery { id: prop_id };
+    let (prop, value_count) = join!(
+        models::Prop::get(&db, &prop_query),
+        db_ops::count_prop_values(&db, prop_id)
+    );
+
+    Ok(components::PropDeleteConfirmation {
+        prop: &prop?,
+        value_count: value_count?,
+    }
+    .render())
+}
+
+pub async fn delete_prop(
+    State(AppState { db }): State<AppState>,
+    Path(prop_id): Path<i32>,
+) -> Result<impl IntoResponse, ServerError> {
+    let prop =
+        models::Prop::get(&db, &db_ops::GetPropQuery { id: prop_id }).await?;
+    let collection_id = prop.collection_id;
+    // Uploads are deleted by cascade, but their contents are kept outside of
+    // the database.
+    let files = if prop.type_id == models::ValueType::Files {
+        file::db_ops::list_prop_files(&db, prop_id).await?
+    } else {
+        vec![]
+    };
+    prop.delete(&db).await?;
+    let storage = file::storage::get_storage();
+    for f in &files {
+        file::db_ops::delete_unused_contents(&db, storage.as_ref(), f).await?;
+    }
+
+    Ok(render_prop_manager(&db, collection_id, None).await?)
 }
 
 #[derive(Deserialize)]
-pub struct PvDateForm {
-    value: chrono::NaiveDate,
+pub struct TypeChangeQuery {
+    type_id: Option<i32>,
 }
-pub async fn save_pv_date(
+pub async fn get_type_change_form(
+    headers: HeaderMap,
     State(AppState { db }): State<AppState>,
-    Path((page_id, prop_id)): Path<(i32, i32)>,
-    Form(PvDateForm { value }): Form<PvDateForm>,
+    Path(prop_id): Path<i32>,
+    Query(query): Query<TypeChangeQuery>,
 ) -> Result<impl IntoResponse, ServerError> {
-    let existing = prop_val::models::PropVal {
-        page_id,
-        prop_id,
-        value: models::Value::Date(value),
+    let Ok(to) = query.type_id.map(models::ValueType::try_from).transpose()
+    else {
+        return Ok((StatusCode::BAD_REQUEST, "Invalid type").into_response());
+    };
+    let prop =
+        models::Prop::get(&db, &db_ops::GetPropQuery { id: prop_id }).await?;
+    let preview = match to {
+        Some(to) => Some(prop.preview_type_change(&db, to).await?),
+        None => None,
     };
-    existing.save(&db).await?;
-    Ok(existing.render())
+    let form = conversion::components::TypeChangeForm {
+        prop: &prop,
+        to,
+        preview: preview.as_ref(),
+    };
+
+    Ok(if headers.contains_key("Hx-Request") {
+        form.render()
+    } else {
+        components::Page {
+            title: "Change Type",
+            children: Box::new(form),
+        }
+        .render()
+    }
+    .into_response())
 }
 
-pub async fn increment_prop_order(
+#[derive(Deserialize)]
+pub struct TypeChangeForm {
+    type_id: i32,
+}
+pub async fn handle_type_change(
     State(AppState { db }): State<AppState>,
-    Path((collection_id, prop_id)): Path<(i32, i32)>,
+    Path(prop_id): Path<i32>,
+    Form(form): Form<TypeChangeForm>,
 ) -> Result<impl IntoResponse, ServerError> {
+    let Ok(to) = models::ValueType::try_from(form.type_id) else {
+        return Ok((StatusCode::BAD_REQUEST, "Invalid type").into_response());
+    };
     let mut prop =
         models::Prop::get(&db, &db_ops::GetPropQuery { id: prop_id }).await?;
-    let mut next_props = models::Prop::list(
-        &db,
-        &db_ops::ListPropQuery {
-            collection_id: Some(collection_id),
-            order_in: Some(vec![prop.order - 1]),
-            exact_ids: None,
-        },
+    prop.change_type(&db, to).await?;
+
+    Ok(htmx::redirect(
+        HeaderMap::new(),
+        &Route::CollectionProps(Some(prop.collection_id)).as_string(),
     )
-    .await?;
-    if next_props.is_empty() {
-        // So, as I point out near the definition of ListPropQuery, this final
-        // query is basically wasteful. It would make more sense to just get all
-        // the props up-front, but I'm doing this purely because I want to
-        // retain the headway gained in setting up the query builder,
-        // and I'm figuring on remove this once I have another real
-        // use-case for the query builder.
-        let all_props = models::Prop::list(
-            &db,
-            &db_ops::ListPropQuery {
-                collection_id: Some(collection_id),
-                order_in: None,
-                exact_ids: None,
-            },
-        )
-        .await?;
+    .into_response())
+}
 
-        return Ok(components::PropOrderForm { props: all_props }.render());
-    };
+#[derive(Deserialize)]
+pub struct ConstraintForm {
+    required: Option<String>,
+    unique: Option<String>,
+    min: Option<String>,
+    max: Option<String>,
+    min_date: Option<String>,
+    max_date: Option<String>,
+    pattern: Option<String>,
+    min_length: Option<String>,
+    max_length: Option<String>,
+}
+
+/// Parse an optional form field, where a blank input means "no value."
+fn parse_optional<T: std::str::FromStr>(
+    field: &Option<String>,
+    label: &str,
+) -> Result<Option<T>, String> {
+    match field.as_deref().map(str::trim) {
+        None | Some("") => Ok(None),
+        Some(text) => text
+            .parse()
+            .map(Some)
+            .map_err(|_| format!(r#"{label} "{text}" is not valid"#)),
+    }
+}
 
-    if next_props.len() != 1 {
-        // If we've got more than one prop in the same collection with the same
-        // order, we've encountered a data-integrity invariant, so we will
-        // panic.
-        panic!("collection {collection_id} did not have exactly one prop");
-    };
-    let mut next_prop = next_props
-        .pop()
-        .expect("we have exactly 1 prop in next_props");
-
-    prop.order -= 1;
-    next_prop.order += 1;
-
-    // Soooooooooooooooooooooooooooooooooooooooooooooooooooooooooooooooooooooo
-    // it would be very nice to have transactions here. But obviously, there
-    // is a problem -- my DbModel abstraction does not support transactions.
-    //
-    // Hm, that's a dilemma.
-    //
-    // I gotta figure that one out.
-    //
-    // Later, though...
-    prop.save(&db).await?;
-    next_prop.save(&db).await?;
-
-    // So, as I point out near the definition of ListPropQuery, this final
-    // query is basically wasteful. It would make more sense to just get all
-    // the props up-front, but I'm doing this purely because I want to retain
-    // the headway gained in setting up the query builder, and I'm figuring
-    // on remove this once I have another real use-case for the query
-    // builder.
-    let all_props = models::Prop::list(
-        &db,
-        &db_ops::ListPropQuery {
-            collection_id: Some(collection_id),
-            order_in: None,
-            exact_ids: None,
-        },
-    )
-    .await?;
+impl ConstraintForm {
+    fn parse(
+        &self,
+        prop_id: i32,
+    ) -> Result<constraint::models::Constraints, String> {
+        let constraints = constraint::models::Constraints {
+            prop_id,
+            required: self.required.is_some(),
+            unique: self.unique.is_some(),
+            min: parse_optional(&self.min, "minimum")?,
+            max: parse_optional(&self.max, "maximum")?,
+            min_date: parse_optional(&self.min_date, "earliest date")?,
+            max_date: parse_optional(&self.max_date, "latest date")?,
+            pattern: self.pattern.as_ref().filter(|p| !p.is_empty()).cloned(),
+            min_length: parse_optional(&self.min_length, "minimum length")?,
+            max_length: parse_optional(&self.max_length, "maximum length")?,
+        };
+        constraints.validate()?;
 
-    Ok(components::PropOrderForm { props: all_props }.render())
+        Ok(constraints)
+    }
 }
 
-pub async fn decrement_prop_order(
+pub async fn get_constraint_form(
+    headers: HeaderMap,
     State(AppState { db }): State<AppState>,
-    Path((collection_id, prop_id)): Path<(i32, i32)>,
+    Path(prop_id): Path<i32>,
 ) -> Result<impl IntoResponse, ServerError> {
-    let mut prop =
+    let prop =
         models::Prop::get(&db, &db_ops::GetPropQuery { id: prop_id }).await?;
-    let mut next_props = models::Prop::list(
-        &db,
-        &db_ops::ListPropQuery {
-            collection_id: Some(collection_id),
-            order_in: Some(vec![prop.order + 1]),
-            exact_ids: None,
-        },
-    )
-    .await?;
-    if next_props.is_empty() {
-        let all_props = models::Prop::list(
-            &db,
-            &db_ops::ListPropQuery {
-                collection_id: Some(collection_id),
-                order_in: None,
-                exact_ids: None,
-            },
-        )
-        .await?;
-        return Ok(components::PropOrderForm { props: all_props }.render());
-    };
-
-    if next_props.len() != 1 {
-        panic!("collection {collection_id} did not have exactly one prop");
+    let constraints = constraint::db_ops::get_constraints(&db, prop_id).await?;
+    let form = constraint::components::ConstraintForm {
+        prop: &prop,
+        constraints: &constraints,
+        error: None,
+        saved: false,
     };
-    let mut next_prop = next_props
-        .pop()
-        .expect("we have exactly 1 prop in next_props");
-
-    prop.order += 1;
-    next_prop.order -= 1;
-    prop.save(&db).await?;
-    next_prop.save(&db).await?;
-    let all_props = models::Prop::list(
-        &db,
-        &db_ops::ListPropQuery {
-            collection_id: Some(collection_id),
-            order_in: None,
-            exact_ids: None,
-        },
-    )
-    .await?;
-
-    Ok(components::PropOrderForm { props: all_props }.render())
-}
 
-pub async fn new_page_form(
-    Path(collection_id): Path<i32>,
-    headers: HeaderMap,
-) -> impl IntoResponse {
-    let form = components::NewPage {
-        collection_id,
-        page_id: None,
-        title: None,
-    };
-    if headers.contains_key("Hx-Request") {
+    Ok(if headers.contains_key("Hx-Request") {
         form.render()
     } else {
         components::Page {
+            title: "Constraints",
             children: Box::new(form),
-            title: "New Page",
         }
         .render()
-    }
+    })
 }
 
-pub async fn existing_page_form(
+pub async fn handle_constraint_save(
     State(AppState { db }): State<AppState>,
-    Path(page_id): Path<i32>,
+    Path(prop_id): Path<i32>,
+    Form(form): Form<ConstraintForm>,
+) -> Result<impl IntoResponse, ServerError> {
+    let prop =
+        models::Prop::get(&db, &db_ops::GetPropQuery { id: prop_id }).await?;
+    let (constraints, error) = match form.parse(prop_id) {
+        Ok(constraints) => {
+            constraint::db_ops::save_constraints(&db, &constraints).await?;
+            (constraints, None)
+        }
+        Err(e) => (
+            constraint::db_ops::get_constraints(&db, prop_id).await?,
+            Some(e),
+        ),
+    };
+
+    Ok(constraint::components::ConstraintForm {
+        prop: &prop,
+        constraints: &constraints,
+        error: error.as_deref(),
+        saved: error.is_none(),
+    }
+    .render())
+}
+
+#[derive(Deserialize)]
+pub struct NumberFormatForm {
+    number_format: String,
+    decimals: Option<String>,
+    currency_code: Option<String>,
+}
+impl NumberFormatForm {
+    fn parse(&self) -> Result<number_format::models::NumberFormat, String> {
+        let decimals = parse_optional(&self.decimals, "decimals")?;
+        number_format::models::NumberFormat::parse(
+            &self.number_format,
+            decimals.unwrap_or_default(),
+            self.currency_code.clone(),
+        )
+        .map_err(|e| e.to_string())
+    }
+}
+
+pub async fn get_number_format_form(
     headers: HeaderMap,
+    State(AppState { db }): State<AppState>,
+    Path(prop_id): Path<i32>,
 ) -> Result<impl IntoResponse, ServerError> {
-    let page =
-        models::Page::get(&db, &db_ops::GetPageQuery { id: page_id }).await?;
+    let prop =
+        models::Prop::get(&db, &db_ops::GetPropQuery { id: prop_id }).await?;
+    let form = number_format::components::NumberFormatForm {
+        prop: &prop,
+        error: None,
+        saved: false,
+    };
 
     Ok(if headers.contains_key("Hx-Request") {
-        components::PageOverview { page: &page }.render()
+        form.render()
     } else {
         components::Page {
-            title: &page.title,
-            children: Box::new(components::PageOverview { page: &page }),
+            title: "Number Format",
+            children: Box::new(form),
         }
         .render()
     })
 }
 
-#[derive(Deserialize)]
-pub struct PageFormSubmission {
-    id: i32,
-    collection_id: i32,
-    title: String,
-}
-pub async fn save_existing_page_form(
+pub async fn handle_number_format_save(
     State(AppState { db }): State<AppState>,
-    Form(form): Form<PageFormSubmission>,
+    Path(prop_id): Path<i32>,
+    Form(form): Form<NumberFormatForm>,
 ) -> Result<impl IntoResponse, ServerError> {
-    let page = models::Page {
-        id: form.id,
-        collection_id: form.collection_id,
-        title: form.title,
-        props: vec![],
-        content: None,
+    let mut prop =
+        models::Prop::get(&db, &db_ops::GetPropQuery { id: prop_id }).await?;
+    let error = match form.parse() {
+        Ok(format) => {
+            number_format::db_ops::save_number_format(&db, prop_id, &format)
+                .await?;
+            prop.number_format = format;
+            None
+        }
+        Err(e) => Some(e),
     };
-    page.save(&db).await?;
 
-    Ok(components::PageForm { page: &page }.render())
+    Ok(number_format::components::NumberFormatForm {
+        prop: &prop,
+        error: error.as_deref(),
+        saved: error.is_none(),
+    }
+    .render())
 }
 
-#[derive(Debug, Deserialize)]
-pub struct PageForm {
-    id: Option<i32>,
-    title: String,
-}
-pub async fn handle_page_submission(
-    State(AppState { db }): State<AppState>,
-    Path(collection_id): Path<i32>,
-    Form(form): Form<PageForm>,
-) -> Result<impl IntoResponse, ServerError> {
-    if let Some(id) = form.id {
-        models::Page {
-            id,
-            collection_id,
-            title: form.title,
-            props: Vec::new(),
-            content: None,
+/// Save `pv` as an edit by `user_id`, and render it. A value which breaks
+/// one of the prop's constraints is not saved; instead, `shown` is rendered
+/// with the reason next to it, swapped over the whole input.
+async fn save_propval(
+    db: &sqlx::PgPool,
+    pv: &prop_val::models::PropVal,
+    shown: &prop_val::models::PropVal,
+    user_id: i32,
+) -> Result<axum::response::Response> {
+    match pv.save(db).await {
+        Ok(()) => {
+            db_ops::touch_page(db, pv.page_id, user_id).await?;
+            Ok(pv.render().into_response())
         }
-        .save(&db)
-        .await?;
-    } else {
-        db_ops::create_page(&db, collection_id, &form.title).await?;
+        Err(e) => render_validation_error(e, shown),
     }
-    let headers = HeaderMap::new();
-    let collection_route = Route::Collection(Some(collection_id));
-    Ok((
-        axum::http::StatusCode::CREATED,
-        htmx::redirect(headers, &collection_route.as_string()),
-        "OK",
-    ))
 }
 
-pub async fn get_content_form(
-    State(AppState { db }): State<AppState>,
-    Path(page_id): Path<i32>,
-) -> Result<impl IntoResponse, ServerError> {
-    let existing_content =
-        models::Content::get(&db, &db_ops::GetDbModelQuery { page_id }).await;
-    if let Ok(c) = existing_content {
-        Ok(c.render())
-    } else {
-        Ok(models::Content {
-            page_id,
-            content: "".to_string(),
+/// Render a [constraint::models::ValidationError] next to `shown`, passing
+/// along any other error. Propval inputs don't swap their own responses, so
+/// this sets `Hx-Reswap` to put the error on the page.
+fn render_validation_error(
+    e: anyhow::Error,
+    shown: &prop_val::models::PropVal,
+) -> Result<axum::response::Response> {
+    match e.downcast_ref::<constraint::models::ValidationError>() {
+        Some(error) => {
+            let mut headers = HeaderMap::new();
+            headers.insert("Hx-Reswap", HeaderValue::from_static("outerHTML"));
+            Ok((
+                headers,
+                constraint::components::InvalidPropVal {
+                    propval: shown,
+                    error: &error.0,
+                }
+                .render(),
+            )
+                .into_response())
         }
-        .render())
+        None => Err(e),
+    }
+}
+
+pub async fn new_bool_propval_form(
+    Path((page_id, prop_id)): Path<(i32, i32)>,
+) -> impl IntoResponse {
+    prop_val::models::PropVal {
+        page_id,
+        prop_id,
+        value: models::Value::Bool(false),
     }
+    .render()
 }
 
 #[derive(Deserialize)]
-pub struct ContentForm {
-    content: String,
+pub struct PvbForm {
+    value: Option<String>,
 }
-pub async fn handle_content_submission(
+
+pub async fn save_pv_bool(
+    headers: HeaderMap,
     State(AppState { db }): State<AppState>,
-    Path(page_id): Path<i32>,
-    Form(ContentForm { content }): Form<ContentForm>,
+    Path((page_id, prop_id)): Path<(i32, i32)>,
+    Form(PvbForm { value }): Form<PvbForm>,
 ) -> Result<impl IntoResponse, ServerError> {
-    let content = models::Content { page_id, content };
-    content.save(&db).await?;
-    Ok(components::ContentDisplay {
+    let user_id = get_user_id(&headers)?;
+    let pvb = prop_val::models::PropVal {
         page_id,
-        content: Some(&content),
+        prop_id,
+        value: models::Value::Bool(value.is_some()),
+    };
+    Ok(save_propval(&db, &pvb, &pvb, user_id).await?)
+}
+
+/// Render an int or float propval in its prop's
+/// [number_format::models::NumberFormat].
+async fn render_number_propval(
+    db: &sqlx::PgPool,
+    pv: &prop_val::models::PropVal,
+) -> Result<String> {
+    let prop =
+        models::Prop::get(db, &db_ops::GetPropQuery { id: pv.prop_id }).await?;
+    Ok(number_format::components::NumberCell {
+        propval: pv,
+        format: &prop.number_format,
     }
     .render())
 }
 
-pub async fn get_filter_toolbar(
-    State(AppState { db }): State<AppState>,
-    Path(collection_id): Path<i32>,
-) -> Result<impl IntoResponse, ServerError> {
-    let filters = filter::models::Filter::list(
-        &db,
-        &filter::db_ops::ListFilterQuery { collection_id },
-    )
-    .await?;
-    if filters.is_empty() {
-        let get_prop_name = |_: i32| panic!("no props");
-        Ok(filter::components::FilterToolbar {
-            filters,
-            collection_id,
-            get_prop_name: &get_prop_name,
-        }
-        .render())
-    } else {
-        let all_props = filters.iter().map(|f| f.prop_id).collect::<Vec<i32>>();
-        let prop_query = db_ops::ListPropQuery {
-            collection_id: None,
-            exact_ids: Some(all_props),
-            order_in: None,
-        };
-        let props = models::Prop::list(&db, &prop_query).await;
-        let mut props = props?;
-        let prop_by_id =
-            props.drain(..).fold(HashMap::new(), |mut acc, prop| {
-                acc.insert(prop.id, prop);
-                acc
-            });
-        let get_prop_name = |prop_id: i32| {
-            &prop_by_id
-                .get(&prop_id)
-                .expect("you lookup a prop that exists")
-                .name as &str
-        };
-        Ok(filter::components::FilterToolbar {
-            filters,
-            collection_id,
-            get_prop_name: &get_prop_name,
+/// Like [save_propval], for int and float propvals.
+async fn save_number_propval(
+    db: &sqlx::PgPool,
+    pv: &prop_val::models::PropVal,
+    user_id: i32,
+) -> Result<axum::response::Response> {
+    match pv.save(db).await {
+        Ok(()) => {
+            db_ops::touch_page(db, pv.page_id, user_id).await?;
+            Ok(render_number_propval(db, pv).await?.into_response())
         }
-        .render())
+        Err(e) => render_validation_error(e, pv),
     }
 }
 
-// This needs to be async because axum requires route handlers to be async.
-pub async fn hide_filter_toolbar(Path(collection_id): Path<i32>) -> String {
-    filter::components::FilterToolbarPlaceholder { collection_id }.render()
+pub async fn new_int_propval_form(
+    State(AppState { db }): State<AppState>,
+    Path((page_id, prop_id)): Path<(i32, i32)>,
+) -> Result<impl IntoResponse, ServerError> {
+    let pv = prop_val::models::PropVal {
+        page_id,
+        prop_id,
+        value: models::Value::Int(0),
+    };
+    Ok(render_number_propval(&db, &pv).await?)
 }
 
-pub async fn get_bool_filter_chip(
+#[derive(Deserialize)]
+pub struct PvIntForm {
+    value: i64,
+}
+pub async fn save_pv_int(
+    headers: HeaderMap,
     State(AppState { db }): State<AppState>,
-    Path(id): Path<i32>,
+    Path((page_id, prop_id)): Path<(i32, i32)>,
+    Form(PvIntForm { value }): Form<PvIntForm>,
 ) -> Result<impl IntoResponse, ServerError> {
-    let filter = &filter::models::Filter::get(
-        &db,
-        &filter::db_ops::GetFilterQuery {
-            id,
-            value_type: models::ValueType::Bool,
-            variant: filter::db_ops::Variant::Single,
-        },
-    )
-    .await?;
-    let related_prop =
-        models::Prop::get(&db, &db_ops::GetPropQuery { id: filter.prop_id })
-            .await?;
-
-    Ok(filter::components::FilterChip {
-        filter,
-        prop_name: &related_prop.name,
-    }
-    .render())
+    let user_id = get_user_id(&headers)?;
+    let existing = prop_val::models::PropVal {
+        page_id,
+        prop_id,
+        value: models::Value::Int(value),
+    };
+    Ok(save_number_propval(&db, &existing, user_id).await?)
 }
 
-pub async fn get_bool_filter_form(
+pub async fn new_float_propval_form(
     State(AppState { db }): State<AppState>,
-    Path(id): Path<i32>,
+    Path((page_id, prop_id)): Path<(i32, i32)>,
 ) -> Result<impl IntoResponse, ServerError> {
-    let filter = &filter::models::Filter::get(
-        &db,
-        &filter::db_ops::GetFilterQuery {
-            id,
-            value_type: models::ValueType::Bool,
-            variant: filter::db_ops::Variant::Single,
-        },
-    )
-    .await?;
-    let related_prop =
-        models::Prop::get(&db, &db_ops::GetPropQuery { id: filter.prop_id })
-            .await?;
-
-    Ok(filter::components::FilterForm {
-        filter,
-        prop_name: &related_prop.name,
-    }
-    .render())
+    let pv = prop_val::models::PropVal {
+        page_id,
+        prop_id,
+        value: models::Value::Float(0.0),
+    };
+    Ok(render_number_propval(&db, &pv).await?)
 }
 
-fn trigger_event(
-    mut headers: HeaderMap,
-    event_name: &'static str,
-) -> HeaderMap {
-    if headers.contains_key("Hx-Trigger") {
-        let val = headers.get("Hx-Trigger").expect("we know it's here");
-        let as_str = val.to_str().expect("existing trigger is ascii");
-        let new_header = format!("{as_str}, {event_name}");
-        headers.insert(
-            "Hx-Trigger",
-            HeaderValue::from_str(&new_header)
-                .expect("event name is a valid string"),
-        );
-    } else {
-        headers.insert(
-            "Hx-Trigger",
-            HeaderValue::from_str(event_name)
-                .expect("event name is a valid string"),
-        );
-    }
-
-    headers
+#[derive(Deserialize)]
+pub struct PvFloatForm {
+    value: f64,
 }
-
-/// Insert the Hx-Trugger header into a HeaderMap such that the table reload
-/// will occur on the collection view.
-fn reload_table(headers: HeaderMap) -> HeaderMap {
-    trigger_event(headers, "reload-pages")
+pub async fn save_pv_float(
+    headers: HeaderMap,
+    State(AppState { db }): State<AppState>,
+    Path((page_id, prop_id)): Path<(i32, i32)>,
+    Form(PvFloatForm { value }): Form<PvFloatForm>,
+) -> Result<impl IntoResponse, ServerError> {
+    let user_id = get_user_id(&headers)?;
+    let pv = prop_val::models::PropVal {
+        page_id,
+        prop_id,
+        value: models::Value::Float(value),
+    };
+    Ok(save_number_propval(&db, &pv, user_id).await?)
 }
 
-fn reload_add_filter_button(headers: HeaderMap) -> HeaderMap {
-    trigger_event(headers, "reload-add-filter-button")
+pub async fn new_str_propval_form(
+    Path((page_id, prop_id)): Path<(i32, i32)>,
+) -> impl IntoResponse {
+    prop_val::models::PropVal {
+        page_id,
+        prop_id,
+        value: models::Value::Str(String::new()),
+    }
+    .render()
 }
 
-#[derive(Debug, Deserialize)]
-pub struct BoolForm {
+#[derive(Deserialize)]
+pub struct PvStrForm {
     value: String,
 }
-pub async fn handle_bool_form_submit(
+pub async fn save_pv_str(
+    headers: HeaderMap,
     State(AppState { db }): State<AppState>,
-    Path(id): Path<i32>,
-    Form(form): Form<BoolForm>,
+    Path((page_id, prop_id)): Path<(i32, i32)>,
+    Form(PvStrForm { value }): Form<PvStrForm>,
 ) -> Result<impl IntoResponse, ServerError> {
-    let filter = filter::models::Filter::get(
-        &db,
-        &filter::db_ops::GetFilterQuery {
-            id,
-            value_type: models::ValueType::Bool,
-            variant: filter::db_ops::Variant::Single,
-        },
-    )
-    .await?;
-    let related_prop =
-        models::Prop::get(&db, &db_ops::GetPropQuery { id: filter.prop_id })
-            .await?;
-    let mut headers = HeaderMap::new();
-    let new_filter = if form.value == "true" {
-        filter::models::Filter {
-            id: filter.id,
-            r#type: filter::models::FilterType::Eq,
-            prop_id: filter.prop_id,
-            value: filter::models::FilterValue::Single(models::Value::Bool(
-                true,
-            )),
-        }
-    } else if form.value == "false" {
-        filter::models::Filter {
-            id: filter.id,
-            r#type: filter::models::FilterType::Eq,
-            prop_id: filter.prop_id,
-            value: filter::models::FilterValue::Single(models::Value::Bool(
-                false,
-            )),
-        }
-    } else if form.value == "is-empty" {
-        filter::models::Filter {
-            id: filter.id,
-            r#type: filter::models::FilterType::IsEmpty,
-            prop_id: filter.prop_id,
-            // We'll keep the same value as before when we're getting marked
-            // as 'is-empty'
-            value: filter.value,
-        }
-    } else {
-        return Ok((
-            StatusCode::BAD_REQUEST,
-            headers,
-            "Invalid value".to_string(),
-        ));
+    let user_id = get_user_id(&headers)?;
+    let pv = prop_val::models::PropVal {
+        page_id,
+        prop_id,
+        value: models::Value::Str(value),
     };
+    Ok(save_propval(&db, &pv, &pv, user_id).await?)
+}
 
-    new_filter.save(&db).await?;
-    headers = reload_table(headers);
-
-    Ok((
-        StatusCode::OK,
-        headers,
-        filter::components::FilterChip {
-            filter: &new_filter,
-            prop_name: &related_prop.name,
-        }
-        .render(),
-    ))
+#[derive(Deserialize)]
+pub struct PvTagForm {
+    value: String,
 }
 
-pub async fn get_int_filter_chip(
-    State(AppState { db }): State<AppState>,
-    Path(id): Path<i32>,
-) -> Result<impl IntoResponse, ServerError> {
-    let filter = &filter::models::Filter::get(
-        &db,
-        &filter::db_ops::GetFilterQuery {
-            id,
-            value_type: models::ValueType::Int,
-            variant: filter::db_ops::Variant::Single,
+/// Fetch the tags currently stored on a page. A missing `propval_multistr`
+/// row just means the page has no tags yet.
+async fn get_pv_tags(
+    db: &sqlx::PgPool,
+    page_id: i32,
+    prop_id: i32,
+) -> Result<prop_val::models::PropVal> {
+    let existing = prop_val::models::PropVal::get(
+        db,
+        &prop_val::db_ops::PvGetQuery {
+            page_id,
+            prop_id,
+            data_type: Some(models::ValueType::MultiStr),
         },
     )
-    .await?;
-    let related_prop =
-        models::Prop::get(&db, &db_ops::GetPropQuery { id: filter.prop_id })
-            .await?;
-
-    Ok(filter::components::FilterChip {
-        filter,
-        prop_name: &related_prop.name,
+    .await;
+    match existing {
+        Ok(pv) => Ok(pv),
+        Err(e) => match e.downcast_ref::<sqlx::Error>() {
+            Some(sqlx::Error::RowNotFound) => Ok(prop_val::models::PropVal {
+                page_id,
+                prop_id,
+                value: models::Value::MultiStr(vec![]),
+            }),
+            _ => Err(e),
+        },
     }
-    .render())
 }
 
-pub async fn get_float_filter_chip(
+pub async fn add_pv_tag(
+    headers: HeaderMap,
     State(AppState { db }): State<AppState>,
-    Path(id): Path<i32>,
+    Path((page_id, prop_id)): Path<(i32, i32)>,
+    Form(PvTagForm { value }): Form<PvTagForm>,
 ) -> Result<impl IntoResponse, ServerError> {
-    let filter = &filter::models::Filter::get(
-        &db,
-        &filter::db_ops::GetFilterQuery {
-            id,
-            value_type: models::ValueType::Float,
-            variant: filter::db_ops::Variant::Single,
-        },
-    )
-    .await?;
-    let related_prop =
-        models::Prop::get(&db, &db_ops::GetPropQuery { id: filter.prop_id })
-            .await?;
-
-    Ok(filter::components::FilterChip {
-        filter,
-        prop_name: &related_prop.name,
+    let user_id = get_user_id(&headers)?;
+    let tag = value.trim();
+    if tag.chars().count() > 511 {
+        return Err(anyhow::anyhow!("tag is too long").into());
     }
-    .render())
+    if !tag.is_empty() {
+        prop_val::db_ops::add_tag(&db, page_id, prop_id, tag, user_id).await?;
+    }
+    let pv = get_pv_tags(&db, page_id, prop_id).await?;
+    Ok(pv.render().into_response())
 }
 
-pub async fn get_date_filter_chip(
+pub async fn remove_pv_tag(
+    headers: HeaderMap,
     State(AppState { db }): State<AppState>,
-    Path(id): Path<i32>,
+    Path((page_id, prop_id)): Path<(i32, i32)>,
+    Query(PvTagForm { value }): Query<PvTagForm>,
 ) -> Result<impl IntoResponse, ServerError> {
-    let filter = &filter::models::Filter::get(
-        &db,
-        &filter::db_ops::GetFilterQuery {
-            id,
-            value_type: models::ValueType::Date,
-            variant: filter::db_ops::Variant::Single,
-        },
-    )
-    .await?;
-    let related_prop =
-        models::Prop::get(&db, &db_ops::GetPropQuery { id: filter.prop_id })
-            .await?;
-
-    Ok(filter::components::FilterChip {
-        filter,
-        prop_name: &related_prop.name,
-    }
-    .render())
+    let user_id = get_user_id(&headers)?;
+    let removed =
+        prop_val::db_ops::remove_tag(&db, page_id, prop_id, &value, user_id)
+            .await;
+    let pv = get_pv_tags(&db, page_id, prop_id).await?;
+    Ok(match removed {
+        Ok(()) => pv.render().into_response(),
+        Err(e) => render_validation_error(e, &pv)?,
+    })
 }
 
-pub async fn get_date_rng_filter_chip(
+pub async fn get_tag_vocab(
     State(AppState { db }): State<AppState>,
-    Path(id): Path<i32>,
+    Path(prop_id): Path<i32>,
 ) -> Result<impl IntoResponse, ServerError> {
-    let filter = &filter::models::Filter::get(
-        &db,
-        &filter::db_ops::GetFilterQuery {
-            id,
-            value_type: models::ValueType::Date,
-            variant: filter::db_ops::Variant::Ranged,
-        },
-    )
-    .await?;
-    let related_prop =
-        models::Prop::get(&db, &db_ops::GetPropQuery { id: filter.prop_id })
-            .await?;
+    let tags = prop_val::db_ops::list_tag_vocab(&db, prop_id).await?;
+    Ok(components::TagVocab { tags: &tags }.render())
+}
 
-    Ok(filter::components::FilterChip {
-        filter,
-        prop_name: &related_prop.name,
+pub async fn new_date_propval_form(
+    Path((page_id, prop_id)): Path<(i32, i32)>,
+) -> impl IntoResponse {
+    prop_val::models::PropVal {
+        page_id,
+        prop_id,
+        value: models::Value::Date(chrono::Local::now().date_naive()),
     }
-    .render())
+    .render()
 }
 
-pub async fn get_float_rng_filter_chip(
+#[derive(Deserialize)]
+pub struct PvDateForm {
+    value: chrono::NaiveDate,
+}
+pub async fn save_pv_date(
+    headers: HeaderMap,
     State(AppState { db }): State<AppState>,
-    Path(id): Path<i32>,
+    Path((page_id, prop_id)): Path<(i32, i32)>,
+    Form(PvDateForm { value }): Form<PvDateForm>,
 ) -> Result<impl IntoResponse, ServerError> {
-    let filter = &filter::models::Filter::get(
-        &db,
-        &filter::db_ops::GetFilterQuery {
-            id,
-            value_type: models::ValueType::Float,
-            variant: filter::db_ops::Variant::Ranged,
-        },
-    )
-    .await?;
-    let related_prop =
-        models::Prop::get(&db, &db_ops::GetPropQuery { id: filter.prop_id })
-            .await?;
-
-    Ok(filter::components::FilterForm {
-        filter,
-        prop_name: &related_prop.name,
-    }
-    .render())
+    let user_id = get_user_id(&headers)?;
+    let existing = prop_val::models::PropVal {
+        page_id,
+        prop_id,
+        value: models::Value::Date(value),
+    };
+    Ok(save_propval(&db, &existing, &existing, user_id).await?)
 }
 
-pub async fn get_int_filter_form(
+pub async fn new_datetime_propval_form(
+    headers: HeaderMap,
     State(AppState { db }): State<AppState>,
-    Path(id): Path<i32>,
+    Path((page_id, prop_id)): Path<(i32, i32)>,
 ) -> Result<impl IntoResponse, ServerError> {
-    let filter = &filter::models::Filter::get(
-        &db,
-        &filter::db_ops::GetFilterQuery {
-            id,
-            value_type: models::ValueType::Int,
-            variant: filter::db_ops::Variant::Single,
-        },
-    )
-    .await?;
-    let related_prop =
-        models::Prop::get(&db, &db_ops::GetPropQuery { id: filter.prop_id })
-            .await?;
-
-    Ok(filter::components::FilterForm {
-        filter,
-        prop_name: &related_prop.name,
+    let tz = get_user_tz(&db, &headers).await?;
+    Ok(prop_val::models::PropVal {
+        page_id,
+        prop_id,
+        value: models::Value::DateTime(chrono::Utc::now().with_timezone(&tz)),
     }
     .render())
 }
 
-pub async fn get_float_filter_form(
+#[derive(Deserialize)]
+pub struct PvDateTimeForm {
+    /// Naive datetime from a `datetime-local` input, in the user's timezone.
+    value: String,
+}
+pub async fn save_pv_datetime(
+    headers: HeaderMap,
     State(AppState { db }): State<AppState>,
-    Path(id): Path<i32>,
+    Path((page_id, prop_id)): Path<(i32, i32)>,
+    Form(PvDateTimeForm { value }): Form<PvDateTimeForm>,
 ) -> Result<impl IntoResponse, ServerError> {
-    let filter = &filter::models::Filter::get(
-        &db,
-        &filter::db_ops::GetFilterQuery {
-            id,
-            value_type: models::ValueType::Float,
-            variant: filter::db_ops::Variant::Single,
-        },
-    )
-    .await?;
-    let related_prop =
-        models::Prop::get(&db, &db_ops::GetPropQuery { id: filter.prop_id })
-            .await?;
-
-    Ok(filter::components::FilterForm {
-        filter,
-        prop_name: &related_prop.name,
-    }
-    .render())
+    let user_id = get_user_id(&headers)?;
+    let tz = get_user_tz(&db, &headers).await?;
+    let pv = prop_val::models::PropVal {
+        page_id,
+        prop_id,
+        value: models::Value::DateTime(models::parse_datetime_local(
+            &value, tz,
+        )?),
+    };
+    Ok(save_propval(&db, &pv, &pv, user_id).await?)
 }
 
-pub async fn get_float_rng_filter_form(
+pub async fn get_select_propval_form(
     State(AppState { db }): State<AppState>,
-    Path(id): Path<i32>,
+    Path((page_id, prop_id)): Path<(i32, i32)>,
 ) -> Result<impl IntoResponse, ServerError> {
-    let filter = &filter::models::Filter::get(
-        &db,
-        &filter::db_ops::GetFilterQuery {
-            id,
-            value_type: models::ValueType::Float,
-            variant: filter::db_ops::Variant::Single,
-        },
-    )
-    .await?;
-    let related_prop =
-        models::Prop::get(&db, &db_ops::GetPropQuery { id: filter.prop_id })
-            .await?;
+    let options_query = db_ops::ListSelectOptionQuery { prop_id };
+    let pv_query = prop_val::db_ops::PvGetQuery {
+        page_id,
+        prop_id,
+        data_type: Some(models::ValueType::Select),
+    };
+    let (options, existing) = join!(
+        models::SelectOption::list(&db, &options_query),
+        prop_val::models::PropVal::get(&db, &pv_query)
+    );
+    let options = options?;
+    // Implicitly treating error as not-found here
+    let selected = match existing {
+        Ok(prop_val::models::PropVal {
+            value: models::Value::Select(option),
+            ..
+        }) => Some(option.id),
+        _ => None,
+    };
 
-    Ok(filter::components::FilterForm {
-        filter,
-        prop_name: &related_prop.name,
+    Ok(prop_val::components::SelectPropValForm {
+        page_id,
+        prop_id,
+        selected,
+        options: &options,
     }
     .render())
 }
 
-pub async fn get_date_filter_form(
+#[derive(Deserialize)]
+pub struct PvSelectForm {
+    /// Option ID, or an empty string to clear the propval.
+    value: String,
+}
+pub async fn save_pv_select(
+    headers: HeaderMap,
     State(AppState { db }): State<AppState>,
-    Path(id): Path<i32>,
+    Path((page_id, prop_id)): Path<(i32, i32)>,
+    Form(PvSelectForm { value }): Form<PvSelectForm>,
 ) -> Result<impl IntoResponse, ServerError> {
-    let filter = &filter::models::Filter::get(
+    let user_id = get_user_id(&headers)?;
+    if value.is_empty() {
+        let existing = prop_val::models::PropVal::get(
+            &db,
+            &prop_val::db_ops::PvGetQuery {
+                page_id,
+                prop_id,
+                data_type: Some(models::ValueType::Select),
+            },
+        )
+        .await;
+        if let Ok(existing) = existing {
+            if let Err(e) = existing.clone().delete(&db).await {
+                return Ok(render_validation_error(e, &existing)?);
+            }
+            db_ops::touch_page(&db, page_id, user_id).await?;
+        }
+        return Ok(components::NullPropvalButton {
+            post_href: &Route::PageSelectProp(Some((page_id, prop_id)))
+                .as_string(),
+        }
+        .render()
+        .into_response());
+    }
+    let option = models::SelectOption::get(
         &db,
-        &filter::db_ops::GetFilterQuery {
-            id,
-            value_type: models::ValueType::Date,
-            variant: filter::db_ops::Variant::Single,
-        },
+        &db_ops::GetSelectOptionQuery { id: value.parse()? },
     )
     .await?;
-    let related_prop =
-        models::Prop::get(&db, &db_ops::GetPropQuery { id: filter.prop_id })
-            .await?;
+    if option.prop_id != prop_id {
+        return Err(anyhow::anyhow!(
+            "option {} does not belong to prop {prop_id}",
+            option.id
+        )
+        .into());
+    }
+    let pv = prop_val::models::PropVal {
+        page_id,
+        prop_id,
+        value: models::Value::Select(option),
+    };
+    Ok(save_propval(&db, &pv, &pv, user_id).await?)
+}
+
+/// Fetch the links currently stored on a page. Like tags, a relation without
+/// any links is just empty.
+async fn get_pv_relation(
+    db: &sqlx::PgPool,
+    page_id: i32,
+    prop_id: i32,
+) -> Result<prop_val::models::PropVal> {
+    let existing = prop_val::models::PropVal::get(
+        db,
+        &prop_val::db_ops::PvGetQuery {
+            page_id,
+            prop_id,
+            data_type: Some(models::ValueType::Relation),
+        },
+    )
+    .await;
+    match existing {
+        Ok(pv) => Ok(pv),
+        Err(e) => match e.downcast_ref::<sqlx::Error>() {
+            Some(sqlx::Error::RowNotFound) => Ok(prop_val::models::PropVal {
+                page_id,
+                prop_id,
+                value: models::Value::Relation(vec![]),
+            }),
+            _ => Err(e),
+        },
+    }
+}
 
-    Ok(filter::components::FilterForm {
-        filter,
-        prop_name: &related_prop.name,
+#[derive(Deserialize)]
+pub struct PvRelationForm {
+    /// Target page ID
+    value: i32,
+}
+pub async fn add_pv_relation(
+    headers: HeaderMap,
+    State(AppState { db }): State<AppState>,
+    Path((page_id, prop_id)): Path<(i32, i32)>,
+    Form(PvRelationForm { value }): Form<PvRelationForm>,
+) -> Result<impl IntoResponse, ServerError> {
+    let user_id = get_user_id(&headers)?;
+    let (target, page) = join!(
+        db_ops::get_relation_target(&db, prop_id),
+        db_ops::get_related_page(&db, value)
+    );
+    if page?.collection_id != target? {
+        return Err(anyhow::anyhow!(
+            "page {value} cannot be linked by prop {prop_id}"
+        )
+        .into());
     }
-    .render())
+    prop_val::db_ops::add_relation(&db, page_id, prop_id, value, user_id)
+        .await?;
+    let pv = get_pv_relation(&db, page_id, prop_id).await?;
+    Ok(pv.render().into_response())
 }
 
-pub async fn get_date_rng_filter_form(
+pub async fn remove_pv_relation(
+    headers: HeaderMap,
     State(AppState { db }): State<AppState>,
-    Path(id): Path<i32>,
+    Path((page_id, prop_id)): Path<(i32, i32)>,
+    Query(PvRelationForm { value }): Query<PvRelationForm>,
 ) -> Result<impl IntoResponse, ServerError> {
-    let filter = &filter::models::Filter::get(
-        &db,
-        &filter::db_ops::GetFilterQuery {
-            id,
-            value_type: models::ValueType::Date,
-            variant: filter::db_ops::Variant::Ranged,
+    let user_id = get_user_id(&headers)?;
+    let removed = prop_val::db_ops::remove_relation(
+        &db, page_id, prop_id, value, user_id,
+    )
+    .await;
+    let pv = get_pv_relation(&db, page_id, prop_id).await?;
+    Ok(match removed {
+        Ok(()) => pv.render().into_response(),
+        Err(e) => render_validation_error(e, &pv)?,
+    })
+}
+
+/// Fetch the uploads currently attached to a page. Like links, a files
+/// propval without any files is just empty.
+async fn get_pv_files(
+    db: &sqlx::PgPool,
+    page_id: i32,
+    prop_id: i32,
+) -> Result<prop_val::models::PropVal> {
+    let existing = prop_val::models::PropVal::get(
+        db,
+        &prop_val::db_ops::PvGetQuery {
+            page_id,
+            prop_id,
+            data_type: Some(models::ValueType::Files),
         },
     )
-    .await?;
-    let related_prop =
-        models::Prop::get(&db, &db_ops::GetPropQuery { id: filter.prop_id })
-            .await?;
+    .await;
+    match existing {
+        Ok(pv) => Ok(pv),
+        Err(e) => match e.downcast_ref::<sqlx::Error>() {
+            Some(sqlx::Error::RowNotFound) => Ok(prop_val::models::PropVal {
+                page_id,
+                prop_id,
+                value: models::Value::Files(vec![]),
+            }),
+            _ => Err(e),
+        },
+    }
+}
 
-    Ok(filter::components::FilterForm {
-        filter,
-        prop_name: &related_prop.name,
+/// Accepts any number of `file` fields. Fields without a file, which
+/// browsers send when nothing was chosen, are skipped.
+pub async fn upload_pv_files(
+    headers: HeaderMap,
+    State(AppState { db }): State<AppState>,
+    Path((page_id, prop_id)): Path<(i32, i32)>,
+    mut multipart: Multipart,
+) -> Result<impl IntoResponse, ServerError> {
+    let user_id = get_user_id(&headers)?;
+    let prop =
+        models::Prop::get(&db, &db_ops::GetPropQuery { id: prop_id }).await?;
+    if prop.type_id != models::ValueType::Files {
+        return Err(
+            anyhow::anyhow!("prop {prop_id} is not a files prop").into()
+        );
     }
-    .render())
+    let storage = file::storage::get_storage();
+    let mut uploaded = false;
+    while let Some(field) = multipart.next_field().await? {
+        if field.name() != Some("file") {
+            continue;
+        }
+        let name = field.file_name().map(str::to_string);
+        let mime_type = field.content_type().map(str::to_string);
+        let bytes = field.bytes().await?;
+        if name.as_deref().unwrap_or_default().is_empty() && bytes.is_empty() {
+            continue;
+        }
+        file::db_ops::upload(
+            &db,
+            storage.as_ref(),
+            page_id,
+            prop_id,
+            name.as_deref(),
+            mime_type.as_deref(),
+            &bytes,
+        )
+        .await?;
+        uploaded = true;
+    }
+    if uploaded {
+        db_ops::touch_page(&db, page_id, user_id).await?;
+    }
+
+    Ok(get_pv_files(&db, page_id, prop_id).await?.render())
 }
 
 #[derive(Deserialize)]
-pub struct IntForm {
-    value: i64,
-    r#type: i32,
+pub struct PvFileForm {
+    /// File ID
+    value: i32,
 }
-pub async fn handle_int_form_submit(
+pub async fn remove_pv_file(
+    headers: HeaderMap,
     State(AppState { db }): State<AppState>,
-    Path(id): Path<i32>,
-    Form(form): Form<IntForm>,
+    Path((page_id, prop_id)): Path<(i32, i32)>,
+    Query(PvFileForm { value }): Query<PvFileForm>,
 ) -> Result<impl IntoResponse, ServerError> {
-    let filter = filter::models::Filter::get(
-        &db,
-        &filter::db_ops::GetFilterQuery {
-            id,
-            value_type: models::ValueType::Int,
-            variant: filter::db_ops::Variant::Single,
-        },
-    )
-    .await?;
-    let related_prop =
-        models::Prop::get(&db, &db_ops::GetPropQuery { id: filter.prop_id })
+    let user_id = get_user_id(&headers)?;
+    let mut pv = get_pv_files(&db, page_id, prop_id).await?;
+    let before = pv.clone();
+    if let models::Value::Files(ref mut files) = pv.value {
+        let Some(removed) = files.iter().position(|f| f.id == value) else {
+            return Ok(pv.render().into_response());
+        };
+        let removed = files.remove(removed);
+        let response = save_propval(&db, &pv, &before, user_id).await?;
+        let storage = file::storage::get_storage();
+        file::db_ops::delete_unused_contents(&db, storage.as_ref(), &removed)
             .await?;
-    let mut headers = HeaderMap::new();
-    let form_type = filter::models::FilterType::from_int(form.r#type);
-    let new_filter = filter::models::Filter {
-        id: filter.id,
-        prop_id: filter.prop_id,
-        r#type: form_type,
-        value: filter::models::FilterValue::Single(models::Value::Int(
-            form.value,
-        )),
-    };
-    new_filter.save(&db).await?;
-    headers = reload_table(headers);
-
-    Ok((
-        headers,
-        filter::components::FilterChip {
-            filter: &new_filter,
-            prop_name: &related_prop.name,
-        }
-        .render(),
-    ))
+        return Ok(response);
+    }
+    Ok(pv.render().into_response())
 }
 
-#[derive(Deserialize)]
-pub struct FloatForm {
-    value: f64,
-    r#type: i32,
-}
-pub async fn handle_float_form_submit(
+/// Streams an upload with its own content type. Images are shown inline,
+/// and everything else is downloaded; see [file::models::is_image].
+pub async fn get_file(
     State(AppState { db }): State<AppState>,
     Path(id): Path<i32>,
-    Form(form): Form<FloatForm>,
 ) -> Result<impl IntoResponse, ServerError> {
-    let filter = filter::models::Filter::get(
-        &db,
-        &filter::db_ops::GetFilterQuery {
-            id,
-            value_type: models::ValueType::Float,
-            variant: filter::db_ops::Variant::Single,
-        },
-    )
-    .await?;
-    let related_prop =
-        models::Prop::get(&db, &db_ops::GetPropQuery { id: filter.prop_id })
-            .await?;
+    let file = file::db_ops::get_file(&db, id).await?;
+    let storage = file::storage::get_storage();
+    let reader = storage.open(&file.stored_name).await?;
     let mut headers = HeaderMap::new();
-    let form_type = filter::models::FilterType::from_int(form.r#type);
-    let new_filter = filter::models::Filter {
-        id: filter.id,
-        prop_id: filter.prop_id,
-        r#type: form_type,
-        value: filter::models::FilterValue::Single(models::Value::Float(
-            form.value,
-        )),
-    };
-    new_filter.save(&db).await?;
-    headers = reload_table(headers);
+    headers.insert("content-type", HeaderValue::from_str(&file.mime_type)?);
+    headers.insert("content-length", HeaderValue::from(file.size));
+    headers.insert(
+        "content-disposition",
+        HeaderValue::from_str(&file::models::content_disposition(
+            &file.name,
+            &file.mime_type,
+        ))?,
+    );
+    headers.insert(
+        "x-content-type-options",
+        HeaderValue::from_static("nosniff"),
+    );
+    // Uploads never change, but they are only for signed-in users.
+    headers.insert(
+        "cache-control",
+        HeaderValue::from_static("private, max-age=31536000, immutable"),
+    );
 
-    Ok((
-        headers,
-        filter::components::FilterChip {
-            filter: &new_filter,
-            prop_name: &related_prop.name,
-        }
-        .render(),
-    ))
+    Ok((headers, StreamBody::new(file::storage::read_chunks(reader))))
 }
 
-#[derive(Deserialize)]
-pub struct DateForm {
-    value: chrono::NaiveDate,
-    r#type: i32,
-}
-pub async fn handle_date_form_submit(
+pub async fn export_view(
+    headers: HeaderMap,
     State(AppState { db }): State<AppState>,
-    Path(id): Path<i32>,
-    Form(form): Form<DateForm>,
+    Path(view_id): Path<i32>,
+    Query(params): Query<Vec<(String, String)>>,
 ) -> Result<impl IntoResponse, ServerError> {
-    let filter = filter::models::Filter::get(
+    let ctx = get_relative_date_ctx(&db, &headers).await?;
+    let view = view::models::View::get(
         &db,
-        &filter::db_ops::GetFilterQuery {
-            id,
-            value_type: models::ValueType::Date,
-            variant: filter::db_ops::Variant::Single,
-        },
+        &view::db_ops::GetViewQuery { id: view_id },
     )
     .await?;
-    let related_prop =
-        models::Prop::get(&db, &db_ops::GetPropQuery { id: filter.prop_id })
+    let adhoc_query = filter::models::AdHocQuery::parse(&params)?;
+    let adhoc = get_adhoc_filters(
+        &db,
+        &view,
+        &adhoc_query,
+        ctx.tz,
+        get_user_id(&headers)?,
+    )
+    .await?;
+    let (pages, props) =
+        export::db_ops::list_all_pages(&db, &view, &ctx, adhoc.as_ref())
             .await?;
     let mut headers = HeaderMap::new();
-    let form_type = filter::models::FilterType::from_int(form.r#type);
-    let new_filter = filter::models::Filter {
-        id: filter.id,
-        prop_id: filter.prop_id,
-        r#type: form_type,
-        value: filter::models::FilterValue::Single(models::Value::Date(
-            form.value,
-        )),
-    };
-    new_filter.save(&db).await?;
-    headers = reload_table(headers);
+    headers.insert(
+        "content-type",
+        HeaderValue::from_static("text/csv; charset=utf-8"),
+    );
+    headers.insert(
+        "content-disposition",
+        HeaderValue::from_str(&file::models::content_disposition(
+            &format!("collection-{}-{}.csv", view.collection_id, view.name),
+            "text/csv",
+        ))?,
+    );
 
-    Ok((
-        headers,
-        filter::components::FilterChip {
-            filter: &new_filter,
-            prop_name: &related_prop.name,
-        }
-        .render(),
-    ))
+    Ok((headers, export::models::to_csv(&pages, &props)))
 }
 
 #[derive(Deserialize)]
-pub struct FloatRngForm {
-    start: f64,
-    end: f64,
-    r#type: i32,
+pub struct RelationOptionsQuery {
+    selected: Option<i32>,
 }
-pub async fn handle_float_rng_form_submit(
+pub async fn get_relation_options(
     State(AppState { db }): State<AppState>,
-    Path(id): Path<i32>,
-    Form(form): Form<FloatRngForm>,
+    Path(prop_id): Path<i32>,
+    Query(RelationOptionsQuery { selected }): Query<RelationOptionsQuery>,
 ) -> Result<impl IntoResponse, ServerError> {
-    let filter = filter::models::Filter::get(
-        &db,
-        &filter::db_ops::GetFilterQuery {
-            id,
-            value_type: models::ValueType::Float,
-            variant: filter::db_ops::Variant::Ranged,
-        },
-    )
-    .await?;
-    let related_prop =
-        models::Prop::get(&db, &db_ops::GetPropQuery { id: filter.prop_id })
-            .await?;
-    let mut headers = HeaderMap::new();
-    let form_type = filter::models::FilterType::from_int(form.r#type);
-    let new_filter = filter::models::Filter {
-        id: filter.id,
-        prop_id: filter.prop_id,
-        r#type: form_type,
-        value: filter::models::FilterValue::Range(
-            models::Value::Float(form.start),
-            models::Value::Float(form.end),
-        ),
-    };
-    new_filter.save(&db).await?;
-    headers = reload_table(headers);
+    let target = db_ops::get_relation_target(&db, prop_id).await?;
+    let pages = db_ops::list_related_page_candidates(&db, target).await?;
 
-    Ok((
-        headers,
-        filter::components::FilterChip {
-            filter: &new_filter,
-            prop_name: &related_prop.name,
-        }
-        .render(),
-    ))
+    Ok(components::RelatedPageOptionList {
+        pages: &pages,
+        selected,
+    }
+    .render())
 }
 
-pub async fn get_int_rng_filter_chip(
+#[derive(Deserialize)]
+pub struct UserOptionsQuery {
+    selected: Option<i32>,
+}
+pub async fn get_user_options(
     State(AppState { db }): State<AppState>,
-    Path(id): Path<i32>,
+    Query(UserOptionsQuery { selected }): Query<UserOptionsQuery>,
 ) -> Result<impl IntoResponse, ServerError> {
-    let filter = &filter::models::Filter::get(
-        &db,
-        &filter::db_ops::GetFilterQuery {
-            id,
-            value_type: models::ValueType::Int,
-            variant: filter::db_ops::Variant::Ranged,
-        },
-    )
-    .await?;
-    let related_prop =
-        models::Prop::get(&db, &db_ops::GetPropQuery { id: filter.prop_id })
-            .await?;
+    let users = models::User::list(&db, &()).await?;
 
-    Ok(filter::components::FilterChip {
-        filter,
-        prop_name: &related_prop.name,
+    Ok(components::UserOptionList {
+        users: &users,
+        selected,
     }
     .render())
 }
 
-#[derive(Deserialize)]
-pub struct DateRngForm {
-    start: chrono::NaiveDate,
-    end: chrono::NaiveDate,
-    r#type: i32,
-}
-pub async fn handle_date_rng_form_submit(
+pub async fn increment_prop_order(
     State(AppState { db }): State<AppState>,
-    Path(id): Path<i32>,
-    Form(form): Form<DateRngForm>,
+    Path((collection_id, prop_id)): Path<(i32, i32)>,
 ) -> Result<impl IntoResponse, ServerError> {
-    let filter = filter::models::Filter::get(
+    let mut prop =
+        models::Prop::get(&db, &db_ops::GetPropQuery { id: prop_id }).await?;
+    let mut next_props = models::Prop::list(
         &db,
-        &filter::db_ops::GetFilterQuery {
-            id,
-            value_type: models::ValueType::Date,
-            variant: filter::db_ops::Variant::Ranged,
+        &db_ops::ListPropQuery {
+            collection_id: Some(collection_id),
+            order_in: Some(vec![prop.order - 1]),
+            exact_ids: None,
         },
     )
     .await?;
-    let related_prop =
-        models::Prop::get(&db, &db_ops::GetPropQuery { id: filter.prop_id })
-            .await?;
-    let mut headers = HeaderMap::new();
-    let form_type = filter::models::FilterType::from_int(form.r#type);
-    let new_filter = filter::models::Filter {
-        id: filter.id,
-        prop_id: filter.prop_id,
-        r#type: form_type,
-        value: filter::models::FilterValue::Range(
-            models::Value::Date(form.start),
-            models::Value::Date(form.end),
-        ),
+    if next_props.is_empty() {
+        // So, as I point out near the definition of ListPropQuery, this final
+        // query is basically wasteful. It would make more sense to just get all
+        // the props up-front, but I'm doing this purely because I want to
+        // retain the headway gained in setting up the query builder,
+        // and I'm figuring on remove this once I have another real
+        // use-case for the query builder.
+        let all_props = models::Prop::list(
+            &db,
+            &db_ops::ListPropQuery {
+                collection_id: Some(collection_id),
+                order_in: None,
+                exact_ids: None,
+            },
+        )
+        .await?;
+
+        return Ok(components::PropOrderForm { props: all_props }.render());
     };
-    new_filter.save(&db).await?;
-    headers = reload_table(headers);
 
-    Ok((
-        headers,
-        filter::components::FilterChip {
-            filter: &new_filter,
-            prop_name: &related_prop.name,
-        }
-        .render(),
-    ))
-}
+    if next_props.len() != 1 {
+        // If we've got more than one prop in the same collection with the same
+        // order, we've encountered a data-integrity invariant, so we will
+        // panic.
+        panic!("collection {collection_id} did not have exactly one prop");
+    };
+    let mut next_prop = next_props
+        .pop()
+        .expect("we have exactly 1 prop in next_props");
 
-pub async fn get_int_rng_filter_form(
-    State(AppState { db }): State<AppState>,
-    Path(id): Path<i32>,
-) -> Result<impl IntoResponse, ServerError> {
-    let filter = &filter::models::Filter::get(
+    prop.order -= 1;
+    next_prop.order += 1;
+
+    // Soooooooooooooooooooooooooooooooooooooooooooooooooooooooooooooooooooooo
+    // it would be very nice to have transactions here. But obviously, there
+    // is a problem -- my DbModel abstraction does not support transactions.
+    //
+    // Hm, that's a dilemma.
+    //
+    // I gotta figure that one out.
+    //
+    // Later, though...
+    prop.save(&db).await?;
+    next_prop.save(&db).await?;
+
+    // So, as I point out near the definition of ListPropQuery, this final
+    // query is basically wasteful. It would make more sense to just get all
+    // the props up-front, but I'm doing this purely because I want to retain
+    // the headway gained in setting up the query builder, and I'm figuring
+    // on remove this once I have another real use-case for the query
+    // builder.
+    let all_props = models::Prop::list(
         &db,
-        &filter::db_ops::GetFilterQuery {
-            id,
-            value_type: models::ValueType::Int,
-            variant: filter::db_ops::Variant::Ranged,
+        &db_ops::ListPropQuery {
+            collection_id: Some(collection_id),
+            order_in: None,
+            exact_ids: None,
         },
     )
     .await?;
-    let related_prop =
-        models::Prop::get(&db, &db_ops::GetPropQuery { id: filter.prop_id })
-            .await?;
 
-    Ok(filter::components::FilterForm {
-        filter,
-        prop_name: &related_prop.name,
-    }
-    .render())
+    Ok(components::PropOrderForm { props: all_props }.render())
 }
 
-#[derive(Deserialize)]
-pub struct IntRngForm {
-    start: i64,
-    end: i64,
-    r#type: i32,
-}
-pub async fn handle_int_rng_form_submit(
+pub async fn decrement_prop_order(
     State(AppState { db }): State<AppState>,
-    Path(id): Path<i32>,
-    Form(form): Form<IntRngForm>,
+    Path((collection_id, prop_id)): Path<(i32, i32)>,
 ) -> Result<impl IntoResponse, ServerError> {
-    let filter = filter::models::Filter::get(
+    let mut prop =
+        models::Prop::get(&db, &db_ops::GetPropQuery { id: prop_id }).await?;
+    let mut next_props = models::Prop::list(
         &db,
-        &filter::db_ops::GetFilterQuery {
-            id,
-            value_type: models::ValueType::Int,
-            variant: filter::db_ops::Variant::Ranged,
+        &db_ops::ListPropQuery {
+            collection_id: Some(collection_id),
+            order_in: Some(vec![prop.order + 1]),
+            exact_ids: None,
         },
     )
     .await?;
-    let related_prop =
-        models::Prop::get(&db, &db_ops::GetPropQuery { id: filter.prop_id })
-            .await?;
-    let mut headers = HeaderMap::new();
-    let form_type = filter::models::FilterType::from_int(form.r#type);
-    let new_filter = filter::models::Filter {
-        id: filter.id,
-        prop_id: filter.prop_id,
-        r#type: form_type,
-        value: filter::models::FilterValue::Range(
-            models::Value::Int(form.start),
-            models::Value::Int(form.end),
-        ),
+    if next_props.is_empty() {
+        let all_props = models::Prop::list(
+            &db,
+            &db_ops::ListPropQuery {
+                collection_id: Some(collection_id),
+                order_in: None,
+                exact_ids: None,
+            },
+        )
+        .await?;
+        return Ok(components::PropOrderForm { props: all_props }.render());
     };
-    new_filter.save(&db).await?;
-    headers = reload_table(headers);
 
-    Ok((
-        headers,
-        filter::components::FilterChip {
-            filter: &new_filter,
-            prop_name: &related_prop.name,
-        }
-        .render(),
-    ))
-}
+    if next_props.len() != 1 {
+        panic!("collection {collection_id} did not have exactly one prop");
+    };
+    let mut next_prop = next_props
+        .pop()
+        .expect("we have exactly 1 prop in next_props");
 
-pub async fn choose_prop_for_filter(
-    State(AppState { db }): State<AppState>,
-    Path(collection_id): Path<i32>,
-) -> Result<impl IntoResponse, ServerError> {
-    let props = models::Prop::list(
+    prop.order += 1;
+    next_prop.order -= 1;
+    prop.save(&db).await?;
+    next_prop.save(&db).await?;
+    let all_props = models::Prop::list(
         &db,
         &db_ops::ListPropQuery {
             collection_id: Some(collection_id),
@@ -1291,500 +1659,1142 @@ pub async fn choose_prop_for_filter(
         },
     )
     .await?;
-    let filters = filter::models::Filter::list(
-        &db,
-        &filter::db_ops::ListFilterQuery { collection_id },
-    )
-    .await?;
-    let mut props_with_filter = HashSet::new();
-    for f in filters {
-        props_with_filter.insert(f.prop_id);
-    }
-    let props: Vec<&models::Prop> = props
-        .iter()
-        .filter(|p| !props_with_filter.contains(&p.id))
-        .collect();
 
-    Ok(filter::components::ChoosePropForFilter { props: &props }.render())
+    Ok(components::PropOrderForm { props: all_props }.render())
 }
 
-pub async fn new_filter_type_select(
+pub async fn new_page_form(
     State(AppState { db }): State<AppState>,
-    Path(prop_id): Path<i32>,
+    Path(collection_id): Path<i32>,
+    headers: HeaderMap,
 ) -> Result<impl IntoResponse, ServerError> {
-    let prop =
-        models::Prop::get(&db, &db_ops::GetPropQuery { id: prop_id }).await?;
-    let options =
-        filter::models::FilterType::get_supported_filter_types(prop.type_id);
-    Ok(filter::components::NewFilterTypeOptions {
-        options: &options,
-        prop_id,
-        prop_type: prop.type_id,
-    }
-    .render())
-}
+    let templates = template::models::PageTemplate::list(
+        &db,
+        &template::db_ops::ListTemplateQuery { collection_id },
+    )
+    .await?;
+    let form = components::NewPage {
+        collection_id,
+        page_id: None,
+        title: None,
+        templates,
+        error: None,
+    };
 
-#[derive(Deserialize)]
-pub struct NewFilterQuery {
-    type_id: Option<i32>,
+    Ok(if headers.contains_key("Hx-Request") {
+        form.render()
+    } else {
+        components::Page {
+            children: Box::new(form),
+            title: "New Page",
+        }
+        .render()
+    })
 }
 
-pub async fn create_new_bool_filter(
+pub async fn existing_page_form(
     State(AppState { db }): State<AppState>,
-    Path(prop_id): Path<i32>,
-    Query(NewFilterQuery { type_id }): Query<NewFilterQuery>,
+    Path(page_id): Path<i32>,
+    headers: HeaderMap,
 ) -> Result<impl IntoResponse, ServerError> {
-    let r#type = if let Some(type_id) = type_id {
-        filter::models::FilterType::from_int(type_id)
-    } else {
-        filter::models::FilterType::Eq
-    };
-    let query = db_ops::GetPropQuery { id: prop_id };
-    let (prop, filter) = join!(
-        models::Prop::get(&db, &query),
-        filter::db_ops::create_filter(
-            &db,
-            prop_id,
-            r#type,
-            models::ValueType::Bool
-        )
-    );
-    let related_prop = prop?;
-    let filter = filter?;
-
-    let headers = HeaderMap::new();
-    let headers = reload_table(headers);
+    let page =
+        models::Page::get(&db, &db_ops::GetPageQuery { id: page_id }).await?;
 
-    let has_capacity =
-        filter::db_ops::does_collection_have_capacity_for_additional_filters(
-            &db,
-            related_prop.collection_id,
-        )
-        .await?;
-    let add_filter_button = if has_capacity {
-        filter::components::AddFilterButton {
-            collection_id: related_prop.collection_id,
-        }
-        .render()
+    Ok(if headers.contains_key("Hx-Request") {
+        components::PageOverview { page: &page }.render()
     } else {
-        filter::components::AddFilterButtonPlaceholder {
-            collection_id: related_prop.collection_id,
+        components::Page {
+            title: &page.title,
+            children: Box::new(components::PageOverview { page: &page }),
         }
         .render()
-    };
+    })
+}
 
-    Ok((
-        headers,
-        [
-            r#"<div class="flex flex-row gap-2">"#,
-            &add_filter_button,
-            &filter::components::FilterForm {
-                filter: &filter,
-                prop_name: &related_prop.name,
-            }
-            .render(),
-            "</div>",
-        ]
-        .join(""),
-    ))
+pub async fn redirect_page_key(
+    State(AppState { db }): State<AppState>,
+    Path(key): Path<String>,
+) -> Result<impl IntoResponse, ServerError> {
+    let key = models::PageKey::parse(&key)?;
+    let page_id = db_ops::get_page_id_by_key(&db, &key).await?;
+
+    Ok(Redirect::to(&Route::Page(Some(page_id)).as_string()))
+}
+
+#[derive(Deserialize)]
+pub struct PageFormSubmission {
+    id: i32,
+    collection_id: i32,
+    title: String,
 }
-pub async fn create_new_int_filter(
+pub async fn save_existing_page_form(
+    headers: HeaderMap,
     State(AppState { db }): State<AppState>,
-    Path(prop_id): Path<i32>,
-    Query(NewFilterQuery { type_id }): Query<NewFilterQuery>,
+    Form(form): Form<PageFormSubmission>,
 ) -> Result<impl IntoResponse, ServerError> {
-    let r#type = if let Some(type_id) = type_id {
-        filter::models::FilterType::from_int(type_id)
-    } else {
-        filter::models::FilterType::Eq
+    let user_id = get_user_id(&headers)?;
+    let page = models::Page {
+        id: form.id,
+        collection_id: form.collection_id,
+        title: form.title,
+        props: vec![],
+        content: None,
+        key: None,
     };
-    let query = db_ops::GetPropQuery { id: prop_id };
-    let (prop, filter) = join!(
-        models::Prop::get(&db, &query),
-        filter::db_ops::create_filter(
-            &db,
-            prop_id,
-            r#type,
-            models::ValueType::Int
-        )
-    );
-    let related_prop = prop?;
-    let filter = filter?;
+    page.save(&db).await?;
+    db_ops::touch_page(&db, page.id, user_id).await?;
 
-    let headers = HeaderMap::new();
-    let headers = reload_table(headers);
+    Ok(components::PageForm { page: &page }.render())
+}
 
-    let has_capacity =
-        filter::db_ops::does_collection_have_capacity_for_additional_filters(
-            &db,
-            related_prop.collection_id,
-        )
-        .await?;
-    let add_filter_button = if has_capacity {
-        filter::components::AddFilterButton {
-            collection_id: related_prop.collection_id,
+#[derive(Debug, Deserialize)]
+pub struct PageForm {
+    id: Option<i32>,
+    title: String,
+    /// Empty when creating a blank page.
+    template_id: Option<String>,
+}
+pub async fn handle_page_submission(
+    headers: HeaderMap,
+    State(AppState { db }): State<AppState>,
+    Path(collection_id): Path<i32>,
+    Form(form): Form<PageForm>,
+) -> Result<impl IntoResponse, ServerError> {
+    let user_id = get_user_id(&headers)?;
+    if let Some(id) = form.id {
+        models::Page {
+            id,
+            collection_id,
+            title: form.title,
+            props: Vec::new(),
+            content: None,
+            key: None,
         }
-        .render()
+        .save(&db)
+        .await?;
+        db_ops::touch_page(&db, id, user_id).await?;
     } else {
-        filter::components::AddFilterButtonPlaceholder {
-            collection_id: related_prop.collection_id,
+        let template_id = form
+            .template_id
+            .filter(|id| !id.is_empty())
+            .map(|id| id.parse::<i32>())
+            .transpose()?;
+        let template = match template_id {
+            Some(id) => Some(
+                template::models::PageTemplate::get(
+                    &db,
+                    &template::db_ops::GetTemplateQuery { id },
+                )
+                .await?,
+            ),
+            None => None,
+        };
+        let created = db_ops::create_page(
+            &db,
+            collection_id,
+            &form.title,
+            template.as_ref(),
+            user_id,
+        )
+        .await;
+        if let Err(e) = created {
+            let Some(error) =
+                e.downcast_ref::<constraint::models::ValidationError>()
+            else {
+                return Err(e.into());
+            };
+            let templates = template::models::PageTemplate::list(
+                &db,
+                &template::db_ops::ListTemplateQuery { collection_id },
+            )
+            .await?;
+            return Ok((
+                StatusCode::OK,
+                HeaderMap::new(),
+                components::NewPage {
+                    collection_id,
+                    page_id: None,
+                    title: Some(form.title),
+                    templates,
+                    error: Some(error.0.clone()),
+                }
+                .render(),
+            ));
         }
-        .render()
-    };
-
+    }
+    let headers = HeaderMap::new();
+    let collection_route = Route::Collection(Some(collection_id));
     Ok((
-        headers,
-        [
-            r#"<div class="flex flex-row gap-2">"#,
-            &add_filter_button,
-            &filter::components::FilterForm {
-                filter: &filter,
-                prop_name: &related_prop.name,
-            }
-            .render(),
-            "</div>",
-        ]
-        .join(""),
+        axum::http::StatusCode::CREATED,
+        htmx::redirect(headers, &collection_route.as_string()),
+        "OK".to_string(),
     ))
 }
 
-pub async fn create_new_int_rng_filter(
+pub async fn get_template_list(
+    headers: HeaderMap,
     State(AppState { db }): State<AppState>,
-    Path(prop_id): Path<i32>,
-    Query(NewFilterQuery { type_id }): Query<NewFilterQuery>,
+    Path(collection_id): Path<i32>,
 ) -> Result<impl IntoResponse, ServerError> {
-    let r#type = if let Some(type_id) = type_id {
-        filter::models::FilterType::from_int(type_id)
-    } else {
-        filter::models::FilterType::InRng
+    let templates = template::models::PageTemplate::list(
+        &db,
+        &template::db_ops::ListTemplateQuery { collection_id },
+    )
+    .await?;
+    let list = template::components::TemplateList {
+        collection_id,
+        templates: &templates,
+        error: None,
     };
-    let query = db_ops::GetPropQuery { id: prop_id };
-    let (prop, filter) = join!(
-        models::Prop::get(&db, &query),
-        filter::db_ops::create_filter(
-            &db,
-            prop_id,
-            r#type,
-            models::ValueType::Int
-        )
-    );
-    let related_prop = prop?;
-    let filter = filter?;
 
-    let headers = HeaderMap::new();
-    let headers = reload_table(headers);
-
-    let has_capacity =
-        filter::db_ops::does_collection_have_capacity_for_additional_filters(
-            &db,
-            related_prop.collection_id,
-        )
-        .await?;
-    let add_filter_button = if has_capacity {
-        filter::components::AddFilterButton {
-            collection_id: related_prop.collection_id,
-        }
-        .render()
+    Ok(if headers.contains_key("Hx-Request") {
+        list.render()
     } else {
-        filter::components::AddFilterButtonPlaceholder {
-            collection_id: related_prop.collection_id,
+        components::Page {
+            title: "Templates",
+            children: Box::new(list),
         }
         .render()
-    };
-
-    Ok((
-        headers,
-        [
-            r#"<div class="flex flex-row gap-2">"#,
-            &add_filter_button,
-            &filter::components::FilterForm {
-                filter: &filter,
-                prop_name: &related_prop.name,
-            }
-            .render(),
-            "</div>",
-        ]
-        .join(""),
-    ))
+    })
 }
 
-pub async fn create_new_float_filter(
+#[derive(Deserialize)]
+pub struct NewTemplateForm {
+    name: String,
+}
+pub async fn handle_new_template(
     State(AppState { db }): State<AppState>,
-    Path(prop_id): Path<i32>,
-    Query(NewFilterQuery { type_id }): Query<NewFilterQuery>,
+    Path(collection_id): Path<i32>,
+    Form(form): Form<NewTemplateForm>,
 ) -> Result<impl IntoResponse, ServerError> {
-    let r#type = if let Some(type_id) = type_id {
-        filter::models::FilterType::from_int(type_id)
+    let templates = template::models::PageTemplate::list(
+        &db,
+        &template::db_ops::ListTemplateQuery { collection_id },
+    )
+    .await?;
+    let name = form.name.trim();
+    let error = if name.is_empty() {
+        Some("templates need a name")
+    } else if templates.iter().any(|t| t.name == name) {
+        Some("there is already a template with that name")
     } else {
-        filter::models::FilterType::Eq
+        None
     };
-    let query = db_ops::GetPropQuery { id: prop_id };
-    let (prop, filter) = join!(
-        models::Prop::get(&db, &query),
-        filter::db_ops::create_filter(
-            &db,
-            prop_id,
-            r#type,
-            models::ValueType::Float
-        )
-    );
-    let related_prop = prop?;
-    let filter = filter?;
-
-    let headers = HeaderMap::new();
-    let headers = reload_table(headers);
+    if error.is_some() {
+        return Ok(template::components::TemplateList {
+            collection_id,
+            templates: &templates,
+            error,
+        }
+        .render()
+        .into_response());
+    }
+    let id =
+        template::db_ops::create_template(&db, collection_id, name).await?;
 
-    let has_capacity =
-        filter::db_ops::does_collection_have_capacity_for_additional_filters(
-            &db,
-            related_prop.collection_id,
+    Ok(
+        htmx::redirect(
+            HeaderMap::new(),
+            &Route::Template(Some(id)).as_string(),
         )
-        .await?;
-    let add_filter_button = if has_capacity {
-        filter::components::AddFilterButton {
-            collection_id: related_prop.collection_id,
+        .into_response(),
+    )
+}
+
+/// Props and select options for the template editor, along with errors for
+/// any defaults which don't parse.
+async fn get_template_editor_data(
+    db: &sqlx::PgPool,
+    template: &template::models::PageTemplate,
+) -> Result<(
+    Vec<models::Prop>,
+    Vec<models::SelectOption>,
+    Vec<(Option<i32>, String)>,
+)> {
+    let props = db_ops::get_prop_set(db, template.collection_id).await?;
+    let options = template::db_ops::list_select_options(db, &props).await?;
+    let mut errors = vec![];
+    for v in &template.values {
+        let Some(prop) = props.iter().find(|p| p.id == v.prop_id) else {
+            continue;
+        };
+        let value =
+            match template::models::parse_value(prop, &v.value, &options) {
+                Ok(value) => value,
+                Err(e) => {
+                    errors.push((Some(prop.id), e));
+                    continue;
+                }
+            };
+        let constraints =
+            constraint::db_ops::get_constraints(db, prop.id).await?;
+        if let Err(e) =
+            template::models::check_default(&constraints, prop, &value)
+        {
+            errors.push((Some(prop.id), e));
         }
-        .render()
+    }
+
+    Ok((props, options, errors))
+}
+
+pub async fn get_template_editor(
+    headers: HeaderMap,
+    State(AppState { db }): State<AppState>,
+    Path(id): Path<i32>,
+) -> Result<impl IntoResponse, ServerError> {
+    let template = template::models::PageTemplate::get(
+        &db,
+        &template::db_ops::GetTemplateQuery { id },
+    )
+    .await?;
+    let (props, options, errors) =
+        get_template_editor_data(&db, &template).await?;
+    let editor = template::components::TemplateEditor {
+        template: &template,
+        props: &props,
+        options: &options,
+        errors: &errors,
+        saved: false,
+    };
+
+    Ok(if headers.contains_key("Hx-Request") {
+        editor.render()
     } else {
-        filter::components::AddFilterButtonPlaceholder {
-            collection_id: related_prop.collection_id,
+        components::Page {
+            title: "Template",
+            children: Box::new(editor),
         }
         .render()
-    };
+    })
+}
 
-    Ok((
-        headers,
-        [
-            r#"<div class="flex flex-row gap-2">"#,
-            &add_filter_button,
-            &filter::components::FilterForm {
-                filter: &filter,
-                prop_name: &related_prop.name,
+/// Besides `name` and `content`, the form has a `prop_{id}` field for each
+/// prop which can have a default.
+pub async fn handle_template_save(
+    State(AppState { db }): State<AppState>,
+    Path(id): Path<i32>,
+    Form(form): Form<HashMap<String, String>>,
+) -> Result<impl IntoResponse, ServerError> {
+    let mut template = template::models::PageTemplate::get(
+        &db,
+        &template::db_ops::GetTemplateQuery { id },
+    )
+    .await?;
+    let name = form.get("name").map(|n| n.trim()).unwrap_or_default();
+    let siblings = template::models::PageTemplate::list(
+        &db,
+        &template::db_ops::ListTemplateQuery {
+            collection_id: template.collection_id,
+        },
+    )
+    .await?;
+    let mut name_error = None;
+    if name.is_empty() {
+        name_error = Some("templates need a name".to_string());
+    } else if siblings.iter().any(|t| t.id != id && t.name == name) {
+        name_error =
+            Some("there is already a template with that name".to_string());
+    }
+    template.name = name.to_string();
+    template.content = form.get("content").cloned().unwrap_or_default();
+    template.values = form
+        .iter()
+        .filter_map(|(key, value)| {
+            let prop_id = key.strip_prefix("prop_")?.parse().ok()?;
+            let value = value.trim();
+            if value.is_empty() {
+                None
+            } else {
+                Some(template::models::TemplateValue {
+                    prop_id,
+                    value: value.to_string(),
+                })
             }
-            .render(),
-            "</div>",
-        ]
-        .join(""),
+        })
+        .collect();
+    let (props, options, mut errors) =
+        get_template_editor_data(&db, &template).await?;
+    template
+        .values
+        .retain(|v| props.iter().any(|p| p.id == v.prop_id));
+    if let Some(error) = name_error {
+        errors.push((None, error));
+    }
+    let saved = errors.is_empty();
+    if saved {
+        template.save(&db).await?;
+    }
+
+    Ok(template::components::TemplateEditor {
+        template: &template,
+        props: &props,
+        options: &options,
+        errors: &errors,
+        saved,
+    }
+    .render())
+}
+
+pub async fn delete_template(
+    State(AppState { db }): State<AppState>,
+    Path(id): Path<i32>,
+) -> Result<impl IntoResponse, ServerError> {
+    let template = template::models::PageTemplate::get(
+        &db,
+        &template::db_ops::GetTemplateQuery { id },
+    )
+    .await?;
+    let collection_id = template.collection_id;
+    template.delete(&db).await?;
+
+    Ok(htmx::redirect(
+        HeaderMap::new(),
+        &Route::CollectionTemplates(Some(collection_id)).as_string(),
     ))
 }
 
-pub async fn create_new_float_rng_filter(
+#[derive(Deserialize)]
+pub struct NewViewForm {
+    name: String,
+}
+pub async fn handle_new_view(
     State(AppState { db }): State<AppState>,
-    Path(prop_id): Path<i32>,
-    Query(NewFilterQuery { type_id }): Query<NewFilterQuery>,
+    Path(collection_id): Path<i32>,
+    Form(form): Form<NewViewForm>,
 ) -> Result<impl IntoResponse, ServerError> {
-    let r#type = if let Some(type_id) = type_id {
-        filter::models::FilterType::from_int(type_id)
+    let views = view::models::View::list(
+        &db,
+        &view::db_ops::ListViewQuery { collection_id },
+    )
+    .await?;
+    let name = form.name.trim();
+    let error = if name.is_empty() {
+        Some("views need a name")
+    } else if views.iter().any(|v| v.name == name) {
+        Some("there is already a view with that name")
     } else {
-        filter::models::FilterType::InRng
+        None
     };
-    let query = db_ops::GetPropQuery { id: prop_id };
-    let (prop, filter) = join!(
-        models::Prop::get(&db, &query),
-        filter::db_ops::create_filter(
-            &db,
-            prop_id,
-            r#type,
-            models::ValueType::Float
-        )
-    );
-    let related_prop = prop?;
-    let filter = filter?;
+    if error.is_some() {
+        return Ok(view::components::NewViewForm {
+            collection_id,
+            error,
+        }
+        .render()
+        .into_response());
+    }
+    let id = view::db_ops::create_view(&db, collection_id, name).await?;
 
-    let headers = HeaderMap::new();
-    let headers = reload_table(headers);
+    Ok(htmx::redirect(
+        HeaderMap::new(),
+        &Route::CollectionView(Some((collection_id, id))).as_string(),
+    )
+    .into_response())
+}
+
+/// Render the [view::components::ViewSettings] of `view`.
+async fn render_view_settings(
+    db: &sqlx::PgPool,
+    view: &view::models::View,
+    error: Option<&str>,
+    saved: bool,
+) -> Result<String> {
+    let props = db_ops::get_prop_set(db, view.collection_id).await?;
+
+    Ok(view::components::ViewSettings {
+        view,
+        props: &view.arrange(&props),
+        error,
+        saved,
+    }
+    .render())
+}
 
-    let has_capacity =
-        filter::db_ops::does_collection_have_capacity_for_additional_filters(
-            &db,
-            related_prop.collection_id,
-        )
+pub async fn get_view_settings(
+    headers: HeaderMap,
+    State(AppState { db }): State<AppState>,
+    Path(id): Path<i32>,
+) -> Result<impl IntoResponse, ServerError> {
+    let view = view::models::View::get(&db, &view::db_ops::GetViewQuery { id })
         .await?;
-    let add_filter_button = if has_capacity {
-        filter::components::AddFilterButton {
-            collection_id: related_prop.collection_id,
-        }
-        .render()
+    let props = db_ops::get_prop_set(&db, view.collection_id).await?;
+    let props = view.arrange(&props);
+    let settings = view::components::ViewSettings {
+        view: &view,
+        props: &props,
+        error: None,
+        saved: false,
+    };
+
+    Ok(if headers.contains_key("Hx-Request") {
+        settings.render()
     } else {
-        filter::components::AddFilterButtonPlaceholder {
-            collection_id: related_prop.collection_id,
+        components::Page {
+            title: "View",
+            children: Box::new(settings),
         }
         .render()
+    })
+}
+
+#[derive(Deserialize)]
+pub struct ViewForm {
+    name: String,
+    layout: String,
+}
+pub async fn handle_view_save(
+    State(AppState { db }): State<AppState>,
+    Path(id): Path<i32>,
+    Form(form): Form<ViewForm>,
+) -> Result<impl IntoResponse, ServerError> {
+    let mut view =
+        view::models::View::get(&db, &view::db_ops::GetViewQuery { id })
+            .await?;
+    let siblings = view::models::View::list(
+        &db,
+        &view::db_ops::ListViewQuery {
+            collection_id: view.collection_id,
+        },
+    )
+    .await?;
+    let name = form.name.trim();
+    let error = if name.is_empty() {
+        Some("views need a name")
+    } else if siblings.iter().any(|v| v.id != id && v.name == name) {
+        Some("there is already a view with that name")
+    } else {
+        None
     };
+    view.name = name.to_string();
+    view.layout = view::models::Layout::from_name(&form.layout)?;
+    if error.is_none() {
+        view.save(&db).await?;
+    }
 
-    Ok((
-        headers,
-        [
-            r#"<div class="flex flex-row gap-2">"#,
-            &add_filter_button,
-            &filter::components::FilterForm {
-                filter: &filter,
-                prop_name: &related_prop.name,
-            }
-            .render(),
-            "</div>",
-        ]
-        .join(""),
+    Ok(render_view_settings(&db, &view, error, error.is_none()).await?)
+}
+
+/// Collections always have at least one view, so the last one can't be
+/// deleted.
+pub async fn delete_view(
+    State(AppState { db }): State<AppState>,
+    Path(id): Path<i32>,
+) -> Result<impl IntoResponse, ServerError> {
+    let view = view::models::View::get(&db, &view::db_ops::GetViewQuery { id })
+        .await?;
+    let collection_id = view.collection_id;
+    let views = view::models::View::list(
+        &db,
+        &view::db_ops::ListViewQuery { collection_id },
+    )
+    .await?;
+    if views.len() <= 1 {
+        return Ok(render_view_settings(
+            &db,
+            &view,
+            Some("the only view of a collection cannot be deleted"),
+            false,
+        )
+        .await?
+        .into_response());
+    }
+    view.delete(&db).await?;
+
+    Ok(htmx::redirect(
+        HeaderMap::new(),
+        &Route::Collection(Some(collection_id)).as_string(),
+    )
+    .into_response())
+}
+
+/// Load a view for one of the column controllers below, change it with
+/// `update`, then save it and render the settings again.
+async fn update_view_columns(
+    db: &sqlx::PgPool,
+    view_id: i32,
+    update: impl FnOnce(&mut view::models::View, &[models::Prop]),
+) -> Result<String> {
+    let mut view = view::models::View::get(
+        db,
+        &view::db_ops::GetViewQuery { id: view_id },
+    )
+    .await?;
+    let props = db_ops::get_prop_set(db, view.collection_id).await?;
+    update(&mut view, &props);
+    view.save(db).await?;
+
+    render_view_settings(db, &view, None, false).await
+}
+
+pub async fn toggle_view_prop(
+    State(AppState { db }): State<AppState>,
+    Path((view_id, prop_id)): Path<(i32, i32)>,
+) -> Result<impl IntoResponse, ServerError> {
+    Ok(update_view_columns(&db, view_id, |view, props| {
+        view.toggle_prop(props, prop_id)
+    })
+    .await?)
+}
+
+/// Move the column of the prop one step towards the left.
+pub async fn increment_view_prop_order(
+    State(AppState { db }): State<AppState>,
+    Path((view_id, prop_id)): Path<(i32, i32)>,
+) -> Result<impl IntoResponse, ServerError> {
+    Ok(update_view_columns(&db, view_id, |view, props| {
+        view.move_prop(props, prop_id, false)
+    })
+    .await?)
+}
+
+/// Move the column of the prop one step towards the right.
+pub async fn decrement_view_prop_order(
+    State(AppState { db }): State<AppState>,
+    Path((view_id, prop_id)): Path<(i32, i32)>,
+) -> Result<impl IntoResponse, ServerError> {
+    Ok(update_view_columns(&db, view_id, |view, props| {
+        view.move_prop(props, prop_id, true)
+    })
+    .await?)
+}
+
+/// Save the ad-hoc filters and sort in the query string to the view, then
+/// go back to the view without them.
+pub async fn save_adhoc_filters(
+    headers: HeaderMap,
+    State(AppState { db }): State<AppState>,
+    Path(view_id): Path<i32>,
+    Query(params): Query<Vec<(String, String)>>,
+) -> Result<impl IntoResponse, ServerError> {
+    let view = view::models::View::get(
+        &db,
+        &view::db_ops::GetViewQuery { id: view_id },
+    )
+    .await?;
+    let tz = get_user_tz(&db, &headers).await?;
+    let query = filter::models::AdHocQuery::parse(&params)?;
+    let adhoc =
+        resolve_adhoc_query(&db, &view, &query, tz, get_user_id(&headers)?)
+            .await?;
+    filter::db_ops::save_adhoc_filters(&db, view_id, &adhoc).await?;
+
+    Ok(htmx::redirect(
+        HeaderMap::new(),
+        &Route::CollectionView(Some((view.collection_id, view_id))).as_string(),
     ))
 }
 
-pub async fn create_new_date_filter(
+pub async fn get_content_form(
     State(AppState { db }): State<AppState>,
-    Path(prop_id): Path<i32>,
-    Query(NewFilterQuery { type_id }): Query<NewFilterQuery>,
+    Path(page_id): Path<i32>,
+) -> Result<impl IntoResponse, ServerError> {
+    let existing_content =
+        models::Content::get(&db, &db_ops::GetDbModelQuery { page_id }).await;
+    if let Ok(c) = existing_content {
+        Ok(c.render())
+    } else {
+        Ok(models::Content {
+            page_id,
+            content: "".to_string(),
+        }
+        .render())
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ContentForm {
+    content: String,
+}
+pub async fn handle_content_submission(
+    headers: HeaderMap,
+    State(AppState { db }): State<AppState>,
+    Path(page_id): Path<i32>,
+    Form(ContentForm { content }): Form<ContentForm>,
 ) -> Result<impl IntoResponse, ServerError> {
-    let r#type = if let Some(type_id) = type_id {
-        filter::models::FilterType::from_int(type_id)
+    let user_id = get_user_id(&headers)?;
+    let content = models::Content { page_id, content };
+    content.save(&db).await?;
+    db_ops::touch_page(&db, page_id, user_id).await?;
+    Ok(components::ContentDisplay {
+        page_id,
+        content: Some(&content),
+    }
+    .render())
+}
+
+/// Render the [filter::components::FilterToolbar] of a view.
+async fn render_filter_toolbar(
+    db: &sqlx::PgPool,
+    headers: &HeaderMap,
+    view_id: i32,
+) -> Result<String> {
+    let tz = get_user_tz(db, headers).await?;
+    let view = view::models::View::get(
+        db,
+        &view::db_ops::GetViewQuery { id: view_id },
+    )
+    .await?;
+    let mut root = filter::db_ops::get_filter_tree(db, view_id).await?;
+    root.localize(tz);
+    let all_props = root
+        .all_filters()
+        .iter()
+        .map(|f| f.prop_id)
+        .collect::<Vec<i32>>();
+    let props = if all_props.is_empty() {
+        vec![]
     } else {
-        filter::models::FilterType::Eq
+        let prop_query = db_ops::ListPropQuery {
+            collection_id: None,
+            exact_ids: Some(all_props),
+            order_in: None,
+        };
+        models::Prop::list(db, &prop_query).await?
     };
-    let query = db_ops::GetPropQuery { id: prop_id };
-    let (prop, filter) = join!(
-        models::Prop::get(&db, &query),
-        filter::db_ops::create_filter(
-            &db,
-            prop_id,
-            r#type,
-            models::ValueType::Date
-        )
+    let mut prop_by_id =
+        props.into_iter().fold(HashMap::new(), |mut acc, prop| {
+            acc.insert(prop.id, prop);
+            acc
+        });
+    prop_by_id.insert(
+        filter::models::TITLE_PROP_ID,
+        filter::models::get_title_prop(view.collection_id),
     );
-    let related_prop = prop?;
-    let filter = filter?;
+    let get_prop = |prop_id: i32| {
+        prop_by_id
+            .get(&prop_id)
+            .expect("you lookup a prop that exists")
+    };
+    // The toolbar is re-rendered by many of the filter controllers, so the
+    // ad-hoc filters are read from the page rather than passed along.
+    let adhoc = filter::models::AdHocQuery::parse(
+        &htmx::get_current_url_params(headers),
+    )
+    .unwrap_or_default();
+    let collection_props = if adhoc.is_empty() {
+        vec![]
+    } else {
+        let mut props = db_ops::get_prop_set(db, view.collection_id).await?;
+        props.push(filter::models::get_title_prop(view.collection_id));
+        props
+    };
+    let adhoc_summary =
+        (!adhoc.is_empty()).then(|| filter::components::AdHocFilterSummary {
+            collection_id: view.collection_id,
+            view_id,
+            query: &adhoc,
+            props: &collection_props,
+        });
+
+    Ok(filter::components::FilterToolbar {
+        root: &root,
+        get_prop: &get_prop,
+        adhoc: adhoc_summary,
+    }
+    .render())
+}
 
-    let headers = HeaderMap::new();
-    let headers = reload_table(headers);
+pub async fn get_filter_toolbar(
+    headers: HeaderMap,
+    State(AppState { db }): State<AppState>,
+    Path(view_id): Path<i32>,
+) -> Result<impl IntoResponse, ServerError> {
+    Ok(render_filter_toolbar(&db, &headers, view_id).await?)
+}
 
-    let has_capacity =
-        filter::db_ops::does_collection_have_capacity_for_additional_filters(
-            &db,
-            related_prop.collection_id,
-        )
+pub async fn create_filter_group(
+    headers: HeaderMap,
+    State(AppState { db }): State<AppState>,
+    Path(parent_id): Path<i32>,
+) -> Result<impl IntoResponse, ServerError> {
+    let parent = filter::db_ops::get_filter_group(&db, parent_id).await?;
+    filter::db_ops::create_filter_group(&db, parent_id).await?;
+
+    Ok(render_filter_toolbar(&db, &headers, parent.view_id).await?)
+}
+
+pub async fn delete_filter_group(
+    headers: HeaderMap,
+    State(AppState { db }): State<AppState>,
+    Path(id): Path<i32>,
+) -> Result<impl IntoResponse, ServerError> {
+    let group = filter::db_ops::get_filter_group(&db, id).await?;
+    filter::db_ops::delete_filter_group(&db, id).await?;
+    let toolbar = render_filter_toolbar(&db, &headers, group.view_id).await?;
+    let response_headers = reload_table(HeaderMap::new());
+
+    Ok((response_headers, toolbar))
+}
+
+pub async fn toggle_filter_group_conjunction(
+    headers: HeaderMap,
+    State(AppState { db }): State<AppState>,
+    Path(id): Path<i32>,
+) -> Result<impl IntoResponse, ServerError> {
+    let group = filter::db_ops::get_filter_group(&db, id).await?;
+    filter::db_ops::set_conjunction(&db, id, group.conjunction.toggle())
         .await?;
-    let add_filter_button = if has_capacity {
-        filter::components::AddFilterButton {
-            collection_id: related_prop.collection_id,
-        }
-        .render()
+    let toolbar = render_filter_toolbar(&db, &headers, group.view_id).await?;
+    let response_headers = reload_table(HeaderMap::new());
+
+    Ok((response_headers, toolbar))
+}
+
+// This needs to be async because axum requires route handlers to be async.
+pub async fn hide_filter_toolbar(Path(view_id): Path<i32>) -> String {
+    filter::components::FilterToolbarPlaceholder { view_id }.render()
+}
+
+fn trigger_event(
+    mut headers: HeaderMap,
+    event_name: &'static str,
+) -> HeaderMap {
+    if headers.contains_key("Hx-Trigger") {
+        let val = headers.get("Hx-Trigger").expect("we know it's here");
+        let as_str = val.to_str().expect("existing trigger is ascii");
+        let new_header = format!("{as_str}, {event_name}");
+        headers.insert(
+            "Hx-Trigger",
+            HeaderValue::from_str(&new_header)
+                .expect("event name is a valid string"),
+        );
     } else {
-        filter::components::AddFilterButtonPlaceholder {
-            collection_id: related_prop.collection_id,
-        }
-        .render()
-    };
+        headers.insert(
+            "Hx-Trigger",
+            HeaderValue::from_str(event_name)
+                .expect("event name is a valid string"),
+        );
+    }
 
-    Ok((
-        headers,
-        [
-            r#"<div class="flex flex-row gap-2">"#,
-            &add_filter_button,
-            &filter::components::FilterForm {
-                filter: &filter,
-                prop_name: &related_prop.name,
-            }
-            .render(),
-            "</div>",
-        ]
-        .join(""),
-    ))
+    headers
+}
+
+/// Insert the Hx-Trugger header into a HeaderMap such that the table reload
+/// will occur on the collection view.
+fn reload_table(headers: HeaderMap) -> HeaderMap {
+    trigger_event(headers, "reload-pages")
+}
+
+fn reload_add_filter_button(headers: HeaderMap) -> HeaderMap {
+    trigger_event(headers, "reload-add-filter-button")
+}
+
+/// Filter `id`, which should be of the kind in the `kind` slug of its
+/// route; see [filter::models::FilterKind].
+async fn get_filter_of_kind(
+    db: &sqlx::PgPool,
+    kind: &str,
+    id: i32,
+) -> Result<(filter::models::FilterKind, filter::models::Filter)> {
+    let kind = filter::models::FilterKind::from_slug(kind)?;
+    let filter =
+        filter::models::Filter::get(db, &filter::db_ops::GetFilterQuery { id })
+            .await?;
+    if filter.get_kind() != kind {
+        anyhow::bail!(
+            "filter {id} is a {} filter, not {kind}",
+            filter.get_kind()
+        );
+    }
+
+    Ok((kind, filter))
+}
+
+/// The prop of a filter, or a stand-in for the page title.
+async fn get_filter_prop(
+    db: &sqlx::PgPool,
+    filter: &filter::models::Filter,
+) -> Result<models::Prop> {
+    if filter.prop_id == filter::models::TITLE_PROP_ID {
+        let collection_id =
+            filter::db_ops::get_filter_collection_id(db, filter.id).await?;
+        Ok(filter::models::get_title_prop(collection_id))
+    } else {
+        models::Prop::get(db, &db_ops::GetPropQuery { id: filter.prop_id })
+            .await
+    }
+}
+
+pub async fn get_filter_chip(
+    headers: HeaderMap,
+    State(AppState { db }): State<AppState>,
+    Path((kind, id)): Path<(String, i32)>,
+) -> Result<impl IntoResponse, ServerError> {
+    let (_, mut filter) = get_filter_of_kind(&db, &kind, id).await?;
+    filter.localize(get_user_tz(&db, &headers).await?);
+    let related_prop = get_filter_prop(&db, &filter).await?;
+
+    Ok(filter::components::FilterChip {
+        filter: &filter,
+        prop: &related_prop,
+    }
+    .render())
 }
 
-pub async fn create_new_date_rng_filter(
+pub async fn get_filter_form(
+    headers: HeaderMap,
     State(AppState { db }): State<AppState>,
-    Path(prop_id): Path<i32>,
-    Query(NewFilterQuery { type_id }): Query<NewFilterQuery>,
+    Path((kind, id)): Path<(String, i32)>,
 ) -> Result<impl IntoResponse, ServerError> {
-    let r#type = if let Some(type_id) = type_id {
-        filter::models::FilterType::from_int(type_id)
-    } else {
-        filter::models::FilterType::InRng
-    };
-    let query = db_ops::GetPropQuery { id: prop_id };
-    let (prop, filter) = join!(
-        models::Prop::get(&db, &query),
-        filter::db_ops::create_filter(
-            &db,
-            prop_id,
-            r#type,
-            models::ValueType::Date
-        )
-    );
-    let related_prop = prop?;
-    let filter = filter?;
+    let (_, mut filter) = get_filter_of_kind(&db, &kind, id).await?;
+    filter.localize(get_user_tz(&db, &headers).await?);
+    let related_prop = get_filter_prop(&db, &filter).await?;
 
-    let headers = HeaderMap::new();
-    let headers = reload_table(headers);
+    Ok(filter::components::FilterForm {
+        filter: &filter,
+        prop_name: &related_prop.name,
+    }
+    .render())
+}
 
-    let has_capacity =
-        filter::db_ops::does_collection_have_capacity_for_additional_filters(
-            &db,
-            related_prop.collection_id,
-        )
-        .await?;
-    let add_filter_button = if has_capacity {
-        filter::components::AddFilterButton {
-            collection_id: related_prop.collection_id,
+/// Every field of the filter forms; which of them are submitted depends on
+/// the [filter::models::FilterKind] of the filter.
+#[derive(Deserialize)]
+pub struct FilterValueForm {
+    r#type: Option<i32>,
+    value: Option<String>,
+    start: Option<String>,
+    end: Option<String>,
+    /// Checkboxes are only submitted when they are checked.
+    case_sensitive: Option<String>,
+    period: Option<String>,
+    amount: Option<i32>,
+}
+
+fn get_form_field<'a>(
+    field: &'a Option<String>,
+    name: &str,
+) -> Result<&'a str> {
+    field
+        .as_deref()
+        .ok_or_else(|| anyhow::anyhow!("{name} is missing from the form"))
+}
+
+/// Parse a filter value of `value_type` from a form field. Options and
+/// pages must be ones that the filtered prop can hold.
+async fn parse_filter_value(
+    db: &sqlx::PgPool,
+    prop_id: i32,
+    value_type: models::ValueType,
+    input: &str,
+    tz: Tz,
+) -> Result<models::Value> {
+    Ok(match value_type {
+        models::ValueType::Int => models::Value::Int(input.parse()?),
+        models::ValueType::Float => models::Value::Float(input.parse()?),
+        models::ValueType::Date => models::Value::Date(input.parse()?),
+        models::ValueType::DateTime => {
+            models::Value::DateTime(models::parse_datetime_local(input, tz)?)
         }
-        .render()
-    } else {
-        filter::components::AddFilterButtonPlaceholder {
-            collection_id: related_prop.collection_id,
+        models::ValueType::Select => {
+            let option = models::SelectOption::get(
+                db,
+                &db_ops::GetSelectOptionQuery { id: input.parse()? },
+            )
+            .await?;
+            if option.prop_id != prop_id {
+                anyhow::bail!(
+                    "option {} does not belong to prop {prop_id}",
+                    option.id
+                );
+            }
+            models::Value::Select(option)
         }
-        .render()
-    };
-
-    Ok((
-        headers,
-        [
-            r#"<div class="flex flex-row gap-2">"#,
-            &add_filter_button,
-            &filter::components::FilterForm {
-                filter: &filter,
-                prop_name: &related_prop.name,
+        models::ValueType::Relation => {
+            let (page, target) = join!(
+                db_ops::get_related_page(db, input.parse()?),
+                db_ops::get_relation_target(db, prop_id)
+            );
+            let page = page?;
+            if page.collection_id != target? {
+                anyhow::bail!(
+                    "page {} cannot be linked by prop {prop_id}",
+                    page.id
+                );
             }
-            .render(),
-            "</div>",
-        ]
-        .join(""),
-    ))
+            models::Value::Relation(vec![page])
+        }
+        models::ValueType::CreatedBy | models::ValueType::LastEditedBy => {
+            models::Value::CreatedBy(
+                db_ops::get_user_by_id(db, input.parse()?).await?,
+            )
+        }
+        value_type => anyhow::bail!(
+            "{} filters cannot be parsed from a form",
+            value_type.get_display_name()
+        ),
+    })
 }
 
-/// I pulled this out into a separate request because it requires its own
-/// database query. We only want to show the filter button if there are
-/// props in the workspace that do not have any filters already.
-pub async fn get_add_filter_button(
+pub async fn handle_filter_form_submit(
+    headers: HeaderMap,
     State(AppState { db }): State<AppState>,
-    Path(collection_id): Path<i32>,
+    Path((kind, id)): Path<(String, i32)>,
+    Form(form): Form<FilterValueForm>,
 ) -> Result<impl IntoResponse, ServerError> {
-    let does_it_tho =
-        filter::db_ops::does_collection_have_capacity_for_additional_filters(
-            &db,
-            collection_id,
-        )
-        .await?;
+    use filter::models::{FilterKind, FilterType, FilterValue};
+    let (kind, filter) = get_filter_of_kind(&db, &kind, id).await?;
+    let tz = get_user_tz(&db, &headers).await?;
+    let related_prop = get_filter_prop(&db, &filter).await?;
+    // Bool and relative date forms pick their type from the value.
+    let form_type = match (kind, form.r#type) {
+        (
+            FilterKind::Single(value_type) | FilterKind::Range(value_type),
+            Some(type_id),
+        ) => {
+            let Ok(form_type) = FilterType::try_from(type_id) else {
+                return Ok((
+                    StatusCode::BAD_REQUEST,
+                    HeaderMap::new(),
+                    "Invalid filter type".to_string(),
+                ));
+            };
+            if FilterKind::new(form_type, value_type).ok() != Some(kind) {
+                return Err(anyhow::anyhow!(
+                    "{form_type} is not a type of {kind} filter"
+                )
+                .into());
+            }
+            Some(form_type)
+        }
+        _ => None,
+    };
+    let get_form_type = || {
+        form_type
+            .ok_or_else(|| anyhow::anyhow!("type is missing from the form"))
+    };
+    let (r#type, value) = match kind {
+        FilterKind::Single(models::ValueType::Bool) => {
+            match get_form_field(&form.value, "value")? {
+                "true" => (
+                    FilterType::Eq,
+                    FilterValue::Single(models::Value::Bool(true)),
+                ),
+                "false" => (
+                    FilterType::Eq,
+                    FilterValue::Single(models::Value::Bool(false)),
+                ),
+                // We'll keep the same value as before when we're getting
+                // marked as 'is-empty'
+                "is-empty" => (FilterType::IsEmpty, filter.value),
+                _ => {
+                    return Ok((
+                        StatusCode::BAD_REQUEST,
+                        HeaderMap::new(),
+                        "Invalid value".to_string(),
+                    ))
+                }
+            }
+        }
+        FilterKind::Single(models::ValueType::Str) => {
+            let r#type = get_form_type()?;
+            let value = form.value.unwrap_or_default();
+            let case_sensitive = form.case_sensitive.is_some();
+            if r#type == FilterType::Matches {
+                if let Err(e) =
+                    filter::db_ops::validate_regex(&db, &value).await
+                {
+                    let error = e.to_string();
+                    return Ok((
+                        StatusCode::OK,
+                        HeaderMap::new(),
+                        filter::components::TextFilterForm {
+                            id: filter.id,
+                            r#type,
+                            value: &value,
+                            case_sensitive,
+                            prop_name: &related_prop.name,
+                            error: Some(&error),
+                        }
+                        .render(),
+                    ));
+                }
+            }
+            (
+                r#type,
+                FilterValue::Text {
+                    value,
+                    case_sensitive,
+                },
+            )
+        }
+        FilterKind::Single(value_type) => (
+            get_form_type()?,
+            FilterValue::Single(
+                parse_filter_value(
+                    &db,
+                    filter.prop_id,
+                    value_type,
+                    get_form_field(&form.value, "value")?,
+                    tz,
+                )
+                .await?,
+            ),
+        ),
+        FilterKind::Range(value_type) => {
+            let (start, end) = join!(
+                parse_filter_value(
+                    &db,
+                    filter.prop_id,
+                    value_type,
+                    get_form_field(&form.start, "start")?,
+                    tz,
+                ),
+                parse_filter_value(
+                    &db,
+                    filter.prop_id,
+                    value_type,
+                    get_form_field(&form.end, "end")?,
+                    tz,
+                )
+            );
+            (get_form_type()?, FilterValue::Range(start?, end?))
+        }
+        FilterKind::RelativeDate => {
+            let FilterValue::Relative { value_type, .. } = filter.value else {
+                unreachable!("relative date filters have relative values");
+            };
+            (
+                FilterType::IsWithin,
+                FilterValue::Relative {
+                    date: filter::models::RelativeDate::parse(
+                        get_form_field(&form.period, "period")?,
+                        form.amount.unwrap_or_default(),
+                    )?,
+                    value_type,
+                },
+            )
+        }
+    };
+    let new_filter = filter::models::Filter {
+        id: filter.id,
+        view_id: filter.view_id,
+        prop_id: filter.prop_id,
+        group_id: filter.group_id,
+        r#type,
+        value,
+        enabled: filter.enabled,
+    };
+    new_filter.save(&db).await?;
+    let headers = reload_table(HeaderMap::new());
 
-    if does_it_tho {
-        Ok(filter::components::AddFilterButton { collection_id }.render())
-    } else {
-        Ok(
-            filter::components::AddFilterButtonPlaceholder { collection_id }
-                .render(),
-        )
-    }
+    Ok((
+        StatusCode::OK,
+        headers,
+        filter::components::FilterChip {
+            filter: &new_filter,
+            prop: &related_prop,
+        }
+        .render(),
+    ))
 }
 
-pub async fn delete_bool_filter(
+pub async fn delete_filter(
     State(AppState { db }): State<AppState>,
-    Path(id): Path<i32>,
+    Path((kind, id)): Path<(String, i32)>,
 ) -> Result<impl IntoResponse, ServerError> {
-    let filter = filter::models::Filter::get(
-        &db,
-        &filter::db_ops::GetFilterQuery {
-            id,
-            value_type: models::ValueType::Bool,
-            variant: filter::db_ops::Variant::Single,
-        },
-    )
-    .await?;
+    let (_, filter) = get_filter_of_kind(&db, &kind, id).await?;
     filter.delete(&db).await?;
 
     let headers = HeaderMap::new();
@@ -1794,158 +2804,341 @@ pub async fn delete_bool_filter(
     Ok((headers, ""))
 }
 
-pub async fn delete_int_filter(
+pub async fn toggle_filter(
+    headers: HeaderMap,
     State(AppState { db }): State<AppState>,
-    Path(id): Path<i32>,
+    Path((kind, id)): Path<(String, i32)>,
 ) -> Result<impl IntoResponse, ServerError> {
-    let filter = filter::models::Filter::get(
-        &db,
-        &filter::db_ops::GetFilterQuery {
-            id,
-            value_type: models::ValueType::Int,
-            variant: filter::db_ops::Variant::Single,
-        },
-    )
-    .await?;
-    filter.delete(&db).await?;
+    let (_, mut filter) = get_filter_of_kind(&db, &kind, id).await?;
+    filter.enabled = !filter.enabled;
+    filter::db_ops::set_filter_enabled(&db, id, filter.enabled).await?;
+    filter.localize(get_user_tz(&db, &headers).await?);
+    let related_prop = get_filter_prop(&db, &filter).await?;
+    let response_headers = reload_table(HeaderMap::new());
 
-    let headers = HeaderMap::new();
-    let headers = reload_table(headers);
-    let headers = reload_add_filter_button(headers);
+    Ok((
+        response_headers,
+        filter::components::FilterChip {
+            filter: &filter,
+            prop: &related_prop,
+        }
+        .render(),
+    ))
+}
 
-    Ok((headers, ""))
+/// Formula and rollup props are filtered like props of their result type,
+/// and created / last edited time props are filtered like date & time props,
+/// so this swaps in the type that the filter toolbar should use.
+async fn with_computed_result_types(
+    db: &sqlx::PgPool,
+    collection_id: i32,
+    mut props: Vec<models::Prop>,
+) -> Result<Vec<models::Prop>> {
+    for prop in props.iter_mut() {
+        if matches!(
+            prop.type_id,
+            models::ValueType::CreatedTime | models::ValueType::LastEditedTime
+        ) {
+            prop.type_id = models::ValueType::DateTime;
+        }
+    }
+    if props.iter().all(|p| {
+        p.type_id != models::ValueType::Formula
+            && p.type_id != models::ValueType::Rollup
+    }) {
+        return Ok(props);
+    }
+    let formula_query = formula::db_ops::ListFormulaQuery { collection_id };
+    let rollup_query = rollup::db_ops::ListRollupQuery { collection_id };
+    let (formulas, rollups) = join!(
+        formula::models::Formula::list(db, &formula_query),
+        rollup::models::Rollup::list(db, &rollup_query)
+    );
+    let formulas = formulas?;
+    let rollups = rollups?;
+    for prop in props.iter_mut() {
+        if let Some(formula) = formulas.iter().find(|f| f.prop_id == prop.id) {
+            prop.type_id = formula.result_type;
+        }
+        if let Some((_, result_type)) = rollups
+            .iter()
+            .find(|r| r.prop_id == prop.id)
+            .and_then(|r| rollup::compile(r).ok())
+        {
+            prop.type_id = result_type;
+        }
+    }
+
+    Ok(props)
 }
 
-pub async fn delete_int_rng_filter(
+#[derive(Deserialize)]
+pub struct FilterGroupQuery {
+    group_id: Option<i32>,
+}
+
+pub async fn choose_prop_for_filter(
     State(AppState { db }): State<AppState>,
-    Path(id): Path<i32>,
+    Path(view_id): Path<i32>,
+    Query(FilterGroupQuery { group_id }): Query<FilterGroupQuery>,
 ) -> Result<impl IntoResponse, ServerError> {
-    let filter = filter::models::Filter::get(
+    let view = view::models::View::get(
         &db,
-        &filter::db_ops::GetFilterQuery {
-            id,
-            value_type: models::ValueType::Int,
-            variant: filter::db_ops::Variant::Ranged,
+        &view::db_ops::GetViewQuery { id: view_id },
+    )
+    .await?;
+    let collection_id = view.collection_id;
+    let props = models::Prop::list(
+        &db,
+        &db_ops::ListPropQuery {
+            collection_id: Some(collection_id),
+            order_in: None,
+            exact_ids: None,
         },
     )
     .await?;
-    filter.delete(&db).await?;
+    let filters = filter::models::Filter::list(
+        &db,
+        &filter::db_ops::ListFilterQuery {
+            view_id: Some(view_id),
+            prop_id: None,
+        },
+    )
+    .await?;
+    let mut props_with_filter = HashSet::new();
+    for f in filters {
+        props_with_filter.insert(f.prop_id);
+    }
+    let props = with_computed_result_types(&db, collection_id, props).await?;
+    let props: Vec<&models::Prop> = props
+        .iter()
+        .filter(|p| !props_with_filter.contains(&p.id))
+        .filter(|p| {
+            !filter::models::FilterType::get_supported_filter_types(p.type_id)
+                .is_empty()
+        })
+        .collect();
 
-    let headers = HeaderMap::new();
-    let headers = reload_table(headers);
-    let headers = reload_add_filter_button(headers);
+    Ok(filter::components::ChoosePropForFilter {
+        view_id,
+        props: &props,
+        can_filter_title: !props_with_filter
+            .contains(&filter::models::TITLE_PROP_ID),
+        group_id,
+    }
+    .render())
+}
 
-    Ok((headers, ""))
+pub async fn new_filter_type_select(
+    State(AppState { db }): State<AppState>,
+    Path((view_id, prop_id)): Path<(i32, i32)>,
+    Query(FilterGroupQuery { group_id }): Query<FilterGroupQuery>,
+) -> Result<impl IntoResponse, ServerError> {
+    let prop =
+        models::Prop::get(&db, &db_ops::GetPropQuery { id: prop_id }).await?;
+    let prop = with_computed_result_types(&db, prop.collection_id, vec![prop])
+        .await?
+        .pop()
+        .expect("we passed one prop in");
+    let options =
+        filter::models::FilterType::get_supported_filter_types(prop.type_id);
+    Ok(filter::components::NewFilterTypeOptions {
+        options: &options,
+        view_id,
+        prop_id,
+        prop_type: prop.type_id,
+        group_id,
+    }
+    .render())
+}
+
+#[derive(Deserialize)]
+pub struct NewFilterQuery {
+    type_id: Option<i32>,
+    /// The group to add the filter to; the root group if not provided.
+    group_id: Option<i32>,
 }
 
-pub async fn delete_float_filter(
+/// Filters are created for the type of values that they compare; see
+/// [with_computed_result_types]. `kind` should agree with that type.
+pub async fn create_new_filter(
+    headers: HeaderMap,
     State(AppState { db }): State<AppState>,
-    Path(id): Path<i32>,
+    Path((view_id, prop_id, kind)): Path<(i32, i32, String)>,
+    Query(NewFilterQuery { type_id, group_id }): Query<NewFilterQuery>,
 ) -> Result<impl IntoResponse, ServerError> {
-    let filter = filter::models::Filter::get(
+    let user_id = get_user_id(&headers)?;
+    let kind = filter::models::FilterKind::from_slug(&kind)?;
+    let Ok(r#type) = type_id
+        .map(filter::models::FilterType::try_from)
+        .transpose()
+    else {
+        return Ok(
+            (StatusCode::BAD_REQUEST, "Invalid filter type").into_response()
+        );
+    };
+    let r#type = r#type.unwrap_or_else(|| kind.get_default_type());
+    let prop =
+        models::Prop::get(&db, &db_ops::GetPropQuery { id: prop_id }).await?;
+    let related_prop =
+        with_computed_result_types(&db, prop.collection_id, vec![prop])
+            .await?
+            .pop()
+            .expect("we passed one prop in");
+    if filter::models::FilterKind::new(r#type, related_prop.type_id)? != kind {
+        return Err(anyhow::anyhow!(
+            "{type} filters on prop {prop_id} are not {kind} filters"
+        )
+        .into());
+    }
+    let mut filter = filter::db_ops::create_filter(
         &db,
-        &filter::db_ops::GetFilterQuery {
-            id,
-            value_type: models::ValueType::Float,
-            variant: filter::db_ops::Variant::Single,
-        },
+        view_id,
+        prop_id,
+        r#type,
+        related_prop.type_id,
+        group_id,
+        user_id,
     )
     .await?;
-    filter.delete(&db).await?;
-
-    let headers = HeaderMap::new();
-    let headers = reload_table(headers);
-    let headers = reload_add_filter_button(headers);
+    filter.localize(get_user_tz(&db, &headers).await?);
 
-    Ok((headers, ""))
+    Ok(render_new_filter(&db, &filter, &related_prop, group_id)
+        .await?
+        .into_response())
 }
 
-pub async fn delete_float_rng_filter(
+pub async fn create_new_title_filter(
     State(AppState { db }): State<AppState>,
-    Path(id): Path<i32>,
+    Path(view_id): Path<i32>,
+    Query(NewFilterQuery { type_id, group_id }): Query<NewFilterQuery>,
 ) -> Result<impl IntoResponse, ServerError> {
-    let filter = filter::models::Filter::get(
-        &db,
-        &filter::db_ops::GetFilterQuery {
-            id,
-            value_type: models::ValueType::Bool,
-            variant: filter::db_ops::Variant::Ranged,
-        },
+    let Ok(r#type) = type_id
+        .map(filter::models::FilterType::try_from)
+        .transpose()
+    else {
+        return Ok(
+            (StatusCode::BAD_REQUEST, "Invalid filter type").into_response()
+        );
+    };
+    let r#type = r#type.unwrap_or(filter::models::FilterType::Contains);
+    // Page titles are filtered like text props.
+    filter::models::FilterKind::new(r#type, models::ValueType::Str)?;
+    let filter =
+        filter::db_ops::create_title_filter(&db, view_id, r#type, group_id)
+            .await?;
+    let related_prop = get_filter_prop(&db, &filter).await?;
+
+    Ok(render_new_filter(&db, &filter, &related_prop, group_id)
+        .await?
+        .into_response())
+}
+
+/// Whether another filter can be added to the view; see
+/// [filter::db_ops::does_view_have_capacity_for_additional_filters].
+async fn has_filter_capacity(db: &sqlx::PgPool, view_id: i32) -> Result<bool> {
+    let view = view::models::View::get(
+        db,
+        &view::db_ops::GetViewQuery { id: view_id },
     )
     .await?;
-    filter.delete(&db).await?;
+    let props = db_ops::get_prop_set(db, view.collection_id).await?;
+    let props =
+        with_computed_result_types(db, view.collection_id, props).await?;
+    filter::db_ops::does_view_have_capacity_for_additional_filters(
+        db, view_id, &props,
+    )
+    .await
+}
 
+/// The form for a new filter, next to the add filter button, which is
+/// replaced with a placeholder once every prop has a filter.
+async fn render_new_filter(
+    db: &sqlx::PgPool,
+    filter: &filter::models::Filter,
+    related_prop: &models::Prop,
+    group_id: Option<i32>,
+) -> Result<(HeaderMap, String), ServerError> {
     let headers = HeaderMap::new();
     let headers = reload_table(headers);
-    let headers = reload_add_filter_button(headers);
 
-    Ok((headers, ""))
+    let has_capacity = has_filter_capacity(db, filter.view_id).await?;
+    let add_filter_button = if has_capacity {
+        filter::components::AddFilterButton {
+            view_id: filter.view_id,
+            group_id,
+        }
+        .render()
+    } else {
+        filter::components::AddFilterButtonPlaceholder {
+            view_id: filter.view_id,
+            group_id,
+        }
+        .render()
+    };
+
+    Ok((
+        headers,
+        [
+            r#"<div class="flex flex-row gap-2">"#,
+            &add_filter_button,
+            &filter::components::FilterForm {
+                filter,
+                prop_name: &related_prop.name,
+            }
+            .render(),
+            "</div>",
+        ]
+        .join(""),
+    ))
 }
 
-pub async fn delete_date_filter(
+/// I pulled this out into a separate request because it requires its own
+/// database query. We only want to show the filter button if there are
+/// props in the workspace that do not have any filters in the view already.
+pub async fn get_add_filter_button(
     State(AppState { db }): State<AppState>,
-    Path(id): Path<i32>,
+    Path(view_id): Path<i32>,
+    Query(FilterGroupQuery { group_id }): Query<FilterGroupQuery>,
 ) -> Result<impl IntoResponse, ServerError> {
-    let filter = filter::models::Filter::get(
-        &db,
-        &filter::db_ops::GetFilterQuery {
-            id,
-            value_type: models::ValueType::Date,
-            variant: filter::db_ops::Variant::Single,
-        },
-    )
-    .await?;
-    filter.delete(&db).await?;
-
-    let headers = HeaderMap::new();
-    let headers = reload_table(headers);
-    let headers = reload_add_filter_button(headers);
+    let does_it_tho = has_filter_capacity(&db, view_id).await?;
 
-    Ok((headers, ""))
+    if does_it_tho {
+        Ok(filter::components::AddFilterButton { view_id, group_id }.render())
+    } else {
+        Ok(
+            filter::components::AddFilterButtonPlaceholder {
+                view_id,
+                group_id,
+            }
+            .render(),
+        )
+    }
 }
 
-pub async fn delete_date_rng_filter(
+pub async fn show_sort_toolbar(
     State(AppState { db }): State<AppState>,
-    Path(id): Path<i32>,
+    Path(view_id): Path<i32>,
 ) -> Result<impl IntoResponse, ServerError> {
-    let filter = filter::models::Filter::get(
+    let view = view::models::View::get(
         &db,
-        &filter::db_ops::GetFilterQuery {
-            id,
-            value_type: models::ValueType::Date,
-            variant: filter::db_ops::Variant::Ranged,
-        },
+        &view::db_ops::GetViewQuery { id: view_id },
     )
     .await?;
-    filter.delete(&db).await?;
-
-    let headers = HeaderMap::new();
-    let headers = reload_table(headers);
-    let headers = reload_add_filter_button(headers);
-
-    Ok((headers, ""))
-}
-pub async fn show_sort_toolbar(
-    State(AppState { db }): State<AppState>,
-    Path(collection_id): Path<i32>,
-) -> Result<impl IntoResponse, ServerError> {
     let props = models::Prop::list(
         &db,
         &db_ops::ListPropQuery {
-            collection_id: Some(collection_id),
+            collection_id: Some(view.collection_id),
             order_in: None,
             exact_ids: None,
         },
     )
     .await?;
-    if let Ok(sort) = models::CollectionSort::get(
-        &db,
-        &db_ops::GetSortQuery { collection_id },
-    )
-    .await
+    if let Ok(sort) =
+        models::ViewSort::get(&db, &db_ops::GetSortQuery { view_id }).await
     {
         Ok(components::SortToolbar {
-            collection_id,
+            view_id,
             prop_choices: &props[..],
             sort_type: sort.r#type,
             default_selected_prop: sort.prop_id,
@@ -1953,7 +3146,7 @@ pub async fn show_sort_toolbar(
         .render())
     } else {
         Ok(components::SortToolbar {
-            collection_id,
+            view_id,
             prop_choices: &props[..],
             sort_type: Some(models::SortType::Asc),
             default_selected_prop: None,
@@ -1962,10 +3155,8 @@ pub async fn show_sort_toolbar(
     }
 }
 
-pub async fn hide_sort_toolbar(
-    Path(collection_id): Path<i32>,
-) -> impl IntoResponse {
-    components::SortToolbarPlaceholder { collection_id }.render()
+pub async fn hide_sort_toolbar(Path(view_id): Path<i32>) -> impl IntoResponse {
+    components::SortToolbarPlaceholder { view_id }.render()
 }
 
 #[derive(Debug, Deserialize)]
@@ -1976,36 +3167,30 @@ pub struct SortForm {
 
 pub async fn handle_sort_form_submit(
     State(AppState { db }): State<AppState>,
-    Path(collection_id): Path<i32>,
+    Path(view_id): Path<i32>,
     Form(form): Form<SortForm>,
 ) -> Result<impl IntoResponse, ServerError> {
     // I'm being a bad person and using -1 as a sentinel for NULL.
     //
     // Don't @ me
     let new_sort = if form.sort_by == -1 {
-        models::CollectionSort {
-            collection_id,
+        models::ViewSort {
+            view_id,
             prop_id: None,
             r#type: None,
         }
     } else {
-        models::CollectionSort {
-            collection_id,
+        models::ViewSort {
+            view_id,
             prop_id: Some(form.sort_by),
             r#type: Some(models::SortType::from_int(form.sort_order)?),
         }
     };
     // Implicitly treating 'error' as 'does not exist'
-    let existing_sort = if let Ok(sort) = models::CollectionSort::get(
-        &db,
-        &db_ops::GetSortQuery { collection_id },
-    )
-    .await
-    {
-        Some(sort)
-    } else {
-        None
-    };
+    let existing_sort =
+        models::ViewSort::get(&db, &db_ops::GetSortQuery { view_id })
+            .await
+            .ok();
     let headers = HeaderMap::new();
     Ok(
         if existing_sort.is_none() || new_sort != existing_sort.unwrap() {
@@ -2013,19 +3198,267 @@ pub async fn handle_sort_form_submit(
             let headers = reload_table(headers);
             (
                 headers,
-                components::SortOrderSavedConfirmation { collection_id }
-                    .render(),
+                components::SortOrderSavedConfirmation { view_id }.render(),
             )
         } else {
             (
                 headers,
-                components::SortOrderSavedConfirmation { collection_id }
-                    .render(),
+                components::SortOrderSavedConfirmation { view_id }.render(),
             )
         },
     )
 }
 
+async fn get_option_editor_data(
+    db: &sqlx::PgPool,
+    prop_id: i32,
+) -> Result<(models::Prop, Vec<models::SelectOption>)> {
+    let prop_query = db_ops::GetPropQuery { id: prop_id };
+    let options_query = db_ops::ListSelectOptionQuery { prop_id };
+    let (prop, options) = join!(
+        models::Prop::get(db, &prop_query),
+        models::SelectOption::list(db, &options_query)
+    );
+    let prop = prop?;
+    let options = options?;
+    if prop.type_id != models::ValueType::Select {
+        anyhow::bail!("prop {prop_id} is not a select prop");
+    }
+
+    Ok((prop, options))
+}
+
+async fn render_option_editor(
+    db: &sqlx::PgPool,
+    prop_id: i32,
+) -> Result<String> {
+    let (prop, options) = get_option_editor_data(db, prop_id).await?;
+
+    Ok(components::SelectOptionEditor {
+        prop: &prop,
+        options: &options,
+    }
+    .render())
+}
+
+pub async fn get_option_editor(
+    headers: HeaderMap,
+    State(AppState { db }): State<AppState>,
+    Path(prop_id): Path<i32>,
+) -> Result<impl IntoResponse, ServerError> {
+    let (prop, options) = get_option_editor_data(&db, prop_id).await?;
+    let editor = components::SelectOptionEditor {
+        prop: &prop,
+        options: &options,
+    };
+
+    Ok(if headers.contains_key("Hx-Request") {
+        editor.render()
+    } else {
+        components::Page {
+            title: "Edit Options",
+            children: Box::new(editor),
+        }
+        .render()
+    })
+}
+
+#[derive(Deserialize)]
+pub struct NewOptionForm {
+    name: String,
+    color: String,
+}
+pub async fn handle_new_option(
+    State(AppState { db }): State<AppState>,
+    Path(prop_id): Path<i32>,
+    Form(form): Form<NewOptionForm>,
+) -> Result<impl IntoResponse, ServerError> {
+    let color = models::SelectColor::from_name(&form.color)?;
+    let name = form.name.trim();
+    if !name.is_empty() {
+        db_ops::create_select_option(&db, prop_id, name, color).await?;
+    }
+
+    Ok(render_option_editor(&db, prop_id).await?)
+}
+
+pub async fn delete_option(
+    State(AppState { db }): State<AppState>,
+    Path((prop_id, option_id)): Path<(i32, i32)>,
+) -> Result<impl IntoResponse, ServerError> {
+    let option = models::SelectOption::get(
+        &db,
+        &db_ops::GetSelectOptionQuery { id: option_id },
+    )
+    .await?;
+    if option.prop_id != prop_id {
+        return Err(anyhow::anyhow!(
+            "option {option_id} does not belong to prop {prop_id}"
+        )
+        .into());
+    }
+    option.delete(&db).await?;
+
+    Ok(render_option_editor(&db, prop_id).await?)
+}
+
+#[derive(Deserialize)]
+pub struct OptionListQuery {
+    selected: Option<i32>,
+}
+pub async fn get_option_list(
+    State(AppState { db }): State<AppState>,
+    Path(prop_id): Path<i32>,
+    Query(OptionListQuery { selected }): Query<OptionListQuery>,
+) -> Result<impl IntoResponse, ServerError> {
+    let options = models::SelectOption::list(
+        &db,
+        &db_ops::ListSelectOptionQuery { prop_id },
+    )
+    .await?;
+
+    Ok(components::SelectOptionList {
+        options: &options,
+        selected,
+    }
+    .render())
+}
+
+pub async fn get_timezone_form(
+    headers: HeaderMap,
+    State(AppState { db }): State<AppState>,
+) -> Result<impl IntoResponse, ServerError> {
+    let ctx = get_relative_date_ctx(&db, &headers).await?;
+    let form = components::TimezoneForm {
+        current: ctx.tz,
+        week_start: ctx.week_start,
+    };
+
+    Ok(if headers.contains_key("Hx-Request") {
+        form.render()
+    } else {
+        components::Page {
+            title: "Timezone",
+            children: Box::new(form),
+        }
+        .render()
+    })
+}
+
+#[derive(Deserialize)]
+pub struct TimezoneForm {
+    timezone: String,
+    /// An ISO weekday, where Monday is 1.
+    week_start: u8,
+}
+pub async fn handle_timezone_submit(
+    headers: HeaderMap,
+    State(AppState { db }): State<AppState>,
+    Form(form): Form<TimezoneForm>,
+) -> Result<impl IntoResponse, ServerError> {
+    let session = session::Session::from_headers(&headers)
+        .ok_or_else(|| anyhow::anyhow!("session is missing"))?;
+    let tz: Tz = form
+        .timezone
+        .parse()
+        .map_err(|e| anyhow::anyhow!("invalid timezone: {e}"))?;
+    let week_start = form
+        .week_start
+        .checked_sub(1)
+        .and_then(|day| chrono::Weekday::try_from(day).ok())
+        .ok_or_else(|| anyhow::anyhow!("invalid first day of the week"))?;
+    db_ops::set_user_timezone(&db, session.user.id, tz).await?;
+    db_ops::set_user_week_start(&db, session.user.id, week_start).await?;
+    let headers = htmx::redirect(HeaderMap::new(), "/collection/1");
+    Ok((headers, "OK".to_string()))
+}
+
+#[derive(Deserialize)]
+pub struct SearchQuery {
+    q: Option<String>,
+}
+pub async fn search(
+    headers: HeaderMap,
+    State(AppState { db }): State<AppState>,
+    Query(SearchQuery { q }): Query<SearchQuery>,
+) -> Result<impl IntoResponse, ServerError> {
+    let text = q.unwrap_or_default();
+    let results = match search::models::normalize(Some(&text)) {
+        Some(text) => Some(search::db_ops::search_pages(&db, text).await?),
+        None => None,
+    };
+    let search_page = search::components::SearchPage {
+        text: &text,
+        results: results.as_deref(),
+    };
+
+    Ok(if headers.contains_key("Hx-Request") {
+        search_page.render()
+    } else {
+        components::Page {
+            title: "Search",
+            children: Box::new(search_page),
+        }
+        .render()
+    })
+}
+
+pub async fn get_key_prefix_form(
+    headers: HeaderMap,
+    State(AppState { db }): State<AppState>,
+    Path(collection_id): Path<i32>,
+) -> Result<impl IntoResponse, ServerError> {
+    let current = db_ops::get_collection_key_prefix(&db, collection_id).await?;
+    let form = components::KeyPrefixForm {
+        collection_id,
+        current: &current,
+        error: None,
+    };
+
+    Ok(if headers.contains_key("Hx-Request") {
+        form.render()
+    } else {
+        components::Page {
+            title: "Page Keys",
+            children: Box::new(form),
+        }
+        .render()
+    })
+}
+
+#[derive(Deserialize)]
+pub struct KeyPrefixForm {
+    key_prefix: String,
+}
+pub async fn handle_key_prefix_submit(
+    State(AppState { db }): State<AppState>,
+    Path(collection_id): Path<i32>,
+    Form(form): Form<KeyPrefixForm>,
+) -> Result<impl IntoResponse, ServerError> {
+    let key_prefix = form.key_prefix.trim();
+    // Invalid or duplicate prefixes are shown to the user, rather than
+    // failing the request.
+    if let Err(e) =
+        db_ops::set_collection_key_prefix(&db, collection_id, key_prefix).await
+    {
+        let error = e.to_string();
+        return Ok((
+            HeaderMap::new(),
+            components::KeyPrefixForm {
+                collection_id,
+                current: key_prefix,
+                error: Some(&error),
+            }
+            .render(),
+        ));
+    }
+    let collection_route = Route::Collection(Some(collection_id));
+    let headers =
+        htmx::redirect(HeaderMap::new(), &collection_route.as_string());
+
+    Ok((headers, "OK".to_string()))
+}
+
 pub async fn get_registration_form(headers: HeaderMap) -> impl IntoResponse {
     let form = components::RegisterForm {};
 
@@ -2046,6 +3479,9 @@ pub struct RegisterForm {
     email: String,
     password: String,
     secret_word: String,
+    /// Detected by the browser; we'll fall back to UTC if it's missing or
+    /// unrecognized.
+    timezone: Option<String>,
 }
 
 pub async fn handle_registration(
@@ -2065,6 +3501,9 @@ pub async fn handle_registration(
     let hashed_pw = pw::hash_new(&form.password);
     let user =
         db_ops::create_user(&db, form.username, form.email, &hashed_pw).await?;
+    if let Some(Ok(tz)) = form.timezone.map(|tz| tz.parse::<Tz>()) {
+        db_ops::set_user_timezone(&db, user.id, tz).await?;
+    }
     let now = std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)?
         .as_secs();
@@ -2119,3 +3558,264 @@ pub async fn handle_login(
         ))
     }
 }
+
+async fn get_formula_editor_data(
+    db: &sqlx::PgPool,
+    prop_id: i32,
+) -> Result<(models::Prop, Vec<models::Prop>, Option<String>)> {
+    let prop =
+        models::Prop::get(db, &db_ops::GetPropQuery { id: prop_id }).await?;
+    if prop.type_id != models::ValueType::Formula {
+        anyhow::bail!("prop {prop_id} is not a formula prop");
+    }
+    let formula_query = formula::db_ops::GetFormulaQuery { prop_id };
+    let (props, formula) = join!(
+        db_ops::get_prop_set(db, prop.collection_id),
+        formula::models::Formula::get(db, &formula_query)
+    );
+    let props = props?;
+    // A formula prop has no expression until one is saved for the first time.
+    let expression = formula.ok().map(|f| f.expression);
+
+    Ok((prop, props, expression))
+}
+
+pub async fn get_formula_editor(
+    headers: HeaderMap,
+    State(AppState { db }): State<AppState>,
+    Path(prop_id): Path<i32>,
+) -> Result<impl IntoResponse, ServerError> {
+    let (prop, props, expression) =
+        get_formula_editor_data(&db, prop_id).await?;
+    let editor = formula::components::FormulaEditor {
+        prop: &prop,
+        expression: expression.as_deref().unwrap_or_default(),
+        props: &props,
+        error: None,
+        saved_type: None,
+    };
+
+    Ok(if headers.contains_key("Hx-Request") {
+        editor.render()
+    } else {
+        components::Page {
+            title: "Edit Formula",
+            children: Box::new(editor),
+        }
+        .render()
+    })
+}
+
+#[derive(Deserialize)]
+pub struct FormulaForm {
+    expression: String,
+}
+/// Invalid expressions are not saved; the error is shown in the editor
+/// instead.
+pub async fn handle_formula_submit(
+    State(AppState { db }): State<AppState>,
+    Path(prop_id): Path<i32>,
+    Form(FormulaForm { expression }): Form<FormulaForm>,
+) -> Result<impl IntoResponse, ServerError> {
+    let (prop, props, _) = get_formula_editor_data(&db, prop_id).await?;
+    let (error, saved_type) = match formula::compile(&expression, &props) {
+        Ok((_, result_type)) => {
+            formula::db_ops::save_formula(
+                &db,
+                &formula::models::Formula {
+                    prop_id,
+                    expression: expression.clone(),
+                    result_type,
+                },
+            )
+            .await?;
+            (None, Some(result_type))
+        }
+        Err(e) => (Some(e.to_string()), None),
+    };
+
+    Ok(formula::components::FormulaEditor {
+        prop: &prop,
+        expression: &expression,
+        props: &props,
+        error: error.as_deref(),
+        saved_type,
+    }
+    .render())
+}
+
+async fn get_rollup_editor_data(
+    db: &sqlx::PgPool,
+    prop_id: i32,
+    relation_prop_id: Option<i32>,
+) -> Result<(
+    models::Prop,
+    Vec<rollup::models::RelationChoice>,
+    Option<rollup::models::Rollup>,
+    Option<i32>,
+    Vec<models::Prop>,
+)> {
+    let prop =
+        models::Prop::get(db, &db_ops::GetPropQuery { id: prop_id }).await?;
+    if prop.type_id != models::ValueType::Rollup {
+        anyhow::bail!("prop {prop_id} is not a rollup prop");
+    }
+    let rollup_query = rollup::db_ops::GetRollupQuery { prop_id };
+    let (relations, saved) = join!(
+        rollup::db_ops::list_relation_choices(db, prop.collection_id),
+        rollup::models::Rollup::get(db, &rollup_query)
+    );
+    let relations = relations?;
+    // A rollup prop has no configuration until it's saved for the first
+    // time.
+    let saved = saved.ok();
+    let relation_prop_id = relation_prop_id
+        .or(saved.as_ref().map(|r| r.relation_prop_id))
+        .or(relations.first().map(|r| r.prop_id));
+    let targets = match relations
+        .iter()
+        .find(|r| Some(r.prop_id) == relation_prop_id)
+    {
+        Some(relation) => {
+            db_ops::get_prop_set(db, relation.linked_collection_id)
+                .await?
+                .into_iter()
+                .filter(|p| {
+                    matches!(
+                        p.type_id,
+                        models::ValueType::Int
+                            | models::ValueType::Float
+                            | models::ValueType::Bool
+                    )
+                })
+                .collect()
+        }
+        None => vec![],
+    };
+
+    Ok((prop, relations, saved, relation_prop_id, targets))
+}
+
+#[derive(Deserialize)]
+pub struct RollupEditorQuery {
+    relation_prop_id: Option<i32>,
+}
+pub async fn get_rollup_editor(
+    headers: HeaderMap,
+    State(AppState { db }): State<AppState>,
+    Path(prop_id): Path<i32>,
+    Query(RollupEditorQuery { relation_prop_id }): Query<RollupEditorQuery>,
+) -> Result<impl IntoResponse, ServerError> {
+    let (prop, relations, saved, relation_prop_id, targets) =
+        get_rollup_editor_data(&db, prop_id, relation_prop_id).await?;
+    // The saved target only makes sense for the saved relation.
+    let target_prop_id = saved
+        .as_ref()
+        .filter(|r| Some(r.relation_prop_id) == relation_prop_id)
+        .and_then(|r| r.target_prop_id);
+    let editor = rollup::components::RollupEditor {
+        prop: &prop,
+        relations: &relations,
+        relation_prop_id,
+        targets: &targets,
+        target_prop_id,
+        aggregate: saved
+            .map(|r| r.aggregate)
+            .unwrap_or(rollup::models::Aggregate::Count),
+        error: None,
+        saved_type: None,
+    };
+
+    Ok(if headers.contains_key("Hx-Request") {
+        editor.render()
+    } else {
+        components::Page {
+            title: "Edit Rollup",
+            children: Box::new(editor),
+        }
+        .render()
+    })
+}
+
+#[derive(Deserialize)]
+pub struct RollupForm {
+    relation_prop_id: i32,
+    /// Empty when no target prop is chosen.
+    target_prop_id: String,
+    aggregate_id: i32,
+}
+/// Rollups which cannot be computed (say, summing a checkbox) are not
+/// saved; the error is shown in the editor instead.
+pub async fn handle_rollup_submit(
+    State(AppState { db }): State<AppState>,
+    Path(prop_id): Path<i32>,
+    Form(form): Form<RollupForm>,
+) -> Result<impl IntoResponse, ServerError> {
+    let (prop, relations, saved, relation_prop_id, targets) =
+        get_rollup_editor_data(&db, prop_id, Some(form.relation_prop_id))
+            .await?;
+    let relation = relations
+        .iter()
+        .find(|r| Some(r.prop_id) == relation_prop_id)
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "prop {} cannot be rolled up into prop {prop_id}",
+                form.relation_prop_id
+            )
+        })?;
+    let target = if form.target_prop_id.is_empty() {
+        None
+    } else {
+        let target_prop_id: i32 = form.target_prop_id.parse()?;
+        Some(targets.iter().find(|p| p.id == target_prop_id).ok_or_else(
+            || {
+                anyhow::anyhow!(
+                    "prop {target_prop_id} is not in the linked collection"
+                )
+            },
+        )?)
+    };
+    let aggregate = rollup::models::Aggregate::all()
+        .into_iter()
+        .find(|a| a.get_int_repr() == form.aggregate_id)
+        .ok_or_else(|| {
+            anyhow::anyhow!("{} is not an aggregate", form.aggregate_id)
+        })?;
+    let new_rollup = rollup::models::Rollup {
+        prop_id,
+        relation_prop_id: relation.prop_id,
+        target_prop_id: target.map(|p| p.id),
+        aggregate,
+        backlink: relation.backlink,
+        target_type: target.map(|p| p.type_id),
+    };
+    let (error, saved_type) = match rollup::compile(&new_rollup) {
+        Ok((_, result_type)) => {
+            // Existing filters are for the old result type.
+            let previous_type = saved
+                .and_then(|r| rollup::compile(&r).ok())
+                .map(|(_, ty)| ty);
+            if previous_type.is_some_and(|ty| ty != result_type) {
+                let mut tx = db.begin().await?;
+                filter::db_ops::delete_filters_for_prop(&mut tx, prop_id)
+                    .await?;
+                tx.commit().await?;
+            }
+            new_rollup.save(&db).await?;
+            (None, Some(result_type))
+        }
+        Err(e) => (Some(e.to_string()), None),
+    };
+
+    Ok(rollup::components::RollupEditor {
+        prop: &prop,
+        relations: &relations,
+        relation_prop_id,
+        targets: &targets,
+        target_prop_id: new_rollup.target_prop_id,
+        aggregate,
+        error: error.as_deref(),
+        saved_type,
+    }
+    .render())
+}
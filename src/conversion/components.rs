@@ -0,0 +1,131 @@
+use super::models::{as_text, is_convertible, Conversion};
+use crate::{
+    components::Component,
+    models::{Prop, ValueType},
+    routes::Route,
+};
+use ammonia::clean;
+use std::fmt::Write;
+
+/// Form for changing the type of a prop. Choosing a type re-renders the form
+/// with a preview of the conversion, so that nothing is lost by surprise.
+pub struct TypeChangeForm<'a> {
+    pub prop: &'a Prop,
+    pub to: Option<ValueType>,
+    pub preview: Option<&'a Conversion>,
+}
+impl Component for TypeChangeForm<'_> {
+    fn render(&self) -> String {
+        let prop_name = clean(&self.prop.name);
+        let from_name = self.prop.type_id.get_display_name();
+        let type_route = Route::PropType(Some(self.prop.id));
+        let props_route = Route::CollectionProps(Some(self.prop.collection_id));
+        let type_options = ValueType::all()
+            .iter()
+            .filter(|t| is_convertible(**t) && **t != self.prop.type_id)
+            .fold(
+                String::from(r#"<option value="">Choose a type</option>"#),
+                |mut acc, t| {
+                    let type_id = t.get_int_repr();
+                    let type_name = t.get_display_name();
+                    let selected =
+                        if Some(*t) == self.to { "selected" } else { "" };
+                    let _ = write!(
+                        acc,
+                        r#"<option {selected} value="{type_id}">{type_name}</option>"#
+                    );
+                    acc
+                },
+            );
+        let preview = match self.preview {
+            Some(preview) => render_preview(preview),
+            None => "".into(),
+        };
+        format!(
+            r#"
+            <div class="flex flex-col gap-2 max-w-md" hx-target="this" hx-swap="outerHTML">
+                <a class="link" href="{props_route}">Back</a>
+                <h1 class="text-xl">Change Type of {prop_name}</h1>
+                <p>{prop_name} is a {from_name} prop.</p>
+                <form class="flex flex-col gap-2" hx-post="{type_route}">
+                    <label for="type_id">New Type</label>
+                    <select
+                        class="dark:text-black"
+                        id="type_id"
+                        name="type_id"
+                        required
+                        hx-get="{type_route}"
+                        hx-trigger="change"
+                    >{type_options}</select>
+                    {preview}
+                </form>
+            </div>
+            "#
+        )
+    }
+}
+
+fn render_preview(preview: &Conversion) -> String {
+    let converted = match preview.converted.len() {
+        1 => "1 value".to_string(),
+        n => format!("{n} values"),
+    };
+    let from_name = preview.from.get_display_name();
+    let to_name = preview.to.get_display_name();
+    let lost = if preview.lost.is_empty() {
+        r#"<p>No values will be lost.</p>"#.to_string()
+    } else {
+        let rows = preview.lost.iter().fold(String::new(), |mut acc, l| {
+            let title = clean(&l.page_title);
+            let page_route = Route::Page(Some(l.page_id));
+            let value = clean(&as_text(&l.value));
+            let reason = clean(&l.reason);
+            let _ = write!(
+                acc,
+                r#"
+                <tr>
+                    <td class="p-1"><a class="link" href="{page_route}">{title}</a></td>
+                    <td class="p-1">{value}</td>
+                    <td class="p-1">{reason}</td>
+                </tr>
+                "#
+            );
+            acc
+        });
+        let count = match preview.lost.len() {
+            1 => "1 value".to_string(),
+            n => format!("{n} values"),
+        };
+        format!(
+            r#"
+            <p class="text-red-600 dark:text-red-400">{count} will be lost:</p>
+            <table class="text-sm">
+                <thead>
+                    <tr>
+                        <th class="p-1 text-left">Page</th>
+                        <th class="p-1 text-left">Value</th>
+                        <th class="p-1 text-left">Reason</th>
+                    </tr>
+                </thead>
+                <tbody>{rows}</tbody>
+            </table>
+            "#
+        )
+    };
+    let kept_filters = preview.rewritten_filters.len();
+    let filters = match (kept_filters, preview.dropped_filters) {
+        (0, 0) => "".to_string(),
+        (kept, 0) => format!("<p>{kept} filter(s) will be kept.</p>"),
+        (kept, dropped) => format!(
+            "<p>{kept} filter(s) will be kept, and {dropped} filter(s) which don't apply to {to_name} props will be deleted.</p>"
+        ),
+    };
+    format!(
+        r#"
+        <p>{converted} will be converted from {from_name} to {to_name}.</p>
+        {lost}
+        {filters}
+        <button class="dark:bg-slate-700 w-36 dark:text-white dark:hover:bg-slate-600 transition shadow hover:shadow-none rounded p-1 block">Change Type</button>
+        "#
+    )
+}
@@ -0,0 +1,536 @@
+use super::models::{is_convertible, Conversion};
+use crate::{
+    filter::{self, db_ops::ListFilterQuery},
+    formula,
+    models::{Prop, SelectColor, SelectOption, Value, ValueType},
+};
+use anyhow::{bail, Result};
+use chrono::Utc;
+use chrono_tz::Tz;
+use sqlx::{query, query_as, PgConnection, PgPool};
+
+impl Prop {
+    /// Work out what changing the type of this prop to `to` would do,
+    /// without changing anything.
+    pub async fn preview_type_change(
+        &self,
+        db: &PgPool,
+        to: ValueType,
+    ) -> Result<Conversion> {
+        let mut conn = db.acquire().await?;
+        plan_conversion(&mut conn, self, self.type_id, to).await
+    }
+
+    /// Change the type of this prop to `to`, moving its values into the
+    /// `propval_*` table for the new type. Values which cannot be converted
+    /// are lost, as are filters which don't apply to the new type; see
+    /// [Prop::preview_type_change]. Formulas which use the prop may change
+    /// type too; see [formula::db_ops::refresh_result_types].
+    ///
+    /// The prop is locked while its values are read and moved, so values
+    /// saved meanwhile wait for the conversion rather than being lost; see
+    /// [crate::prop_val::models::PropVal::save_in].
+    pub async fn change_type(
+        &mut self,
+        db: &PgPool,
+        to: ValueType,
+    ) -> Result<Conversion> {
+        let mut tx = db.begin().await?;
+        let locked = query!(
+            "select type_id from property where id = $1 for update",
+            self.id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        let from = ValueType::from_int(locked.type_id);
+        let conversion = plan_conversion(&mut tx, self, from, to).await?;
+        delete_values(&mut tx, self.id, from).await?;
+        filter::db_ops::delete_filters_for_prop(&mut tx, self.id).await?;
+        query!(
+            "update property set type_id = $1 where id = $2",
+            to.get_int_repr(),
+            self.id
+        )
+        .execute(&mut *tx)
+        .await?;
+        insert_values(&mut tx, self.id, to, &conversion.converted).await?;
+        for filter in &conversion.rewritten_filters {
+            filter::db_ops::insert_filter(&mut tx, filter).await?;
+        }
+        formula::db_ops::refresh_result_types(&mut tx, self.collection_id)
+            .await?;
+        tx.commit().await?;
+        self.type_id = to;
+
+        Ok(conversion)
+    }
+}
+
+/// Read the values and filters of `prop`, currently of type `from`, and work
+/// out what converting them to `to` would do.
+async fn plan_conversion(
+    conn: &mut PgConnection,
+    prop: &Prop,
+    from: ValueType,
+    to: ValueType,
+) -> Result<Conversion> {
+    if !is_convertible(from) || !is_convertible(to) {
+        bail!(
+            "cannot convert {} props into {} props",
+            from.get_display_name(),
+            to.get_display_name()
+        );
+    }
+    if from == to {
+        bail!(
+            "prop {} is already a {} prop",
+            prop.id,
+            to.get_display_name()
+        );
+    }
+    let propvals = select_values(conn, prop.id, from).await?;
+    let filters = filter::db_ops::list_filters(
+        conn,
+        &ListFilterQuery {
+            view_id: None,
+            prop_id: Some(prop.id),
+        },
+    )
+    .await?;
+
+    Ok(Conversion::new(from, to, propvals, filters))
+}
+
+/// Every value of the prop, as `(page_id, page_title, value)` in page order.
+async fn select_values(
+    conn: &mut PgConnection,
+    prop_id: i32,
+    ty: ValueType,
+) -> Result<Vec<(i32, String, Value)>> {
+    struct Qres<T> {
+        page_id: i32,
+        title: String,
+        value: T,
+    }
+    Ok(match ty {
+        ValueType::Bool => query_as!(
+            Qres::<bool>,
+            "select v.page_id, p.title, v.value
+            from propval_bool v
+            join page p on p.id = v.page_id
+            where v.prop_id = $1
+            order by v.page_id",
+            prop_id
+        )
+        .fetch_all(&mut *conn)
+        .await?
+        .into_iter()
+        .map(|r| (r.page_id, r.title, Value::Bool(r.value)))
+        .collect(),
+        ValueType::Int => query_as!(
+            Qres::<i64>,
+            "select v.page_id, p.title, v.value
+            from propval_int v
+            join page p on p.id = v.page_id
+            where v.prop_id = $1
+            order by v.page_id",
+            prop_id
+        )
+        .fetch_all(&mut *conn)
+        .await?
+        .into_iter()
+        .map(|r| (r.page_id, r.title, Value::Int(r.value)))
+        .collect(),
+        ValueType::Float => query_as!(
+            Qres::<f64>,
+            "select v.page_id, p.title, v.value
+            from propval_float v
+            join page p on p.id = v.page_id
+            where v.prop_id = $1
+            order by v.page_id",
+            prop_id
+        )
+        .fetch_all(&mut *conn)
+        .await?
+        .into_iter()
+        .map(|r| (r.page_id, r.title, Value::Float(r.value)))
+        .collect(),
+        ValueType::Str => query_as!(
+            Qres::<String>,
+            "select v.page_id, p.title, v.value
+            from propval_str v
+            join page p on p.id = v.page_id
+            where v.prop_id = $1
+            order by v.page_id",
+            prop_id
+        )
+        .fetch_all(&mut *conn)
+        .await?
+        .into_iter()
+        .map(|r| (r.page_id, r.title, Value::Str(r.value)))
+        .collect(),
+        ValueType::MultiStr => query_as!(
+            Qres::<Vec<String>>,
+            r#"select
+                m.page_id,
+                p.title,
+                coalesce(
+                    array_agg(v.value order by v.value)
+                        filter (where v.value is not null),
+                    '{}'
+                ) as "value!"
+            from propval_multistr m
+            join page p on p.id = m.page_id
+            left join propval_multistr__value v
+                on v.propval_multistr_id = m.id
+            where m.prop_id = $1
+            group by m.page_id, p.title
+            order by m.page_id"#,
+            prop_id
+        )
+        .fetch_all(&mut *conn)
+        .await?
+        .into_iter()
+        .map(|r| (r.page_id, r.title, Value::MultiStr(r.value)))
+        .collect(),
+        ValueType::Date => query_as!(
+            Qres::<chrono::NaiveDate>,
+            "select v.page_id, p.title, v.value
+            from propval_date v
+            join page p on p.id = v.page_id
+            where v.prop_id = $1
+            order by v.page_id",
+            prop_id
+        )
+        .fetch_all(&mut *conn)
+        .await?
+        .into_iter()
+        .map(|r| (r.page_id, r.title, Value::Date(r.value)))
+        .collect(),
+        ValueType::DateTime => query_as!(
+            Qres::<chrono::DateTime<Utc>>,
+            "select v.page_id, p.title, v.value
+            from propval_datetime v
+            join page p on p.id = v.page_id
+            where v.prop_id = $1
+            order by v.page_id",
+            prop_id
+        )
+        .fetch_all(&mut *conn)
+        .await?
+        .into_iter()
+        .map(|r| {
+            (
+                r.page_id,
+                r.title,
+                Value::DateTime(r.value.with_timezone(&Tz::UTC)),
+            )
+        })
+        .collect(),
+        ValueType::Select => {
+            struct QresSelect {
+                page_id: i32,
+                title: String,
+                option_id: i32,
+                name: String,
+                color: String,
+                order: i16,
+            }
+            query_as!(
+                QresSelect,
+                r#"select
+                    v.page_id,
+                    p.title,
+                    po.id option_id,
+                    po.name,
+                    po.color,
+                    po."order"
+                from propval_select v
+                join page p on p.id = v.page_id
+                join property_option po on po.id = v.value
+                where v.prop_id = $1
+                order by v.page_id"#,
+                prop_id
+            )
+            .fetch_all(&mut *conn)
+            .await?
+            .into_iter()
+            .map(|r| {
+                let option = SelectOption {
+                    id: r.option_id,
+                    prop_id,
+                    name: r.name,
+                    color: SelectColor::from_name(&r.color)?,
+                    order: r.order,
+                };
+                Ok((r.page_id, r.title, Value::Select(option)))
+            })
+            .collect::<Result<_>>()?
+        }
+        ty => bail!("{} props cannot be converted", ty.get_display_name()),
+    })
+}
+
+async fn delete_values(
+    conn: &mut PgConnection,
+    prop_id: i32,
+    ty: ValueType,
+) -> Result<()> {
+    match ty {
+        ValueType::Bool => {
+            query!("delete from propval_bool where prop_id = $1", prop_id)
+                .execute(&mut *conn)
+                .await?;
+        }
+        ValueType::Int => {
+            query!("delete from propval_int where prop_id = $1", prop_id)
+                .execute(&mut *conn)
+                .await?;
+        }
+        ValueType::Float => {
+            query!("delete from propval_float where prop_id = $1", prop_id)
+                .execute(&mut *conn)
+                .await?;
+        }
+        ValueType::Str => {
+            query!("delete from propval_str where prop_id = $1", prop_id)
+                .execute(&mut *conn)
+                .await?;
+        }
+        // The tag vocabulary is kept, like it is when tags are removed from
+        // a page.
+        ValueType::MultiStr => {
+            query!("delete from propval_multistr where prop_id = $1", prop_id)
+                .execute(&mut *conn)
+                .await?;
+        }
+        ValueType::Date => {
+            query!("delete from propval_date where prop_id = $1", prop_id)
+                .execute(&mut *conn)
+                .await?;
+        }
+        ValueType::DateTime => {
+            query!("delete from propval_datetime where prop_id = $1", prop_id)
+                .execute(&mut *conn)
+                .await?;
+        }
+        // Deleting the options takes their propvals with them.
+        ValueType::Select => {
+            query!("delete from property_option where prop_id = $1", prop_id)
+                .execute(&mut *conn)
+                .await?;
+        }
+        ty => bail!("{} props cannot be converted", ty.get_display_name()),
+    };
+
+    Ok(())
+}
+
+async fn insert_values(
+    conn: &mut PgConnection,
+    prop_id: i32,
+    ty: ValueType,
+    values: &[(i32, Value)],
+) -> Result<()> {
+    match ty {
+        ValueType::Bool => {
+            let (page_ids, vals): (Vec<i32>, Vec<bool>) = values
+                .iter()
+                .filter_map(|(page_id, value)| match value {
+                    Value::Bool(val) => Some((*page_id, *val)),
+                    _ => None,
+                })
+                .unzip();
+            query!(
+                "insert into propval_bool (page_id, prop_id, value)
+                select unnest($1::int[]), $2, unnest($3::bool[])",
+                &page_ids,
+                prop_id,
+                &vals
+            )
+            .execute(&mut *conn)
+            .await?;
+        }
+        ValueType::Int => {
+            let (page_ids, vals): (Vec<i32>, Vec<i64>) = values
+                .iter()
+                .filter_map(|(page_id, value)| match value {
+                    Value::Int(val) => Some((*page_id, *val)),
+                    _ => None,
+                })
+                .unzip();
+            query!(
+                "insert into propval_int (page_id, prop_id, value)
+                select unnest($1::int[]), $2, unnest($3::bigint[])",
+                &page_ids,
+                prop_id,
+                &vals
+            )
+            .execute(&mut *conn)
+            .await?;
+        }
+        ValueType::Float => {
+            let (page_ids, vals): (Vec<i32>, Vec<f64>) = values
+                .iter()
+                .filter_map(|(page_id, value)| match value {
+                    Value::Float(val) => Some((*page_id, *val)),
+                    _ => None,
+                })
+                .unzip();
+            query!(
+                "insert into propval_float (page_id, prop_id, value)
+                select unnest($1::int[]), $2, unnest($3::float8[])",
+                &page_ids,
+                prop_id,
+                &vals
+            )
+            .execute(&mut *conn)
+            .await?;
+        }
+        ValueType::Str => {
+            let (page_ids, vals): (Vec<i32>, Vec<String>) = values
+                .iter()
+                .filter_map(|(page_id, value)| match value {
+                    Value::Str(val) => Some((*page_id, val.clone())),
+                    _ => None,
+                })
+                .unzip();
+            query!(
+                "insert into propval_str (page_id, prop_id, value)
+                select unnest($1::int[]), $2, unnest($3::varchar[])",
+                &page_ids,
+                prop_id,
+                &vals
+            )
+            .execute(&mut *conn)
+            .await?;
+        }
+        ValueType::MultiStr => {
+            let tagged =
+                values.iter().filter_map(|(page_id, value)| match value {
+                    Value::MultiStr(tags) => Some((page_id, tags)),
+                    _ => None,
+                });
+            for (page_id, tags) in tagged {
+                let parent = query!(
+                    "insert into propval_multistr (page_id, prop_id)
+                    values ($1, $2)
+                    returning id",
+                    page_id,
+                    prop_id
+                )
+                .fetch_one(&mut *conn)
+                .await?;
+                query!(
+                    "insert into propval_multistr__value
+                        (value, propval_multistr_id)
+                    select distinct unnest($1::varchar[]), $2::int",
+                    tags,
+                    parent.id
+                )
+                .execute(&mut *conn)
+                .await?;
+                query!(
+                    "insert into propval_multistr__vocab (value, prop_id)
+                    select unnest($1::varchar[]), $2
+                    on conflict do nothing",
+                    tags,
+                    prop_id
+                )
+                .execute(&mut *conn)
+                .await?;
+            }
+        }
+        ValueType::Date => {
+            let (page_ids, vals): (Vec<i32>, Vec<chrono::NaiveDate>) = values
+                .iter()
+                .filter_map(|(page_id, value)| match value {
+                    Value::Date(val) => Some((*page_id, *val)),
+                    _ => None,
+                })
+                .unzip();
+            query!(
+                "insert into propval_date (page_id, prop_id, value)
+                select unnest($1::int[]), $2, unnest($3::date[])",
+                &page_ids,
+                prop_id,
+                &vals
+            )
+            .execute(&mut *conn)
+            .await?;
+        }
+        ValueType::DateTime => {
+            let (page_ids, vals): (Vec<i32>, Vec<chrono::DateTime<Utc>>) =
+                values
+                    .iter()
+                    .filter_map(|(page_id, value)| match value {
+                        Value::DateTime(val) => {
+                            Some((*page_id, val.with_timezone(&Utc)))
+                        }
+                        _ => None,
+                    })
+                    .unzip();
+            query!(
+                "insert into propval_datetime (page_id, prop_id, value)
+                select unnest($1::int[]), $2, unnest($3::timestamptz[])",
+                &page_ids,
+                prop_id,
+                &vals
+            )
+            .execute(&mut *conn)
+            .await?;
+        }
+        // Converted select values are option names; there is an option for
+        // each distinct name, in the order they first appear.
+        ValueType::Select => {
+            let (page_ids, names): (Vec<i32>, Vec<String>) = values
+                .iter()
+                .filter_map(|(page_id, value)| match value {
+                    Value::Str(name) => Some((*page_id, name.clone())),
+                    _ => None,
+                })
+                .unzip();
+            let mut option_names: Vec<String> = vec![];
+            for name in &names {
+                if !option_names.contains(name) {
+                    option_names.push(name.clone());
+                }
+            }
+            let colors: Vec<String> = (0..option_names.len())
+                .map(|i| {
+                    SelectColor::ALL[i % SelectColor::ALL.len()]
+                        .get_name()
+                        .to_string()
+                })
+                .collect();
+            let orders: Vec<i16> =
+                (1..=option_names.len()).map(|i| i as i16).collect();
+            query!(
+                r#"insert into property_option (prop_id, name, color, "order")
+                select $1, unnest($2::varchar[]), unnest($3::varchar[]),
+                    unnest($4::smallint[])"#,
+                prop_id,
+                &option_names,
+                &colors,
+                &orders
+            )
+            .execute(&mut *conn)
+            .await?;
+            query!(
+                "insert into propval_select (page_id, prop_id, value)
+                select v.page_id, $1, po.id
+                from unnest($2::int[], $3::varchar[]) as v(page_id, name)
+                join property_option po
+                    on po.prop_id = $1 and po.name = v.name",
+                prop_id,
+                &page_ids,
+                &names
+            )
+            .execute(&mut *conn)
+            .await?;
+        }
+        ty => bail!("cannot convert into {} props", ty.get_display_name()),
+    };
+
+    Ok(())
+}
@@ -0,0 +1,12 @@
+//! Changing the type of a prop. Values are stored in a `propval_*` table per
+//! type, so they are moved into the table for the new type, and converted
+//! along the way; integers become numbers, dates become text, text is
+//! parsed into numbers, and so on. See [models::coerce] for the rules.
+//!
+//! Values which cannot be converted are lost, so the conversion is
+//! previewed before it is applied. Filters are rewritten for the new type
+//! where that makes sense, and otherwise deleted.
+
+pub mod components;
+pub mod db_ops;
+pub mod models;
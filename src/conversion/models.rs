@@ -0,0 +1,379 @@
+use crate::{
+    filter::models::{Filter, FilterType, FilterValue},
+    models::{Value, ValueType},
+};
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use chrono_tz::Tz;
+
+/// Only stored types can be converted; computed and system props don't have
+/// any values of their own to move, and relations point at pages rather
+/// than holding values.
+pub fn is_convertible(ty: ValueType) -> bool {
+    matches!(
+        ty,
+        ValueType::Bool
+            | ValueType::Int
+            | ValueType::Float
+            | ValueType::Str
+            | ValueType::MultiStr
+            | ValueType::Date
+            | ValueType::DateTime
+            | ValueType::Select
+    )
+}
+
+/// Text values are limited to the length of `propval_str.value` and
+/// `propval_multistr__value.value`.
+const MAX_TEXT_LEN: usize = 511;
+/// The length of `property_option.name`.
+const MAX_OPTION_LEN: usize = 255;
+
+/// The text a value becomes when it is converted into a text prop. Dates
+/// and times use formats which [coerce] can parse again, so that converting
+/// to text and back loses nothing.
+pub fn as_text(value: &Value) -> String {
+    match value {
+        Value::Bool(val) => val.to_string(),
+        Value::Int(val) => val.to_string(),
+        Value::Float(val) => val.to_string(),
+        Value::Str(val) => val.clone(),
+        Value::MultiStr(vals) => vals.join(", "),
+        Value::Date(val) => val.format("%Y-%m-%d").to_string(),
+        Value::DateTime(val)
+        | Value::CreatedTime(val)
+        | Value::LastEditedTime(val) => val.with_timezone(&Utc).to_rfc3339(),
+        Value::Select(option) => option.name.clone(),
+        Value::Relation(pages) => pages
+            .iter()
+            .map(|p| p.title.clone())
+            .collect::<Vec<_>>()
+            .join(", "),
+        Value::Formula(val) | Value::Rollup(val) => as_text(val),
+        Value::CreatedBy(user) | Value::LastEditedBy(user) => {
+            user.username.clone()
+        }
+        Value::Files(files) => files
+            .iter()
+            .map(|f| f.name.clone())
+            .collect::<Vec<_>>()
+            .join(", "),
+    }
+}
+
+fn parse_datetime(input: &str) -> Option<chrono::DateTime<Tz>> {
+    if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(input) {
+        return Some(dt.with_timezone(&Tz::UTC));
+    }
+    ["%Y-%m-%dT%H:%M", "%Y-%m-%d %H:%M"]
+        .iter()
+        .find_map(|fmt| NaiveDateTime::parse_from_str(input, fmt).ok())
+        .map(|naive| naive.and_utc().with_timezone(&Tz::UTC))
+}
+
+/// Convert `value` into a value of type `to`. The error is the reason that
+/// the value would be lost, for display in the preview.
+///
+/// Values which convert into a select prop come back as [Value::Str]; the
+/// options don't exist until the conversion is applied, at which point one
+/// is created for each distinct name.
+pub fn coerce(value: &Value, to: ValueType) -> Result<Value, String> {
+    let converted = match (value, to) {
+        (Value::Str(val), ValueType::Str) => Value::Str(val.clone()),
+        (Value::MultiStr(vals), ValueType::MultiStr) => {
+            Value::MultiStr(vals.clone())
+        }
+        (_, ValueType::Str) => Value::Str(as_text(value)),
+
+        (Value::Bool(val), ValueType::Bool) => Value::Bool(*val),
+        (Value::Bool(val), ValueType::Int) => Value::Int(i64::from(*val)),
+        (Value::Bool(val), ValueType::Float) => {
+            Value::Float(if *val { 1.0 } else { 0.0 })
+        }
+
+        (Value::Int(val), ValueType::Int) => Value::Int(*val),
+        (Value::Int(val), ValueType::Bool) => Value::Bool(*val != 0),
+        (Value::Int(val), ValueType::Float) => {
+            // Floats can't represent every integer beyond 2^53.
+            let float = *val as f64;
+            if float as i128 != i128::from(*val) {
+                return Err(format!("{val} is too large to be a number"));
+            }
+            Value::Float(float)
+        }
+
+        (Value::Float(val), ValueType::Float) => Value::Float(*val),
+        (Value::Float(val), ValueType::Bool) => Value::Bool(*val != 0.0),
+        (Value::Float(val), ValueType::Int) => {
+            if val.fract() != 0.0 {
+                return Err(format!("{val} is not a whole number"));
+            }
+            if !(i64::MIN as f64..i64::MAX as f64).contains(val) {
+                return Err(format!("{val} is too large to be an integer"));
+            }
+            Value::Int(*val as i64)
+        }
+
+        (Value::Date(val), ValueType::Date) => Value::Date(*val),
+        (Value::Date(val), ValueType::DateTime) => Value::DateTime(
+            val.and_time(NaiveTime::MIN)
+                .and_utc()
+                .with_timezone(&Tz::UTC),
+        ),
+        (Value::DateTime(val), ValueType::DateTime) => Value::DateTime(*val),
+        // Date & time values are stored in UTC, so that is also the day
+        // they fall on.
+        (Value::DateTime(val), ValueType::Date) => {
+            Value::Date(val.with_timezone(&Utc).date_naive())
+        }
+
+        (Value::Str(val), ValueType::Int) => match val.trim().parse() {
+            Ok(int) => Value::Int(int),
+            Err(_) => return Err(format!(r#""{val}" is not an integer"#)),
+        },
+        (Value::Str(val), ValueType::Float) => {
+            match val.trim().parse::<f64>() {
+                Ok(float) if float.is_finite() => Value::Float(float),
+                _ => return Err(format!(r#""{val}" is not a number"#)),
+            }
+        }
+        (Value::Str(val), ValueType::Bool) => {
+            match &val.trim().to_lowercase()[..] {
+                "true" | "yes" | "1" => Value::Bool(true),
+                "false" | "no" | "0" => Value::Bool(false),
+                _ => return Err(format!(r#""{val}" is not true or false"#)),
+            }
+        }
+        (Value::Str(val), ValueType::Date) => {
+            match NaiveDate::parse_from_str(val.trim(), "%Y-%m-%d") {
+                Ok(date) => Value::Date(date),
+                Err(_) => {
+                    return Err(format!(
+                        r#""{val}" is not a date like 2024-01-31"#
+                    ))
+                }
+            }
+        }
+        (Value::Str(val), ValueType::DateTime) => {
+            match parse_datetime(val.trim()) {
+                Some(dt) => Value::DateTime(dt),
+                None => {
+                    return Err(format!(
+                        r#""{val}" is not a date & time like 2024-01-31 13:00"#
+                    ))
+                }
+            }
+        }
+        (Value::Str(val), ValueType::MultiStr) => {
+            let mut tags: Vec<String> = vec![];
+            for tag in val.split(',').map(str::trim) {
+                if !tag.is_empty() && !tags.iter().any(|t| t == tag) {
+                    tags.push(tag.into());
+                }
+            }
+            Value::MultiStr(tags)
+        }
+        (_, ValueType::MultiStr) => Value::MultiStr(vec![as_text(value)]),
+
+        (Value::MultiStr(vals), ValueType::Select) => match &vals[..] {
+            [tag] => Value::Str(tag.clone()),
+            [] => return Err("there are no tags".into()),
+            _ => {
+                return Err(format!(
+                    "{} tags cannot become a single option",
+                    vals.len()
+                ))
+            }
+        },
+        (_, ValueType::Select) => Value::Str(as_text(value)),
+
+        (_, to) => {
+            return Err(format!(
+                "{} values cannot become {}",
+                ValueType::of_value(value).get_display_name(),
+                to.get_display_name()
+            ))
+        }
+    };
+
+    let max_len = if to == ValueType::Select {
+        MAX_OPTION_LEN
+    } else {
+        MAX_TEXT_LEN
+    };
+    let too_long = match &converted {
+        Value::Str(val) => val.chars().count() > max_len,
+        Value::MultiStr(vals) => {
+            vals.iter().any(|v| v.chars().count() > max_len)
+        }
+        _ => false,
+    };
+    if too_long {
+        return Err(format!("text is longer than {max_len} characters"));
+    }
+
+    Ok(converted)
+}
+
+/// A propval which does not survive the conversion.
+#[derive(Debug, Clone)]
+pub struct Lost {
+    pub page_id: i32,
+    pub page_title: String,
+    pub value: Value,
+    pub reason: String,
+}
+
+/// The outcome of changing a prop's type; this is shown as a preview before
+/// the change is applied, and is what gets applied.
+#[derive(Debug)]
+pub struct Conversion {
+    pub from: ValueType,
+    pub to: ValueType,
+    /// `(page_id, value)` for each propval that survives.
+    pub converted: Vec<(i32, Value)>,
+    pub lost: Vec<Lost>,
+    /// Filters which still make sense for the new type, with their values
+    /// converted. Their IDs are meaningless, since they will be inserted
+    /// into a different table.
+    pub rewritten_filters: Vec<Filter>,
+    pub dropped_filters: usize,
+}
+
+impl Conversion {
+    /// `propvals` are `(page_id, page_title, value)` for every propval of
+    /// the prop, and `filters` are the filters on the prop.
+    pub fn new(
+        from: ValueType,
+        to: ValueType,
+        propvals: Vec<(i32, String, Value)>,
+        filters: Vec<Filter>,
+    ) -> Self {
+        let mut converted = vec![];
+        let mut lost = vec![];
+        for (page_id, page_title, value) in propvals {
+            match coerce(&value, to) {
+                Ok(new_value) => converted.push((page_id, new_value)),
+                Err(reason) => lost.push(Lost {
+                    page_id,
+                    page_title,
+                    value,
+                    reason,
+                }),
+            }
+        }
+        let filter_count = filters.len();
+        let rewritten_filters: Vec<Filter> = filters
+            .into_iter()
+            .filter_map(|filter| rewrite_filter(filter, to))
+            .collect();
+
+        Self {
+            from,
+            to,
+            converted,
+            lost,
+            dropped_filters: filter_count - rewritten_filters.len(),
+            rewritten_filters,
+        }
+    }
+}
+
+/// Filters are only rewritten onto types which have filters with plain
+/// values; select filters would need options which don't exist yet.
+fn rewrite_filter(filter: Filter, to: ValueType) -> Option<Filter> {
+    if !matches!(
+        to,
+        ValueType::Bool
+            | ValueType::Int
+            | ValueType::Float
+            | ValueType::Date
+            | ValueType::DateTime
+    ) || !FilterType::get_supported_filter_types(to).contains(&filter.r#type)
+    {
+        return None;
+    }
+    let value = match &filter.value {
+        FilterValue::Single(val) => FilterValue::Single(coerce(val, to).ok()?),
+        FilterValue::Range(start, end) => {
+            FilterValue::Range(coerce(start, to).ok()?, coerce(end, to).ok()?)
+        }
+        FilterValue::Text { .. } | FilterValue::Relative { .. } => return None,
+    };
+    Some(Filter { value, ..filter })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_coerce() {
+        let date = NaiveDate::from_ymd_opt(2024, 1, 31).unwrap();
+        let cases = [
+            (Value::Int(3), ValueType::Float, Value::Float(3.0)),
+            (Value::Bool(true), ValueType::Int, Value::Int(1)),
+            (Value::Float(4.0), ValueType::Int, Value::Int(4)),
+            (
+                Value::Date(date),
+                ValueType::Str,
+                Value::Str("2024-01-31".into()),
+            ),
+            (Value::Str(" 42 ".into()), ValueType::Int, Value::Int(42)),
+            (
+                Value::Str("2.5".into()),
+                ValueType::Float,
+                Value::Float(2.5),
+            ),
+            (Value::Str("Yes".into()), ValueType::Bool, Value::Bool(true)),
+            (
+                Value::Str("2024-01-31".into()),
+                ValueType::Date,
+                Value::Date(date),
+            ),
+            (
+                Value::Str("a, b, a,".into()),
+                ValueType::MultiStr,
+                Value::MultiStr(vec!["a".into(), "b".into()]),
+            ),
+            (
+                Value::MultiStr(vec!["a".into(), "b".into()]),
+                ValueType::Str,
+                Value::Str("a, b".into()),
+            ),
+            (Value::Int(7), ValueType::Select, Value::Str("7".into())),
+        ];
+        for (value, to, expected) in cases {
+            let converted = coerce(&value, to).unwrap();
+            assert_eq!(format!("{converted:?}"), format!("{expected:?}"));
+        }
+    }
+
+    #[test]
+    fn test_coerce_round_trips_dates_through_text() {
+        let dt = parse_datetime("2024-01-31 13:45").unwrap();
+        let text = coerce(&Value::DateTime(dt), ValueType::Str).unwrap();
+        let back = coerce(&text, ValueType::DateTime).unwrap();
+        assert_eq!(format!("{back:?}"), format!("{:?}", Value::DateTime(dt)));
+    }
+
+    #[test]
+    fn test_coerce_failures() {
+        let cases = [
+            (Value::Str("twelve".into()), ValueType::Int),
+            (Value::Str("NaN".into()), ValueType::Float),
+            (Value::Str("maybe".into()), ValueType::Bool),
+            (Value::Str("31/01/2024".into()), ValueType::Date),
+            (Value::Float(2.5), ValueType::Int),
+            (Value::Int(i64::MAX), ValueType::Float),
+            (Value::Bool(true), ValueType::Date),
+            (
+                Value::MultiStr(vec!["a".into(), "b".into()]),
+                ValueType::Select,
+            ),
+            (Value::Str("x".repeat(512)), ValueType::Str),
+        ];
+        for (value, to) in cases {
+            assert!(coerce(&value, to).is_err(), "{value:?} to {to:?}");
+        }
+    }
+}
@@ -1,11 +1,17 @@
 //! Database operations; squirrel code lives here.
 
-use super::{config, config::PROP_SET_MAX, filter, models, prop_val, pw};
+use super::{
+    config, config::PROP_SET_MAX, constraint, filter, formula, models,
+    number_format::models::NumberFormat, prop_val, pw, rollup, search,
+    template, view,
+};
 use anyhow::{bail, Result};
 use async_trait::async_trait;
+use chrono::Weekday;
+use chrono_tz::Tz;
 use futures::join;
 use sqlx::{
-    postgres::{PgPool, PgPoolOptions, Postgres},
+    postgres::{PgConnection, PgPool, PgPoolOptions, Postgres},
     query, query_as,
     query_builder::QueryBuilder,
     Row,
@@ -77,12 +83,16 @@ impl DbModel<GetPageQuery, ListPageQuery> for models::Page {
             collection_id: i32,
             title: String,
             content: Option<String>,
+            number: i32,
+            key_prefix: String,
         }
         let res = query_as!(
             Qres,
             r#"select
-                p.collection_id collection_id, p.title title, pc.content as "content?"
+                p.collection_id collection_id, p.title title, pc.content as "content?",
+                p.number, c.key_prefix
             from page p
+            join collection c on c.id = p.collection_id
             left join page_content pc on pc.page_id = p.id
             where p.id = $1"#,
             query.id
@@ -99,11 +109,16 @@ impl DbModel<GetPageQuery, ListPageQuery> for models::Page {
                 page_id: query.id,
                 content,
             }),
+            key: Some(models::PageKey {
+                prefix: res.key_prefix,
+                number: res.number,
+            }),
         })
     }
     async fn list(_db: &PgPool, _query: &ListPageQuery) -> Result<Vec<Self>> {
         todo!()
     }
+    /// Call [touch_page] afterwards to record who renamed the page.
     async fn save(&self, db: &PgPool) -> Result<()> {
         query!(
             "update page set title = $1 where id = $2",
@@ -184,6 +199,9 @@ struct QresProp {
     collection_id: i32,
     name: String,
     order: i16,
+    number_format: String,
+    number_decimals: i16,
+    currency_code: Option<String>,
 }
 impl QresProp {
     fn into_prop(self) -> models::Prop {
@@ -193,6 +211,11 @@ impl QresProp {
             name: self.name,
             order: self.order,
             type_id: models::ValueType::from_int(self.type_id),
+            number_format: NumberFormat::from_columns(
+                &self.number_format,
+                self.number_decimals,
+                self.currency_code,
+            ),
         }
     }
 }
@@ -202,7 +225,8 @@ impl DbModel<GetPropQuery, ListPropQuery> for models::Prop {
     async fn get(db: &PgPool, query: &GetPropQuery) -> Result<Self> {
         let raw_prop = query_as!(
             QresProp,
-            r#"select id, type_id, collection_id, name, "order"
+            r#"select id, type_id, collection_id, name, "order",
+                number_format, number_decimals, currency_code
             from property
             where id = $1"#,
             query.id
@@ -214,7 +238,8 @@ impl DbModel<GetPropQuery, ListPropQuery> for models::Prop {
     }
     async fn list(db: &PgPool, query: &ListPropQuery) -> Result<Vec<Self>> {
         let mut builder: QueryBuilder<Postgres> = QueryBuilder::new(
-            r#"select id, type_id, collection_id, name, "order"
+            r#"select id, type_id, collection_id, name, "order",
+                number_format, number_decimals, currency_code
             from property "#,
         );
 
@@ -270,49 +295,384 @@ impl DbModel<GetPropQuery, ListPropQuery> for models::Prop {
 
         Ok(())
     }
-    async fn delete(self, _db: &PgPool) -> Result<()> {
-        todo!()
+    /// Propvals, filters, and computed prop configs referring to the prop are
+    /// deleted by cascade. Views sorted by this prop are un-sorted, and the
+    /// props after it move up to close the gap in the ordering.
+    ///
+    /// Rollups over the prop lose their config, and are empty from then on;
+    /// their filters and sorts were for the type they used to have, so they
+    /// are deleted too.
+    async fn delete(self, db: &PgPool) -> Result<()> {
+        let mut tx = db.begin().await?;
+        let rollup_ids: Vec<i32> = query!(
+            "select prop_id from property_rollup
+            where relation_prop_id = $1 or target_prop_id = $1",
+            self.id
+        )
+        .fetch_all(&mut *tx)
+        .await?
+        .into_iter()
+        .map(|row| row.prop_id)
+        .collect();
+        query!("delete from filter where prop_id = any($1)", &rollup_ids)
+            .execute(&mut *tx)
+            .await?;
+        query!(
+            "update view set sort_by_prop_id = null, sort_type_id = null
+            where sort_by_prop_id = $1 or sort_by_prop_id = any($2)",
+            self.id,
+            &rollup_ids
+        )
+        .execute(&mut *tx)
+        .await?;
+        query!("delete from property where id = $1", self.id)
+            .execute(&mut *tx)
+            .await?;
+        query!(
+            r#"update property set "order" = "order" - 1
+            where collection_id = $1 and "order" > $2"#,
+            self.collection_id,
+            self.order
+        )
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(())
     }
 }
 
-async fn get_page_list_ctx(
+/// Add a prop to the end of the collection's prop order. Relation props
+/// also need the collection they link to.
+pub async fn create_prop(
     db: &PgPool,
     collection_id: i32,
+    name: &str,
+    type_id: models::ValueType,
+    target_collection_id: Option<i32>,
+) -> Result<i32> {
+    if type_id == models::ValueType::Relation && target_collection_id.is_none()
+    {
+        bail!("relation props need a collection to link to");
+    }
+    struct Qres {
+        id: i32,
+    }
+    let mut tx = db.begin().await?;
+    let prop_id = query_as!(
+        Qres,
+        r#"insert into property (name, type_id, collection_id, "order")
+        select $1, $2, $3, coalesce(max("order"), 0) + 1
+        from property where collection_id = $3
+        returning id"#,
+        name,
+        type_id.get_int_repr(),
+        collection_id
+    )
+    .fetch_one(&mut *tx)
+    .await?
+    .id;
+    if type_id == models::ValueType::Relation {
+        query!(
+            "insert into property_relation (prop_id, target_collection_id)
+            values ($1, $2)",
+            prop_id,
+            target_collection_id
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+
+    Ok(prop_id)
+}
+
+/// The number of values which would be lost by deleting a prop. Computed
+/// and system props don't store any values of their own.
+pub async fn count_prop_values(db: &PgPool, prop_id: i32) -> Result<i64> {
+    struct Qres {
+        cnt: Option<i64>,
+    }
+    let res = query_as!(
+        Qres,
+        "select
+            (select count(1) from propval_bool where prop_id = $1)
+            + (select count(1) from propval_int where prop_id = $1)
+            + (select count(1) from propval_float where prop_id = $1)
+            + (select count(1) from propval_str where prop_id = $1)
+            + (select count(1) from propval_multistr where prop_id = $1)
+            + (select count(1) from propval_date where prop_id = $1)
+            + (select count(1) from propval_datetime where prop_id = $1)
+            + (select count(1) from propval_select where prop_id = $1)
+            + (select count(1) from propval_relation where prop_id = $1)
+            + (select count(1) from propval_file where prop_id = $1)
+            as cnt",
+        prop_id
+    )
+    .fetch_one(db)
+    .await?;
+
+    Ok(res.cnt.unwrap_or_default())
+}
+
+pub async fn list_collections(db: &PgPool) -> Result<Vec<models::Collection>> {
+    Ok(query_as!(
+        models::Collection,
+        "select id, name from collection order by name"
+    )
+    .fetch_all(db)
+    .await?)
+}
+
+pub struct GetSelectOptionQuery {
+    pub id: i32,
+}
+
+pub struct ListSelectOptionQuery {
+    pub prop_id: i32,
+}
+
+struct QresSelectOption {
+    id: i32,
+    prop_id: i32,
+    name: String,
+    color: String,
+    order: i16,
+}
+impl QresSelectOption {
+    fn into_option(self) -> Result<models::SelectOption> {
+        Ok(models::SelectOption {
+            id: self.id,
+            prop_id: self.prop_id,
+            name: self.name,
+            color: models::SelectColor::from_name(&self.color)?,
+            order: self.order,
+        })
+    }
+}
+
+#[async_trait]
+impl DbModel<GetSelectOptionQuery, ListSelectOptionQuery>
+    for models::SelectOption
+{
+    async fn get(db: &PgPool, query: &GetSelectOptionQuery) -> Result<Self> {
+        query_as!(
+            QresSelectOption,
+            r#"select id, prop_id, name, color, "order"
+            from property_option
+            where id = $1"#,
+            query.id
+        )
+        .fetch_one(db)
+        .await?
+        .into_option()
+    }
+    /// Options are returned in their display order.
+    async fn list(
+        db: &PgPool,
+        query: &ListSelectOptionQuery,
+    ) -> Result<Vec<Self>> {
+        query_as!(
+            QresSelectOption,
+            r#"select id, prop_id, name, color, "order"
+            from property_option
+            where prop_id = $1
+            order by "order""#,
+            query.prop_id
+        )
+        .fetch_all(db)
+        .await?
+        .drain(..)
+        .map(|o| o.into_option())
+        .collect()
+    }
+    async fn save(&self, db: &PgPool) -> Result<()> {
+        query!(
+            r#"update property_option set
+                name = $1,
+                color = $2,
+                "order" = $3
+            where id = $4"#,
+            self.name,
+            self.color.get_name(),
+            self.order,
+            self.id
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+    /// Propvals and filters referencing this option will be deleted as well.
+    async fn delete(self, db: &PgPool) -> Result<()> {
+        query!("delete from property_option where id = $1", self.id)
+            .execute(db)
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// New options are placed at the end of the prop's existing options.
+pub async fn create_select_option(
+    db: &PgPool,
+    prop_id: i32,
+    name: &str,
+    color: models::SelectColor,
+) -> Result<models::SelectOption> {
+    let res = query_as!(
+        QresSelectOption,
+        r#"insert into property_option (prop_id, name, color, "order")
+        select $1, $2, $3, coalesce(max("order"), 0) + 1
+        from property_option
+        where prop_id = $1
+        returning id, prop_id, name, color, "order""#,
+        prop_id,
+        name,
+        color.get_name()
+    )
+    .fetch_one(db)
+    .await?;
+
+    res.into_option()
+}
+
+/// The collection which a relation prop links into.
+pub async fn get_relation_target(db: &PgPool, prop_id: i32) -> Result<i32> {
+    let res = query!(
+        "select target_collection_id from property_relation
+        where prop_id = $1",
+        prop_id
+    )
+    .fetch_one(db)
+    .await?;
+
+    Ok(res.target_collection_id)
+}
+
+pub async fn get_related_page(
+    db: &PgPool,
+    page_id: i32,
+) -> Result<models::RelatedPage> {
+    Ok(query_as!(
+        models::RelatedPage,
+        "select id, collection_id, title from page where id = $1",
+        page_id
+    )
+    .fetch_one(db)
+    .await?)
+}
+
+/// Every page which could be linked into a relation prop targeting
+/// `collection_id`, ordered by title.
+pub async fn list_related_page_candidates(
+    db: &PgPool,
+    collection_id: i32,
+) -> Result<Vec<models::RelatedPage>> {
+    Ok(query_as!(
+        models::RelatedPage,
+        "select id, collection_id, title from page
+        where collection_id = $1
+        order by title, id",
+        collection_id
+    )
+    .fetch_all(db)
+    .await?)
+}
+
+async fn get_page_list_ctx(
+    db: &PgPool,
+    view: &view::models::View,
 ) -> Result<(
-    Vec<filter::models::Filter>,
+    filter::models::FilterGroup,
     Vec<models::Prop>,
-    Option<models::CollectionSort>,
+    Option<models::ViewSort>,
+    Vec<formula::models::Formula>,
+    Vec<rollup::models::Rollup>,
 )> {
-    let sort_query = GetSortQuery { collection_id };
-    let filter_query = filter::db_ops::ListFilterQuery { collection_id };
-    let (filters, collection_prop_set, sort_details) = join!(
-        filter::models::Filter::list(db, &filter_query),
+    let collection_id = view.collection_id;
+    let sort_query = GetSortQuery { view_id: view.id };
+    let formula_query = formula::db_ops::ListFormulaQuery { collection_id };
+    let rollup_query = rollup::db_ops::ListRollupQuery { collection_id };
+    let (filters, collection_prop_set, sort_details, formulas, rollups) = join!(
+        filter::db_ops::get_filter_tree(db, view.id),
         get_prop_set(db, collection_id),
-        models::CollectionSort::get(db, &sort_query)
+        models::ViewSort::get(db, &sort_query),
+        formula::models::Formula::list(db, &formula_query),
+        rollup::models::Rollup::list(db, &rollup_query)
     );
     let filters = filters?;
     let collection_prop_set = collection_prop_set?;
+    let formulas = formulas?;
+    let rollups = rollups?;
 
     // Implicitly treating error as not-found here
-    let sort_details = if let Ok(d) = sort_details {
-        Some(d)
-    } else {
-        None
-    };
+    let sort_details = sort_details.ok();
 
-    Ok((filters, collection_prop_set, sort_details))
+    Ok((
+        filters,
+        collection_prop_set,
+        sort_details,
+        formulas,
+        rollups,
+    ))
 }
 
+/// The pages of a collection which pass the filters of `view`, in its sort
+/// order. The props of each page, and the props which are returned, are
+/// the visible columns of the view; see [view::models::View::arrange].
+///
+/// `ctx` resolves relative date filters, like "this week", for the user
+/// who is looking at the pages. `search` narrows the pages further, to
+/// those whose title or content matches; see [search::models::normalize].
+/// `adhoc` filters and sorts the pages without changing the view; see
+/// [filter::models::AdHocFilters::apply].
+///
+/// Pages come in chunks of 100, unless `page_number` is `None`, in which
+/// case every page is listed at once.
 pub async fn list_pages(
     db: &PgPool,
-    collection_id: i32,
-    page_number: i32,
+    view: &view::models::View,
+    page_number: Option<i32>,
+    ctx: &filter::models::RelativeDateCtx,
+    search: Option<&str>,
+    adhoc: Option<&filter::models::AdHocFilters>,
 ) -> Result<(Vec<models::Page>, Vec<models::Prop>)> {
-    let (filters, collection_prop_set, sort_details) =
-        get_page_list_ctx(db, collection_id).await?;
+    let (filters, collection_prop_set, sort_details, formulas, rollups) =
+        get_page_list_ctx(db, view).await?;
+    let (filters, sort_details) = match adhoc {
+        Some(adhoc) => {
+            (adhoc.apply(filters), adhoc.sort.clone().or(sort_details))
+        }
+        None => (filters, sort_details),
+    };
+    // Filters and sorting may use props which aren't visible, so every
+    // prop is joined, but only the columns of the view are read back.
+    let visible_props = view.get_visible_props(&collection_prop_set);
+    // Rollups which no longer compile (say, because the target prop's type
+    // changed) are empty.
+    let compiled_rollups: Vec<(i32, Option<(String, models::ValueType)>)> =
+        rollups
+            .iter()
+            .map(|r| (r.prop_id, rollup::compile(r).ok()))
+            .collect();
+    // Formulas are empty when they no longer compile (say, because a prop
+    // they reference was renamed), or when their result type has changed
+    // since they were saved (say, because a prop they reference was
+    // converted), since their filters were made for the saved type.
+    let compiled_formulas: Vec<(i32, Option<(String, models::ValueType)>)> =
+        formulas
+            .iter()
+            .map(|f| {
+                let compiled =
+                    formula::compile(&f.expression, &collection_prop_set)
+                        .ok()
+                        .filter(|(_, ty)| *ty == f.result_type);
+                (f.prop_id, compiled)
+            })
+            .collect();
 
     let page_size = 100;
-    let offset = page_number * page_size;
 
     let mut query = QueryBuilder::new("select ");
 
@@ -320,17 +680,153 @@ pub async fn list_pages(
     sep.push("page.id id");
     sep.push("page.title title");
     sep.push("page.collection_id collection_id");
+    sep.push("page.number number");
+    sep.push("collection.key_prefix key_prefix");
     for prop in &collection_prop_set[..] {
         sep.push(format!("prop{}.value prop{}", prop.id, prop.id));
+        if prop.type_id == models::ValueType::Select {
+            sep.push(format!("prop{}.name prop{}_name", prop.id, prop.id));
+            sep.push(format!("prop{}.color prop{}_color", prop.id, prop.id));
+            sep.push(format!(
+                r#"prop{}."order" prop{}_order"#,
+                prop.id, prop.id
+            ));
+        }
+        if matches!(
+            prop.type_id,
+            models::ValueType::CreatedBy | models::ValueType::LastEditedBy
+        ) {
+            sep.push(format!(
+                "prop{}.username prop{}_username",
+                prop.id, prop.id
+            ));
+            sep.push(format!("prop{}.email prop{}_email", prop.id, prop.id));
+        }
+        if prop.type_id == models::ValueType::Files {
+            for column in ["names", "stored_names", "mime_types", "sizes"] {
+                sep.push(format!(
+                    "prop{}.{column} prop{}_{column}",
+                    prop.id, prop.id
+                ));
+            }
+        }
+        if prop.type_id == models::ValueType::Relation {
+            sep.push(format!("prop{}.titles prop{}_titles", prop.id, prop.id));
+            sep.push(format!(
+                "prop{}.collection_ids prop{}_collection_ids",
+                prop.id, prop.id
+            ));
+        }
     }
-    query.push(" from page ");
+    query.push(
+        " from page join collection on collection.id = page.collection_id ",
+    );
 
     for prop in &collection_prop_set[..] {
         let table_name = match prop.type_id {
             models::ValueType::Int => "propval_int",
             models::ValueType::Bool => "propval_bool",
             models::ValueType::Float => "propval_float",
+            models::ValueType::Str => "propval_str",
+            // Tags are spread across two tables, so we aggregate them into
+            // a single array-valued column which can be joined like the
+            // other propval tables.
+            models::ValueType::MultiStr => {
+                "(select
+                    m.page_id,
+                    m.prop_id,
+                    array_agg(v.value order by v.value) as value
+                from propval_multistr m
+                join propval_multistr__value v
+                    on v.propval_multistr_id = m.id
+                group by m.page_id, m.prop_id)"
+            }
             models::ValueType::Date => "propval_date",
+            models::ValueType::DateTime => "propval_datetime",
+            // The option's name and color are needed for display, and its
+            // order is used for sorting.
+            models::ValueType::Select => {
+                r#"(select
+                    ps.page_id,
+                    ps.prop_id,
+                    ps.value,
+                    po.name,
+                    po.color,
+                    po."order"
+                from propval_select ps
+                join property_option po on po.id = ps.value)"#
+            }
+            // Formulas and rollups are joined below, once all the props they
+            // might reference have been joined. The ones which haven't been
+            // set up yet are empty.
+            models::ValueType::Formula | models::ValueType::Rollup => {
+                let is_configured =
+                    formulas.iter().any(|f| f.prop_id == prop.id)
+                        || rollups.iter().any(|r| r.prop_id == prop.id);
+                if !is_configured {
+                    query.push(format!(
+                        "left join lateral (select null::text as value)
+                            as prop{} on true ",
+                        prop.id
+                    ));
+                }
+                continue;
+            }
+            // System props come from the page itself, rather than from a
+            // propval table.
+            models::ValueType::CreatedTime
+            | models::ValueType::LastEditedTime
+            | models::ValueType::CreatedBy
+            | models::ValueType::LastEditedBy => {
+                let select = match prop.type_id {
+                    models::ValueType::CreatedTime => {
+                        "select page.created_at as value"
+                    }
+                    models::ValueType::LastEditedTime => {
+                        "select page.updated_at as value"
+                    }
+                    models::ValueType::CreatedBy => {
+                        "select u.id as value, u.username, u.email
+                        from users u where u.id = page.created_by"
+                    }
+                    _ => {
+                        "select u.id as value, u.username, u.email
+                        from users u where u.id = page.updated_by"
+                    }
+                };
+                query.push(format!(
+                    "left join lateral ({select}) as prop{prop_id} on true ",
+                    prop_id = prop.id
+                ));
+                continue;
+            }
+            // Like tags, links are aggregated into arrays; `value` holds the
+            // target page IDs, which is what relation filters test against.
+            models::ValueType::Relation => {
+                "(select
+                    r.page_id,
+                    r.prop_id,
+                    array_agg(t.id order by t.title, t.id) as value,
+                    array_agg(t.title order by t.title, t.id) as titles,
+                    array_agg(t.collection_id order by t.title, t.id)
+                        as collection_ids
+                from propval_relation r
+                join page t on t.id = r.target_page_id
+                group by r.page_id, r.prop_id)"
+            }
+            // Uploads are aggregated like links, oldest first.
+            models::ValueType::Files => {
+                "(select
+                    page_id,
+                    prop_id,
+                    array_agg(id order by id) as value,
+                    array_agg(name order by id) as names,
+                    array_agg(stored_name order by id) as stored_names,
+                    array_agg(mime_type order by id) as mime_types,
+                    array_agg(size order by id) as sizes
+                from propval_file
+                group by page_id, prop_id)"
+            }
         };
         query.push(format!(
             "left join {table} as prop{prop_id}
@@ -341,79 +837,85 @@ pub async fn list_pages(
         ));
     }
 
-    if !filters.is_empty() {
-        query.push("where ");
-
-        let mut sep = query.separated(" and ");
-        for filter in &filters[..] {
-            let prop_id = filter.prop_id;
-            match filter.r#type {
-                filter::models::FilterType::Eq
-                | filter::models::FilterType::Neq
-                | filter::models::FilterType::Lt
-                | filter::models::FilterType::Gt => {
-                    if let filter::models::FilterValue::Single(val) =
-                        &filter.value
-                    {
-                        let operator = &filter.r#type.get_operator_str();
-                        let value = val.as_sql();
-                        // The value here is a boolean, not a user-input string,
-                        // so I think that direct
-                        // interpolation without binding
-                        // is safe.
-                        sep.push(format!(
-                            "prop{prop_id}.value {operator} {value} "
-                        ));
-                    } else {
-                        panic!("these filter types should not have ranged value types");
-                    }
-                }
-                filter::models::FilterType::IsEmpty => {
-                    sep.push(format!("prop{prop_id}.value is null"));
-                }
-                filter::models::FilterType::InRng => {
-                    if let filter::models::FilterValue::Range(v1, v2) =
-                        &filter.value
-                    {
-                        let v1 = v1.as_sql();
-                        let v2 = v2.as_sql();
-                        sep.push(format!(
-                            "prop{prop_id}.value > {v1} and prop{prop_id}.value < {v2} "
-                        ));
-                    } else {
-                        panic!("these filter types should not have singular value types");
-                    }
-                }
-                filter::models::FilterType::NotInRng => {
-                    if let filter::models::FilterValue::Range(v1, v2) =
-                        &filter.value
-                    {
-                        let v1 = v1.as_sql();
-                        let v2 = v2.as_sql();
-                        sep.push(format!(
-                            "prop{prop_id}.value < {v1} and prop{prop_id}.value > {v2} "
-                        ));
-                    } else {
-                        panic!("these filter types should not have singular value types");
-                    }
-                }
-            }
-        }
-    };
+    for (prop_id, compiled) in &compiled_rollups[..] {
+        let sql = match compiled {
+            Some((sql, _)) => sql,
+            None => "null::bigint",
+        };
+        query.push(format!(
+            "left join lateral (select {sql} as value) as prop{prop_id}
+                on true "
+        ));
+    }
+    for (formula, (prop_id, compiled)) in
+        formulas.iter().zip(&compiled_formulas[..])
+    {
+        // Broken formulas are still typed as saved, so that their filters
+        // apply to them.
+        let sql = match compiled {
+            Some((sql, _)) => sql,
+            None => match formula.result_type {
+                models::ValueType::Bool => "null::boolean",
+                models::ValueType::Int => "null::bigint",
+                models::ValueType::Float => "null::float8",
+                models::ValueType::Date => "null::date",
+                _ => "null::text",
+            },
+        };
+        query.push(format!(
+            "left join lateral (select {sql} as value) as prop{prop_id}
+                on true "
+        ));
+    }
+
+    query.push("where page.collection_id = ");
+    query.push_bind(view.collection_id);
+
+    if filters.has_enabled_filters() {
+        query.push(" and ");
+        filters.push_sql(&mut query, ctx);
+    }
+
+    if let Some(text) = search {
+        query.push(" and ");
+        search::models::push_match_sql(&mut query, text);
+    }
 
+    // Pages which sort the same are ordered by ID, so that each page of
+    // results continues where the last one ended.
+    query.push(" order by ");
     if let Some(sort) = sort_details {
         if let Some(ty) = sort.r#type {
             if let Some(prop) = sort.prop_id {
                 let prop_id = prop;
                 let order_name = ty.get_sql();
-                query.push(format!(
-                    " order by prop{prop_id}.value {order_name} "
-                ));
+                // Select props sort by the order of their options, rather than
+                // by option ID, relations sort by the title of their first
+                // linked page, and people sort by username.
+                let column = match collection_prop_set
+                    .iter()
+                    .find(|p| p.id == prop_id)
+                    .map(|p| p.type_id)
+                {
+                    Some(models::ValueType::Select) => r#""order""#,
+                    Some(models::ValueType::Relation) => "titles[1]",
+                    Some(models::ValueType::Files) => "names[1]",
+                    Some(
+                        models::ValueType::CreatedBy
+                        | models::ValueType::LastEditedBy,
+                    ) => "username",
+                    _ => "value",
+                };
+                query.push(format!("prop{prop_id}.{column} {order_name}, "));
             }
         }
     };
+    query.push("page.id ");
 
-    query.push(format!(" limit {page_size} offset {offset} "));
+    if let Some(page_number) = page_number {
+        let offset = page_number * page_size;
+        query.push(format!(" limit {page_size} offset {offset} "));
+    }
 
     let res = query.build().fetch_all(db).await?;
     let pages: Vec<models::Page> = res
@@ -422,7 +924,11 @@ pub async fn list_pages(
             let id: i32 = row.get("id");
             let title: String = row.get("title");
             let collection_id: i32 = row.get("collection_id");
-            let props: Vec<models::PvOrType> = collection_prop_set
+            let key = models::PageKey {
+                prefix: row.get("key_prefix"),
+                number: row.get("number"),
+            };
+            let props: Vec<models::PvOrType> = visible_props
                 .iter()
                 .map(|prop| {
                     let prop_alias = format!("prop{}", prop.id);
@@ -478,6 +984,40 @@ pub async fn list_pages(
                                 )
                             }
                         }
+                        models::ValueType::Str => {
+                            if let Ok(value) = row.try_get(&prop_alias as &str)
+                            {
+                                models::PvOrType::Pv(
+                                    prop_val::models::PropVal {
+                                        page_id: id,
+                                        prop_id: prop.id,
+                                        value: models::Value::Str(value),
+                                    },
+                                )
+                            } else {
+                                models::PvOrType::Tp(
+                                    models::ValueType::Str,
+                                    prop.id,
+                                )
+                            }
+                        }
+                        models::ValueType::MultiStr => {
+                            if let Ok(value) = row.try_get(&prop_alias as &str)
+                            {
+                                models::PvOrType::Pv(
+                                    prop_val::models::PropVal {
+                                        page_id: id,
+                                        prop_id: prop.id,
+                                        value: models::Value::MultiStr(value),
+                                    },
+                                )
+                            } else {
+                                models::PvOrType::Tp(
+                                    models::ValueType::MultiStr,
+                                    prop.id,
+                                )
+                            }
+                        }
                         models::ValueType::Date => {
                             if let Ok(value) = row.try_get(&prop_alias as &str)
                             {
@@ -495,6 +1035,284 @@ pub async fn list_pages(
                                 )
                             }
                         }
+                        models::ValueType::Select => {
+                            if let Ok(option_id) =
+                                row.try_get::<i32, &str>(&prop_alias)
+                            {
+                                let name: String =
+                                    row.get(&format!("{prop_alias}_name") as &str);
+                                let color: String =
+                                    row.get(&format!("{prop_alias}_color") as &str);
+                                let order: i16 =
+                                    row.get(&format!("{prop_alias}_order") as &str);
+                                models::PvOrType::Pv(
+                                    prop_val::models::PropVal {
+                                        page_id: id,
+                                        prop_id: prop.id,
+                                        value: models::Value::Select(
+                                            models::SelectOption {
+                                                id: option_id,
+                                                prop_id: prop.id,
+                                                name,
+                                                color: models::SelectColor::from_name(&color)
+                                                    .unwrap_or(models::SelectColor::Gray),
+                                                order,
+                                            },
+                                        ),
+                                    },
+                                )
+                            } else {
+                                models::PvOrType::Tp(
+                                    models::ValueType::Select,
+                                    prop.id,
+                                )
+                            }
+                        }
+                        models::ValueType::Relation => {
+                            if let Ok(ids) =
+                                row.try_get::<Vec<i32>, &str>(&prop_alias)
+                            {
+                                let titles: Vec<String> = row
+                                    .get(&format!("{prop_alias}_titles") as &str);
+                                let collection_ids: Vec<i32> = row.get(
+                                    &format!("{prop_alias}_collection_ids")
+                                        as &str,
+                                );
+                                let pages = ids
+                                    .into_iter()
+                                    .zip(titles)
+                                    .zip(collection_ids)
+                                    .map(|((id, title), collection_id)| {
+                                        models::RelatedPage {
+                                            id,
+                                            collection_id,
+                                            title,
+                                        }
+                                    })
+                                    .collect();
+                                models::PvOrType::Pv(
+                                    prop_val::models::PropVal {
+                                        page_id: id,
+                                        prop_id: prop.id,
+                                        value: models::Value::Relation(pages),
+                                    },
+                                )
+                            } else {
+                                models::PvOrType::Tp(
+                                    models::ValueType::Relation,
+                                    prop.id,
+                                )
+                            }
+                        }
+                        models::ValueType::Files => {
+                            if let Ok(ids) =
+                                row.try_get::<Vec<i32>, &str>(&prop_alias)
+                            {
+                                let column = |name: &str| {
+                                    format!("{prop_alias}_{name}")
+                                };
+                                let names: Vec<String> =
+                                    row.get(&column("names") as &str);
+                                let stored_names: Vec<String> =
+                                    row.get(&column("stored_names") as &str);
+                                let mime_types: Vec<String> =
+                                    row.get(&column("mime_types") as &str);
+                                let sizes: Vec<i64> =
+                                    row.get(&column("sizes") as &str);
+                                let files = ids
+                                    .into_iter()
+                                    .zip(names)
+                                    .zip(stored_names)
+                                    .zip(mime_types)
+                                    .zip(sizes)
+                                    .map(
+                                        |(
+                                            (((id, name), stored_name), mime_type),
+                                            size,
+                                        )| {
+                                            models::StoredFile {
+                                                id,
+                                                name,
+                                                stored_name,
+                                                mime_type,
+                                                size,
+                                            }
+                                        },
+                                    )
+                                    .collect();
+                                models::PvOrType::Pv(
+                                    prop_val::models::PropVal {
+                                        page_id: id,
+                                        prop_id: prop.id,
+                                        value: models::Value::Files(files),
+                                    },
+                                )
+                            } else {
+                                models::PvOrType::Tp(
+                                    models::ValueType::Files,
+                                    prop.id,
+                                )
+                            }
+                        }
+                        models::ValueType::Formula => {
+                            let result_type = compiled_formulas
+                                .iter()
+                                .find(|(prop_id, _)| *prop_id == prop.id)
+                                .and_then(|(_, compiled)| compiled.as_ref())
+                                .map(|(_, ty)| *ty);
+                            let value = match result_type {
+                                Some(models::ValueType::Bool) => row
+                                    .try_get(&prop_alias as &str)
+                                    .ok()
+                                    .map(models::Value::Bool),
+                                Some(models::ValueType::Int) => row
+                                    .try_get(&prop_alias as &str)
+                                    .ok()
+                                    .map(models::Value::Int),
+                                Some(models::ValueType::Float) => row
+                                    .try_get(&prop_alias as &str)
+                                    .ok()
+                                    .map(models::Value::Float),
+                                Some(models::ValueType::Str) => row
+                                    .try_get(&prop_alias as &str)
+                                    .ok()
+                                    .map(models::Value::Str),
+                                Some(models::ValueType::Date) => row
+                                    .try_get(&prop_alias as &str)
+                                    .ok()
+                                    .map(models::Value::Date),
+                                _ => None,
+                            };
+                            if let Some(value) = value {
+                                models::PvOrType::Pv(
+                                    prop_val::models::PropVal {
+                                        page_id: id,
+                                        prop_id: prop.id,
+                                        value: models::Value::Formula(
+                                            Box::new(value),
+                                        ),
+                                    },
+                                )
+                            } else {
+                                models::PvOrType::Tp(
+                                    models::ValueType::Formula,
+                                    prop.id,
+                                )
+                            }
+                        }
+                        models::ValueType::Rollup => {
+                            let result_type = compiled_rollups
+                                .iter()
+                                .find(|(prop_id, _)| *prop_id == prop.id)
+                                .and_then(|(_, compiled)| compiled.as_ref())
+                                .map(|(_, ty)| *ty);
+                            let value = match result_type {
+                                Some(models::ValueType::Int) => row
+                                    .try_get(&prop_alias as &str)
+                                    .ok()
+                                    .map(models::Value::Int),
+                                Some(models::ValueType::Float) => row
+                                    .try_get(&prop_alias as &str)
+                                    .ok()
+                                    .map(models::Value::Float),
+                                _ => None,
+                            };
+                            if let Some(value) = value {
+                                models::PvOrType::Pv(
+                                    prop_val::models::PropVal {
+                                        page_id: id,
+                                        prop_id: prop.id,
+                                        value: models::Value::Rollup(
+                                            Box::new(value),
+                                        ),
+                                    },
+                                )
+                            } else {
+                                models::PvOrType::Tp(
+                                    models::ValueType::Rollup,
+                                    prop.id,
+                                )
+                            }
+                        }
+                        models::ValueType::CreatedTime
+                        | models::ValueType::LastEditedTime => {
+                            if let Ok(value) = row
+                                .try_get::<chrono::DateTime<chrono::Utc>, &str>(
+                                    &prop_alias,
+                                )
+                            {
+                                let value = value.with_timezone(&Tz::UTC);
+                                models::PvOrType::Pv(
+                                    prop_val::models::PropVal {
+                                        page_id: id,
+                                        prop_id: prop.id,
+                                        value: if prop.type_id
+                                            == models::ValueType::CreatedTime
+                                        {
+                                            models::Value::CreatedTime(value)
+                                        } else {
+                                            models::Value::LastEditedTime(value)
+                                        },
+                                    },
+                                )
+                            } else {
+                                models::PvOrType::Tp(prop.type_id, prop.id)
+                            }
+                        }
+                        models::ValueType::CreatedBy
+                        | models::ValueType::LastEditedBy => {
+                            if let Ok(user_id) =
+                                row.try_get::<i32, &str>(&prop_alias)
+                            {
+                                let user = models::User {
+                                    id: user_id,
+                                    username: row.get(
+                                        &format!("{prop_alias}_username")
+                                            as &str,
+                                    ),
+                                    email: row.get(
+                                        &format!("{prop_alias}_email") as &str,
+                                    ),
+                                };
+                                models::PvOrType::Pv(
+                                    prop_val::models::PropVal {
+                                        page_id: id,
+                                        prop_id: prop.id,
+                                        value: if prop.type_id
+                                            == models::ValueType::CreatedBy
+                                        {
+                                            models::Value::CreatedBy(user)
+                                        } else {
+                                            models::Value::LastEditedBy(user)
+                                        },
+                                    },
+                                )
+                            } else {
+                                models::PvOrType::Tp(prop.type_id, prop.id)
+                            }
+                        }
+                        models::ValueType::DateTime => {
+                            if let Ok(value) = row
+                                .try_get::<chrono::DateTime<chrono::Utc>, &str>(
+                                    &prop_alias,
+                                )
+                            {
+                                models::PvOrType::Pv(
+                                    prop_val::models::PropVal {
+                                        page_id: id,
+                                        prop_id: prop.id,
+                                        value: models::Value::DateTime(
+                                            value.with_timezone(&Tz::UTC),
+                                        ),
+                                    },
+                                )
+                            } else {
+                                models::PvOrType::Tp(
+                                    models::ValueType::DateTime,
+                                    prop.id,
+                                )
+                            }
+                        }
                     }
                 })
                 .collect();
@@ -505,11 +1323,12 @@ pub async fn list_pages(
                 collection_id,
                 props,
                 content: None,
+                key: Some(key),
             }
         })
         .collect();
 
-    Ok((pages, collection_prop_set))
+    Ok((pages, visible_props))
 }
 
 pub async fn get_collection_name(db: &PgPool, id: i32) -> Result<String> {
@@ -523,15 +1342,84 @@ pub async fn get_collection_name(db: &PgPool, id: i32) -> Result<String> {
     Ok(res.name)
 }
 
-pub async fn create_page(
+pub async fn get_page_id_by_key(
+    db: &PgPool,
+    key: &models::PageKey,
+) -> Result<i32> {
+    struct Qres {
+        id: i32,
+    }
+    let res = query_as!(
+        Qres,
+        "select p.id from page p
+        join collection c on c.id = p.collection_id
+        where c.key_prefix = $1 and p.number = $2",
+        key.prefix,
+        key.number
+    )
+    .fetch_optional(db)
+    .await?;
+
+    match res {
+        Some(res) => Ok(res.id),
+        None => bail!("there is no page with key {key}"),
+    }
+}
+
+pub async fn get_collection_key_prefix(
     db: &PgPool,
     collection_id: i32,
-    title: &str,
+) -> Result<String> {
+    struct Qres {
+        key_prefix: String,
+    }
+    let res = query_as!(
+        Qres,
+        "select key_prefix from collection where id = $1",
+        collection_id
+    )
+    .fetch_one(db)
+    .await?;
+
+    Ok(res.key_prefix)
+}
+
+/// Changing the prefix changes the key of every page in the collection, but
+/// numbers are kept, so `NC-113` simply becomes `OPS-113`.
+pub async fn set_collection_key_prefix(
+    db: &PgPool,
+    collection_id: i32,
+    key_prefix: &str,
 ) -> Result<()> {
+    models::PageKey::validate_prefix(key_prefix)?;
+    let key_prefix = key_prefix.to_ascii_uppercase();
+    let taken = query!(
+        "select id from collection where key_prefix = $1 and id <> $2",
+        key_prefix,
+        collection_id
+    )
+    .fetch_optional(db)
+    .await?;
+    if taken.is_some() {
+        bail!("another collection already uses the prefix {key_prefix}");
+    }
     query!(
-        "insert into page (collection_id, title) values ($1, $2)",
-        collection_id,
-        title
+        "update collection set key_prefix = $1 where id = $2",
+        key_prefix,
+        collection_id
+    )
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+/// Record that `user_id` just edited a page.
+pub async fn touch_page(db: &PgPool, page_id: i32, user_id: i32) -> Result<()> {
+    query!(
+        "update page set updated_at = now(), updated_by = $2 where id = $1",
+        page_id,
+        user_id
     )
     .execute(db)
     .await?;
@@ -539,49 +1427,116 @@ pub async fn create_page(
     Ok(())
 }
 
+/// Create a page, starting from `template` if one is given. The page, its
+/// content, and its default propvals are created in one transaction. A
+/// default which breaks the constraints of its prop fails with a
+/// [constraint::models::ValidationError], and no page is created.
+pub async fn create_page(
+    db: &PgPool,
+    collection_id: i32,
+    title: &str,
+    template: Option<&template::models::PageTemplate>,
+    user_id: i32,
+) -> Result<()> {
+    let defaults = match template {
+        Some(template) => template.get_defaults(db).await?,
+        None => vec![],
+    };
+    let mut tx = db.begin().await?;
+    // Taking the number and bumping the counter happen in one statement, so
+    // concurrent inserts can't be given the same number.
+    let page_id = query_as!(
+        Id,
+        "with counter as (
+            update collection
+            set next_page_number = next_page_number + 1
+            where id = $1
+            returning next_page_number - 1 as number
+        )
+        insert into page (collection_id, title, number, created_by, updated_by)
+        select $1, $2, counter.number, $3, $3 from counter
+        returning id",
+        collection_id,
+        title,
+        user_id
+    )
+    .fetch_one(&mut *tx)
+    .await?
+    .id;
+    if let Some(template) = template.filter(|t| !t.content.is_empty()) {
+        query!(
+            "insert into page_content (page_id, content) values ($1, $2)",
+            page_id,
+            template.content
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+    for (prop, value) in defaults {
+        let pv = prop_val::models::PropVal {
+            page_id,
+            prop_id: prop.id,
+            value,
+        };
+        // Defaults are checked when the template is saved, but the
+        // constraints of the prop may have changed since.
+        constraint::db_ops::validate(&mut tx, &pv)
+            .await
+            .map_err(|e| {
+                match e.downcast::<constraint::models::ValidationError>() {
+                    Ok(constraint::models::ValidationError(e)) => {
+                        constraint::models::ValidationError(format!(
+                            "the template's {} {e}",
+                            prop.name
+                        ))
+                        .into()
+                    }
+                    Err(e) => e,
+                }
+            })?;
+        pv.save_in(&mut tx).await?;
+    }
+    tx.commit().await?;
+
+    Ok(())
+}
+
 pub async fn get_prop_set(
     db: &PgPool,
     collection_id: i32,
 ) -> Result<Vec<models::Prop>> {
-    struct Qres {
-        id: i32,
-        type_id: i32,
-        collection_id: i32,
-        name: String,
-        order: i16,
-    }
+    get_prop_set_in(&mut *db.acquire().await?, collection_id).await
+}
+
+/// Like [get_prop_set], as part of a larger transaction.
+pub async fn get_prop_set_in(
+    conn: &mut PgConnection,
+    collection_id: i32,
+) -> Result<Vec<models::Prop>> {
     let mut props = query_as!(
-        Qres,
-        r#"select id, type_id, collection_id, name, "order"
+        QresProp,
+        r#"select id, type_id, collection_id, name, "order",
+            number_format, number_decimals, currency_code
         from property
         where collection_id = $1"#,
         collection_id
     )
-    .fetch_all(db)
+    .fetch_all(&mut *conn)
     .await?;
     props.sort_by_key(|p| p.order);
     if props.len() > config::PROP_SET_MAX {
         bail!("Collection {collection_id} has too many props");
     } else {
-        Ok(props
-            .drain(..)
-            .map(|p| models::Prop {
-                id: p.id,
-                collection_id: p.collection_id,
-                name: p.name,
-                order: p.order,
-                type_id: models::ValueType::from_int(p.type_id),
-            })
-            .collect())
+        Ok(props.drain(..).map(QresProp::into_prop).collect())
     }
 }
 
 pub struct GetSortQuery {
-    pub collection_id: i32,
+    pub view_id: i32,
 }
 
 #[async_trait]
-impl DbModel<GetSortQuery, ()> for models::CollectionSort {
+impl DbModel<GetSortQuery, ()> for models::ViewSort {
     async fn get(db: &PgPool, query: &GetSortQuery) -> Result<Self> {
         struct Qres {
             id: i32,
@@ -591,8 +1546,8 @@ impl DbModel<GetSortQuery, ()> for models::CollectionSort {
         let res = query_as!(
             Qres,
             "select id, sort_by_prop_id prop_id, sort_type_id type_id
-            from collection where id = $1",
-            query.collection_id
+            from view where id = $1",
+            query.view_id
         )
         .fetch_one(db)
         .await?;
@@ -608,7 +1563,7 @@ impl DbModel<GetSortQuery, ()> for models::CollectionSort {
         };
 
         Ok(Self {
-            collection_id: res.id,
+            view_id: res.id,
             prop_id: Some(sort_prop_id),
             r#type: Some(sort_type),
         })
@@ -617,26 +1572,33 @@ impl DbModel<GetSortQuery, ()> for models::CollectionSort {
         todo!()
     }
     async fn save(&self, db: &PgPool) -> Result<()> {
+        self.save_in(&mut *db.acquire().await?).await
+    }
+    async fn delete(self, _db: &PgPool) -> Result<()> {
+        todo!()
+    }
+}
+
+impl models::ViewSort {
+    /// Save the sort as part of a larger transaction.
+    pub async fn save_in(&self, conn: &mut PgConnection) -> Result<()> {
         let tp = self.r#type.as_ref().map(|t| t.get_int_repr());
         query!(
             r#"
-            update collection set
+            update view set
                 sort_by_prop_id = $1,
                 sort_type_id = $2
             where id = $3
             "#,
             self.prop_id,
             tp,
-            self.collection_id
+            self.view_id
         )
-        .execute(db)
+        .execute(&mut *conn)
         .await?;
 
         Ok(())
     }
-    async fn delete(self, _db: &PgPool) -> Result<()> {
-        todo!()
-    }
 }
 
 pub struct GetUserQuery<'a> {
@@ -656,8 +1618,14 @@ impl DbModel<GetUserQuery<'_>, ()> for models::User {
         .fetch_one(db)
         .await?)
     }
-    async fn list(_db: &PgPool, _query: &()) -> Result<Vec<Self>> {
-        todo!()
+    /// All users, ordered by username.
+    async fn list(db: &PgPool, _query: &()) -> Result<Vec<Self>> {
+        Ok(query_as!(
+            Self,
+            "select id, username, email from users order by username, id"
+        )
+        .fetch_all(db)
+        .await?)
     }
     async fn save(&self, _db: &PgPool) -> Result<()> {
         todo!()
@@ -667,6 +1635,16 @@ impl DbModel<GetUserQuery<'_>, ()> for models::User {
     }
 }
 
+pub async fn get_user_by_id(db: &PgPool, id: i32) -> Result<models::User> {
+    Ok(query_as!(
+        models::User,
+        "select id, username, email from users where id = $1",
+        id
+    )
+    .fetch_one(db)
+    .await?)
+}
+
 pub async fn create_user(
     db: &PgPool,
     username: String,
@@ -686,3 +1664,73 @@ pub async fn create_user(
         email,
     })
 }
+
+/// The timezone which `user_id` uses to display and enter datetimes.
+pub async fn get_user_timezone(db: &PgPool, user_id: i32) -> Result<Tz> {
+    struct Qres {
+        timezone: String,
+    }
+    let res =
+        query_as!(Qres, "select timezone from users where id = $1", user_id)
+            .fetch_one(db)
+            .await?;
+
+    res.timezone
+        .parse()
+        .map_err(|e| anyhow::anyhow!("bad timezone for user {user_id}: {e}"))
+}
+
+/// The first day of the week for `user_id`, which decides what "this week"
+/// means in relative date filters.
+pub async fn get_user_week_start(db: &PgPool, user_id: i32) -> Result<Weekday> {
+    struct Qres {
+        week_start: i16,
+    }
+    let res =
+        query_as!(Qres, "select week_start from users where id = $1", user_id)
+            .fetch_one(db)
+            .await?;
+
+    // Stored as an ISO weekday, but chrono counts from 0.
+    u8::try_from(res.week_start - 1)
+        .ok()
+        .and_then(|day| Weekday::try_from(day).ok())
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "bad week start {} for user {user_id}",
+                res.week_start
+            )
+        })
+}
+
+pub async fn set_user_week_start(
+    db: &PgPool,
+    user_id: i32,
+    week_start: Weekday,
+) -> Result<()> {
+    query!(
+        "update users set week_start = $1 where id = $2",
+        week_start.number_from_monday() as i16,
+        user_id
+    )
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn set_user_timezone(
+    db: &PgPool,
+    user_id: i32,
+    tz: Tz,
+) -> Result<()> {
+    query!(
+        "update users set timezone = $1 where id = $2",
+        tz.name(),
+        user_id
+    )
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
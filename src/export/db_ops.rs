@@ -0,0 +1,20 @@
+use crate::{
+    db_ops::list_pages,
+    filter::models::{AdHocFilters, RelativeDateCtx},
+    models,
+    view::models::View,
+};
+use anyhow::Result;
+use sqlx::PgPool;
+
+/// Every page in the view which passes its filters, rather than one page of
+/// results like [list_pages]. The pages are read in a single query, so that
+/// pages which change during the export are neither repeated nor skipped.
+pub async fn list_all_pages(
+    db: &PgPool,
+    view: &View,
+    ctx: &RelativeDateCtx,
+    adhoc: Option<&AdHocFilters>,
+) -> Result<(Vec<models::Page>, Vec<models::Prop>)> {
+    list_pages(db, view, None, ctx, None, adhoc).await
+}
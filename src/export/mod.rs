@@ -0,0 +1,8 @@
+//! Downloading a view as CSV, for use in spreadsheets. The export has the
+//! same pages, in the same order, as the page list; that is, with the
+//! view's filters and sorting applied, and just its visible columns.
+//! Numbers are written in their prop's
+//! [crate::number_format::models::NumberFormat].
+
+pub mod db_ops;
+pub mod models;
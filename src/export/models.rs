@@ -0,0 +1,78 @@
+use crate::{
+    conversion::models::as_text,
+    models::{Page, Prop, PvOrType, Value},
+};
+
+/// The text of one cell in an export.
+fn cell_text(pv_or_type: &PvOrType, prop: &Prop) -> String {
+    match pv_or_type {
+        PvOrType::Pv(pv) => match pv.value {
+            Value::Int(val) => prop.number_format.format(val as f64),
+            Value::Float(val) => prop.number_format.format(val),
+            ref value => as_text(value),
+        },
+        PvOrType::Tp(..) => "".into(),
+    }
+}
+
+/// Quote `text` for CSV, if needed. Spreadsheets run cells which start with
+/// `=`, `+`, or `@` as formulas, so those get a leading `'` to keep them as
+/// text.
+fn csv_field(text: &str) -> String {
+    let text = if text.starts_with(['=', '+', '@', '\t', '\r']) {
+        format!("'{text}")
+    } else {
+        text.to_string()
+    };
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text
+    }
+}
+
+/// `pages` as CSV, with a header row. `props` must be in the same order as
+/// the props of each page, as they are from [crate::db_ops::list_pages].
+pub fn to_csv(pages: &[Page], props: &[Prop]) -> String {
+    let mut csv = String::new();
+    let header = ["Key", "Title"]
+        .into_iter()
+        .chain(props.iter().map(|p| p.name.as_str()))
+        .map(csv_field)
+        .collect::<Vec<_>>()
+        .join(",");
+    csv.push_str(&header);
+    csv.push_str("\r\n");
+    for page in pages {
+        let key = page.key.as_ref().map(|k| k.to_string()).unwrap_or_default();
+        let row = [csv_field(&key), csv_field(&page.title)]
+            .into_iter()
+            .chain(
+                page.props
+                    .iter()
+                    .zip(props)
+                    .map(|(pv, prop)| csv_field(&cell_text(pv, prop))),
+            )
+            .collect::<Vec<_>>()
+            .join(",");
+        csv.push_str(&row);
+        csv.push_str("\r\n");
+    }
+
+    csv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_field() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("$1,234.50"), r#""$1,234.50""#);
+        assert_eq!(csv_field(r#"say "hi""#), r#""say ""hi""""#);
+        assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+        assert_eq!(csv_field("=1+1"), "'=1+1");
+        assert_eq!(csv_field("-5"), "-5");
+    }
+}
@@ -0,0 +1,120 @@
+use super::{
+    models::{clean_file_name, hash_name, normalize_mime_type},
+    storage::Storage,
+};
+use crate::models::StoredFile;
+use anyhow::Result;
+use sqlx::{query, query_as, PgConnection, PgPool};
+
+pub async fn get_file(db: &PgPool, id: i32) -> Result<StoredFile> {
+    Ok(query_as!(
+        StoredFile,
+        "select id, name, stored_name, mime_type, size
+        from propval_file
+        where id = $1",
+        id
+    )
+    .fetch_one(db)
+    .await?)
+}
+
+/// Every upload attached by the files prop `prop_id`, across all pages.
+pub async fn list_prop_files(
+    db: &PgPool,
+    prop_id: i32,
+) -> Result<Vec<StoredFile>> {
+    Ok(query_as!(
+        StoredFile,
+        "select id, name, stored_name, mime_type, size
+        from propval_file
+        where prop_id = $1",
+        prop_id
+    )
+    .fetch_all(db)
+    .await?)
+}
+
+/// Uploads with the same contents share one blob in storage. While the blob
+/// is being written or deleted, its `stored_name` is locked until the
+/// transaction ends, so [delete_unused_contents] can't delete the blob of an
+/// upload which is still being attached.
+async fn lock_stored_name(
+    conn: &mut PgConnection,
+    stored_name: &str,
+) -> Result<()> {
+    query!(
+        "select pg_advisory_xact_lock(hashtextextended($1, 0))::text",
+        stored_name
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Store an upload, and attach it to the page. `name` and `mime_type` are
+/// whatever the browser sent, and are cleaned up here. Call
+/// [crate::db_ops::touch_page] afterwards to record who uploaded it.
+///
+/// The row is inserted before the contents are stored, so that a failed
+/// insert doesn't leave contents behind which nothing refers to.
+pub async fn upload(
+    db: &PgPool,
+    storage: &dyn Storage,
+    page_id: i32,
+    prop_id: i32,
+    name: Option<&str>,
+    mime_type: Option<&str>,
+    bytes: &[u8],
+) -> Result<()> {
+    let stored_name = hash_name(bytes);
+    let mut tx = db.begin().await?;
+    lock_stored_name(&mut tx, &stored_name).await?;
+    query!(
+        "insert into propval_file
+            (name, stored_name, mime_type, size, page_id, prop_id)
+        values ($1, $2, $3, $4, $5, $6)",
+        clean_file_name(name),
+        stored_name,
+        normalize_mime_type(mime_type),
+        bytes.len() as i64,
+        page_id,
+        prop_id
+    )
+    .execute(&mut *tx)
+    .await?;
+    storage.put(&stored_name, bytes).await?;
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Delete the stored contents of `file` from `storage`, unless another
+/// upload with the same contents still needs them. Call this once `file` has
+/// been detached from its page.
+pub async fn delete_unused_contents(
+    db: &PgPool,
+    storage: &dyn Storage,
+    file: &StoredFile,
+) -> Result<()> {
+    struct Qres {
+        used: Option<bool>,
+    }
+    let mut tx = db.begin().await?;
+    lock_stored_name(&mut tx, &file.stored_name).await?;
+    let res = query_as!(
+        Qres,
+        "select exists(
+            select 1 from propval_file where stored_name = $1
+        ) used",
+        file.stored_name
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+    if !res.used.unwrap_or_default() {
+        storage.delete(&file.stored_name).await?;
+    }
+    tx.commit().await?;
+
+    Ok(())
+}
@@ -0,0 +1,13 @@
+//! Files props hold uploads, like screenshots and logs. Uploads are sent to
+//! [crate::routes::Route::PageFilesProp] as `multipart/form-data`, their
+//! contents are kept by a [storage::Storage], and `propval_file` records
+//! their name, MIME type, and size.
+//!
+//! Downloads go through [crate::routes::Route::File], which requires a
+//! session like any other route, but is left out of
+//! [crate::middleware::html_headers] so that files are served with their own
+//! content type.
+
+pub mod db_ops;
+pub mod models;
+pub mod storage;
@@ -0,0 +1,147 @@
+use sha2::{Digest, Sha256};
+
+/// The name which the contents of an upload are stored under; its SHA-256,
+/// in hex.
+pub fn hash_name(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+/// Image types which browsers can show without running anything. These are
+/// shown as thumbnails, and served inline; everything else is downloaded as
+/// an attachment, so that an uploaded HTML or SVG file can never run
+/// scripts on our origin.
+pub fn is_image(mime_type: &str) -> bool {
+    matches!(
+        mime_type,
+        "image/png" | "image/jpeg" | "image/gif" | "image/webp"
+    )
+}
+
+/// The MIME type of an upload, as declared by the browser. Anything which
+/// doesn't look like `type/subtype` is treated as opaque bytes.
+pub fn normalize_mime_type(declared: Option<&str>) -> String {
+    let fallback = "application/octet-stream";
+    let Some(declared) = declared else {
+        return fallback.into();
+    };
+    let essence = declared.split(';').next().unwrap_or_default().trim();
+    let is_token = |s: &str| {
+        !s.is_empty()
+            && s.chars()
+                .all(|c| c.is_ascii_alphanumeric() || "!#$&-^_.+".contains(c))
+    };
+    match essence.split_once('/') {
+        Some((ty, subtype))
+            if essence.len() <= 255 && is_token(ty) && is_token(subtype) =>
+        {
+            essence.to_ascii_lowercase()
+        }
+        _ => fallback.into(),
+    }
+}
+
+/// The file name to keep for an upload. Browsers may send a whole path, and
+/// `propval_file.name` is limited to 255 characters.
+pub fn clean_file_name(name: Option<&str>) -> String {
+    let name = name
+        .unwrap_or_default()
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or_default()
+        .trim();
+    let name: String =
+        name.chars().filter(|c| !c.is_control()).take(255).collect();
+    if name.is_empty() {
+        "file".into()
+    } else {
+        name
+    }
+}
+
+/// The `content-disposition` for downloading a file called `name`. Images
+/// are shown in the browser, and everything else is saved; see [is_image].
+pub fn content_disposition(name: &str, mime_type: &str) -> String {
+    let disposition = if is_image(mime_type) {
+        "inline"
+    } else {
+        "attachment"
+    };
+    // Old browsers only understand the plain `filename`, so it gets an
+    // ASCII-only copy of the name.
+    let fallback: String = name
+        .chars()
+        .map(|c| {
+            if c == ' ' || (c.is_ascii_graphic() && c != '"' && c != '\\') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let encoded = name.bytes().fold(String::new(), |mut acc, b| {
+        if b.is_ascii_alphanumeric() || b"!#$&+-.^_`|~".contains(&b) {
+            acc.push(b as char);
+        } else {
+            acc.push_str(&format!("%{b:02X}"));
+        }
+        acc
+    });
+    format!(
+        r#"{disposition}; filename="{fallback}"; filename*=UTF-8''{encoded}"#
+    )
+}
+
+/// A human-readable file size, like `1.5 MB`.
+pub fn format_size(bytes: i64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1000 {
+        return format!("{bytes} B");
+    }
+    let mut size = bytes as f64;
+    let mut unit = "B";
+    for u in UNITS {
+        if size < 1000.0 {
+            break;
+        }
+        size /= 1000.0;
+        unit = u;
+    }
+    format!("{size:.1} {unit}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_upload_metadata() {
+        assert_eq!(
+            hash_name(b"hello"),
+            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+        );
+        assert_eq!(normalize_mime_type(Some("image/PNG")), "image/png");
+        assert_eq!(
+            normalize_mime_type(Some("text/plain; charset=utf-8")),
+            "text/plain"
+        );
+        assert_eq!(
+            normalize_mime_type(Some("text/html\r\nx: y")),
+            "application/octet-stream"
+        );
+        assert_eq!(normalize_mime_type(None), "application/octet-stream");
+        assert_eq!(clean_file_name(Some(r"C:\logs\app.log")), "app.log");
+        assert_eq!(clean_file_name(Some("../../etc/passwd")), "passwd");
+        assert_eq!(clean_file_name(Some("")), "file");
+        assert_eq!(
+            content_disposition("shot.png", "image/png"),
+            r#"inline; filename="shot.png"; filename*=UTF-8''shot.png"#
+        );
+        assert_eq!(
+            content_disposition("crash \"log\" é.txt", "text/plain"),
+            r#"attachment; filename="crash _log_ _.txt"; filename*=UTF-8''crash%20%22log%22%20%C3%A9.txt"#
+        );
+        assert_eq!(format_size(999), "999 B");
+        assert_eq!(format_size(1500), "1.5 KB");
+        assert_eq!(format_size(25_000_000), "25.0 MB");
+    }
+}
@@ -0,0 +1,96 @@
+//! Where the contents of uploads are kept. Only the local disk is supported
+//! for now, but everything else goes through [Storage], so that another
+//! backend only needs an implementation and a branch in [get_storage].
+
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use axum::body::Bytes;
+use futures::{stream, Stream};
+use std::{env, io, path::PathBuf, pin::Pin};
+use tokio::{
+    fs,
+    io::{AsyncRead, AsyncReadExt},
+};
+
+/// A store of blobs, addressed by the names handed out by
+/// [super::models::hash_name].
+#[async_trait]
+pub trait Storage: Send + Sync {
+    /// Store `bytes` under `name`, replacing anything already there.
+    async fn put(&self, name: &str, bytes: &[u8]) -> Result<()>;
+    /// Open the blob stored under `name` for reading.
+    async fn open(&self, name: &str) -> Result<Pin<Box<dyn AsyncRead + Send>>>;
+    /// Delete the blob stored under `name`. Deleting a blob which does not
+    /// exist is fine.
+    async fn delete(&self, name: &str) -> Result<()>;
+}
+
+/// Blobs stored as files in one directory.
+pub struct LocalStorage {
+    dir: PathBuf,
+}
+
+impl LocalStorage {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+    fn path(&self, name: &str) -> Result<PathBuf> {
+        // Names are hex digests, but make sure that nothing else can escape
+        // the upload directory.
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric()) {
+            bail!("{name:?} is not a valid stored file name");
+        }
+        Ok(self.dir.join(name))
+    }
+}
+
+#[async_trait]
+impl Storage for LocalStorage {
+    async fn put(&self, name: &str, bytes: &[u8]) -> Result<()> {
+        let path = self.path(name)?;
+        fs::create_dir_all(&self.dir).await?;
+        // Write to a temporary file first, so that a failed upload never
+        // leaves a partial blob behind under the real name.
+        let tmp = self.dir.join(format!(".{name}.tmp"));
+        fs::write(&tmp, bytes).await?;
+        fs::rename(&tmp, &path).await?;
+
+        Ok(())
+    }
+    async fn open(&self, name: &str) -> Result<Pin<Box<dyn AsyncRead + Send>>> {
+        Ok(Box::pin(fs::File::open(self.path(name)?).await?))
+    }
+    async fn delete(&self, name: &str) -> Result<()> {
+        match fs::remove_file(self.path(name)?).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// The storage backend for uploads. Files are kept in the directory named by
+/// `UPLOAD_DIR`, which defaults to `./uploads`.
+pub fn get_storage() -> Box<dyn Storage> {
+    let dir = env::var("UPLOAD_DIR").unwrap_or_else(|_| "uploads".into());
+    Box::new(LocalStorage::new(dir))
+}
+
+/// Read `reader` in chunks, so that blobs can be streamed into a response
+/// body without holding the whole thing in memory. The stream ends after the
+/// first error.
+pub fn read_chunks(
+    reader: Pin<Box<dyn AsyncRead + Send>>,
+) -> impl Stream<Item = io::Result<Bytes>> {
+    stream::unfold(Some(reader), |reader| async move {
+        let mut reader = reader?;
+        let mut buf = vec![0; 64 * 1024];
+        match reader.read(&mut buf).await {
+            Ok(0) => None,
+            Ok(n) => {
+                buf.truncate(n);
+                Some((Ok(Bytes::from(buf)), Some(reader)))
+            }
+            Err(e) => Some((Err(e), None)),
+        }
+    })
+}
@@ -1,10 +1,10 @@
 use super::models;
 use crate::{
     components::{Chevron, ChevronVariant, Component, DeleteButton},
-    models::{Prop, Value, ValueType},
+    models::{Prop, SortType, Value, ValueType},
     routes::Route,
 };
-use ammonia::clean;
+use ammonia::{clean, clean_text};
 
 pub struct FilterIcon;
 impl Component for FilterIcon {
@@ -36,12 +36,11 @@ impl Component for FilterIcon {
 }
 
 pub struct FilterToolbarPlaceholder {
-    pub collection_id: i32,
+    pub view_id: i32,
 }
 impl Component for FilterToolbarPlaceholder {
     fn render(&self) -> String {
-        let show_toolbar =
-            Route::CollectionShowFilterToolbar(Some(self.collection_id));
+        let show_toolbar = Route::ViewShowFilterToolbar(Some(self.view_id));
         format!(
             r#"
             <div
@@ -53,88 +52,276 @@ impl Component for FilterToolbarPlaceholder {
     }
 }
 
+/// The filters of a view, arranged into their groups.
 pub struct FilterToolbar<'a> {
-    pub filters: Vec<models::Filter>,
-    pub collection_id: i32,
-    pub get_prop_name: &'a dyn Fn(i32) -> &'a str,
+    pub root: &'a models::FilterGroup,
+    pub get_prop: &'a dyn Fn(i32) -> &'a Prop,
+    /// Shown above the filters of the view, if the page was opened with an
+    /// ad-hoc query.
+    pub adhoc: Option<AdHocFilterSummary<'a>>,
 }
 impl Component for FilterToolbar<'_> {
     fn render(&self) -> String {
-        let collection_id = self.collection_id;
-        let rendered_filters =
-            self.filters.iter().fold(String::new(), |mut acc, f| {
-                let prop_name = (self.get_prop_name)(f.prop_id);
-                acc.push_str(
-                    &FilterChip {
-                        filter: f,
-                        prop_name,
-                    }
-                    .render(),
-                );
-                acc
-            });
         let hide_toolbar =
-            Route::CollectionHideSortToolbar(Some(collection_id));
-        let add_filter = Route::CollectionAddFilterButton(Some(collection_id));
+            Route::ViewHideFilterToolbar(Some(self.root.view_id));
+        let adhoc = self
+            .adhoc
+            .as_ref()
+            .map(|summary| summary.render())
+            .unwrap_or_default();
+        let root = FilterGroupContents {
+            group: self.root,
+            get_prop: self.get_prop,
+        }
+        .render();
         format!(
             r#"
             <div
+                id="filter-toolbar"
                 hx-get="{hide_toolbar}"
                 hx-trigger="toggle-filter-toolbar from:body"
-                class="flex flex-row gap-2 mt-3 mb-4"
+                class="flex flex-row flex-wrap items-center gap-2 mt-3 mb-4"
             >
-            <div hx-trigger="load" hx-get="{add_filter}"></div>
-                {rendered_filters}
+                {adhoc}
+                {root}
             </div>
             "#
         )
     }
 }
 
+/// The filters and sort of an [models::AdHocQuery], which can be saved to
+/// the view, or cleared by going back to the view without them. `props`
+/// are the props of the collection, along with the title.
+pub struct AdHocFilterSummary<'a> {
+    pub collection_id: i32,
+    pub view_id: i32,
+    pub query: &'a models::AdHocQuery,
+    pub props: &'a [Prop],
+}
+impl Component for AdHocFilterSummary<'_> {
+    fn render(&self) -> String {
+        let prop_name = |name: &str| {
+            clean(
+                models::find_prop(self.props, name)
+                    .map(|p| p.name.as_str())
+                    .unwrap_or(name),
+            )
+        };
+        let mut items =
+            self.query.filters.iter().fold(String::new(), |mut acc, f| {
+                let name = prop_name(&f.prop);
+                let type_name = f.r#type.get_display_name();
+                let value = clean(&f.value);
+                acc.push_str(&format!("<li>{name} {type_name} {value}</li>"));
+                acc
+            });
+        if let Some(sort) = &self.query.sort {
+            let name = prop_name(&sort.prop);
+            let order = match sort.r#type {
+                SortType::Asc => "ascending",
+                SortType::Desc => "descending",
+            };
+            items.push_str(&format!("<li>Sorted by {name}, {order}</li>"));
+        }
+        let mode = if self.query.replace {
+            "in place of the filters of this view"
+        } else {
+            "on top of the filters of this view"
+        };
+        let save_route = self
+            .query
+            .append_to(&Route::ViewSaveAdHocFilters(Some(self.view_id)));
+        let clear_route =
+            Route::CollectionView(Some((self.collection_id, self.view_id)));
+        format!(
+            r#"
+            <div class="w-full flex flex-col gap-1 p-2 rounded border border-yellow-500">
+                <p class="text-sm">From this link, {mode}:</p>
+                <ul class="ml-4 text-sm list-disc">{items}</ul>
+                <div class="flex gap-2 items-center">
+                    <button
+                        hx-post="{save_route}"
+                        class="text-sm dark:bg-slate-700 dark:text-white dark:hover:bg-slate-600 transition shadow hover:shadow-none rounded p-1"
+                    >Save to this view</button>
+                    <a class="link text-sm" href="{clear_route}">Clear</a>
+                </div>
+            </div>
+            "#
+        )
+    }
+}
+
+/// `route`, with a `group_id` query parameter for adding filters to a
+/// subgroup. Filters added without one go into the root group.
+fn with_group_id(
+    route: impl std::fmt::Display,
+    group_id: Option<i32>,
+) -> String {
+    let route = route.to_string();
+    match group_id {
+        Some(id) => {
+            let sep = if route.contains('?') { '&' } else { '?' };
+            format!("{route}{sep}group_id={id}")
+        }
+        None => route,
+    }
+}
+
+/// The members of a filter group, and the controls for changing it. Changes
+/// to the group re-render the whole [FilterToolbar].
+struct FilterGroupContents<'a> {
+    group: &'a models::FilterGroup,
+    get_prop: &'a dyn Fn(i32) -> &'a Prop,
+}
+impl Component for FilterGroupContents<'_> {
+    fn render(&self) -> String {
+        let group = self.group;
+        let group_route = Route::FilterGroup(Some(group.id));
+        let conjunction_route = Route::FilterGroupConjunction(Some(group.id));
+        let conjunction = group.conjunction.get_display_name();
+        let add_filter = with_group_id(
+            Route::ViewAddFilterButton(Some(group.view_id)),
+            group.parent_id.map(|_| group.id),
+        );
+        let chips = group.filters.iter().fold(String::new(), |mut acc, f| {
+            let prop = (self.get_prop)(f.prop_id);
+            acc.push_str(&FilterChip { filter: f, prop }.render());
+            acc
+        });
+        let subgroups = group.groups.iter().fold(String::new(), |mut acc, g| {
+            let contents = FilterGroupContents {
+                group: g,
+                get_prop: self.get_prop,
+            }
+            .render();
+            acc.push_str(&format!(
+                r#"
+                <div class="flex flex-row flex-wrap items-center gap-2 rounded-lg border-2 border-dashed border-slate-400 p-2">
+                    {contents}
+                </div>
+                "#
+            ));
+            acc
+        });
+        let remove_group = if group.parent_id.is_some() {
+            DeleteButton {
+                delete_href: &group_route.as_string(),
+                hx_target: Some("#filter-toolbar"),
+            }
+            .render()
+        } else {
+            "".into()
+        };
+        format!(
+            r##"
+            <button
+                class="text-xs whitespace-nowrap rounded bg-slate-100 text-black p-1 shadow hover:shadow-none"
+                hx-post="{conjunction_route}"
+                hx-target="#filter-toolbar"
+                title="Toggle between matching all and any of these filters"
+            >{conjunction}</button>
+            {chips}
+            {subgroups}
+            <div hx-trigger="load" hx-get="{add_filter}"></div>
+            <button
+                class="text-xs whitespace-nowrap link"
+                hx-post="{group_route}"
+                hx-target="#filter-toolbar"
+            >Add group</button>
+            {remove_group}
+            "##
+        )
+    }
+}
+
 const FILTER_CONTAINER_STYLE: &str = "max-w-sm text-sm border border-slate-600 bg-gradient-to-tr from-blue-100 to-fuchsia-100 dark:bg-gradient-to-tr dark:from-fuchsia-800 dark:to-violet-700 rounded p-2 flex flex-row gap-2 items-center justify-center";
 
 pub struct FilterChip<'a> {
     pub filter: &'a models::Filter,
-    pub prop_name: &'a str,
+    /// The filtered prop; numbers are shown in its format.
+    pub prop: &'a Prop,
 }
 impl Component for FilterChip<'_> {
     fn render(&self) -> String {
-        let href = match &self.filter.value {
-            models::FilterValue::Single(val) => self
-                .filter
-                .r#type
-                .get_form_route(self.filter.id, ValueType::of_value(val)),
-            models::FilterValue::Range(v1, _) => self
-                .filter
-                .r#type
-                .get_form_route(self.filter.id, ValueType::of_value(v1)),
-        };
-        let subject = clean(self.prop_name);
+        let href =
+            Route::Filter(Some((self.filter.get_kind(), self.filter.id)));
+        let subject = clean(&self.prop.name);
+        let number_format = &self.prop.number_format;
         let operator_text = clean(self.filter.r#type.get_display_name());
         let range_arrow_icon = ArrowLeftRight {}.render();
         let rendered_value = match self.filter.r#type {
             models::FilterType::IsEmpty => "".into(),
             _ => match &self.filter.value {
                 models::FilterValue::Single(val) => match val {
-                    Value::Int(val) => format!("{val}"),
+                    Value::Int(val) => number_format.format(*val as f64),
                     Value::Bool(val) => format!("{val}"),
                     Value::Date(val) => format!("{val}"),
-                    Value::Float(val) => format!("{val}"),
+                    Value::Float(val) => number_format.format(*val),
+                    Value::Str(val) => clean(val),
+                    Value::MultiStr(vals) => clean(&vals.join(", ")),
+                    Value::Select(option) => clean(&option.name),
+                    Value::Formula(_) | Value::Rollup(_) => {
+                        unreachable!("filter values are never computed")
+                    }
+                    Value::Relation(pages) => clean(
+                        &pages
+                            .iter()
+                            .map(|p| p.title.as_str())
+                            .collect::<Vec<_>>()
+                            .join(", "),
+                    ),
+                    Value::DateTime(val)
+                    | Value::CreatedTime(val)
+                    | Value::LastEditedTime(val) => {
+                        format!("{}", val.format("%Y-%m-%d %H:%M %Z"))
+                    }
+                    Value::CreatedBy(user) | Value::LastEditedBy(user) => {
+                        clean(&user.username)
+                    }
+                    Value::Files(_) => {
+                        unreachable!("files props cannot be filtered")
+                    }
                 },
                 models::FilterValue::Range(v1, v2) => match (v1, v2) {
                     (Value::Int(start), Value::Int(end)) => {
+                        let start = number_format.format(*start as f64);
+                        let end = number_format.format(*end as f64);
                         format!("{start} {range_arrow_icon} {end}")
                     }
                     (Value::Float(start), Value::Float(end)) => {
+                        let start = number_format.format(*start);
+                        let end = number_format.format(*end);
                         format!("{start} {range_arrow_icon} {end}")
                     }
                     (Value::Date(start), Value::Date(end)) => {
                         format!("{start} {range_arrow_icon} {end}")
                     }
+                    (Value::DateTime(start), Value::DateTime(end)) => {
+                        let start = start.format("%Y-%m-%d %H:%M");
+                        let end = end.format("%Y-%m-%d %H:%M %Z");
+                        format!("{start} {range_arrow_icon} {end}")
+                    }
                     (v1, v2) => {
                         panic!("{v1:?} and {v2:?} are different value types for ranged filter (component render)");
                     }
                 },
+                models::FilterValue::Text {
+                    value,
+                    case_sensitive,
+                } => {
+                    let value = clean_text(value);
+                    if *case_sensitive {
+                        format!(
+                            r#"&quot;{value}&quot; <span class="text-[10px]" title="Case sensitive">Aa</span>"#
+                        )
+                    } else {
+                        format!("&quot;{value}&quot;")
+                    }
+                }
+                models::FilterValue::Relative { date, .. } => {
+                    format!("{date}")
+                }
             },
         };
         let chevron = Chevron {
@@ -146,15 +333,25 @@ impl Component for FilterChip<'_> {
             hx_target: Some("closest div"),
         }
         .render();
+        let toggle_btn = ToggleFilterButton {
+            filter: self.filter,
+        }
+        .render();
+        let disabled_style = if self.filter.enabled {
+            ""
+        } else {
+            "opacity-50"
+        };
         format!(
             r#"
             <div
                 hx-get="{href}"
-                class="{FILTER_CONTAINER_STYLE} h-10 cursor-pointer">
+                class="{FILTER_CONTAINER_STYLE} h-10 cursor-pointer {disabled_style}">
                 {chevron}
                 <span class="text-xs">{subject}</span>
                 <span class="text-[10px] bg-slate-100 bg-opacity-40 rounded text-black p-1 shadow italic whitespace-nowrap">{operator_text}</span>
                 {rendered_value}
+                {toggle_btn}
                 {delete_btn}
             </div>
             "#
@@ -162,6 +359,45 @@ impl Component for FilterChip<'_> {
     }
 }
 
+/// Turns a filter off, or back on, without opening its form.
+struct ToggleFilterButton<'a> {
+    filter: &'a models::Filter,
+}
+impl Component for ToggleFilterButton<'_> {
+    fn render(&self) -> String {
+        let enabled_route = Route::FilterEnabled(Some((
+            self.filter.get_kind(),
+            self.filter.id,
+        )));
+        let (title, icon) = if self.filter.enabled {
+            (
+                "Disable filter",
+                r#"<path stroke-linecap="round" stroke-linejoin="round" d="M2.036 12.322a1.012 1.012 0 010-.639C3.423 7.51 7.36 4.5 12 4.5c4.638 0 8.573 3.007 9.963 7.178.07.207.07.431 0 .639C20.577 16.49 16.64 19.5 12 19.5c-4.638 0-8.573-3.007-9.963-7.178z" />
+                <path stroke-linecap="round" stroke-linejoin="round" d="M15 12a3 3 0 11-6 0 3 3 0 016 0z" />"#,
+            )
+        } else {
+            (
+                "Enable filter",
+                r#"<path stroke-linecap="round" stroke-linejoin="round" d="M3.98 8.223A10.477 10.477 0 001.934 12C3.226 16.338 7.244 19.5 12 19.5c.993 0 1.953-.138 2.863-.395M6.228 6.228A10.45 10.45 0 0112 4.5c4.756 0 8.773 3.162 10.065 7.498a10.523 10.523 0 01-4.293 5.774M6.228 6.228L3 3m3.228 3.228l3.65 3.65m7.894 7.894L21 21m-3.228-3.228l-3.65-3.65m0 0a3 3 0 10-4.243-4.243m4.242 4.242L9.88 9.88" />"#,
+            )
+        };
+        format!(
+            r#"
+            <button
+                title="{title}"
+                hx-post="{enabled_route}"
+                hx-target="closest div"
+                onclick="(arguments[0] || window.event).stopPropagation();"
+            >
+                <svg xmlns="http://www.w3.org/2000/svg" fill="none" viewBox="0 0 24 24" stroke-width="1.5" stroke="currentColor" class="w-4 h-4">
+                    {icon}
+                </svg>
+            </button>
+            "#
+        )
+    }
+}
+
 struct ArrowLeftRight;
 impl Component for ArrowLeftRight {
     fn render(&self) -> String {
@@ -180,6 +416,7 @@ impl Component for FilterForm<'_> {
         match &self.filter.value {
             models::FilterValue::Single(value) => SingleFilterForm {
                 id: self.filter.id,
+                prop_id: self.filter.prop_id,
                 value,
                 r#type: self.filter.r#type,
                 prop_name: self.prop_name,
@@ -187,18 +424,206 @@ impl Component for FilterForm<'_> {
             .render(),
             models::FilterValue::Range(start, end) => RangeFilterForm {
                 id: self.filter.id,
+                prop_id: self.filter.prop_id,
                 start,
                 end,
                 r#type: self.filter.r#type,
                 prop_name: self.prop_name,
             }
             .render(),
+            models::FilterValue::Text {
+                value,
+                case_sensitive,
+            } => TextFilterForm {
+                id: self.filter.id,
+                r#type: self.filter.r#type,
+                value,
+                case_sensitive: *case_sensitive,
+                prop_name: self.prop_name,
+                error: None,
+            }
+            .render(),
+            models::FilterValue::Relative { date, value_type } => {
+                RelativeDateFilterForm {
+                    id: self.filter.id,
+                    date: *date,
+                    value_type: *value_type,
+                    prop_name: self.prop_name,
+                }
+                .render()
+            }
         }
     }
 }
 
+/// Form for "is within" filters on date and date & time props. The amount
+/// is only used by periods like "last N days".
+pub struct RelativeDateFilterForm<'a> {
+    pub id: i32,
+    pub date: models::RelativeDate,
+    pub value_type: ValueType,
+    pub prop_name: &'a str,
+}
+impl Component for RelativeDateFilterForm<'_> {
+    fn render(&self) -> String {
+        let kind = models::FilterKind::RelativeDate;
+        let form_route = Route::Filter(Some((kind, self.id)));
+        let chip_route = Route::FilterChip(Some((kind, self.id)));
+        let filter_type_options = FilterTypeField {
+            selected_type: models::FilterType::IsWithin,
+            value_type: self.value_type,
+        }
+        .render();
+        let period = self.date.get_name();
+        let period_options = models::RelativeDate::NAMES.iter().fold(
+            String::new(),
+            |mut acc, name| {
+                let selected = if *name == period { "selected" } else { "" };
+                let display_name = models::RelativeDate::get_display_name(name);
+                acc.push_str(&format!(
+                    r#"<option value="{name}" {selected}>{display_name}</option>"#
+                ));
+                acc
+            },
+        );
+        let amount = match self.date.get_amount() {
+            0 => 7,
+            amount => amount,
+        };
+        let chevron = Chevron {
+            variant: ChevronVariant::Open,
+        }
+        .render();
+        let prop_name = clean(self.prop_name);
+        format!(
+            r#"
+            <form
+                hx-post="{form_route}"
+                class="{FILTER_CONTAINER_STYLE}"
+            >
+                <button
+                    class="self-start"
+                    hx-get="{chip_route}"
+                    hx-target="closest form"
+                    >{chevron}</button>
+                <div class="flex flex-col gap-2">
+                    <h1 class="text-lg">{prop_name}</h1>
+                    <div>
+                        {filter_type_options}
+                    </div>
+                    <div>
+                        <label class="text-sm" for="period">Period</label>
+                        <select
+                            id="period"
+                            name="period"
+                            class="dark:text-white text-sm dark:bg-slate-700 rounded"
+                            >{period_options}</select>
+                    </div>
+                    <div>
+                        <label class="text-sm" for="amount">N</label>
+                        <input
+                            class="dark:text-black w-20"
+                            id="amount"
+                            name="amount"
+                            type="number"
+                            min="1"
+                            max="{max_amount}"
+                            value="{amount}" />
+                    </div>
+                    <div>
+                        <button class="dark:bg-slate-700 dark:text-white dark:hover:bg-slate-600 transition shadow hover:shadow-none rounded p-1 block">Save</button>
+                    </div>
+                </div>
+            </form>
+            "#,
+            max_amount = models::MAX_RELATIVE_AMOUNT
+        )
+    }
+}
+
+/// Form for filters on text props and page titles. Regular expressions
+/// which Postgres rejects are shown as an `error`.
+pub struct TextFilterForm<'a> {
+    pub id: i32,
+    pub r#type: models::FilterType,
+    pub value: &'a str,
+    pub case_sensitive: bool,
+    pub prop_name: &'a str,
+    pub error: Option<&'a str>,
+}
+impl Component for TextFilterForm<'_> {
+    fn render(&self) -> String {
+        let kind = models::FilterKind::Single(ValueType::Str);
+        let form_route = Route::Filter(Some((kind, self.id)));
+        let chip_route = Route::FilterChip(Some((kind, self.id)));
+        let filter_type_options = FilterTypeField {
+            selected_type: self.r#type,
+            value_type: ValueType::Str,
+        }
+        .render();
+        let value = clean_text(self.value);
+        let checked = if self.case_sensitive { "checked" } else { "" };
+        let error = match self.error {
+            Some(error) => {
+                let error = clean(error);
+                format!(
+                    r#"<p class="text-red-600 dark:text-red-400">{error}</p>"#
+                )
+            }
+            None => "".into(),
+        };
+        let chevron = Chevron {
+            variant: ChevronVariant::Open,
+        }
+        .render();
+        let prop_name = clean(self.prop_name);
+        format!(
+            r#"
+            <form
+                hx-post="{form_route}"
+                class="{FILTER_CONTAINER_STYLE}"
+            >
+                <button
+                    class="self-start"
+                    hx-get="{chip_route}"
+                    hx-target="closest form"
+                    >{chevron}</button>
+                <div class="flex flex-col gap-2">
+                    <h1 class="text-lg">{prop_name}</h1>
+                    <div>
+                        {filter_type_options}
+                    </div>
+                    <div>
+                        <label for="value">Value</label>
+                        <input
+                            class="dark:text-black"
+                            id="value"
+                            name="value"
+                            type="text"
+                            value="{value}" />
+                    </div>
+                    <div class="flex flex-row gap-2 items-center">
+                        <input
+                            id="case_sensitive"
+                            name="case_sensitive"
+                            type="checkbox"
+                            {checked} />
+                        <label class="text-sm" for="case_sensitive">Case sensitive</label>
+                    </div>
+                    {error}
+                    <div>
+                        <button class="dark:bg-slate-700 dark:text-white dark:hover:bg-slate-600 transition shadow hover:shadow-none rounded p-1 block">Save</button>
+                    </div>
+                </div>
+            </form>
+            "#
+        )
+    }
+}
+
 pub struct SingleFilterForm<'a> {
     pub id: i32,
+    pub prop_id: i32,
     pub value: &'a Value,
     pub r#type: models::FilterType,
     pub prop_name: &'a str,
@@ -214,13 +639,14 @@ impl Component for SingleFilterForm<'_> {
         };
         let value_type = ValueType::of_value(self.value);
         let form_route = self.r#type.get_form_route(self.id, value_type);
-        let chip_route = self.r#type.get_form_route(self.id, value_type);
+        let chip_route = self.r#type.get_chip_route(self.id, value_type);
         let filter_type_options = FilterTypeField {
             selected_type: self.r#type,
             value_type,
         }
         .render();
         let value_field = FilterValueField {
+            prop_id: self.prop_id,
             value: self.value,
             label: "Value",
             name: "value",
@@ -262,6 +688,7 @@ impl Component for SingleFilterForm<'_> {
 
 pub struct RangeFilterForm<'a> {
     pub id: i32,
+    pub prop_id: i32,
     pub r#type: models::FilterType,
     pub start: &'a Value,
     pub end: &'a Value,
@@ -285,12 +712,14 @@ impl Component for RangeFilterForm<'_> {
         }
         .render();
         let start_field = FilterValueField {
+            prop_id: self.prop_id,
             value: self.start,
             label: "Start",
             name: "start",
         }
         .render();
         let end_filed = FilterValueField {
+            prop_id: self.prop_id,
             value: self.end,
             label: "End",
             name: "end",
@@ -338,9 +767,13 @@ pub struct FilterTypeField {
 }
 impl Component for FilterTypeField {
     fn render(&self) -> String {
+        // Each kind of filter value has its own form, so the type can only
+        // change to one with the same kind of value.
         let options =
             models::FilterType::get_supported_filter_types(self.value_type);
-        let html_options = options.iter().map(|option| {
+        let html_options = options.iter().filter(|option| {
+            option.has_same_value_kind(&self.selected_type)
+        }).map(|option| {
             let int_repr = option.get_int_repr();
             let description = option.get_display_name();
             if option == &self.selected_type {
@@ -363,6 +796,9 @@ impl Component for FilterTypeField {
 }
 
 pub struct FilterValueField<'a> {
+    /// Needed for value types whose choices are loaded lazily, like
+    /// relations.
+    pub prop_id: i32,
     pub value: &'a Value,
     pub label: &'a str,
     pub name: &'a str,
@@ -422,6 +858,114 @@ impl Component for FilterValueField<'_> {
                     "#
                 )
             }
+            Value::Str(val) => {
+                let val = clean_text(val);
+                format!(
+                    r#"
+                    <label for="{name}">{label}</label>
+                    <input
+                        id="{name}"
+                        name="{name}"
+                        type="text"
+                        value="{val}" />
+                    "#
+                )
+            }
+            Value::DateTime(val) => {
+                let tz = val.timezone();
+                let val = val.format("%Y-%m-%dT%H:%M");
+                format!(
+                    r#"
+                    <label for="{name}">{label} ({tz})</label>
+                    <input
+                        id="{name}"
+                        name="{name}"
+                        type="datetime-local"
+                        value="{val}" />
+                    "#
+                )
+            }
+            Value::Select(option) => {
+                // The rest of the options are loaded lazily, since we only
+                // have the selected one on hand.
+                let option_list_route =
+                    Route::PropOptionList(Some(option.prop_id));
+                let option_id = option.id;
+                let option_name = clean_text(&option.name);
+                format!(
+                    r#"
+                    <label for="{name}">{label}</label>
+                    <select
+                        id="{name}"
+                        name="{name}"
+                        class="dark:text-white text-sm dark:bg-slate-700 rounded"
+                        hx-get="{option_list_route}?selected={option_id}"
+                        hx-trigger="load"
+                        hx-target="this"
+                        hx-swap="innerHTML"
+                    >
+                        <option selected value="{option_id}">{option_name}</option>
+                    </select>
+                    "#
+                )
+            }
+            Value::Relation(pages) => {
+                // As with select filters, the rest of the pages are loaded
+                // lazily. Relation filters always hold exactly one page.
+                let options_route =
+                    Route::PropRelationOptions(Some(self.prop_id));
+                let (page_id, title) = match pages.first() {
+                    Some(page) => (page.id, clean_text(&page.title)),
+                    None => panic!("relation filter without a page"),
+                };
+                format!(
+                    r#"
+                    <label for="{name}">{label}</label>
+                    <select
+                        id="{name}"
+                        name="{name}"
+                        class="dark:text-white text-sm dark:bg-slate-700 rounded"
+                        hx-get="{options_route}?selected={page_id}"
+                        hx-trigger="load"
+                        hx-target="this"
+                        hx-swap="innerHTML"
+                    >
+                        <option selected value="{page_id}">{title}</option>
+                    </select>
+                    "#
+                )
+            }
+            Value::CreatedBy(user) | Value::LastEditedBy(user) => {
+                // As with relation filters, the rest of the users are loaded
+                // lazily.
+                let options_route = Route::UserOptions;
+                let user_id = user.id;
+                let username = clean_text(&user.username);
+                format!(
+                    r#"
+                    <label for="{name}">{label}</label>
+                    <select
+                        id="{name}"
+                        name="{name}"
+                        class="dark:text-white text-sm dark:bg-slate-700 rounded"
+                        hx-get="{options_route}?selected={user_id}"
+                        hx-trigger="load"
+                        hx-target="this"
+                        hx-swap="innerHTML"
+                    >
+                        <option selected value="{user_id}">{username}</option>
+                    </select>
+                    "#
+                )
+            }
+            Value::MultiStr(_) => panic!("tag filters are not supported"),
+            Value::Files(_) => panic!("file filters are not supported"),
+            Value::Formula(_) | Value::Rollup(_) => {
+                panic!("computed props are filtered by their result type")
+            }
+            Value::CreatedTime(_) | Value::LastEditedTime(_) => {
+                panic!("timestamp props are filtered as date & time props")
+            }
         }
     }
 }
@@ -441,8 +985,9 @@ impl Component for BoolFilterForm<'_> {
         }
         .render();
         let prop_name = self.prop_name;
-        let submit_url = Route::FilterBool(Some(filter_id));
-        let chip_route = Route::FilterBoolChip(Some(filter_id));
+        let kind = models::FilterKind::Single(ValueType::Bool);
+        let submit_url = Route::Filter(Some((kind, filter_id)));
+        let chip_route = Route::FilterChip(Some((kind, filter_id)));
         format!(
             r##"
             <div id="{container_id}" class="{FILTER_CONTAINER_STYLE} flex-col">
@@ -494,7 +1039,12 @@ impl Component for BoolFilterForm<'_> {
 }
 
 pub struct ChoosePropForFilter<'a> {
+    pub view_id: i32,
     pub props: &'a Vec<&'a Prop>,
+    /// Whether the page title can be filtered; it has only one filter, like
+    /// each prop.
+    pub can_filter_title: bool,
+    pub group_id: Option<i32>,
 }
 impl Component for ChoosePropForFilter<'_> {
     fn render(&self) -> String {
@@ -503,12 +1053,30 @@ impl Component for ChoosePropForFilter<'_> {
             self.props.iter().fold(String::new(), |mut acc, p| {
                 let prop_id = p.id;
                 let prop_name = clean(&p.name);
-                let href = Route::PropNewFilterTypeSelect(Some(prop_id));
+                let href = with_group_id(
+                    Route::ViewNewFilterTypeSelect(Some((
+                        self.view_id,
+                        prop_id,
+                    ))),
+                    self.group_id,
+                );
                 let type_string = match p.type_id {
                     ValueType::Int => "integer",
                     ValueType::Bool => "checkbox",
                     ValueType::Float => "percent",
                     ValueType::Date => "date",
+                    ValueType::Str => "text",
+                    ValueType::MultiStr => "tags",
+                    ValueType::DateTime => "date & time",
+                    ValueType::Select => "select",
+                    ValueType::Relation => "relation",
+                    ValueType::Formula => "formula",
+                    ValueType::Rollup => "rollup",
+                    ValueType::CreatedTime => "created time",
+                    ValueType::LastEditedTime => "last edited time",
+                    ValueType::CreatedBy => "created by",
+                    ValueType::LastEditedBy => "last edited by",
+                    ValueType::Files => "files",
                 };
                 acc.push_str(&format!(
                     r#"
@@ -521,10 +1089,30 @@ impl Component for ChoosePropForFilter<'_> {
                 ));
                 acc
             });
+        // Title filters start out as "contains", which is what people
+        // usually want; the type can be changed in the form.
+        let title_button = if self.can_filter_title {
+            let href = with_group_id(
+                Route::ViewNewTitleFilter(Some(self.view_id)),
+                self.group_id,
+            );
+            format!(
+                r#"
+                <button
+                    hx-post="{href}"
+                    hx-target="closest div"
+                    class="{button_style}"
+                    >Title (text)</button>
+                "#
+            )
+        } else {
+            "".into()
+        };
 
         format!(
             r#"
             <div class="flex flex-col {FILTER_CONTAINER_STYLE}">
+                {title_button}
                 {prop_buttons}
             </div>
             "#
@@ -533,12 +1121,15 @@ impl Component for ChoosePropForFilter<'_> {
 }
 
 pub struct AddFilterButton {
-    pub collection_id: i32,
+    pub view_id: i32,
+    pub group_id: Option<i32>,
 }
 impl Component for AddFilterButton {
     fn render(&self) -> String {
-        let collection_route =
-            Route::CollectionChoosePropForFilter(Some(self.collection_id));
+        let choose_prop_route = with_group_id(
+            Route::ViewChoosePropForFilter(Some(self.view_id)),
+            self.group_id,
+        );
         format!(
             r#"
             <button
@@ -554,7 +1145,7 @@ impl Component for AddFilterButton {
                     border-2
                     border-slate-600
                 "
-                hx-get="{collection_route}"
+                hx-get="{choose_prop_route}"
                 >
                 Add filter
             </button>
@@ -563,17 +1154,21 @@ impl Component for AddFilterButton {
     }
 }
 
-/// If the collection does not have capacity for any more filters, we will
+/// If the view does not have capacity for any more filters, we will
 /// render this component instead of the add filter button above. It is hidden
 /// but it will receive events from Hx-Trigger headers when filters are
 /// deleted, meaning that we've most likely gained capacity for a new filter
 /// again.
 pub struct AddFilterButtonPlaceholder {
-    pub collection_id: i32,
+    pub view_id: i32,
+    pub group_id: Option<i32>,
 }
 impl Component for AddFilterButtonPlaceholder {
     fn render(&self) -> String {
-        let route = Route::CollectionAddFilterButton(Some(self.collection_id));
+        let route = with_group_id(
+            Route::ViewAddFilterButton(Some(self.view_id)),
+            self.group_id,
+        );
         format!(
             r#"
             <div
@@ -585,66 +1180,36 @@ impl Component for AddFilterButtonPlaceholder {
     }
 }
 
-/// Kind of a hack to get the ending chunk of the URL and query parameters to
-/// dynamically construct a URL for the appropriate new filter form.
-fn get_slug(filter_type: models::FilterType, prop_type: ValueType) -> String {
-    let filter_type_id = filter_type.get_int_repr();
-    match filter_type {
-        models::FilterType::Neq
-        | models::FilterType::Lt
-        | models::FilterType::Gt
-        | models::FilterType::Eq
-        | models::FilterType::IsEmpty => match prop_type {
-            ValueType::Bool => {
-                format!("new-bool-filter?type_id={filter_type_id}")
-            }
-            ValueType::Int => {
-                format!("new-int-filter?type_id={filter_type_id}")
-            }
-            ValueType::Float => {
-                format!("new-float-filter?type_id={filter_type_id}")
-            }
-            ValueType::Date => {
-                format!("new-date-filter?type_id={filter_type_id}")
-            }
-        },
-        models::FilterType::InRng | models::FilterType::NotInRng => {
-            match prop_type {
-                ValueType::Bool => {
-                    panic!("in-rng and not-in-rng not supported for bool")
-                }
-                ValueType::Int => {
-                    format!("new-int-rng-filter?type_id={filter_type_id}")
-                }
-                ValueType::Float => {
-                    format!("new-float-rng-filter?type_id={filter_type_id}")
-                }
-                ValueType::Date => {
-                    format!("new-date-rng-filter?type_id={filter_type_id}")
-                }
-            }
-        }
-    }
-}
-
 pub struct NewFilterTypeOptions<'a> {
     pub options: &'a Vec<models::FilterType>,
+    pub view_id: i32,
     pub prop_id: i32,
     pub prop_type: ValueType,
+    pub group_id: Option<i32>,
 }
 impl Component for NewFilterTypeOptions<'_> {
     fn render(&self) -> String {
         let button_style = "p-2 w-full text-md rounded dark:bg-blue-700 dark:hover:bg-blue-600 shadow hover:shadow-none";
+        let view_id = self.view_id;
         let prop_id = self.prop_id;
         let rendered_options =
             self.options.iter().fold(String::new(), |mut str, opt| {
                 let opt_text = clean(opt.get_display_name());
-                let type_slug = get_slug(*opt, self.prop_type);
+                let kind = models::FilterKind::new(*opt, self.prop_type)
+                    .unwrap_or_else(|e| panic!("{e}"));
+                let new_filter_route = with_group_id(
+                    format!(
+                        "{}?type_id={}",
+                        Route::ViewNewFilter(Some((view_id, prop_id, kind))),
+                        opt.get_int_repr()
+                    ),
+                    self.group_id,
+                );
                 str.push_str(&format!(
                     r#"
                     <button
                         class="{button_style}"
-                        hx-post="/prop/{prop_id}/{type_slug}"
+                        hx-post="{new_filter_route}"
                         hx-target="closest div"
                         >{opt_text}</button>
                     "#
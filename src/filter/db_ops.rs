@@ -1,772 +1,948 @@
 use super::models;
 use crate::{
     db_ops::DbModel,
-    models::{Value, ValueType},
+    models::{
+        Prop, RelatedPage, SelectColor, SelectOption, User, Value, ValueType,
+    },
 };
 use anyhow::{bail, Result};
 use async_trait::async_trait;
-use futures::join;
-use sqlx::{query, query_as, PgPool};
+use chrono_tz::Tz;
+use sqlx::{query, query_as, PgConnection, PgPool};
 
-/// We're really into hackery here, and exposing a modeling mistake; whoopsies.
-pub enum Variant {
-    Single,
-    Ranged,
-}
 pub struct GetFilterQuery {
     pub id: i32,
-    pub value_type: ValueType,
-    pub variant: Variant,
 }
 
+/// Filters in a view, on a prop, or both.
 pub struct ListFilterQuery {
-    pub collection_id: i32,
+    pub view_id: Option<i32>,
+    pub prop_id: Option<i32>,
 }
 
-async fn get_primitive_filter(
-    db: &PgPool,
+/// A row of the `filter` table, along with the option, page, or user that
+/// it refers to. Only the value columns for `value_type_id` are set.
+struct QresFilter {
     id: i32,
-    r#type: ValueType,
-) -> Result<models::Filter> {
-    struct Qres<T> {
-        id: i32,
-        type_id: i32,
-        prop_id: i32,
-        value: T,
-    }
-    Ok(match r#type {
-        ValueType::Int => {
-            let res = query_as!(
-                Qres::<i64>,
-                "select id, type_id, prop_id, value
-                        from filter_int f
-                        where f.id = $1",
-                id
-            )
-            .fetch_one(db)
-            .await?;
-            models::Filter {
-                id: res.id,
-                prop_id: res.prop_id,
-                r#type: models::FilterType::from_int(res.type_id),
-                value: models::FilterValue::Single(Value::Int(res.value)),
-            }
-        }
-        ValueType::Bool => {
-            let res = query_as!(
-                Qres::<bool>,
-                "select id, type_id, prop_id, value
-                        from filter_bool f
-                        where f.id = $1",
-                id
-            )
-            .fetch_one(db)
-            .await?;
-            models::Filter {
-                id: res.id,
-                prop_id: res.prop_id,
-                r#type: models::FilterType::from_int(res.type_id),
-                value: models::FilterValue::Single(Value::Bool(res.value)),
-            }
-        }
-        ValueType::Float => {
-            let res = query_as!(
-                Qres::<f64>,
-                "select id, type_id, prop_id, value
-                        from filter_float f
-                        where f.id = $1",
-                id
-            )
-            .fetch_one(db)
-            .await?;
-            models::Filter {
-                id: res.id,
-                prop_id: res.prop_id,
-                r#type: models::FilterType::from_int(res.type_id),
-                value: models::FilterValue::Single(Value::Float(res.value)),
-            }
-        }
-        ValueType::Date => {
-            let res = query_as!(
-                Qres::<chrono::NaiveDate>,
-                "select id, type_id, prop_id, value
-                        from filter_date f
-                        where f.id = $1",
-                id
+    view_id: i32,
+    type_id: i32,
+    prop_id: Option<i32>,
+    group_id: Option<i32>,
+    value_type_id: i32,
+    bool_value: Option<bool>,
+    int_value: Option<i64>,
+    int_end: Option<i64>,
+    float_value: Option<f64>,
+    float_end: Option<f64>,
+    date_value: Option<chrono::NaiveDate>,
+    date_end: Option<chrono::NaiveDate>,
+    datetime_value: Option<chrono::DateTime<chrono::Utc>>,
+    datetime_end: Option<chrono::DateTime<chrono::Utc>>,
+    text_value: Option<String>,
+    case_sensitive: bool,
+    option_id: Option<i32>,
+    option_name: Option<String>,
+    option_color: Option<String>,
+    option_order: Option<i16>,
+    page_id: Option<i32>,
+    page_collection_id: Option<i32>,
+    page_title: Option<String>,
+    user_id: Option<i32>,
+    username: Option<String>,
+    email: Option<String>,
+    period: Option<String>,
+    amount: Option<i32>,
+    enabled: bool,
+}
+impl QresFilter {
+    fn into_filter(self) -> Result<models::Filter> {
+        let id = self.id;
+        let r#type = models::FilterType::from_int(self.type_id);
+        let value_type = ValueType::from_int(self.value_type_id);
+        let missing = || {
+            anyhow::anyhow!(
+                "{} filter {id} is missing its value",
+                value_type.get_display_name()
             )
-            .fetch_one(db)
-            .await?;
-            models::Filter {
-                id: res.id,
-                prop_id: res.prop_id,
-                r#type: models::FilterType::from_int(res.type_id),
-                value: models::FilterValue::Single(Value::Date(res.value)),
+        };
+        let value = match r#type {
+            models::FilterType::IsWithin => models::FilterValue::Relative {
+                date: models::RelativeDate::parse(
+                    &self.period.ok_or_else(missing)?,
+                    self.amount.unwrap_or_default(),
+                )?,
+                value_type,
+            },
+            _ if value_type == ValueType::Str => models::FilterValue::Text {
+                value: self.text_value.ok_or_else(missing)?,
+                case_sensitive: self.case_sensitive,
+            },
+            models::FilterType::InRng | models::FilterType::NotInRng => {
+                match value_type {
+                    ValueType::Int => models::FilterValue::Range(
+                        Value::Int(self.int_value.ok_or_else(missing)?),
+                        Value::Int(self.int_end.ok_or_else(missing)?),
+                    ),
+                    ValueType::Float => models::FilterValue::Range(
+                        Value::Float(self.float_value.ok_or_else(missing)?),
+                        Value::Float(self.float_end.ok_or_else(missing)?),
+                    ),
+                    ValueType::Date => models::FilterValue::Range(
+                        Value::Date(self.date_value.ok_or_else(missing)?),
+                        Value::Date(self.date_end.ok_or_else(missing)?),
+                    ),
+                    ValueType::DateTime => models::FilterValue::Range(
+                        Value::DateTime(
+                            self.datetime_value
+                                .ok_or_else(missing)?
+                                .with_timezone(&Tz::UTC),
+                        ),
+                        Value::DateTime(
+                            self.datetime_end
+                                .ok_or_else(missing)?
+                                .with_timezone(&Tz::UTC),
+                        ),
+                    ),
+                    ty => bail!(
+                        "{} range filter does not exist",
+                        ty.get_display_name()
+                    ),
+                }
             }
-        }
-    })
-}
+            _ => models::FilterValue::Single(match value_type {
+                ValueType::Bool => {
+                    Value::Bool(self.bool_value.ok_or_else(missing)?)
+                }
+                ValueType::Int => {
+                    Value::Int(self.int_value.ok_or_else(missing)?)
+                }
+                ValueType::Float => {
+                    Value::Float(self.float_value.ok_or_else(missing)?)
+                }
+                ValueType::Date => {
+                    Value::Date(self.date_value.ok_or_else(missing)?)
+                }
+                ValueType::DateTime => Value::DateTime(
+                    self.datetime_value
+                        .ok_or_else(missing)?
+                        .with_timezone(&Tz::UTC),
+                ),
+                ValueType::Select => Value::Select(SelectOption {
+                    id: self.option_id.ok_or_else(missing)?,
+                    prop_id: self.prop_id.ok_or_else(missing)?,
+                    name: self.option_name.ok_or_else(missing)?,
+                    color: SelectColor::from_name(
+                        &self.option_color.ok_or_else(missing)?,
+                    )?,
+                    order: self.option_order.ok_or_else(missing)?,
+                }),
+                ValueType::Relation => Value::Relation(vec![RelatedPage {
+                    id: self.page_id.ok_or_else(missing)?,
+                    collection_id: self
+                        .page_collection_id
+                        .ok_or_else(missing)?,
+                    title: self.page_title.ok_or_else(missing)?,
+                }]),
+                ValueType::CreatedBy | ValueType::LastEditedBy => {
+                    Value::CreatedBy(User {
+                        id: self.user_id.ok_or_else(missing)?,
+                        username: self.username.ok_or_else(missing)?,
+                        email: self.email.ok_or_else(missing)?,
+                    })
+                }
+                ty => {
+                    bail!("{} filters are not supported", ty.get_display_name())
+                }
+            }),
+        };
 
-async fn get_ranged_filter(
-    db: &PgPool,
-    id: i32,
-    r#type: ValueType,
-) -> Result<models::Filter> {
-    struct Qres<T> {
-        id: i32,
-        type_id: i32,
-        prop_id: i32,
-        start: T,
-        end: T,
+        Ok(models::Filter {
+            id,
+            view_id: self.view_id,
+            prop_id: self.prop_id.unwrap_or(models::TITLE_PROP_ID),
+            group_id: self.group_id,
+            r#type,
+            value,
+            enabled: self.enabled,
+        })
     }
-    Ok(match r#type {
-        ValueType::Int => {
-            let res = query_as!(
-                Qres::<i64>,
-                r#"select id, type_id, prop_id, start, "end"
-                from filter_int_range f
-                where f.id = $1"#,
-                id
-            )
-            .fetch_one(db)
-            .await?;
-            models::Filter {
-                id: res.id,
-                prop_id: res.prop_id,
-                r#type: models::FilterType::from_int(res.type_id),
-                value: models::FilterValue::Range(
-                    Value::Int(res.start),
-                    Value::Int(res.end),
-                ),
+}
+
+/// The value columns of the `filter` table which hold a
+/// [models::FilterValue]; the rest are left null.
+#[derive(Default)]
+struct ValueColumns {
+    value_type_id: i32,
+    bool_value: Option<bool>,
+    int_value: Option<i64>,
+    int_end: Option<i64>,
+    float_value: Option<f64>,
+    float_end: Option<f64>,
+    date_value: Option<chrono::NaiveDate>,
+    date_end: Option<chrono::NaiveDate>,
+    datetime_value: Option<chrono::DateTime<chrono::Utc>>,
+    datetime_end: Option<chrono::DateTime<chrono::Utc>>,
+    text_value: Option<String>,
+    case_sensitive: bool,
+    option_id: Option<i32>,
+    page_id: Option<i32>,
+    user_id: Option<i32>,
+    period: Option<String>,
+    amount: Option<i32>,
+}
+impl ValueColumns {
+    fn of(value: &models::FilterValue) -> Result<Self> {
+        let mut cols = Self::default();
+        match value {
+            models::FilterValue::Single(val) => {
+                cols.value_type_id = ValueType::of_value(val).get_int_repr();
+                match val {
+                    Value::Bool(val) => cols.bool_value = Some(*val),
+                    Value::Int(val) => cols.int_value = Some(*val),
+                    Value::Float(val) => cols.float_value = Some(*val),
+                    Value::Date(val) => cols.date_value = Some(*val),
+                    Value::DateTime(val) => {
+                        cols.datetime_value =
+                            Some(val.with_timezone(&chrono::Utc))
+                    }
+                    Value::Select(option) => cols.option_id = Some(option.id),
+                    Value::Relation(pages) => match pages.first() {
+                        Some(page) => cols.page_id = Some(page.id),
+                        None => bail!("relation filters need a page"),
+                    },
+                    Value::CreatedBy(user) | Value::LastEditedBy(user) => {
+                        cols.user_id = Some(user.id)
+                    }
+                    val => bail!("{val:?} cannot be filtered by"),
+                }
             }
-        }
-        ValueType::Bool => {
-            // Uh oh, go to Rust data modeling Jail, do not pass Go, do not
-            // collect $200.
-            panic!("I am sorry Rust, I have failed you");
-        }
-        ValueType::Date => {
-            let res = query_as!(
-                Qres::<chrono::NaiveDate>,
-                r#"select id, type_id, prop_id, start, "end"
-                from filter_date_range f
-                where f.id = $1"#,
-                id
-            )
-            .fetch_one(db)
-            .await?;
-            models::Filter {
-                id: res.id,
-                prop_id: res.prop_id,
-                r#type: models::FilterType::from_int(res.type_id),
-                value: models::FilterValue::Range(
-                    Value::Date(res.start),
-                    Value::Date(res.end),
-                ),
+            models::FilterValue::Range(start, end) => {
+                cols.value_type_id = ValueType::of_value(start).get_int_repr();
+                match (start, end) {
+                    (Value::Int(start), Value::Int(end)) => {
+                        cols.int_value = Some(*start);
+                        cols.int_end = Some(*end);
+                    }
+                    (Value::Float(start), Value::Float(end)) => {
+                        cols.float_value = Some(*start);
+                        cols.float_end = Some(*end);
+                    }
+                    (Value::Date(start), Value::Date(end)) => {
+                        cols.date_value = Some(*start);
+                        cols.date_end = Some(*end);
+                    }
+                    (Value::DateTime(start), Value::DateTime(end)) => {
+                        cols.datetime_value =
+                            Some(start.with_timezone(&chrono::Utc));
+                        cols.datetime_end =
+                            Some(end.with_timezone(&chrono::Utc));
+                    }
+                    (v1, v2) => {
+                        bail!("{v1:?} and {v2:?} are different value types for ranged filter");
+                    }
+                }
             }
-        }
-        ValueType::Float => {
-            let res = query_as!(
-                Qres::<f64>,
-                r#"select id, type_id, prop_id, start, "end"
-                from filter_float_range f
-                where f.id = $1"#,
-                id
-            )
-            .fetch_one(db)
-            .await?;
-            models::Filter {
-                id: res.id,
-                prop_id: res.prop_id,
-                r#type: models::FilterType::from_int(res.type_id),
-                value: models::FilterValue::Range(
-                    Value::Float(res.start),
-                    Value::Float(res.end),
-                ),
+            models::FilterValue::Text {
+                value,
+                case_sensitive,
+            } => {
+                cols.value_type_id = ValueType::Str.get_int_repr();
+                cols.text_value = Some(value.clone());
+                cols.case_sensitive = *case_sensitive;
             }
-        }
-    })
+            models::FilterValue::Relative { date, value_type } => {
+                cols.value_type_id = value_type.get_int_repr();
+                cols.period = Some(date.get_name().into());
+                cols.amount = Some(date.get_amount());
+            }
+        };
+
+        Ok(cols)
+    }
 }
 
 #[async_trait]
 impl DbModel<GetFilterQuery, ListFilterQuery> for models::Filter {
     async fn get(db: &PgPool, query: &GetFilterQuery) -> Result<Self> {
-        Ok(match query.variant {
-            Variant::Single => {
-                get_primitive_filter(db, query.id, query.value_type).await?
-            }
-            Variant::Ranged => {
-                get_ranged_filter(db, query.id, query.value_type).await?
-            }
-        })
+        query_as!(
+            QresFilter,
+            r#"select
+                f.id, f.view_id, f.type_id, f.prop_id, f.group_id,
+                f.value_type_id,
+                f.bool_value, f.int_value, f.int_end, f.float_value,
+                f.float_end, f.date_value, f.date_end, f.datetime_value,
+                f.datetime_end, f.text_value, f.case_sensitive, f.option_id,
+                po.name as "option_name?", po.color as "option_color?",
+                po."order" as "option_order?",
+                f.page_id, t.collection_id as "page_collection_id?",
+                t.title as "page_title?",
+                f.user_id, u.username as "username?", u.email as "email?",
+                f.period, f.amount, f.enabled
+            from filter f
+            left join property_option po on po.id = f.option_id
+            left join page t on t.id = f.page_id
+            left join users u on u.id = f.user_id
+            where f.id = $1"#,
+            query.id
+        )
+        .fetch_one(db)
+        .await?
+        .into_filter()
     }
 
     async fn list(db: &PgPool, query: &ListFilterQuery) -> Result<Vec<Self>> {
-        struct Qres<T> {
-            id: i32,
-            prop_id: i32,
-            r#type_id: i32,
-            value: T,
-        }
-        let bools = query_as!(
-            Qres::<bool>,
-            "select f.id, f.prop_id, f.type_id, f.value
-            from filter_bool f
-            join property p on p.id = f.prop_id
-            where p.collection_id = $1",
-            query.collection_id
-        )
-        .map(|row| models::Filter {
-            id: row.id,
-            prop_id: row.prop_id,
-            r#type: models::FilterType::from_int(row.type_id),
-            value: models::FilterValue::Single(Value::Bool(row.value)),
-        })
-        .fetch_all(db);
-        let ints = query_as!(
-            Qres::<i64>,
-            "select f.id, f.prop_id, f.type_id, f.value
-            from filter_int f
-            join property p on p.id = f.prop_id
-            where p.collection_id = $1",
-            query.collection_id
-        )
-        .map(|row| models::Filter {
-            id: row.id,
-            prop_id: row.prop_id,
-            r#type: models::FilterType::from_int(row.type_id),
-            value: models::FilterValue::Single(Value::Int(row.value)),
-        })
-        .fetch_all(db);
-        struct QresRng<T> {
-            id: i32,
-            prop_id: i32,
-            r#type_id: i32,
-            start: T,
-            end: T,
-        }
-        let int_ranges = query_as!(
-            QresRng::<i64>,
-            "select f.id, f.prop_id, f.type_id, f.start, f.end
-            from filter_int_range f
-            join property p on p.id = f.prop_id
-            where p.collection_id = $1",
-            query.collection_id
-        )
-        .map(|row| models::Filter {
-            id: row.id,
-            prop_id: row.prop_id,
-            r#type: models::FilterType::from_int(row.type_id),
-            value: models::FilterValue::Range(
-                Value::Int(row.start),
-                Value::Int(row.end),
-            ),
-        })
-        .fetch_all(db);
-        let floats = query_as!(
-            Qres::<f64>,
-            "select f.id, f.prop_id, f.type_id, f.value
-            from filter_float f
-            join property p on p.id = f.prop_id
-            where p.collection_id = $1",
-            query.collection_id
-        )
-        .map(|row| models::Filter {
-            id: row.id,
-            prop_id: row.prop_id,
-            r#type: models::FilterType::from_int(row.type_id),
-            value: models::FilterValue::Single(Value::Float(row.value)),
-        })
-        .fetch_all(db);
-        let float_ranges = query_as!(
-            QresRng::<f64>,
-            "select f.id, f.prop_id, f.type_id, f.start, f.end
-            from filter_float_range f
-            join property p on p.id = f.prop_id
-            where p.collection_id = $1",
-            query.collection_id
-        )
-        .map(|row| models::Filter {
-            id: row.id,
-            prop_id: row.prop_id,
-            r#type: models::FilterType::from_int(row.type_id),
-            value: models::FilterValue::Range(
-                Value::Float(row.start),
-                Value::Float(row.end),
-            ),
-        })
-        .fetch_all(db);
-        let dates = query_as!(
-            Qres::<chrono::NaiveDate>,
-            "select f.id, f.prop_id, f.type_id, f.value
-            from filter_date f
-            join property p on p.id = f.prop_id
-            where p.collection_id = $1",
-            query.collection_id
-        )
-        .map(|row| models::Filter {
-            id: row.id,
-            prop_id: row.prop_id,
-            r#type: models::FilterType::from_int(row.type_id),
-            value: models::FilterValue::Single(Value::Date(row.value)),
-        })
-        .fetch_all(db);
-        let date_ranges = query_as!(
-            QresRng::<chrono::NaiveDate>,
-            "select f.id, f.prop_id, f.type_id, f.start, f.end
-            from filter_date_range f
-            join property p on p.id = f.prop_id
-            where p.collection_id = $1",
-            query.collection_id
-        )
-        .map(|row| models::Filter {
-            id: row.id,
-            prop_id: row.prop_id,
-            r#type: models::FilterType::from_int(row.type_id),
-            value: models::FilterValue::Range(
-                Value::Date(row.start),
-                Value::Date(row.end),
-            ),
-        })
-        .fetch_all(db);
-
-        let (bools, ints, int_ranges, floats, float_ranges, dates, date_ranges) = join![
-            bools,
-            ints,
-            int_ranges,
-            floats,
-            float_ranges,
-            dates,
-            date_ranges
-        ];
-
-        let mut bools = bools?;
-        let mut ints = ints?;
-        let mut int_ranges = int_ranges?;
-        let mut floats = floats?;
-        let mut float_ranges = float_ranges?;
-        let mut dates = dates?;
-        let mut date_ranges = date_ranges?;
-
-        let mut result = Vec::with_capacity(
-            bools.len()
-                + ints.len()
-                + int_ranges.len()
-                + floats.len()
-                + float_ranges.len()
-                + dates.len()
-                + date_ranges.len(),
-        );
-
-        result.append(&mut bools);
-        result.append(&mut ints);
-        result.append(&mut int_ranges);
-        result.append(&mut floats);
-        result.append(&mut float_ranges);
-        result.append(&mut dates);
-        result.append(&mut date_ranges);
-
-        Ok(result)
+        list_filters(&mut *db.acquire().await?, query).await
     }
 
     async fn save(&self, db: &PgPool) -> Result<()> {
-        match &self.value {
-            models::FilterValue::Single(val) => match val {
-                Value::Int(val) => {
-                    query!(
-                        "update filter_int set type_id = $1, value = $2
-                        where id = $3",
-                        self.r#type.get_int_repr(),
-                        val,
-                        self.id
-                    )
-                    .execute(db)
-                    .await?;
-                }
-                Value::Bool(val) => {
-                    query!(
-                        "update filter_bool set type_id = $1, value = $2
-                        where id = $3",
-                        self.r#type.get_int_repr(),
-                        val,
-                        self.id
-                    )
-                    .execute(db)
-                    .await?;
-                }
-                Value::Date(val) => {
-                    query!(
-                        "update filter_date set type_id = $1, value = $2
-                        where id = $3",
-                        self.r#type.get_int_repr(),
-                        val,
-                        self.id
-                    )
-                    .execute(db)
-                    .await?;
-                }
-                Value::Float(val) => {
-                    query!(
-                        "update filter_float set type_id = $1, value = $2
-                        where id = $3",
-                        self.r#type.get_int_repr(),
-                        val,
-                        self.id
-                    )
-                    .execute(db)
-                    .await?;
-                }
-            },
-            models::FilterValue::Range(v1, v2) => match (v1, v2) {
-                (Value::Int(start), Value::Int(end)) => {
-                    query!(
-                        r#"update filter_int_range
-                        set type_id = $1, start = $2, "end" = $3
-                        where id = $4"#,
-                        self.r#type.get_int_repr(),
-                        start,
-                        end,
-                        self.id
-                    )
-                    .execute(db)
-                    .await?;
-                }
-                (Value::Float(start), Value::Float(end)) => {
-                    query!(
-                        r#"update filter_float_range
-                        set type_id = $1, start = $2, "end" = $3
-                        where id = $4"#,
-                        self.r#type.get_int_repr(),
-                        start,
-                        end,
-                        self.id
-                    )
-                    .execute(db)
-                    .await?;
-                }
-                (Value::Date(start), Value::Date(end)) => {
-                    query!(
-                        r#"update filter_date_range
-                        set type_id = $1, start = $2, "end" = $3
-                        where id = $4"#,
-                        self.r#type.get_int_repr(),
-                        start,
-                        end,
-                        self.id
-                    )
-                    .execute(db)
-                    .await?;
-                }
-                (v1, v2) => {
-                    bail!("{v1:?} and {v2:?} are different value types for ranged filter (save)");
-                }
-            },
-        };
+        let cols = ValueColumns::of(&self.value)?;
+        query!(
+            "update filter set
+                type_id = $1, value_type_id = $2, bool_value = $3,
+                int_value = $4, int_end = $5, float_value = $6,
+                float_end = $7, date_value = $8, date_end = $9,
+                datetime_value = $10, datetime_end = $11, text_value = $12,
+                case_sensitive = $13, option_id = $14, page_id = $15,
+                user_id = $16, period = $17, amount = $18, enabled = $19
+            where id = $20",
+            self.r#type.get_int_repr(),
+            cols.value_type_id,
+            cols.bool_value,
+            cols.int_value,
+            cols.int_end,
+            cols.float_value,
+            cols.float_end,
+            cols.date_value,
+            cols.date_end,
+            cols.datetime_value,
+            cols.datetime_end,
+            cols.text_value,
+            cols.case_sensitive,
+            cols.option_id,
+            cols.page_id,
+            cols.user_id,
+            cols.period,
+            cols.amount,
+            self.enabled,
+            self.id
+        )
+        .execute(db)
+        .await?;
 
         Ok(())
     }
 
     async fn delete(self, db: &PgPool) -> Result<()> {
-        match self.value {
-            models::FilterValue::Single(val) => match val {
-                Value::Int(_) => {
-                    query!("delete from filter_int where id = $1", self.id)
-                        .execute(db)
-                        .await?;
-                }
-                Value::Bool(_) => {
-                    query!("delete from filter_bool where id = $1", self.id)
-                        .execute(db)
-                        .await?;
-                }
-                Value::Date(_) => {
-                    query!("delete from filter_date where id = $1", self.id)
-                        .execute(db)
-                        .await?;
-                }
-                Value::Float(_) => {
-                    query!("delete from filter_float where id = $1", self.id)
-                        .execute(db)
-                        .await?;
-                }
-            },
-            models::FilterValue::Range(v1, v2) => match (v1, v2) {
-                (Value::Int(_), Value::Int(_)) => {
-                    query!(
-                        "delete from filter_int_range where id = $1",
-                        self.id
-                    )
-                    .execute(db)
-                    .await?;
-                }
-                (Value::Float(_), Value::Float(_)) => {
-                    query!(
-                        "delete from filter_float_range where id = $1",
-                        self.id
-                    )
-                    .execute(db)
-                    .await?;
-                }
-                (Value::Date(_), Value::Date(_)) => {
-                    query!(
-                        "delete from filter_float_range where id = $1",
-                        self.id
-                    )
-                    .execute(db)
-                    .await?;
-                }
-                (v1, v2) => {
-                    bail!("{v1:?} and {v2:?} are different value types for ranged filter (delete)");
-                }
-            },
-        };
+        query!("delete from filter where id = $1", self.id)
+            .execute(db)
+            .await?;
 
         Ok(())
     }
 }
 
-pub async fn create_filter(
+/// Insert `filter` into its view, returning its ID. The ID of `filter`
+/// itself is ignored.
+pub async fn insert_filter(
+    conn: &mut PgConnection,
+    filter: &models::Filter,
+) -> Result<i32> {
+    struct Qres {
+        id: i32,
+    }
+    let cols = ValueColumns::of(&filter.value)?;
+    let prop_id = if filter.prop_id == models::TITLE_PROP_ID {
+        None
+    } else {
+        Some(filter.prop_id)
+    };
+    let res = query_as!(
+        Qres,
+        "insert into filter (
+            type_id, prop_id, view_id, group_id, value_type_id,
+            bool_value, int_value, int_end, float_value, float_end,
+            date_value, date_end, datetime_value, datetime_end, text_value,
+            case_sensitive, option_id, page_id, user_id, period, amount,
+            enabled
+        ) values (
+            $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15,
+            $16, $17, $18, $19, $20, $21, $22
+        )
+        returning id",
+        filter.r#type.get_int_repr(),
+        prop_id,
+        filter.view_id,
+        filter.group_id,
+        cols.value_type_id,
+        cols.bool_value,
+        cols.int_value,
+        cols.int_end,
+        cols.float_value,
+        cols.float_end,
+        cols.date_value,
+        cols.date_end,
+        cols.datetime_value,
+        cols.datetime_end,
+        cols.text_value,
+        cols.case_sensitive,
+        cols.option_id,
+        cols.page_id,
+        cols.user_id,
+        cols.period,
+        cols.amount,
+        filter.enabled
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    Ok(res.id)
+}
+
+/// The value which new filters of `filter_type` on `prop_id` start out
+/// with. `user_id` is the user creating the filter.
+pub async fn get_default_value(
     db: &PgPool,
     prop_id: i32,
     filter_type: models::FilterType,
     value_type: ValueType,
-) -> Result<models::Filter> {
-    struct Qres {
-        /// The ID of the newly created filter; we'll know the enum variants
-        /// based on which branch we're in, and we are providing default values
-        /// here.
-        id: i32,
-    }
+    user_id: i32,
+) -> Result<models::FilterValue> {
     Ok(match filter_type {
-        models::FilterType::Eq
-        | models::FilterType::Lt
-        | models::FilterType::Gt
-        | models::FilterType::Neq
-        | models::FilterType::IsEmpty => match value_type {
-            ValueType::Int => {
-                let new_id = query_as!(
-                    Qres,
-                    "insert into filter_int
-                    (type_id, prop_id, value)
-                    values
-                    ($1, $2, $3)
-                    returning id
-                    ",
-                    filter_type.get_int_repr(),
-                    prop_id,
-                    0
-                )
-                .fetch_one(db)
-                .await?
-                .id;
-                models::Filter {
-                    id: new_id,
-                    prop_id,
-                    r#type: filter_type,
-                    value: models::FilterValue::Single(Value::Int(0)),
-                }
-            }
-            ValueType::Bool => {
-                let new_id = query_as!(
-                    Qres,
-                    "insert into filter_bool
-                    (type_id, prop_id, value)
-                    values
-                    ($1, $2, $3)
-                    returning id
-                    ",
-                    filter_type.get_int_repr(),
-                    prop_id,
-                    false
-                )
-                .fetch_one(db)
-                .await?
-                .id;
-                models::Filter {
-                    id: new_id,
-                    prop_id,
-                    r#type: filter_type,
-                    value: models::FilterValue::Single(Value::Bool(false)),
-                }
+        models::FilterType::IsWithin => {
+            if !matches!(value_type, ValueType::Date | ValueType::DateTime) {
+                bail!("only dates can be within a relative date");
             }
-            ValueType::Date => {
-                let new_id = query_as!(
-                    Qres,
-                    "insert into filter_date
-                    (type_id, prop_id, value)
-                    values
-                    ($1, $2, $3)
-                    returning id
-                    ",
-                    filter_type.get_int_repr(),
-                    prop_id,
-                    chrono::Local::now().date_naive()
-                )
-                .fetch_one(db)
-                .await?
-                .id;
-                models::Filter {
-                    id: new_id,
-                    prop_id,
-                    r#type: filter_type,
-                    value: models::FilterValue::Single(Value::Date(
-                        chrono::Local::now().date_naive(),
-                    )),
-                }
-            }
-            ValueType::Float => {
-                let new_id = query_as!(
-                    Qres,
-                    "insert into filter_float
-                    (type_id, prop_id, value)
-                    values
-                    ($1, $2, $3)
-                    returning id
-                    ",
-                    filter_type.get_int_repr(),
-                    prop_id,
-                    0.0
-                )
-                .fetch_one(db)
-                .await?
-                .id;
-                models::Filter {
-                    id: new_id,
-                    prop_id,
-                    r#type: filter_type,
-                    value: models::FilterValue::Single(Value::Float(0.0)),
-                }
+            models::FilterValue::Relative {
+                date: models::RelativeDate::ThisWeek,
+                value_type,
             }
-        },
+        }
         models::FilterType::InRng | models::FilterType::NotInRng => {
             match value_type {
                 ValueType::Int => {
-                    let new_id = query_as!(
-                        Qres,
-                        r#"insert into filter_int_range
-                        (type_id, prop_id, start, "end")
-                        values
-                            ($1, $2, $3, $4)
-                        returning id
-                        "#,
-                        filter_type.get_int_repr(),
-                        prop_id,
-                        0,
-                        10
-                    )
-                    .fetch_one(db)
-                    .await?
-                    .id;
-                    models::Filter {
-                        id: new_id,
-                        prop_id,
-                        r#type: filter_type,
-                        value: models::FilterValue::Range(
-                            Value::Int(0),
-                            Value::Int(10),
-                        ),
-                    }
+                    models::FilterValue::Range(Value::Int(0), Value::Int(10))
                 }
+                ValueType::Float => models::FilterValue::Range(
+                    Value::Float(0.0),
+                    Value::Float(10.0),
+                ),
                 ValueType::Date => {
                     let start = (chrono::Local::now()
                         - chrono::Duration::days(10))
                     .date_naive();
                     let end = chrono::Local::now().date_naive();
-                    let new_id = query_as!(
-                        Qres,
-                        r#"insert into filter_date_range
-                        (type_id, prop_id, start, "end")
-                        values
-                            ($1, $2, $3, $4)
-                        returning id
-                        "#,
-                        filter_type.get_int_repr(),
-                        prop_id,
-                        start,
-                        end
+                    models::FilterValue::Range(
+                        Value::Date(start),
+                        Value::Date(end),
                     )
-                    .fetch_one(db)
-                    .await?
-                    .id;
-                    models::Filter {
-                        id: new_id,
-                        prop_id,
-                        r#type: filter_type,
-                        value: models::FilterValue::Range(
-                            Value::Date(start),
-                            Value::Date(end),
-                        ),
-                    }
                 }
-                ValueType::Float => {
-                    let new_id = query_as!(
-                        Qres,
-                        r#"insert into filter_float_range
-                        (type_id, prop_id, start, "end")
-                        values
-                            ($1, $2, $3, $4)
-                        returning id
-                        "#,
-                        filter_type.get_int_repr(),
-                        prop_id,
-                        0.0,
-                        10.0
+                ValueType::DateTime => {
+                    let end = chrono::Utc::now().with_timezone(&Tz::UTC);
+                    let start = end - chrono::Duration::days(10);
+                    models::FilterValue::Range(
+                        Value::DateTime(start),
+                        Value::DateTime(end),
                     )
-                    .fetch_one(db)
-                    .await?
-                    .id;
-                    models::Filter {
-                        id: new_id,
-                        prop_id,
-                        r#type: filter_type,
-                        value: models::FilterValue::Range(
-                            Value::Float(0.0),
-                            Value::Float(10.0),
-                        ),
-                    }
                 }
                 ValueType::Bool => {
-                    panic!("boolean range filter does not exist")
+                    bail!("boolean range filter does not exist")
+                }
+                ValueType::Select => {
+                    bail!("select range filter does not exist")
+                }
+                ValueType::Relation => {
+                    bail!("relation range filter does not exist")
+                }
+                ValueType::CreatedBy | ValueType::LastEditedBy => {
+                    bail!("user range filter does not exist")
+                }
+                ValueType::CreatedTime | ValueType::LastEditedTime => {
+                    bail!("timestamp props are filtered as date & time props")
+                }
+                ValueType::Str | ValueType::MultiStr => {
+                    bail!("text range filter does not exist")
+                }
+                ValueType::Files => bail!("file filters are not supported"),
+                ValueType::Formula | ValueType::Rollup => {
+                    bail!("computed props are filtered by their result type")
                 }
             }
         }
+        _ => match value_type {
+            ValueType::Int => models::FilterValue::Single(Value::Int(0)),
+            ValueType::Bool => models::FilterValue::Single(Value::Bool(false)),
+            ValueType::Float => models::FilterValue::Single(Value::Float(0.0)),
+            ValueType::Date => models::FilterValue::Single(Value::Date(
+                chrono::Local::now().date_naive(),
+            )),
+            ValueType::DateTime => models::FilterValue::Single(
+                Value::DateTime(chrono::Utc::now().with_timezone(&Tz::UTC)),
+            ),
+            ValueType::Select => {
+                let options = SelectOption::list(
+                    db,
+                    &crate::db_ops::ListSelectOptionQuery { prop_id },
+                )
+                .await?;
+                match options.into_iter().next() {
+                    Some(option) => {
+                        models::FilterValue::Single(Value::Select(option))
+                    }
+                    None => bail!("select prop {prop_id} has no options"),
+                }
+            }
+            ValueType::Relation => {
+                let target =
+                    crate::db_ops::get_relation_target(db, prop_id).await?;
+                let candidates =
+                    crate::db_ops::list_related_page_candidates(db, target)
+                        .await?;
+                match candidates.into_iter().next() {
+                    Some(page) => {
+                        models::FilterValue::Single(Value::Relation(vec![page]))
+                    }
+                    None => bail!("collection {target} has no pages to link"),
+                }
+            }
+            ValueType::CreatedBy | ValueType::LastEditedBy => {
+                let users = User::list(db, &()).await?;
+                // New user filters start out matching whoever is creating
+                // them.
+                match users.iter().find(|u| u.id == user_id).or(users.first()) {
+                    Some(user) => models::FilterValue::Single(
+                        Value::CreatedBy(user.clone()),
+                    ),
+                    None => bail!("there are no users to filter by"),
+                }
+            }
+            ValueType::Str => models::FilterValue::Text {
+                value: "".into(),
+                case_sensitive: false,
+            },
+            ValueType::CreatedTime | ValueType::LastEditedTime => {
+                bail!("timestamp props are filtered as date & time props")
+            }
+            ValueType::MultiStr => bail!("tag filters are not supported"),
+            ValueType::Files => bail!("file filters are not supported"),
+            ValueType::Formula | ValueType::Rollup => {
+                bail!("computed props are filtered by their result type")
+            }
+        },
     })
 }
 
-pub async fn does_collection_have_capacity_for_additional_filters(
+/// Check that `group_id`, if there is one, is a group of `view_id`.
+async fn check_group_view(
+    db: &PgPool,
+    view_id: i32,
+    group_id: Option<i32>,
+) -> Result<()> {
+    if let Some(group_id) = group_id {
+        let group = get_filter_group(db, group_id).await?;
+        if group.view_id != view_id {
+            bail!("filter group {group_id} is not in view {view_id}");
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn create_filter(
     db: &PgPool,
-    collection_id: i32,
+    view_id: i32,
+    prop_id: i32,
+    filter_type: models::FilterType,
+    value_type: ValueType,
+    group_id: Option<i32>,
+    user_id: i32,
+) -> Result<models::Filter> {
+    struct Qres {
+        is_in_view: Option<bool>,
+    }
+    let res = query_as!(
+        Qres,
+        "select exists(
+            select 1 from property p
+            join view v on v.collection_id = p.collection_id
+            where p.id = $1 and v.id = $2
+        ) is_in_view",
+        prop_id,
+        view_id
+    )
+    .fetch_one(db)
+    .await?;
+    if !res.is_in_view.unwrap_or_default() {
+        bail!("prop {prop_id} is not in the collection of view {view_id}");
+    }
+    check_group_view(db, view_id, group_id).await?;
+    let mut filter = models::Filter {
+        id: 0,
+        view_id,
+        prop_id,
+        group_id,
+        r#type: filter_type,
+        value: get_default_value(db, prop_id, filter_type, value_type, user_id)
+            .await?,
+        enabled: true,
+    };
+    filter.id = insert_filter(&mut *db.acquire().await?, &filter).await?;
+
+    Ok(filter)
+}
+
+/// Store ad-hoc filters in the root group of their view, in place of all of
+/// its filters and groups if `adhoc.replace` is set. Views have only one
+/// title filter, so a title filter replaces the one that the view has.
+/// `adhoc.sort` becomes the view's sort, in the same transaction.
+pub async fn save_adhoc_filters(
+    db: &PgPool,
+    view_id: i32,
+    adhoc: &models::AdHocFilters,
+) -> Result<()> {
+    let title_filter_count = adhoc
+        .filters
+        .iter()
+        .filter(|f| f.prop_id == models::TITLE_PROP_ID)
+        .count();
+    if title_filter_count > 1 {
+        bail!("a view can only have one title filter");
+    }
+    let mut tx = db.begin().await?;
+    if adhoc.replace {
+        query!("delete from filter where view_id = $1", view_id)
+            .execute(&mut *tx)
+            .await?;
+        query!(
+            "delete from filter_group
+            where view_id = $1 and parent_id is not null",
+            view_id
+        )
+        .execute(&mut *tx)
+        .await?;
+    } else if title_filter_count > 0 {
+        query!(
+            "delete from filter where view_id = $1 and prop_id is null",
+            view_id
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+    for filter in &adhoc.filters {
+        if filter.view_id != view_id {
+            bail!("filter is not in view {view_id}");
+        }
+        insert_filter(&mut tx, filter).await?;
+    }
+    if let Some(sort) = &adhoc.sort {
+        if sort.view_id != view_id {
+            bail!("sort is not for view {view_id}");
+        }
+        sort.save_in(&mut tx).await?;
+    }
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Whether there is a prop, or the page title, which doesn't have a filter
+/// in the view yet. `props` are the props of the view's collection, with
+/// the types they are filtered as; only those which can be filtered count.
+/// See [models::FilterKind::exists_for].
+pub async fn does_view_have_capacity_for_additional_filters(
+    db: &PgPool,
+    view_id: i32,
+    props: &[Prop],
 ) -> Result<bool> {
     struct Qres {
-        cnt: Option<i64>,
+        has_capacity: Option<bool>,
+    }
+    let filterable_prop_ids: Vec<i32> = props
+        .iter()
+        .filter(|p| models::FilterKind::exists_for(p.type_id))
+        .map(|p| p.id)
+        .collect();
+    let res = query_as!(
+        Qres,
+        "select
+            exists(
+                select 1 from unnest($2::int[]) as p(id)
+                where not exists(
+                    select 1 from filter f
+                    where f.prop_id = p.id and f.view_id = $1
+                )
+            )
+            or not exists(
+                select 1 from filter
+                where view_id = $1 and prop_id is null
+            ) has_capacity",
+        view_id,
+        &filterable_prop_ids
+    )
+    .fetch_one(db)
+    .await?;
+
+    Ok(res.has_capacity.unwrap_or_default())
+}
+
+/// Create a filter on the page titles of a view. See
+/// [models::TITLE_PROP_ID].
+pub async fn create_title_filter(
+    db: &PgPool,
+    view_id: i32,
+    filter_type: models::FilterType,
+    group_id: Option<i32>,
+) -> Result<models::Filter> {
+    check_group_view(db, view_id, group_id).await?;
+    let mut filter = models::Filter {
+        id: 0,
+        view_id,
+        prop_id: models::TITLE_PROP_ID,
+        group_id,
+        r#type: filter_type,
+        value: models::FilterValue::Text {
+            value: "".into(),
+            case_sensitive: false,
+        },
+        enabled: true,
+    };
+    filter.id = insert_filter(&mut *db.acquire().await?, &filter).await?;
+
+    Ok(filter)
+}
+
+/// The collection of a filter; title filters have no prop to look it up
+/// through.
+pub async fn get_filter_collection_id(
+    db: &PgPool,
+    filter_id: i32,
+) -> Result<i32> {
+    struct Qres {
+        collection_id: i32,
     }
     let res = query_as!(
         Qres,
-        "select count(1) cnt from property p
-        left join filter_bool fb on p.id = fb.prop_id
-        left join filter_int fi on p.id = fi.prop_id
-        left join filter_int_range fri on p.id = fri.prop_id
-        left join filter_float ffl on p.id = ffl.prop_id
-        left join filter_float_range fflr on p.id = fflr.prop_id
-        left join filter_date fd on p.id = fd.prop_id
-        left join filter_date_range fdr on p.id = fdr.prop_id
+        "select v.collection_id
+        from filter f join view v on v.id = f.view_id
+        where f.id = $1",
+        filter_id
+    )
+    .fetch_one(db)
+    .await?;
+
+    Ok(res.collection_id)
+}
+
+/// Check that `pattern` is a regular expression which Postgres accepts, so
+/// that a bad one can't break the page list of a whole collection.
+pub async fn validate_regex(db: &PgPool, pattern: &str) -> Result<()> {
+    if let Err(e) = query!("select '' ~ $1 matches", pattern)
+        .fetch_one(db)
+        .await
+    {
+        match e {
+            sqlx::Error::Database(e) => {
+                bail!("{pattern} is not a valid regular expression: {e}")
+            }
+            e => return Err(e.into()),
+        }
+    }
+
+    Ok(())
+}
+
+/// Like [models::Filter::list], as part of a larger transaction.
+pub async fn list_filters(
+    conn: &mut PgConnection,
+    query: &ListFilterQuery,
+) -> Result<Vec<models::Filter>> {
+    query_as!(
+        QresFilter,
+        r#"select
+            f.id, f.view_id, f.type_id, f.prop_id, f.group_id,
+            f.value_type_id,
+            f.bool_value, f.int_value, f.int_end, f.float_value,
+            f.float_end, f.date_value, f.date_end, f.datetime_value,
+            f.datetime_end, f.text_value, f.case_sensitive, f.option_id,
+            po.name as "option_name?", po.color as "option_color?",
+            po."order" as "option_order?",
+            f.page_id, t.collection_id as "page_collection_id?",
+            t.title as "page_title?",
+            f.user_id, u.username as "username?", u.email as "email?",
+            f.period, f.amount, f.enabled
+        from filter f
+        left join property_option po on po.id = f.option_id
+        left join page t on t.id = f.page_id
+        left join users u on u.id = f.user_id
         where
-            p.collection_id = $1
-            and fb.id is null
-            and fi.id is null
-            and fri.id is null
-            and ffl.id is null
-            and fflr.id is null
-            and fd.id is null
-            and fdr.id is null
-        ",
-        collection_id
+            ($1::int is null or f.view_id = $1)
+            and ($2::int is null or f.prop_id = $2)
+        order by f.id"#,
+        query.view_id,
+        query.prop_id
+    )
+    .fetch_all(&mut *conn)
+    .await?
+    .into_iter()
+    .map(QresFilter::into_filter)
+    .collect()
+}
+
+/// Delete every filter on a prop. Needed when the type of a prop's values
+/// changes, since the filters hold values of the old type. This is usually
+/// one step of a larger change, so the caller brings the transaction.
+pub async fn delete_filters_for_prop(
+    conn: &mut PgConnection,
+    prop_id: i32,
+) -> Result<()> {
+    query!("delete from filter where prop_id = $1", prop_id)
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(())
+}
+
+struct QresGroup {
+    id: i32,
+    view_id: i32,
+    parent_id: Option<i32>,
+    conjunction: String,
+}
+impl QresGroup {
+    fn into_group(self) -> Result<models::FilterGroup> {
+        Ok(models::FilterGroup {
+            id: self.id,
+            view_id: self.view_id,
+            parent_id: self.parent_id,
+            conjunction: models::Conjunction::from_name(&self.conjunction)?,
+            filters: vec![],
+            groups: vec![],
+        })
+    }
+}
+
+/// The root filter group of the view, which is created along with the
+/// view; see [crate::view::db_ops::create_view].
+pub async fn get_root_group(
+    db: &PgPool,
+    view_id: i32,
+) -> Result<models::FilterGroup> {
+    query_as!(
+        QresGroup,
+        "select id, view_id, parent_id, conjunction
+        from filter_group
+        where view_id = $1 and parent_id is null",
+        view_id
+    )
+    .fetch_one(db)
+    .await?
+    .into_group()
+}
+
+/// A filter group, without its filters or subgroups.
+pub async fn get_filter_group(
+    db: &PgPool,
+    id: i32,
+) -> Result<models::FilterGroup> {
+    query_as!(
+        QresGroup,
+        "select id, view_id, parent_id, conjunction
+        from filter_group
+        where id = $1",
+        id
     )
     .fetch_one(db)
+    .await?
+    .into_group()
+}
+
+/// Every filter in the view, arranged into its groups.
+pub async fn get_filter_tree(
+    db: &PgPool,
+    view_id: i32,
+) -> Result<models::FilterGroup> {
+    let root = get_root_group(db, view_id).await?;
+    let groups = query_as!(
+        QresGroup,
+        "select id, view_id, parent_id, conjunction
+        from filter_group
+        where view_id = $1 and parent_id is not null",
+        view_id
+    )
+    .fetch_all(db)
+    .await?
+    .into_iter()
+    .map(QresGroup::into_group)
+    .collect::<Result<Vec<_>>>()?;
+    let filters = models::Filter::list(
+        db,
+        &ListFilterQuery {
+            view_id: Some(view_id),
+            prop_id: None,
+        },
+    )
     .await?;
 
-    let count = res
-        .cnt
-        .expect("idk... why would count(1) not return a count? WTF");
+    Ok(models::FilterGroup::build_tree(root, groups, filters))
+}
+
+/// Create an empty group inside of `parent_id`, returning its ID.
+pub async fn create_filter_group(db: &PgPool, parent_id: i32) -> Result<i32> {
+    struct Qres {
+        id: i32,
+    }
+    Ok(query_as!(
+        Qres,
+        "insert into filter_group (view_id, parent_id)
+        select view_id, id from filter_group where id = $1
+        returning id",
+        parent_id
+    )
+    .fetch_one(db)
+    .await?
+    .id)
+}
+
+pub async fn set_conjunction(
+    db: &PgPool,
+    group_id: i32,
+    conjunction: models::Conjunction,
+) -> Result<()> {
+    query!(
+        "update filter_group set conjunction = $1 where id = $2",
+        conjunction.get_name(),
+        group_id
+    )
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn set_filter_enabled(
+    db: &PgPool,
+    filter_id: i32,
+    enabled: bool,
+) -> Result<()> {
+    query!(
+        "update filter set enabled = $1 where id = $2",
+        enabled,
+        filter_id
+    )
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+/// Delete a group, along with its filters and subgroups. The root group
+/// cannot be deleted.
+pub async fn delete_filter_group(db: &PgPool, group_id: i32) -> Result<()> {
+    query!(
+        "delete from filter_group where id = $1 and parent_id is not null",
+        group_id
+    )
+    .execute(db)
+    .await?;
 
-    Ok(count > 0)
+    Ok(())
 }
@@ -1,7 +1,12 @@
 use crate::{
-    models::{Value, ValueType},
+    models::{Prop, SortType, Value, ValueType, ViewSort},
     routes::Route,
 };
+use anyhow::{bail, Result};
+use chrono::{Datelike, Months, NaiveDate, TimeZone, Weekday};
+use chrono_tz::Tz;
+use sqlx::{Postgres, QueryBuilder};
+use std::collections::HashMap;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FilterType {
@@ -12,11 +17,21 @@ pub enum FilterType {
     InRng,
     NotInRng,
     IsEmpty,
+    Contains,
+    DoesNotContain,
+    StartsWith,
+    EndsWith,
+    /// A POSIX regular expression, as understood by Postgres.
+    Matches,
+    /// Dates inside a [RelativeDate].
+    IsWithin,
 }
 
-impl FilterType {
-    pub fn from_int(int: i32) -> Self {
-        match int {
+impl TryFrom<i32> for FilterType {
+    type Error = anyhow::Error;
+
+    fn try_from(int: i32) -> Result<Self> {
+        Ok(match int {
             1 => Self::Eq,
             2 => Self::Neq,
             3 => Self::Gt,
@@ -24,7 +39,23 @@ impl FilterType {
             5 => Self::InRng,
             6 => Self::NotInRng,
             7 => Self::IsEmpty,
-            _ => panic!("{int} is not a valid filter type"),
+            8 => Self::Contains,
+            9 => Self::DoesNotContain,
+            10 => Self::StartsWith,
+            11 => Self::EndsWith,
+            12 => Self::Matches,
+            13 => Self::IsWithin,
+            _ => bail!("{int} is not a valid filter type"),
+        })
+    }
+}
+
+impl FilterType {
+    /// Use [FilterType::try_from] for IDs which don't come from the database.
+    pub fn from_int(int: i32) -> Self {
+        match Self::try_from(int) {
+            Ok(ty) => ty,
+            Err(e) => panic!("{e}"),
         }
     }
     pub fn get_int_repr(&self) -> i32 {
@@ -36,6 +67,12 @@ impl FilterType {
             Self::InRng => 5,
             Self::NotInRng => 6,
             Self::IsEmpty => 7,
+            Self::Contains => 8,
+            Self::DoesNotContain => 9,
+            Self::StartsWith => 10,
+            Self::EndsWith => 11,
+            Self::Matches => 12,
+            Self::IsWithin => 13,
         }
     }
     pub fn get_supported_filter_types(prop_type: ValueType) -> Vec<Self> {
@@ -61,15 +98,43 @@ impl FilterType {
                 FilterType::NotInRng,
                 FilterType::IsEmpty,
             ],
-            ValueType::Date => vec![
+            ValueType::Date | ValueType::DateTime => vec![
                 FilterType::Eq,
                 FilterType::Gt,
                 FilterType::Neq,
                 FilterType::Lt,
                 FilterType::InRng,
                 FilterType::NotInRng,
+                FilterType::IsWithin,
                 FilterType::IsEmpty,
             ],
+            ValueType::Select => {
+                vec![FilterType::Eq, FilterType::Neq, FilterType::IsEmpty]
+            }
+            ValueType::Relation => {
+                vec![FilterType::Contains, FilterType::IsEmpty]
+            }
+            ValueType::Str => vec![
+                FilterType::Contains,
+                FilterType::DoesNotContain,
+                FilterType::Eq,
+                FilterType::Neq,
+                FilterType::StartsWith,
+                FilterType::EndsWith,
+                FilterType::Matches,
+                FilterType::IsEmpty,
+            ],
+            // Tag and file filters are not supported yet.
+            ValueType::MultiStr | ValueType::Files => vec![],
+            ValueType::CreatedBy | ValueType::LastEditedBy => {
+                vec![FilterType::Eq, FilterType::Neq, FilterType::IsEmpty]
+            }
+            // Formulas and rollups are filtered like props of their result
+            // type, and timestamps are filtered like date & time props.
+            ValueType::Formula
+            | ValueType::Rollup
+            | ValueType::CreatedTime
+            | ValueType::LastEditedTime => vec![],
         }
     }
     pub fn get_display_name(&self) -> &'static str {
@@ -81,59 +146,43 @@ impl FilterType {
             FilterType::InRng => "Is Inside Range",
             FilterType::NotInRng => "Is Not Inside Range",
             FilterType::IsEmpty => "Is Empty",
+            FilterType::Contains => "Contains",
+            FilterType::DoesNotContain => "Does not Contain",
+            FilterType::StartsWith => "Starts With",
+            FilterType::EndsWith => "Ends With",
+            FilterType::Matches => "Matches Regex",
+            FilterType::IsWithin => "Is Within",
         }
     }
+    /// Whether filters of type `other` store the same kind of value as
+    /// this one, and can be switched to in the same form.
+    pub fn has_same_value_kind(&self, other: &Self) -> bool {
+        let is_ranged = |ty: &Self| matches!(ty, Self::InRng | Self::NotInRng);
+        let is_relative = |ty: &Self| matches!(ty, Self::IsWithin);
+        is_ranged(self) == is_ranged(other)
+            && is_relative(self) == is_relative(other)
+    }
+    /// Panics if this type of filter is not supported for `value_type`; see
+    /// [FilterKind::new].
     pub fn get_form_route(
         &self,
         filter_id: i32,
         value_type: ValueType,
     ) -> Route {
-        match self {
-            FilterType::Eq
-            | FilterType::Gt
-            | FilterType::Lt
-            | FilterType::Neq
-            | FilterType::IsEmpty => match value_type {
-                ValueType::Int => Route::FilterInt(Some(filter_id)),
-                ValueType::Bool => Route::FilterBool(Some(filter_id)),
-                ValueType::Date => Route::FilterDate(Some(filter_id)),
-                ValueType::Float => Route::FilterFloat(Some(filter_id)),
-            },
-            FilterType::InRng | FilterType::NotInRng => match value_type {
-                ValueType::Float => Route::FilterFloatRng(Some(filter_id)),
-                ValueType::Date => Route::FilterDateRng(Some(filter_id)),
-                ValueType::Bool => {
-                    panic!("boolean filters are not supported")
-                }
-                ValueType::Int => Route::FilterIntRng(Some(filter_id)),
-            },
-        }
+        let kind = FilterKind::new(*self, value_type)
+            .unwrap_or_else(|e| panic!("{e}"));
+        Route::Filter(Some((kind, filter_id)))
     }
+    /// Panics if this type of filter is not supported for `value_type`; see
+    /// [FilterKind::new].
     pub fn get_chip_route(
         &self,
         filter_id: i32,
         value_type: ValueType,
     ) -> Route {
-        match self {
-            FilterType::Eq
-            | FilterType::Gt
-            | FilterType::Lt
-            | FilterType::Neq
-            | FilterType::IsEmpty => match value_type {
-                ValueType::Int => Route::FilterIntChip(Some(filter_id)),
-                ValueType::Bool => Route::FilterBoolChip(Some(filter_id)),
-                ValueType::Date => Route::FilterDateChip(Some(filter_id)),
-                ValueType::Float => Route::FilterFloatChip(Some(filter_id)),
-            },
-            FilterType::InRng | FilterType::NotInRng => match value_type {
-                ValueType::Float => Route::FilterFloatRngChip(Some(filter_id)),
-                ValueType::Date => Route::FilterDateRngChip(Some(filter_id)),
-                ValueType::Bool => {
-                    panic!("boolean filters are not supported")
-                }
-                ValueType::Int => Route::FilterIntRngChip(Some(filter_id)),
-            },
-        }
+        let kind = FilterKind::new(*self, value_type)
+            .unwrap_or_else(|e| panic!("{e}"));
+        Route::FilterChip(Some((kind, filter_id)))
     }
     pub fn get_operator_str(&self) -> &'static str {
         match self {
@@ -146,6 +195,157 @@ impl FilterType {
             ),
         }
     }
+    /// How this type of filter is written in an [AdHocQuery], like the
+    /// `gt` in `sprint.gt.3`.
+    pub fn get_slug(&self) -> &'static str {
+        match self {
+            Self::Eq => "eq",
+            Self::Neq => "neq",
+            Self::Gt => "gt",
+            Self::Lt => "lt",
+            Self::InRng => "in",
+            Self::NotInRng => "notin",
+            Self::IsEmpty => "empty",
+            Self::Contains => "contains",
+            Self::DoesNotContain => "notcontains",
+            Self::StartsWith => "startswith",
+            Self::EndsWith => "endswith",
+            Self::Matches => "matches",
+            Self::IsWithin => "within",
+        }
+    }
+    pub fn from_slug(slug: &str) -> Result<Self> {
+        Ok(match slug {
+            "eq" => Self::Eq,
+            "neq" => Self::Neq,
+            "gt" => Self::Gt,
+            "lt" => Self::Lt,
+            "in" => Self::InRng,
+            "notin" => Self::NotInRng,
+            "empty" => Self::IsEmpty,
+            "contains" => Self::Contains,
+            "notcontains" => Self::DoesNotContain,
+            "startswith" => Self::StartsWith,
+            "endswith" => Self::EndsWith,
+            "matches" => Self::Matches,
+            "within" => Self::IsWithin,
+            _ => bail!("{slug} is not a type of filter"),
+        })
+    }
+}
+
+/// The kind of value which a filter holds, which decides the form that
+/// edits it. Filters are routed by kind, like `/filter/int-rng/:id`, and
+/// the kind is written as that slug.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterKind {
+    Single(ValueType),
+    Range(ValueType),
+    RelativeDate,
+}
+
+impl FilterKind {
+    /// The kind of filter for `filter_type` on props of `value_type`. Both
+    /// user types are filtered the same way, so they share a kind.
+    pub fn new(filter_type: FilterType, value_type: ValueType) -> Result<Self> {
+        if !FilterType::get_supported_filter_types(value_type)
+            .contains(&filter_type)
+        {
+            bail!(
+                "{filter_type} filters are not supported for {} props",
+                value_type.get_display_name()
+            );
+        }
+        Ok(match filter_type {
+            FilterType::IsWithin => Self::RelativeDate,
+            FilterType::InRng | FilterType::NotInRng => Self::Range(value_type),
+            _ => Self::Single(match value_type {
+                ValueType::LastEditedBy => ValueType::CreatedBy,
+                value_type => value_type,
+            }),
+        })
+    }
+    pub fn from_slug(slug: &str) -> Result<Self> {
+        if slug == "relative-date" {
+            return Ok(Self::RelativeDate);
+        }
+        let (value_slug, is_range) = match slug.strip_suffix("-rng") {
+            Some(value_slug) => (value_slug, true),
+            None => (slug, false),
+        };
+        let value_type = match value_slug {
+            "bool" => ValueType::Bool,
+            "int" => ValueType::Int,
+            "float" => ValueType::Float,
+            "date" => ValueType::Date,
+            "datetime" => ValueType::DateTime,
+            "select" => ValueType::Select,
+            "relation" => ValueType::Relation,
+            "user" => ValueType::CreatedBy,
+            "str" => ValueType::Str,
+            _ => bail!("{slug} is not a kind of filter"),
+        };
+        let kind = if is_range {
+            Self::Range(value_type)
+        } else {
+            Self::Single(value_type)
+        };
+        if !FilterType::get_supported_filter_types(value_type)
+            .iter()
+            .any(|ty| Self::new(*ty, value_type).ok() == Some(kind))
+        {
+            bail!("{slug} is not a kind of filter");
+        }
+
+        Ok(kind)
+    }
+    /// Whether there is any kind of filter for props of `value_type`.
+    /// Formulas, rollups, and timestamps are filtered as some other type,
+    /// which is the one to ask about; props of those types themselves, like
+    /// formulas which haven't been set up, can't be filtered.
+    pub fn exists_for(value_type: ValueType) -> bool {
+        FilterType::get_supported_filter_types(value_type)
+            .into_iter()
+            .any(|ty| Self::new(ty, value_type).is_ok())
+    }
+    /// The type of filter which is created for this kind when no type is
+    /// asked for.
+    pub fn get_default_type(&self) -> FilterType {
+        match self {
+            Self::Single(ValueType::Str | ValueType::Relation) => {
+                FilterType::Contains
+            }
+            Self::Single(_) => FilterType::Eq,
+            Self::Range(_) => FilterType::InRng,
+            Self::RelativeDate => FilterType::IsWithin,
+        }
+    }
+}
+
+impl std::fmt::Display for FilterKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (value_type, suffix) = match self {
+            Self::RelativeDate => return write!(f, "relative-date"),
+            Self::Single(value_type) => (value_type, ""),
+            Self::Range(value_type) => (value_type, "-rng"),
+        };
+        let value_slug = match value_type {
+            ValueType::Bool => "bool",
+            ValueType::Int => "int",
+            ValueType::Float => "float",
+            ValueType::Date => "date",
+            ValueType::DateTime => "datetime",
+            ValueType::Select => "select",
+            ValueType::Relation => "relation",
+            ValueType::CreatedBy | ValueType::LastEditedBy => "user",
+            ValueType::Str => "str",
+            value_type => panic!(
+                "{} props cannot be filtered",
+                value_type.get_display_name()
+            ),
+        };
+        write!(f, "{value_slug}{suffix}")
+    }
 }
 
 impl std::fmt::Display for FilterType {
@@ -161,23 +361,1234 @@ impl std::fmt::Display for FilterType {
                 FilterType::InRng => "Is Inside Range",
                 FilterType::NotInRng => "Is Not Inside Range",
                 FilterType::IsEmpty => "Is Empty",
+                FilterType::Contains => "Contains",
+                FilterType::DoesNotContain => "Does not Contain",
+                FilterType::StartsWith => "Starts With",
+                FilterType::EndsWith => "Ends With",
+                FilterType::Matches => "Matches Regex",
+                FilterType::IsWithin => "Is Within",
             }
         )
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum FilterValue {
     /// For typical filters, like Eq, Neq, Gt, Lt
     Single(Value),
     /// For filters with left and right values like InRng, NotInRng
     Range(Value, Value),
+    /// For text filters, which ignore case unless `case_sensitive` is set.
+    Text { value: String, case_sensitive: bool },
+    /// For IsWithin filters on date and date & time props. `value_type` is
+    /// [ValueType::Date] or [ValueType::DateTime], which are compared
+    /// differently.
+    Relative {
+        date: RelativeDate,
+        value_type: ValueType,
+    },
 }
 
-#[derive(Debug)]
+/// The page title is not a prop, but it can be filtered like a text prop.
+/// Filters on the title use this in place of a prop ID; prop IDs start at 1.
+pub const TITLE_PROP_ID: i32 = 0;
+
+/// Stands in for the prop of filters on the page title, so that they can be
+/// shown like other filters.
+pub fn get_title_prop(collection_id: i32) -> Prop {
+    Prop {
+        id: TITLE_PROP_ID,
+        type_id: ValueType::Str,
+        collection_id,
+        name: "Title".into(),
+        order: 0,
+        number_format: Default::default(),
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct Filter {
     pub id: i32,
+    pub view_id: i32,
+    /// [TITLE_PROP_ID] for filters on the page title.
     pub prop_id: i32,
+    /// `None` for filters in the root [FilterGroup].
+    pub group_id: Option<i32>,
     pub r#type: FilterType,
     pub value: FilterValue,
+    /// Disabled filters are kept, but left out of [FilterGroup::push_sql].
+    pub enabled: bool,
+}
+
+impl Filter {
+    /// See [Value::localize].
+    pub fn localize(&mut self, tz: Tz) {
+        match &mut self.value {
+            FilterValue::Single(val) => val.localize(tz),
+            FilterValue::Range(start, end) => {
+                start.localize(tz);
+                end.localize(tz);
+            }
+            FilterValue::Text { .. } | FilterValue::Relative { .. } => {}
+        }
+    }
+    pub fn get_kind(&self) -> FilterKind {
+        match &self.value {
+            FilterValue::Single(Value::LastEditedBy(_)) => {
+                FilterKind::Single(ValueType::CreatedBy)
+            }
+            FilterValue::Single(val) => {
+                FilterKind::Single(ValueType::of_value(val))
+            }
+            FilterValue::Range(start, _) => {
+                FilterKind::Range(ValueType::of_value(start))
+            }
+            FilterValue::Text { .. } => FilterKind::Single(ValueType::Str),
+            FilterValue::Relative { .. } => FilterKind::RelativeDate,
+        }
+    }
+    /// The column which this filter applies to in
+    /// [crate::db_ops::list_pages].
+    fn get_column(&self) -> String {
+        if self.prop_id == TITLE_PROP_ID {
+            "page.title".into()
+        } else {
+            format!("prop{}.value", self.prop_id)
+        }
+    }
+}
+
+impl Filter {
+    /// Push the SQL condition for this filter onto `query`, against the
+    /// `prop{id}` aliases of [crate::db_ops::list_pages]. Filter values are
+    /// always bound as parameters; see [Value::push_bind]. Relative dates
+    /// are resolved against `ctx`.
+    pub fn push_sql(
+        &self,
+        query: &mut QueryBuilder<'_, Postgres>,
+        ctx: &RelativeDateCtx,
+    ) {
+        match &self.value {
+            FilterValue::Text {
+                value,
+                case_sensitive,
+            } => return self.push_text_sql(query, value, *case_sensitive),
+            FilterValue::Relative { date, value_type } => {
+                return self.push_relative_sql(query, *date, *value_type, ctx)
+            }
+            FilterValue::Single(_) | FilterValue::Range(..) => {}
+        }
+        let prop_id = self.prop_id;
+        match (self.r#type, &self.value) {
+            (
+                FilterType::Eq
+                | FilterType::Neq
+                | FilterType::Lt
+                | FilterType::Gt,
+                FilterValue::Single(val),
+            ) => {
+                let operator = self.r#type.get_operator_str();
+                query.push(format!("prop{prop_id}.value {operator} "));
+                val.push_bind(query);
+            }
+            (FilterType::IsEmpty, _) => {
+                query.push(format!("prop{prop_id}.value is null"));
+            }
+            (FilterType::Contains, FilterValue::Single(val)) => {
+                query.push(format!("prop{prop_id}.value @> "));
+                val.push_bind(query);
+            }
+            (FilterType::InRng, FilterValue::Range(v1, v2)) => {
+                query.push(format!("(prop{prop_id}.value > "));
+                v1.push_bind(query);
+                query.push(format!(" and prop{prop_id}.value < "));
+                v2.push_bind(query);
+                query.push(")");
+            }
+            (FilterType::NotInRng, FilterValue::Range(v1, v2)) => {
+                query.push(format!("(prop{prop_id}.value < "));
+                v1.push_bind(query);
+                query.push(format!(" or prop{prop_id}.value > "));
+                v2.push_bind(query);
+                query.push(")");
+            }
+            (ty, value) => panic!("{ty} filters cannot have {value:?}"),
+        }
+    }
+    /// Dates are compared directly. Datetimes are compared against midnight
+    /// in the user's timezone, so that "today" starts when their day does.
+    fn push_relative_sql(
+        &self,
+        query: &mut QueryBuilder<'_, Postgres>,
+        date: RelativeDate,
+        value_type: ValueType,
+        ctx: &RelativeDateCtx,
+    ) {
+        let column = self.get_column();
+        let push_bound = |query: &mut QueryBuilder<'_, Postgres>, date| {
+            if value_type == ValueType::Date {
+                query.push_bind(date);
+            } else {
+                query.push_bind(ctx.get_start_of_day(date));
+            }
+        };
+        let (start, end) = date.resolve(ctx.today, ctx.week_start);
+        query.push("(");
+        match start {
+            Some(start) => {
+                query.push(format!("{column} >= "));
+                push_bound(query, start);
+            }
+            None => {
+                query.push(format!("{column} is not null"));
+            }
+        }
+        if let Some(end) = end {
+            query.push(format!(" and {column} < "));
+            push_bound(query, end);
+        }
+        query.push(")");
+    }
+    /// Case-insensitive filters compare both sides in lower case, except for
+    /// regular expressions, which have their own case-insensitive operator.
+    /// `strpos` is used rather than `like`, so that `%` and `_` in the value
+    /// are matched literally.
+    fn push_text_sql(
+        &self,
+        query: &mut QueryBuilder<'_, Postgres>,
+        value: &str,
+        case_sensitive: bool,
+    ) {
+        let column = self.get_column();
+        let (column, bind_prefix, bind_suffix) = if case_sensitive {
+            (column, "", "")
+        } else {
+            (format!("lower({column})"), "lower(", ")")
+        };
+        let push_value = |query: &mut QueryBuilder<'_, Postgres>| {
+            query.push(bind_prefix);
+            query.push_bind(value.to_string());
+            query.push(bind_suffix);
+        };
+        match self.r#type {
+            FilterType::Eq => {
+                query.push(format!("{column} = "));
+                push_value(query);
+            }
+            FilterType::Neq => {
+                query.push(format!("{column} != "));
+                push_value(query);
+            }
+            FilterType::Contains => {
+                query.push(format!("strpos({column}, "));
+                push_value(query);
+                query.push(") > 0");
+            }
+            // Pages without a value don't contain anything.
+            FilterType::DoesNotContain => {
+                query.push(format!("coalesce(strpos({column}, "));
+                push_value(query);
+                query.push("), 0) = 0");
+            }
+            FilterType::StartsWith => {
+                query.push(format!("starts_with({column}, "));
+                push_value(query);
+                query.push(")");
+            }
+            FilterType::EndsWith => {
+                query.push(format!("right({column}, length("));
+                push_value(query);
+                query.push(")) = ");
+                push_value(query);
+            }
+            FilterType::Matches => {
+                let column = self.get_column();
+                let operator = if case_sensitive { "~" } else { "~*" };
+                query.push(format!("{column} {operator} "));
+                query.push_bind(value.to_string());
+            }
+            FilterType::IsEmpty => {
+                let column = self.get_column();
+                query.push(format!("coalesce({column}, '') = ''"));
+            }
+            ty => panic!("{ty} filters cannot have text values"),
+        }
+    }
+}
+
+/// How the members of a [FilterGroup] are combined. Stored by name in
+/// `filter_group.conjunction`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Conjunction {
+    And,
+    Or,
+}
+
+impl Conjunction {
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name {
+            "and" => Ok(Self::And),
+            "or" => Ok(Self::Or),
+            _ => bail!("{name} is not a valid conjunction"),
+        }
+    }
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Self::And => "and",
+            Self::Or => "or",
+        }
+    }
+    pub fn get_display_name(&self) -> &'static str {
+        match self {
+            Self::And => "All of",
+            Self::Or => "Any of",
+        }
+    }
+    pub fn toggle(&self) -> Self {
+        match self {
+            Self::And => Self::Or,
+            Self::Or => Self::And,
+        }
+    }
+}
+
+/// A set of filters and nested groups, which pages must match all or any
+/// of. Each view has a root group; see [FilterGroup::build_tree].
+#[derive(Debug)]
+pub struct FilterGroup {
+    pub id: i32,
+    pub view_id: i32,
+    /// `None` for the root group.
+    pub parent_id: Option<i32>,
+    pub conjunction: Conjunction,
+    pub filters: Vec<Filter>,
+    pub groups: Vec<FilterGroup>,
+}
+
+impl FilterGroup {
+    /// Arrange `groups` and `filters` into a tree under `root`. Groups
+    /// without a parent and filters without a group belong to `root`.
+    pub fn build_tree(
+        mut root: FilterGroup,
+        groups: Vec<FilterGroup>,
+        filters: Vec<Filter>,
+    ) -> FilterGroup {
+        let root_id = root.id;
+        let mut groups_by_parent: HashMap<i32, Vec<FilterGroup>> =
+            HashMap::new();
+        for group in groups.into_iter().filter(|g| g.id != root_id) {
+            groups_by_parent
+                .entry(group.parent_id.unwrap_or(root_id))
+                .or_default()
+                .push(group);
+        }
+        let mut filters_by_group: HashMap<i32, Vec<Filter>> = HashMap::new();
+        for filter in filters {
+            filters_by_group
+                .entry(filter.group_id.unwrap_or(root_id))
+                .or_default()
+                .push(filter);
+        }
+        fn attach(
+            group: &mut FilterGroup,
+            groups_by_parent: &mut HashMap<i32, Vec<FilterGroup>>,
+            filters_by_group: &mut HashMap<i32, Vec<Filter>>,
+        ) {
+            group
+                .filters
+                .extend(filters_by_group.remove(&group.id).unwrap_or_default());
+            let mut children =
+                groups_by_parent.remove(&group.id).unwrap_or_default();
+            children.sort_by_key(|g| g.id);
+            for mut child in children {
+                attach(&mut child, groups_by_parent, filters_by_group);
+                group.groups.push(child);
+            }
+        }
+        attach(&mut root, &mut groups_by_parent, &mut filters_by_group);
+
+        root
+    }
+    /// Every filter in this group and its subgroups.
+    pub fn all_filters(&self) -> Vec<&Filter> {
+        self.filters
+            .iter()
+            .chain(self.groups.iter().flat_map(|g| g.all_filters()))
+            .collect()
+    }
+    /// See [Value::localize].
+    pub fn localize(&mut self, tz: Tz) {
+        for filter in &mut self.filters {
+            filter.localize(tz);
+        }
+        for group in &mut self.groups {
+            group.localize(tz);
+        }
+    }
+    /// Whether this group or any of its subgroups has a filter which is
+    /// enabled.
+    pub fn has_enabled_filters(&self) -> bool {
+        self.filters.iter().any(|f| f.enabled)
+            || self.groups.iter().any(|g| g.has_enabled_filters())
+    }
+    /// Push the SQL condition for the whole group onto `query`, in
+    /// parentheses. Callers should check [FilterGroup::has_enabled_filters]
+    /// first; disabled filters and empty subgroups are ignored, rather than
+    /// matching everything or nothing.
+    pub fn push_sql(
+        &self,
+        query: &mut QueryBuilder<'_, Postgres>,
+        ctx: &RelativeDateCtx,
+    ) {
+        let conjunction = format!(" {} ", self.conjunction.get_name());
+        query.push("(");
+        let mut first = true;
+        for filter in self.filters.iter().filter(|f| f.enabled) {
+            if !first {
+                query.push(&conjunction);
+            }
+            first = false;
+            filter.push_sql(query, ctx);
+        }
+        for group in self.groups.iter().filter(|g| g.has_enabled_filters()) {
+            if !first {
+                query.push(&conjunction);
+            }
+            first = false;
+            group.push_sql(query, ctx);
+        }
+        query.push(")");
+    }
+}
+
+/// A range of dates which moves with the current date, so that saved
+/// filters like "this week" don't go stale. Stored by name in
+/// `filter.period`, along with an `amount` for the periods which have one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RelativeDate {
+    Today,
+    Yesterday,
+    Tomorrow,
+    /// The previous `n` days, and today.
+    LastDays(u32),
+    /// Today, and the following `n` days.
+    NextDays(u32),
+    LastWeeks(u32),
+    NextWeeks(u32),
+    LastMonths(u32),
+    NextMonths(u32),
+    /// The week containing today; see [RelativeDateCtx::week_start].
+    ThisWeek,
+    ThisMonth,
+    ThisQuarter,
+    /// Overdue.
+    BeforeToday,
+}
+
+/// The largest `n` in "last `n` days"; about ten years of days.
+pub const MAX_RELATIVE_AMOUNT: u32 = 3650;
+
+impl RelativeDate {
+    /// Names for the `filter.period` column, in the order that they are
+    /// offered in the UI.
+    pub const NAMES: [&'static str; 13] = [
+        "today",
+        "yesterday",
+        "tomorrow",
+        "last_days",
+        "next_days",
+        "last_weeks",
+        "next_weeks",
+        "last_months",
+        "next_months",
+        "this_week",
+        "this_month",
+        "this_quarter",
+        "before_today",
+    ];
+
+    /// `amount` is ignored by periods which don't have one.
+    pub fn parse(name: &str, amount: i32) -> Result<Self> {
+        let n = || -> Result<u32> {
+            match u32::try_from(amount) {
+                Ok(n) if (1..=MAX_RELATIVE_AMOUNT).contains(&n) => Ok(n),
+                _ => bail!(
+                    "the amount must be between 1 and {MAX_RELATIVE_AMOUNT}"
+                ),
+            }
+        };
+        Ok(match name {
+            "today" => Self::Today,
+            "yesterday" => Self::Yesterday,
+            "tomorrow" => Self::Tomorrow,
+            "last_days" => Self::LastDays(n()?),
+            "next_days" => Self::NextDays(n()?),
+            "last_weeks" => Self::LastWeeks(n()?),
+            "next_weeks" => Self::NextWeeks(n()?),
+            "last_months" => Self::LastMonths(n()?),
+            "next_months" => Self::NextMonths(n()?),
+            "this_week" => Self::ThisWeek,
+            "this_month" => Self::ThisMonth,
+            "this_quarter" => Self::ThisQuarter,
+            "before_today" => Self::BeforeToday,
+            _ => bail!("{name} is not a relative date"),
+        })
+    }
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Self::Today => "today",
+            Self::Yesterday => "yesterday",
+            Self::Tomorrow => "tomorrow",
+            Self::LastDays(_) => "last_days",
+            Self::NextDays(_) => "next_days",
+            Self::LastWeeks(_) => "last_weeks",
+            Self::NextWeeks(_) => "next_weeks",
+            Self::LastMonths(_) => "last_months",
+            Self::NextMonths(_) => "next_months",
+            Self::ThisWeek => "this_week",
+            Self::ThisMonth => "this_month",
+            Self::ThisQuarter => "this_quarter",
+            Self::BeforeToday => "before_today",
+        }
+    }
+    /// The `n` of periods like "last `n` days", or 0 for other periods.
+    pub fn get_amount(&self) -> i32 {
+        match self {
+            Self::LastDays(n)
+            | Self::NextDays(n)
+            | Self::LastWeeks(n)
+            | Self::NextWeeks(n)
+            | Self::LastMonths(n)
+            | Self::NextMonths(n) => *n as i32,
+            _ => 0,
+        }
+    }
+    /// How the period named `name` is described in the UI, with `N` in
+    /// place of the amount.
+    pub fn get_display_name(name: &str) -> &'static str {
+        match name {
+            "today" => "Today",
+            "yesterday" => "Yesterday",
+            "tomorrow" => "Tomorrow",
+            "last_days" => "Last N days",
+            "next_days" => "Next N days",
+            "last_weeks" => "Last N weeks",
+            "next_weeks" => "Next N weeks",
+            "last_months" => "Last N months",
+            "next_months" => "Next N months",
+            "this_week" => "This week",
+            "this_month" => "This month",
+            "this_quarter" => "This quarter",
+            "before_today" => "Before today",
+            _ => "Unknown",
+        }
+    }
+    /// The first date in the range, and the first date after it. `None`
+    /// means that the range is open at that end.
+    pub fn resolve(
+        &self,
+        today: NaiveDate,
+        week_start: Weekday,
+    ) -> (Option<NaiveDate>, Option<NaiveDate>) {
+        let days = |n: u32| chrono::Duration::days(n.into());
+        let tomorrow = today + days(1);
+        let (start, end) = match *self {
+            Self::Today => (today, tomorrow),
+            Self::Yesterday => (today - days(1), today),
+            Self::Tomorrow => (tomorrow, tomorrow + days(1)),
+            Self::LastDays(n) => (today - days(n), tomorrow),
+            Self::NextDays(n) => (today, tomorrow + days(n)),
+            Self::LastWeeks(n) => (today - days(n * 7), tomorrow),
+            Self::NextWeeks(n) => (today, tomorrow + days(n * 7)),
+            Self::LastMonths(n) => (today - Months::new(n), tomorrow),
+            Self::NextMonths(n) => (today, tomorrow + Months::new(n)),
+            Self::ThisWeek => {
+                let start = today.week(week_start).first_day();
+                (start, start + days(7))
+            }
+            Self::ThisMonth => {
+                let start = today.with_day(1).expect("every month has a 1st");
+                (start, start + Months::new(1))
+            }
+            Self::ThisQuarter => {
+                let month = (today.month0() / 3) * 3 + 1;
+                let start = NaiveDate::from_ymd_opt(today.year(), month, 1)
+                    .expect("every quarter starts on the 1st");
+                (start, start + Months::new(3))
+            }
+            Self::BeforeToday => return (None, Some(today)),
+        };
+        (Some(start), Some(end))
+    }
+}
+
+impl std::fmt::Display for RelativeDate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let amount = self.get_amount();
+        let name = Self::get_display_name(self.get_name());
+        if amount == 0 {
+            write!(f, "{name}")
+        } else {
+            write!(f, "{}", name.replace('N', &amount.to_string()))
+        }
+    }
+}
+
+/// What relative dates are resolved against: the current date, the first
+/// day of the week, and the timezone of the user looking at the pages.
+#[derive(Debug, Clone, Copy)]
+pub struct RelativeDateCtx {
+    pub today: NaiveDate,
+    pub week_start: Weekday,
+    pub tz: Tz,
+}
+
+impl RelativeDateCtx {
+    pub fn now(tz: Tz, week_start: Weekday) -> Self {
+        Self {
+            today: chrono::Utc::now().with_timezone(&tz).date_naive(),
+            week_start,
+            tz,
+        }
+    }
+    /// Midnight at the start of `date` in [RelativeDateCtx::tz], in UTC.
+    /// Where midnight is skipped by a daylight saving change, the day
+    /// starts at the first time which does exist.
+    pub fn get_start_of_day(
+        &self,
+        date: NaiveDate,
+    ) -> chrono::DateTime<chrono::Utc> {
+        let midnight = date.and_time(chrono::NaiveTime::MIN);
+        (0..24)
+            .find_map(|hour| {
+                self.tz
+                    .from_local_datetime(
+                        &(midnight + chrono::Duration::hours(hour)),
+                    )
+                    .earliest()
+            })
+            .unwrap_or_else(|| self.tz.from_utc_datetime(&midnight))
+            .with_timezone(&chrono::Utc)
+    }
+}
+
+/// A filter written into a URL, like `sprint.gt.3`; see [AdHocQuery].
+#[derive(Debug, PartialEq, Eq)]
+pub struct AdHocFilter {
+    /// See [find_prop].
+    pub prop: String,
+    pub r#type: FilterType,
+    /// Written like the value of a filter form. Ranges are written as
+    /// `start..end`, and relative dates by name, with an amount for the
+    /// periods which have one, like `last_days:7`. Empty for
+    /// [FilterType::IsEmpty].
+    pub value: String,
+}
+
+impl AdHocFilter {
+    fn parse(expr: &str) -> Result<Self> {
+        let mut parts = expr.splitn(3, '.');
+        let (Some(prop), Some(r#type)) = (parts.next(), parts.next()) else {
+            bail!("{expr} should look like prop.type.value");
+        };
+        let r#type = FilterType::from_slug(r#type)?;
+        let value = parts.next().unwrap_or_default().to_string();
+        if value.is_empty() != (r#type == FilterType::IsEmpty) {
+            bail!("{expr} should have a value unless it is an empty filter");
+        }
+        Ok(Self {
+            prop: prop.to_string(),
+            r#type,
+            value,
+        })
+    }
+    fn to_expr(&self) -> String {
+        let mut expr = format!("{}.{}", self.prop, self.r#type.get_slug());
+        if !self.value.is_empty() {
+            expr.push('.');
+            expr.push_str(&self.value);
+        }
+        expr
+    }
+}
+
+/// A sort written into a URL, like `age.desc`; see [AdHocQuery].
+#[derive(Debug, PartialEq, Eq)]
+pub struct AdHocSort {
+    /// See [find_prop].
+    pub prop: String,
+    pub r#type: SortType,
+}
+
+impl AdHocSort {
+    fn parse(expr: &str) -> Result<Self> {
+        let Some((prop, r#type)) = expr.split_once('.') else {
+            bail!("{expr} should look like prop.asc or prop.desc");
+        };
+        let r#type = match r#type {
+            "asc" => SortType::Asc,
+            "desc" => SortType::Desc,
+            _ => bail!("{type} is not a sort order; use asc or desc"),
+        };
+        Ok(Self {
+            prop: prop.to_string(),
+            r#type,
+        })
+    }
+    fn to_expr(&self) -> String {
+        let order = match self.r#type {
+            SortType::Asc => "asc",
+            SortType::Desc => "desc",
+        };
+        format!("{}.{order}", self.prop)
+    }
+}
+
+/// Filters and a sort which are written into the query string of a view,
+/// like `?f=sprint.gt.3&f=completed.eq.false&sort=age.desc`, so that they
+/// can be shared as a link without changing the view for everyone else.
+/// They are added to the filters of the view, or used in place of them with
+/// `mode=replace`. Other parameters are ignored.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct AdHocQuery {
+    pub filters: Vec<AdHocFilter>,
+    pub sort: Option<AdHocSort>,
+    pub replace: bool,
+}
+
+impl AdHocQuery {
+    pub fn parse(params: &[(String, String)]) -> Result<Self> {
+        let mut query = Self::default();
+        for (key, value) in params {
+            match key.as_str() {
+                "f" => query.filters.push(AdHocFilter::parse(value)?),
+                "sort" => query.sort = Some(AdHocSort::parse(value)?),
+                "mode" => {
+                    query.replace = match value.as_str() {
+                        "replace" => true,
+                        "add" => false,
+                        _ => bail!("{value} is not a mode; use add or replace"),
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(query)
+    }
+    pub fn is_empty(&self) -> bool {
+        self.filters.is_empty() && self.sort.is_none()
+    }
+    /// The query string which [AdHocQuery::parse] reads back, without the
+    /// leading `?`.
+    pub fn to_query_string(&self) -> String {
+        let mut params: Vec<(&str, String)> =
+            self.filters.iter().map(|f| ("f", f.to_expr())).collect();
+        if let Some(sort) = &self.sort {
+            params.push(("sort", sort.to_expr()));
+        }
+        if self.replace {
+            params.push(("mode", "replace".into()));
+        }
+        serde_urlencoded::to_string(params)
+            .expect("pairs of strings can be encoded")
+    }
+    /// `route`, with this query appended if there is one.
+    pub fn append_to(&self, route: &Route) -> String {
+        if self.is_empty() {
+            route.as_string()
+        } else {
+            format!("{route}?{}", self.to_query_string())
+        }
+    }
+}
+
+/// The filters and sort of an [AdHocQuery], once its props and values have
+/// been looked up.
+#[derive(Debug)]
+pub struct AdHocFilters {
+    /// Enabled filters in the root group of the view, which have not been
+    /// saved.
+    pub filters: Vec<Filter>,
+    pub sort: Option<ViewSort>,
+    pub replace: bool,
+}
+
+impl AdHocFilters {
+    /// Combine these filters with `stored`, the filter tree of the view.
+    pub fn apply(&self, stored: FilterGroup) -> FilterGroup {
+        let filters = self.filters.clone();
+        if self.replace {
+            FilterGroup {
+                conjunction: Conjunction::And,
+                filters,
+                groups: vec![],
+                ..stored
+            }
+        } else {
+            FilterGroup {
+                id: stored.id,
+                view_id: stored.view_id,
+                parent_id: None,
+                conjunction: Conjunction::And,
+                filters,
+                groups: vec![stored],
+            }
+        }
+    }
+}
+
+/// How a prop is written in an [AdHocQuery]: its name in lower case, with
+/// an underscore in place of each run of spaces and punctuation, like
+/// `sprint_number`.
+pub fn get_prop_slug(name: &str) -> String {
+    name.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+/// The prop which `name` refers to in an [AdHocQuery]. That is the prop with
+/// that ID or slug (see [get_prop_slug]), or else the one prop whose slug
+/// starts with `name`, so that `sprint` is enough for "Sprint Number".
+pub fn find_prop<'a>(props: &'a [Prop], name: &str) -> Result<&'a Prop> {
+    if let Ok(id) = name.parse::<i32>() {
+        if let Some(prop) = props.iter().find(|p| p.id == id) {
+            return Ok(prop);
+        }
+    }
+    let slug = get_prop_slug(name);
+    let find = |matches: &dyn Fn(&str) -> bool| {
+        let mut found =
+            props.iter().filter(|p| matches(&get_prop_slug(&p.name)));
+        match (found.next(), found.next()) {
+            (Some(prop), None) => Ok(Some(prop)),
+            (Some(_), Some(_)) => bail!("{name} could mean more than one prop"),
+            (None, _) => Ok(None),
+        }
+    };
+    if let Some(prop) = find(&|s| s == slug)? {
+        return Ok(prop);
+    }
+    match find(&|s| !slug.is_empty() && s.starts_with(&slug))? {
+        Some(prop) => Ok(prop),
+        None => bail!("there is no prop called {name}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A Wednesday.
+    fn ctx() -> RelativeDateCtx {
+        RelativeDateCtx {
+            today: NaiveDate::from_ymd_opt(2024, 5, 15).unwrap(),
+            week_start: Weekday::Mon,
+            tz: Tz::UTC,
+        }
+    }
+
+    #[test]
+    fn test_filter_tree_sql() {
+        let group = |id, parent_id, conjunction| FilterGroup {
+            id,
+            view_id: 1,
+            parent_id,
+            conjunction,
+            filters: vec![],
+            groups: vec![],
+        };
+        let filter = |prop_id, group_id, r#type, value| Filter {
+            id: prop_id,
+            view_id: 1,
+            prop_id,
+            group_id,
+            r#type,
+            value: FilterValue::Single(value),
+            enabled: true,
+        };
+        let root = group(1, None, Conjunction::Or);
+        let groups = vec![
+            group(2, Some(1), Conjunction::And),
+            group(3, Some(2), Conjunction::Or),
+            // Empty groups don't affect the result.
+            group(4, Some(1), Conjunction::And),
+        ];
+        let filters = vec![
+            filter(2, None, FilterType::Eq, Value::Bool(false)),
+            filter(1, Some(2), FilterType::Gt, Value::Int(3)),
+            filter(3, Some(3), FilterType::Lt, Value::Int(10)),
+            filter(6, Some(3), FilterType::IsEmpty, Value::Int(0)),
+        ];
+        let tree = FilterGroup::build_tree(root, groups, filters);
+        assert_eq!(tree.all_filters().len(), 4);
+        assert!(tree.has_enabled_filters());
+        let mut query = QueryBuilder::new("");
+        tree.push_sql(&mut query, &ctx());
+        assert_eq!(
+            query.sql(),
+            "(prop2.value = $1 or (prop1.value > $2 and \
+            (prop3.value < $3 or prop6.value is null)))"
+        );
+        let empty = group(1, None, Conjunction::And);
+        let empty_groups = vec![group(2, Some(1), Conjunction::Or)];
+        assert!(!FilterGroup::build_tree(empty, empty_groups, vec![])
+            .has_enabled_filters());
+        // Disabled filters are left out, as are groups with only disabled
+        // filters.
+        let root = group(1, None, Conjunction::Or);
+        let groups = vec![group(2, Some(1), Conjunction::And)];
+        let mut disabled = filter(1, Some(2), FilterType::Gt, Value::Int(3));
+        disabled.enabled = false;
+        let filters = vec![
+            filter(2, None, FilterType::Eq, Value::Bool(false)),
+            disabled,
+        ];
+        let tree = FilterGroup::build_tree(root, groups, filters);
+        assert_eq!(tree.all_filters().len(), 2);
+        let mut query = QueryBuilder::new("");
+        tree.push_sql(&mut query, &ctx());
+        assert_eq!(query.sql(), "(prop2.value = $1)");
+        let root = group(1, None, Conjunction::Or);
+        let mut disabled = filter(2, None, FilterType::Eq, Value::Bool(false));
+        disabled.enabled = false;
+        assert!(!FilterGroup::build_tree(root, vec![], vec![disabled])
+            .has_enabled_filters());
+    }
+
+    #[test]
+    fn test_filter_values_are_bound() {
+        let hostile = [
+            "'; drop table page; --",
+            "' or 1=1 --",
+            "0) or pg_sleep(10) is null or (0",
+            "\\'); delete from collection; --",
+        ];
+        let values = hostile
+            .iter()
+            .map(|s| Value::Str(s.to_string()))
+            .chain([Value::MultiStr(
+                hostile.iter().map(|s| s.to_string()).collect(),
+            )])
+            .chain([Value::Formula(Box::new(Value::Str(hostile[0].into())))]);
+        for value in values {
+            let single = Filter {
+                id: 1,
+                view_id: 1,
+                prop_id: 7,
+                group_id: None,
+                r#type: FilterType::Eq,
+                value: FilterValue::Single(value.clone()),
+                enabled: true,
+            };
+            let range = Filter {
+                id: 2,
+                view_id: 1,
+                prop_id: 8,
+                group_id: None,
+                r#type: FilterType::NotInRng,
+                value: FilterValue::Range(value.clone(), value),
+                enabled: true,
+            };
+            let mut query = QueryBuilder::new("select 1 where ");
+            single.push_sql(&mut query, &ctx());
+            query.push(" and ");
+            range.push_sql(&mut query, &ctx());
+            let sql = query.sql();
+            for input in hostile {
+                assert!(!sql.contains(input), "{input} leaked into {sql}");
+            }
+            assert!(!sql.contains('\''), "{sql}");
+            assert!(!sql.contains("--"), "{sql}");
+            let cast = if sql.contains("::varchar[]") {
+                "::varchar[]"
+            } else {
+                ""
+            };
+            assert_eq!(
+                sql,
+                format!(
+                    "select 1 where prop7.value = $1{cast} and \
+                    (prop8.value < $2{cast} or prop8.value > $3{cast})"
+                )
+            );
+        }
+    }
+
+    #[test]
+    fn test_text_filter_sql() {
+        let text = |prop_id, r#type, case_sensitive| Filter {
+            id: 1,
+            view_id: 1,
+            prop_id,
+            group_id: None,
+            r#type,
+            value: FilterValue::Text {
+                value: "50%_off".into(),
+                case_sensitive,
+            },
+            enabled: true,
+        };
+        let cases = [
+            (
+                text(6, FilterType::Contains, false),
+                "strpos(lower(prop6.value), lower($1)) > 0",
+            ),
+            (
+                text(6, FilterType::DoesNotContain, true),
+                "coalesce(strpos(prop6.value, $1), 0) = 0",
+            ),
+            (
+                text(TITLE_PROP_ID, FilterType::StartsWith, false),
+                "starts_with(lower(page.title), lower($1))",
+            ),
+            (
+                text(TITLE_PROP_ID, FilterType::EndsWith, true),
+                "right(page.title, length($1)) = $2",
+            ),
+            (
+                text(6, FilterType::Eq, false),
+                "lower(prop6.value) = lower($1)",
+            ),
+            (text(6, FilterType::Matches, false), "prop6.value ~* $1"),
+            (text(6, FilterType::Matches, true), "prop6.value ~ $1"),
+            (
+                text(6, FilterType::IsEmpty, false),
+                "coalesce(prop6.value, '') = ''",
+            ),
+        ];
+        for (filter, expected) in cases {
+            let mut query = QueryBuilder::new("");
+            filter.push_sql(&mut query, &ctx());
+            assert_eq!(query.sql(), expected, "{:?}", filter.r#type);
+        }
+    }
+
+    #[test]
+    fn test_relative_dates() {
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d);
+        let today = ctx().today;
+        let cases = [
+            (RelativeDate::Today, date(2024, 5, 15), date(2024, 5, 16)),
+            (
+                RelativeDate::Yesterday,
+                date(2024, 5, 14),
+                date(2024, 5, 15),
+            ),
+            (
+                RelativeDate::LastDays(7),
+                date(2024, 5, 8),
+                date(2024, 5, 16),
+            ),
+            (
+                RelativeDate::NextWeeks(1),
+                date(2024, 5, 15),
+                date(2024, 5, 23),
+            ),
+            (
+                RelativeDate::LastMonths(3),
+                date(2024, 2, 15),
+                date(2024, 5, 16),
+            ),
+            (RelativeDate::ThisWeek, date(2024, 5, 13), date(2024, 5, 20)),
+            (RelativeDate::ThisMonth, date(2024, 5, 1), date(2024, 6, 1)),
+            (
+                RelativeDate::ThisQuarter,
+                date(2024, 4, 1),
+                date(2024, 7, 1),
+            ),
+            (RelativeDate::BeforeToday, None, date(2024, 5, 15)),
+        ];
+        for (relative, start, end) in cases {
+            assert_eq!(
+                relative.resolve(today, Weekday::Mon),
+                (start, end),
+                "{relative}"
+            );
+        }
+        assert_eq!(
+            RelativeDate::ThisWeek.resolve(today, Weekday::Sun),
+            (date(2024, 5, 12), date(2024, 5, 19))
+        );
+        assert_eq!(
+            RelativeDate::parse("last_days", 30).unwrap(),
+            RelativeDate::LastDays(30)
+        );
+        assert_eq!(RelativeDate::LastDays(30).to_string(), "Last 30 days");
+        assert!(RelativeDate::parse("last_days", 0).is_err());
+        assert!(RelativeDate::parse("fortnight", 1).is_err());
+        for name in RelativeDate::NAMES {
+            let relative = RelativeDate::parse(name, 2).unwrap();
+            assert_eq!(relative.get_name(), name);
+        }
+
+        let within = |value_type, date| Filter {
+            id: 1,
+            view_id: 1,
+            prop_id: 5,
+            group_id: None,
+            r#type: FilterType::IsWithin,
+            value: FilterValue::Relative { date, value_type },
+            enabled: true,
+        };
+        let mut query = QueryBuilder::new("");
+        within(ValueType::Date, RelativeDate::ThisWeek)
+            .push_sql(&mut query, &ctx());
+        assert_eq!(query.sql(), "(prop5.value >= $1 and prop5.value < $2)");
+        let mut query = QueryBuilder::new("");
+        within(ValueType::DateTime, RelativeDate::BeforeToday)
+            .push_sql(&mut query, &ctx());
+        assert_eq!(
+            query.sql(),
+            "(prop5.value is not null and prop5.value < $1)"
+        );
+
+        // Datetimes are compared against the start of the user's day.
+        let new_york = RelativeDateCtx {
+            tz: chrono_tz::America::New_York,
+            ..ctx()
+        };
+        assert_eq!(
+            new_york.get_start_of_day(today).to_rfc3339(),
+            "2024-05-15T04:00:00+00:00"
+        );
+        // Midnight doesn't exist on the day that clocks go forward here.
+        let santiago = RelativeDateCtx {
+            tz: chrono_tz::America::Santiago,
+            ..ctx()
+        };
+        assert_eq!(
+            santiago
+                .get_start_of_day(date(2024, 9, 8).unwrap())
+                .to_rfc3339(),
+            "2024-09-08T04:00:00+00:00"
+        );
+    }
+
+    #[test]
+    fn test_filter_kinds() {
+        for slug in ["bool", "int-rng", "datetime-rng", "user", "relative-date"]
+        {
+            assert_eq!(FilterKind::from_slug(slug).unwrap().to_string(), slug);
+        }
+        assert!(FilterKind::from_slug("bool-rng").is_err());
+        assert!(FilterKind::from_slug("files").is_err());
+        assert_eq!(
+            FilterKind::new(FilterType::Neq, ValueType::LastEditedBy).unwrap(),
+            FilterKind::Single(ValueType::CreatedBy)
+        );
+        assert_eq!(
+            FilterKind::new(FilterType::IsWithin, ValueType::DateTime).unwrap(),
+            FilterKind::RelativeDate
+        );
+        assert!(FilterKind::new(FilterType::Lt, ValueType::Str).is_err());
+        assert!(FilterKind::exists_for(ValueType::Relation));
+        for ty in [ValueType::MultiStr, ValueType::Files, ValueType::Formula] {
+            assert!(!FilterKind::exists_for(ty), "{ty:?}");
+        }
+    }
+
+    #[test]
+    fn test_filter_type_try_from() {
+        for int in 1..=13 {
+            let ty = FilterType::try_from(int).unwrap();
+            assert_eq!(ty.get_int_repr(), int);
+        }
+        assert!(FilterType::try_from(0).is_err());
+        assert!(FilterType::try_from(14).is_err());
+    }
+
+    #[test]
+    fn test_adhoc_query() {
+        let params = |pairs: &[(&str, &str)]| -> Vec<(String, String)> {
+            pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect()
+        };
+        let query = AdHocQuery::parse(&params(&[
+            ("f", "sprint.gt.3"),
+            ("f", "title.contains.a.b"),
+            ("f", "due.empty"),
+            ("sort", "age.desc"),
+            ("search", "ignored"),
+        ]))
+        .unwrap();
+        assert_eq!(
+            query.filters[1],
+            AdHocFilter {
+                prop: "title".into(),
+                r#type: FilterType::Contains,
+                value: "a.b".into()
+            }
+        );
+        assert_eq!(query.filters[2].value, "");
+        assert_eq!(query.sort.as_ref().unwrap().r#type, SortType::Desc);
+        assert!(!query.replace);
+        assert_eq!(
+            query.to_query_string(),
+            "f=sprint.gt.3&f=title.contains.a.b&f=due.empty&sort=age.desc"
+        );
+        assert_eq!(
+            query.append_to(&Route::ViewListPages(Some(1))),
+            "/view/1/list-pages?f=sprint.gt.3&f=title.contains.a.b\
+            &f=due.empty&sort=age.desc"
+        );
+
+        let replaced = AdHocQuery::parse(&params(&[
+            ("f", "a.eq.x&y"),
+            ("mode", "replace"),
+        ]))
+        .unwrap();
+        assert!(replaced.replace);
+        assert_eq!(replaced.to_query_string(), "f=a.eq.x%26y&mode=replace");
+        assert!(AdHocQuery::parse(&params(&[("page", "1")]))
+            .unwrap()
+            .is_empty());
+
+        for bad in [
+            ("f", "sprint"),
+            ("f", "sprint.bigger.3"),
+            ("f", "sprint.gt"),
+            ("f", "sprint.empty.3"),
+            ("sort", "age"),
+            ("sort", "age.up"),
+            ("mode", "both"),
+        ] {
+            assert!(AdHocQuery::parse(&params(&[bad])).is_err(), "{bad:?}");
+        }
+    }
+
+    #[test]
+    fn test_find_prop() {
+        let prop = |id, name: &str| Prop {
+            id,
+            type_id: ValueType::Int,
+            collection_id: 1,
+            name: name.into(),
+            order: id as i16,
+            number_format: Default::default(),
+        };
+        let props = [
+            prop(4, "Sprint Number"),
+            prop(5, "Age"),
+            prop(6, "Age (Days)"),
+            prop(7, "Next Sprint"),
+            prop(8, "Status"),
+        ];
+        let find = |name| find_prop(&props, name).map(|p| p.id);
+        assert_eq!(get_prop_slug("Age (Days)"), "age_days");
+        assert_eq!(find("sprint_number").unwrap(), 4);
+        assert_eq!(find("sprint").unwrap(), 4);
+        assert_eq!(find("Next Sprint").unwrap(), 7);
+        assert_eq!(find("7").unwrap(), 7);
+        // An exact match wins over other props which start the same way.
+        assert_eq!(find("age").unwrap(), 5);
+        assert_eq!(find("age_d").unwrap(), 6);
+        assert_eq!(find("n").unwrap(), 7);
+        assert!(find("s").is_err());
+        assert!(find("x").is_err());
+        assert!(find("").is_err());
+    }
 }
@@ -0,0 +1,78 @@
+use super::models::type_name;
+use crate::{
+    components::Component,
+    models::{Prop, ValueType},
+    routes::Route,
+};
+use ammonia::{clean, clean_text};
+use std::fmt::Write;
+
+/// Form for editing a formula prop's expression. The other props in the
+/// collection are listed for reference.
+pub struct FormulaEditor<'a> {
+    pub prop: &'a Prop,
+    pub expression: &'a str,
+    /// Every prop in the collection, including the formula itself.
+    pub props: &'a [Prop],
+    /// The type-checking error from the last submission, if any.
+    pub error: Option<&'a str>,
+    /// Set after the expression was saved successfully.
+    pub saved_type: Option<ValueType>,
+}
+impl Component for FormulaEditor<'_> {
+    fn render(&self) -> String {
+        let prop_name = clean(&self.prop.name);
+        let formula_route = Route::PropFormula(Some(self.prop.id));
+        let collection_route = Route::Collection(Some(self.prop.collection_id));
+        let expression = clean_text(self.expression);
+        let reference = self.props.iter().filter(|p| p.id != self.prop.id).fold(
+            String::new(),
+            |mut acc, p| {
+                let name = clean(&p.name);
+                let ty = type_name(p.type_id);
+                let _ = write!(
+                    acc,
+                    r#"<li><code>prop("{name}")</code> <span class="text-xs italic">{ty}</span></li>"#
+                );
+                acc
+            },
+        );
+        let message = match (self.error, self.saved_type) {
+            (Some(error), _) => {
+                let error = clean(error);
+                format!(
+                    r#"<p class="text-red-600 dark:text-red-400">{error}</p>"#
+                )
+            }
+            (None, Some(ty)) => {
+                let ty = type_name(ty);
+                format!(
+                    r#"<p class="text-green-700 dark:text-green-400">Saved; this formula produces {ty} values.</p>"#
+                )
+            }
+            (None, None) => "".into(),
+        };
+        format!(
+            r#"
+            <div class="flex flex-col gap-2 max-w-md" hx-target="this" hx-swap="outerHTML">
+                <a class="link" href="{collection_route}">Back</a>
+                <h1 class="text-xl">Formula for {prop_name}</h1>
+                <form class="flex flex-col gap-2" hx-post="{formula_route}">
+                    <label for="expression">Expression</label>
+                    <textarea
+                        class="dark:text-black font-mono"
+                        id="expression"
+                        name="expression"
+                        rows="3"
+                        required
+                    >{expression}</textarea>
+                    {message}
+                    <button class="dark:bg-slate-700 w-36 dark:text-white dark:hover:bg-slate-600 transition shadow hover:shadow-none rounded p-1 block">Save</button>
+                </form>
+                <h2 class="text-lg">Props</h2>
+                <ul class="flex flex-col gap-1">{reference}</ul>
+            </div>
+            "#
+        )
+    }
+}
@@ -0,0 +1,160 @@
+use super::{compile, models::Formula};
+use crate::{
+    db_ops::{get_prop_set_in, DbModel},
+    filter::db_ops::delete_filters_for_prop,
+    models::ValueType,
+};
+use anyhow::Result;
+use async_trait::async_trait;
+use sqlx::{query, query_as, PgConnection, PgPool};
+
+pub struct GetFormulaQuery {
+    pub prop_id: i32,
+}
+
+pub struct ListFormulaQuery {
+    pub collection_id: i32,
+}
+
+struct Qres {
+    prop_id: i32,
+    expression: String,
+    result_type_id: i32,
+}
+impl Qres {
+    fn into_formula(self) -> Formula {
+        Formula {
+            prop_id: self.prop_id,
+            expression: self.expression,
+            result_type: ValueType::from_int(self.result_type_id),
+        }
+    }
+}
+
+#[async_trait]
+impl DbModel<GetFormulaQuery, ListFormulaQuery> for Formula {
+    async fn get(db: &PgPool, query: &GetFormulaQuery) -> Result<Self> {
+        Ok(query_as!(
+            Qres,
+            "select prop_id, expression, result_type_id
+            from property_formula
+            where prop_id = $1",
+            query.prop_id
+        )
+        .fetch_one(db)
+        .await?
+        .into_formula())
+    }
+    async fn list(db: &PgPool, query: &ListFormulaQuery) -> Result<Vec<Self>> {
+        list_formulas(&mut *db.acquire().await?, query).await
+    }
+    async fn save(&self, db: &PgPool) -> Result<()> {
+        self.save_in(&mut *db.acquire().await?).await
+    }
+    async fn delete(self, db: &PgPool) -> Result<()> {
+        query!(
+            "delete from property_formula where prop_id = $1",
+            self.prop_id
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+}
+
+impl Formula {
+    /// Save the formula as part of a larger transaction.
+    pub async fn save_in(&self, conn: &mut PgConnection) -> Result<()> {
+        query!(
+            "insert into property_formula (prop_id, expression, result_type_id)
+            values ($1, $2, $3)
+            on conflict (prop_id)
+            do update set expression = $2, result_type_id = $3",
+            self.prop_id,
+            self.expression,
+            self.result_type.get_int_repr()
+        )
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
+}
+
+/// Save a formula the user submitted. If its result type changed, the
+/// filters on it are for the old type, so they are deleted in the same
+/// transaction.
+pub async fn save_formula(db: &PgPool, formula: &Formula) -> Result<()> {
+    let mut tx = db.begin().await?;
+    let previous = query!(
+        "select result_type_id from property_formula
+        where prop_id = $1
+        for update",
+        formula.prop_id
+    )
+    .fetch_optional(&mut *tx)
+    .await?;
+    if previous.is_some_and(|row| {
+        row.result_type_id != formula.result_type.get_int_repr()
+    }) {
+        delete_filters_for_prop(&mut tx, formula.prop_id).await?;
+    }
+    formula.save_in(&mut tx).await?;
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Like [Formula::list], as part of a larger transaction.
+pub async fn list_formulas(
+    conn: &mut PgConnection,
+    query: &ListFormulaQuery,
+) -> Result<Vec<Formula>> {
+    Ok(query_as!(
+        Qres,
+        "select f.prop_id, f.expression, f.result_type_id
+        from property_formula f
+        join property p on p.id = f.prop_id
+        where p.collection_id = $1",
+        query.collection_id
+    )
+    .fetch_all(&mut *conn)
+    .await?
+    .into_iter()
+    .map(|row| row.into_formula())
+    .collect())
+}
+
+/// Recompile every formula of a collection, after a change to its props
+/// (like a type conversion) which may have changed their result types.
+/// Filters on formulas whose result type changed are deleted, since they
+/// are for the old type. Formulas which no longer compile are left alone;
+/// they are empty until they are fixed.
+pub async fn refresh_result_types(
+    conn: &mut PgConnection,
+    collection_id: i32,
+) -> Result<()> {
+    let props = get_prop_set_in(&mut *conn, collection_id).await?;
+    let formulas =
+        list_formulas(&mut *conn, &ListFormulaQuery { collection_id }).await?;
+    for formula in formulas {
+        let Ok((_, result_type)) = compile(&formula.expression, &props) else {
+            continue;
+        };
+        if result_type == formula.result_type {
+            continue;
+        }
+        query!(
+            "update property_formula set result_type_id = $1
+            where prop_id = $2",
+            result_type.get_int_repr(),
+            formula.prop_id
+        )
+        .execute(&mut *conn)
+        .await?;
+        delete_filters_for_prop(&mut *conn, formula.prop_id).await?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,21 @@
+//! Formula props are computed from the other props of the same page. The
+//! expression is parsed and type-checked when it is saved, and then compiled
+//! into SQL which is evaluated as part of [crate::db_ops::list_pages]. That
+//! means formulas with a numeric, boolean, or date result can be sorted and
+//! filtered just like any other prop.
+//!
+//! The expression language is small;
+//!
+//! - literals: `1`, `2.5`, `"text"`, `true`, `false`, `date("2024-01-31")`
+//! - prop references: `prop("Sprint Number")`
+//! - arithmetic: `+ - * /`; `+` also concatenates text
+//! - comparisons: `== != < > <= >=`, combined with `and`, `or`, `not`
+//! - `if(condition, then, else)`
+//! - date math: `today()`, `date + int`, `date - int`, `date - date` (days)
+
+pub mod components;
+pub mod db_ops;
+pub mod models;
+mod sql;
+
+pub use sql::compile;
@@ -0,0 +1,452 @@
+use crate::models::ValueType;
+use anyhow::{bail, Result};
+
+/// A formula as it's stored on a prop. `result_type` is determined by
+/// type-checking the expression when it is saved.
+#[derive(Debug, Clone)]
+pub struct Formula {
+    pub prop_id: i32,
+    pub expression: String,
+    pub result_type: ValueType,
+}
+
+/// How a type is described to users writing formulas.
+pub fn type_name(ty: ValueType) -> &'static str {
+    match ty {
+        ValueType::Bool => "checkbox",
+        ValueType::Int => "integer",
+        ValueType::Float => "number",
+        ValueType::Str | ValueType::Select => "text",
+        ValueType::MultiStr => "tags",
+        ValueType::Date => "date",
+        ValueType::DateTime => "date & time",
+        ValueType::Relation => "relation",
+        ValueType::Formula => "formula",
+        ValueType::Rollup => "rollup",
+        ValueType::CreatedTime | ValueType::LastEditedTime => "date & time",
+        ValueType::CreatedBy | ValueType::LastEditedBy => "person",
+        ValueType::Files => "files",
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Eq,
+    Neq,
+    Lt,
+    Gt,
+    Lte,
+    Gte,
+    And,
+    Or,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnaryOp {
+    Neg,
+    Not,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Int(i64),
+    Float(f64),
+    Str(String),
+    Bool(bool),
+    Date(chrono::NaiveDate),
+    Today,
+    /// A reference to another prop, by name.
+    Prop(String),
+    Unary(UnaryOp, Box<Expr>),
+    Binary(BinOp, Box<Expr>, Box<Expr>),
+    If(Box<Expr>, Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Int(i64),
+    Float(f64),
+    Str(String),
+    Ident(String),
+    Op(&'static str),
+    LParen,
+    RParen,
+    Comma,
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = vec![];
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit() {
+            let start = i;
+            while i < chars.len()
+                && (chars[i].is_ascii_digit() || chars[i] == '.')
+            {
+                i += 1;
+            }
+            let text: String = chars[start..i].iter().collect();
+            if text.contains('.') {
+                let val: f64 = text.parse()?;
+                if !val.is_finite() {
+                    bail!("the number {text} is too large");
+                }
+                tokens.push(Token::Float(val));
+            } else {
+                tokens.push(Token::Int(text.parse()?));
+            }
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len()
+                && (chars[i].is_alphanumeric() || chars[i] == '_')
+            {
+                i += 1;
+            }
+            tokens.push(Token::Ident(chars[start..i].iter().collect()));
+        } else if c == '"' {
+            let mut text = String::new();
+            i += 1;
+            loop {
+                match chars.get(i) {
+                    None => bail!("unterminated string"),
+                    Some('"') => break,
+                    Some('\\') => {
+                        match chars.get(i + 1) {
+                            Some(escaped) => text.push(*escaped),
+                            None => bail!("unterminated string"),
+                        };
+                        i += 2;
+                    }
+                    Some(other) => {
+                        text.push(*other);
+                        i += 1;
+                    }
+                }
+            }
+            i += 1;
+            tokens.push(Token::Str(text));
+        } else {
+            let next = chars.get(i + 1).copied();
+            let (token, len) = match (c, next) {
+                ('=', Some('=')) => (Token::Op("=="), 2),
+                ('!', Some('=')) => (Token::Op("!="), 2),
+                ('<', Some('=')) => (Token::Op("<="), 2),
+                ('>', Some('=')) => (Token::Op(">="), 2),
+                ('<', _) => (Token::Op("<"), 1),
+                ('>', _) => (Token::Op(">"), 1),
+                ('+', _) => (Token::Op("+"), 1),
+                ('-', _) => (Token::Op("-"), 1),
+                ('*', _) => (Token::Op("*"), 1),
+                ('/', _) => (Token::Op("/"), 1),
+                ('(', _) => (Token::LParen, 1),
+                (')', _) => (Token::RParen, 1),
+                (',', _) => (Token::Comma, 1),
+                _ => bail!("unexpected character '{c}'"),
+            };
+            tokens.push(token);
+            i += len;
+        }
+    }
+    Ok(tokens)
+}
+
+/// The longest formula we will try to parse, in characters.
+const MAX_FORMULA_LEN: usize = 1000;
+
+/// How deeply the expression tree may nest. Parentheses, calls, `not`, and
+/// unary minus recurse in the parser, and every operator adds a level to the
+/// compiled SQL, so this keeps both well clear of the end of the stack.
+const MAX_DEPTH: usize = 64;
+
+/// Recursive descent parser. From lowest to highest precedence, we have `or`,
+/// `and`, `not`, comparisons, `+ -`, `* /`, unary minus, and then literals,
+/// calls, and parenthesized expressions.
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+    fn expect(&mut self, expected: Token) -> Result<()> {
+        match self.next() {
+            Some(token) if token == expected => Ok(()),
+            Some(token) => bail!("expected {expected:?} but found {token:?}"),
+            None => bail!("expected {expected:?} but the formula ended"),
+        }
+    }
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Ident(i)) if i == keyword)
+    }
+    fn is_op(&self, ops: &[&str]) -> Option<&'static str> {
+        match self.peek() {
+            Some(Token::Op(op)) if ops.contains(op) => Some(op),
+            _ => None,
+        }
+    }
+
+    /// Go one level deeper into the expression tree, failing once the formula
+    /// is nested too deeply.
+    fn descend(&mut self) -> Result<()> {
+        if self.depth >= MAX_DEPTH {
+            bail!("the formula is nested too deeply");
+        }
+        self.depth += 1;
+        Ok(())
+    }
+    fn nested(&mut self, parse: fn(&mut Self) -> Result<Expr>) -> Result<Expr> {
+        self.descend()?;
+        let expr = parse(self)?;
+        self.depth -= 1;
+        Ok(expr)
+    }
+
+    fn or(&mut self) -> Result<Expr> {
+        let depth = self.depth;
+        let mut left = self.and()?;
+        while self.is_keyword("or") {
+            self.pos += 1;
+            self.descend()?;
+            let right = self.and()?;
+            left = Expr::Binary(BinOp::Or, Box::new(left), Box::new(right));
+        }
+        self.depth = depth;
+        Ok(left)
+    }
+    fn and(&mut self) -> Result<Expr> {
+        let depth = self.depth;
+        let mut left = self.not()?;
+        while self.is_keyword("and") {
+            self.pos += 1;
+            self.descend()?;
+            let right = self.not()?;
+            left = Expr::Binary(BinOp::And, Box::new(left), Box::new(right));
+        }
+        self.depth = depth;
+        Ok(left)
+    }
+    fn not(&mut self) -> Result<Expr> {
+        if self.is_keyword("not") {
+            self.pos += 1;
+            return Ok(Expr::Unary(
+                UnaryOp::Not,
+                Box::new(self.nested(Self::not)?),
+            ));
+        }
+        self.comparison()
+    }
+    fn comparison(&mut self) -> Result<Expr> {
+        let left = self.additive()?;
+        let op = match self.is_op(&["==", "!=", "<", ">", "<=", ">="]) {
+            Some("==") => BinOp::Eq,
+            Some("!=") => BinOp::Neq,
+            Some("<") => BinOp::Lt,
+            Some(">") => BinOp::Gt,
+            Some("<=") => BinOp::Lte,
+            Some(">=") => BinOp::Gte,
+            _ => return Ok(left),
+        };
+        self.pos += 1;
+        let right = self.additive()?;
+        Ok(Expr::Binary(op, Box::new(left), Box::new(right)))
+    }
+    fn additive(&mut self) -> Result<Expr> {
+        let depth = self.depth;
+        let mut left = self.multiplicative()?;
+        while let Some(op) = self.is_op(&["+", "-"]) {
+            self.pos += 1;
+            self.descend()?;
+            let right = self.multiplicative()?;
+            let op = if op == "+" { BinOp::Add } else { BinOp::Sub };
+            left = Expr::Binary(op, Box::new(left), Box::new(right));
+        }
+        self.depth = depth;
+        Ok(left)
+    }
+    fn multiplicative(&mut self) -> Result<Expr> {
+        let depth = self.depth;
+        let mut left = self.unary()?;
+        while let Some(op) = self.is_op(&["*", "/"]) {
+            self.pos += 1;
+            self.descend()?;
+            let right = self.unary()?;
+            let op = if op == "*" { BinOp::Mul } else { BinOp::Div };
+            left = Expr::Binary(op, Box::new(left), Box::new(right));
+        }
+        self.depth = depth;
+        Ok(left)
+    }
+    fn unary(&mut self) -> Result<Expr> {
+        if self.is_op(&["-"]).is_some() {
+            self.pos += 1;
+            return Ok(Expr::Unary(
+                UnaryOp::Neg,
+                Box::new(self.nested(Self::unary)?),
+            ));
+        }
+        self.primary()
+    }
+    fn args(&mut self) -> Result<Vec<Expr>> {
+        self.expect(Token::LParen)?;
+        let mut args = vec![];
+        if self.peek() == Some(&Token::RParen) {
+            self.pos += 1;
+            return Ok(args);
+        }
+        loop {
+            args.push(self.nested(Self::or)?);
+            match self.next() {
+                Some(Token::Comma) => continue,
+                Some(Token::RParen) => return Ok(args),
+                _ => bail!("expected ',' or ')' in function arguments"),
+            }
+        }
+    }
+    fn primary(&mut self) -> Result<Expr> {
+        match self.next() {
+            Some(Token::Int(val)) => Ok(Expr::Int(val)),
+            Some(Token::Float(val)) => Ok(Expr::Float(val)),
+            Some(Token::Str(val)) => Ok(Expr::Str(val)),
+            Some(Token::LParen) => {
+                let expr = self.nested(Self::or)?;
+                self.expect(Token::RParen)?;
+                Ok(expr)
+            }
+            Some(Token::Ident(ident)) => match &ident[..] {
+                "true" => Ok(Expr::Bool(true)),
+                "false" => Ok(Expr::Bool(false)),
+                "prop" => match &self.args()?[..] {
+                    [Expr::Str(name)] => Ok(Expr::Prop(name.clone())),
+                    _ => bail!(r#"prop() takes a name, like prop("Age")"#),
+                },
+                "date" => match &self.args()?[..] {
+                    [Expr::Str(date)] => Ok(Expr::Date(
+                        chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")?,
+                    )),
+                    _ => {
+                        bail!(r#"date() takes a date, like date("2024-01-31")"#)
+                    }
+                },
+                "today" => match &self.args()?[..] {
+                    [] => Ok(Expr::Today),
+                    _ => bail!("today() does not take any arguments"),
+                },
+                "if" => {
+                    let mut args = self.args()?;
+                    if args.len() != 3 {
+                        bail!("if() takes a condition, a value if true, and a value if false");
+                    }
+                    let otherwise = args.pop().expect("3 args");
+                    let then = args.pop().expect("3 args");
+                    let condition = args.pop().expect("3 args");
+                    Ok(Expr::If(
+                        Box::new(condition),
+                        Box::new(then),
+                        Box::new(otherwise),
+                    ))
+                }
+                other => bail!("unknown name '{other}'"),
+            },
+            Some(token) => bail!("unexpected {token:?}"),
+            None => bail!("the formula ended unexpectedly"),
+        }
+    }
+}
+
+impl Expr {
+    pub fn parse(input: &str) -> Result<Self> {
+        if input.chars().count() > MAX_FORMULA_LEN {
+            bail!("formulas can be at most {MAX_FORMULA_LEN} characters long");
+        }
+        let mut parser = Parser {
+            tokens: tokenize(input)?,
+            pos: 0,
+            depth: 0,
+        };
+        let expr = parser.or()?;
+        if let Some(token) = parser.peek() {
+            bail!("unexpected {token:?} after the end of the formula");
+        }
+        Ok(expr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_precedence() {
+        let expr =
+            Expr::parse(r#"prop("Age") + 2 * 3 > 10 and not false"#).unwrap();
+        assert_eq!(
+            expr,
+            Expr::Binary(
+                BinOp::And,
+                Box::new(Expr::Binary(
+                    BinOp::Gt,
+                    Box::new(Expr::Binary(
+                        BinOp::Add,
+                        Box::new(Expr::Prop("Age".into())),
+                        Box::new(Expr::Binary(
+                            BinOp::Mul,
+                            Box::new(Expr::Int(2)),
+                            Box::new(Expr::Int(3))
+                        ))
+                    )),
+                    Box::new(Expr::Int(10))
+                )),
+                Box::new(Expr::Unary(
+                    UnaryOp::Not,
+                    Box::new(Expr::Bool(false))
+                ))
+            )
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(Expr::parse("1 +").is_err());
+        assert!(Expr::parse(r#"prop("Age""#).is_err());
+        assert!(Expr::parse(r#""unterminated"#).is_err());
+        assert!(Expr::parse("if(true, 1)").is_err());
+        assert!(Expr::parse("1 2").is_err());
+        assert!(Expr::parse("explode()").is_err());
+    }
+
+    #[test]
+    fn test_parse_limits() {
+        let nested = format!("{}1{}", "(".repeat(400), ")".repeat(400));
+        assert!(Expr::parse(&nested).is_err());
+        assert!(Expr::parse(&"- ".repeat(400)).is_err());
+        assert!(Expr::parse(&format!("{}true", "not ".repeat(200))).is_err());
+        assert!(Expr::parse(&format!("{}1", "1+".repeat(100))).is_err());
+        assert!(Expr::parse(&format!("{}1", "1 + ".repeat(300))).is_err());
+        assert!(Expr::parse(&format!("{}1", "1+".repeat(50))).is_ok());
+        let nested = format!("{}1{}", "(".repeat(20), ")".repeat(20));
+        assert_eq!(Expr::parse(&nested).unwrap(), Expr::Int(1));
+    }
+
+    #[test]
+    fn test_parse_float_too_large() {
+        let huge = format!("1{}.0", "0".repeat(400));
+        assert!(Expr::parse(&huge).is_err());
+        assert_eq!(Expr::parse("1.5").unwrap(), Expr::Float(1.5));
+    }
+}
@@ -0,0 +1,316 @@
+//! Type-checking formulas and compiling them into SQL. Prop references
+//! compile into columns of the `prop{id}` joins in
+//! [crate::db_ops::list_pages].
+
+use super::models::{type_name, BinOp, Expr, UnaryOp};
+use crate::models::{Prop, ValueType};
+use anyhow::{bail, Result};
+
+fn is_numeric(ty: ValueType) -> bool {
+    matches!(ty, ValueType::Int | ValueType::Float)
+}
+
+/// Postgres raises an error when integer arithmetic leaves the range of
+/// `bigint`, which would fail the whole page list. `numeric_sql` is computed
+/// as `numeric` instead, and results out of range are empty.
+///
+/// The `offset 0` stops Postgres from pulling the subquery up and copying
+/// `numeric_sql` into each use of `v`, which would make planning exponential
+/// in how deeply the arithmetic is nested.
+fn checked_int(numeric_sql: String) -> String {
+    format!(
+        "(select case when v between {} and {} then v::bigint end \
+        from (select {numeric_sql} as v offset 0) as t)",
+        i64::MIN,
+        i64::MAX
+    )
+}
+
+/// The first and last days Postgres can store, as offsets from 2000-01-01.
+const MIN_DATE_OFFSET: i64 = -2_451_545;
+const MAX_DATE_OFFSET: i64 = 2_145_031_948;
+
+/// `date` moved by `days` (an int expression), or empty if the result is
+/// outside the range of `date`; see [checked_int].
+fn checked_date(date: &str, days: String) -> String {
+    format!(
+        "(select case when v between {MIN_DATE_OFFSET} and {MAX_DATE_OFFSET} \
+        then '2000-01-01'::date + v::int end \
+        from (select ({date} - '2000-01-01'::date)::numeric + {days} as v \
+        offset 0) as t)"
+    )
+}
+
+/// Compile `expression` against the collection's prop set, returning the SQL
+/// and the type of the result. Empty props are null, so the result is null
+/// whenever a referenced prop is empty.
+pub fn compile(
+    expression: &str,
+    props: &[Prop],
+) -> Result<(String, ValueType)> {
+    let (sql, ty) = check(&Expr::parse(expression)?, props)?;
+    let cast = match ty {
+        ValueType::Bool => "boolean",
+        ValueType::Int => "bigint",
+        ValueType::Float => "float8",
+        ValueType::Str => "text",
+        ValueType::Date => "date",
+        _ => unreachable!("check only returns primitive types"),
+    };
+    Ok((format!("({sql})::{cast}"), ty))
+}
+
+fn check(expr: &Expr, props: &[Prop]) -> Result<(String, ValueType)> {
+    Ok(match expr {
+        Expr::Int(val) => (format!("({val})::bigint"), ValueType::Int),
+        Expr::Float(val) => (format!("({val:?})::float8"), ValueType::Float),
+        Expr::Bool(val) => (format!("{val}"), ValueType::Bool),
+        Expr::Str(val) => (
+            format!("'{}'::text", val.replace('\'', "''").replace('\0', "")),
+            ValueType::Str,
+        ),
+        Expr::Date(val) => (format!("'{val}'::date"), ValueType::Date),
+        Expr::Today => ("current_date".into(), ValueType::Date),
+        Expr::Prop(name) => {
+            let prop = match props.iter().find(|p| &p.name == name) {
+                Some(prop) => prop,
+                None => bail!(r#"there is no prop named "{name}""#),
+            };
+            let id = prop.id;
+            match prop.type_id {
+                ValueType::Bool
+                | ValueType::Int
+                | ValueType::Float
+                | ValueType::Str
+                | ValueType::Date => (format!("prop{id}.value"), prop.type_id),
+                // Select options are used by name.
+                ValueType::Select => (format!("prop{id}.name"), ValueType::Str),
+                ty => bail!(
+                    r#""{name}" is a {} prop, which cannot be used in formulas"#,
+                    type_name(ty)
+                ),
+            }
+        }
+        Expr::Unary(op, inner) => {
+            let (sql, ty) = check(inner, props)?;
+            match (op, ty) {
+                (UnaryOp::Neg, ValueType::Int) => (
+                    checked_int(format!("(-({sql})::numeric)")),
+                    ValueType::Int,
+                ),
+                (UnaryOp::Neg, ValueType::Float) => {
+                    (format!("(-{sql})"), ValueType::Float)
+                }
+                (UnaryOp::Not, ValueType::Bool) => {
+                    (format!("(not {sql})"), ValueType::Bool)
+                }
+                (UnaryOp::Neg, ty) => {
+                    bail!("cannot negate {}", type_name(ty))
+                }
+                (UnaryOp::Not, ty) => {
+                    bail!("'not' needs a checkbox, but got {}", type_name(ty))
+                }
+            }
+        }
+        Expr::Binary(op, left, right) => {
+            let (l, lt) = check(left, props)?;
+            let (r, rt) = check(right, props)?;
+            check_binary(*op, l, lt, r, rt)?
+        }
+        Expr::If(condition, then, otherwise) => {
+            let (c, ct) = check(condition, props)?;
+            if ct != ValueType::Bool {
+                bail!(
+                    "the condition of if() must be a checkbox, but got {}",
+                    type_name(ct)
+                );
+            }
+            let (t, tt) = check(then, props)?;
+            let (o, ot) = check(otherwise, props)?;
+            let ty = if tt == ot {
+                tt
+            } else if is_numeric(tt) && is_numeric(ot) {
+                ValueType::Float
+            } else {
+                bail!(
+                    "both branches of if() must have the same type, but got {} and {}",
+                    type_name(tt),
+                    type_name(ot)
+                );
+            };
+            (format!("(case when {c} then {t} else {o} end)"), ty)
+        }
+    })
+}
+
+fn check_binary(
+    op: BinOp,
+    l: String,
+    lt: ValueType,
+    r: String,
+    rt: ValueType,
+) -> Result<(String, ValueType)> {
+    use ValueType::{Bool, Date, Float, Int, Str};
+    // Integer arithmetic is checked against overflow; see [checked_int].
+    let arithmetic = |operator: &str| {
+        if lt == Int && rt == Int {
+            let sql = format!("(({l})::numeric {operator} ({r})::numeric)");
+            (checked_int(sql), Int)
+        } else {
+            (format!("({l} {operator} {r})"), Float)
+        }
+    };
+    Ok(match op {
+        BinOp::Add => match (lt, rt) {
+            (Str, Str) => (format!("({l} || {r})"), Str),
+            (Date, Int) => (checked_date(&l, format!("({r})::numeric")), Date),
+            (Int, Date) => (checked_date(&r, format!("({l})::numeric")), Date),
+            _ if is_numeric(lt) && is_numeric(rt) => arithmetic("+"),
+            _ => bail!("cannot add {} and {}", type_name(lt), type_name(rt)),
+        },
+        BinOp::Sub => match (lt, rt) {
+            (Date, Int) => {
+                (checked_date(&l, format!("(-({r})::numeric)")), Date)
+            }
+            (Date, Date) => (format!("({l} - {r})"), Int),
+            _ if is_numeric(lt) && is_numeric(rt) => arithmetic("-"),
+            _ => bail!(
+                "cannot subtract {} from {}",
+                type_name(rt),
+                type_name(lt)
+            ),
+        },
+        BinOp::Mul => {
+            if !(is_numeric(lt) && is_numeric(rt)) {
+                bail!(
+                    "cannot multiply {} and {}",
+                    type_name(lt),
+                    type_name(rt)
+                );
+            }
+            arithmetic("*")
+        }
+        // Division always produces a number, and dividing by zero produces an
+        // empty value rather than an error.
+        BinOp::Div => {
+            if !(is_numeric(lt) && is_numeric(rt)) {
+                bail!("cannot divide {} by {}", type_name(lt), type_name(rt));
+            }
+            (format!("(({l})::float8 / nullif(({r})::float8, 0))"), Float)
+        }
+        BinOp::Eq
+        | BinOp::Neq
+        | BinOp::Lt
+        | BinOp::Gt
+        | BinOp::Lte
+        | BinOp::Gte => {
+            if !(lt == rt || is_numeric(lt) && is_numeric(rt)) {
+                bail!("cannot compare {} and {}", type_name(lt), type_name(rt));
+            }
+            let operator = match op {
+                BinOp::Eq => "=",
+                BinOp::Neq => "<>",
+                BinOp::Lt => "<",
+                BinOp::Gt => ">",
+                BinOp::Lte => "<=",
+                _ => ">=",
+            };
+            (format!("({l} {operator} {r})"), Bool)
+        }
+        BinOp::And | BinOp::Or => {
+            if lt != Bool || rt != Bool {
+                bail!(
+                    "'and' and 'or' need checkboxes, but got {} and {}",
+                    type_name(lt),
+                    type_name(rt)
+                );
+            }
+            let operator = if op == BinOp::And { "and" } else { "or" };
+            (format!("({l} {operator} {r})"), Bool)
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn props() -> Vec<Prop> {
+        [
+            (1, ValueType::Int, "Sprint Number"),
+            (2, ValueType::Bool, "Completed"),
+            (5, ValueType::Date, "Birthday"),
+            (6, ValueType::Str, "Owner"),
+            (7, ValueType::MultiStr, "Tags"),
+        ]
+        .into_iter()
+        .map(|(id, type_id, name)| Prop {
+            id,
+            type_id,
+            collection_id: 1,
+            name: name.into(),
+            order: id as i16,
+            number_format: Default::default(),
+        })
+        .collect()
+    }
+
+    #[test]
+    fn test_compile_result_types() {
+        let props = props();
+        let cases = [
+            (r#"prop("Sprint Number") + 1"#, ValueType::Int),
+            (r#"prop("Sprint Number") / 2"#, ValueType::Float),
+            (r#"today() - prop("Birthday")"#, ValueType::Int),
+            (r#"prop("Birthday") + 7"#, ValueType::Date),
+            (r#""by " + prop("Owner")"#, ValueType::Str),
+            (r#"if(prop("Completed"), 1, 0.5)"#, ValueType::Float),
+            (r#"not prop("Completed") or 1 < 2.5"#, ValueType::Bool),
+        ];
+        for (expression, expected) in cases {
+            let (_, ty) = compile(expression, &props).unwrap();
+            assert_eq!(ty, expected, "{expression}");
+        }
+        let (sql, _) = compile(r#"prop("Owner") + "it's""#, &props).unwrap();
+        assert_eq!(sql, "((prop6.value || 'it''s'::text))::text");
+    }
+
+    #[test]
+    fn test_compile_overflow() {
+        let props = props();
+        let (sql, _) = compile("9223372036854775807 + 1", &props).unwrap();
+        assert_eq!(
+            sql,
+            "((select case when v between -9223372036854775808 and \
+            9223372036854775807 then v::bigint end from (select \
+            (((9223372036854775807)::bigint)::numeric + \
+            ((1)::bigint)::numeric) as v offset 0) as t))::bigint"
+        );
+        let (sql, _) =
+            compile(r#"prop("Birthday") + prop("Sprint Number")"#, &props)
+                .unwrap();
+        assert_eq!(
+            sql,
+            "((select case when v between -2451545 and 2145031948 then \
+            '2000-01-01'::date + v::int end from (select (prop5.value - \
+            '2000-01-01'::date)::numeric + (prop1.value)::numeric as v \
+            offset 0) as t))::date"
+        );
+    }
+
+    #[test]
+    fn test_compile_type_errors() {
+        let props = props();
+        for expression in [
+            r#"prop("Owner") + 1"#,
+            r#"prop("Tags")"#,
+            r#"prop("Nope")"#,
+            r#"if(1, 2, 3)"#,
+            r#"if(true, 1, "one")"#,
+            r#"prop("Completed") and 1"#,
+            r#"prop("Birthday") * 2"#,
+        ] {
+            assert!(compile(expression, &props).is_err(), "{expression}");
+        }
+    }
+}
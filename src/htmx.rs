@@ -12,3 +12,16 @@ pub fn redirect(mut headers: HeaderMap, to: &str) -> HeaderMap {
     );
     headers
 }
+
+/// The query params of the page which made the request, from the
+/// `Hx-Current-URL` header that htmx sends along with each request. Empty if
+/// the header is missing, or if it can't be parsed.
+pub fn get_current_url_params(headers: &HeaderMap) -> Vec<(String, String)> {
+    headers
+        .get("Hx-Current-URL")
+        .and_then(|url| url.to_str().ok())
+        .and_then(|url| url.split_once('?'))
+        .map(|(_, query)| query.split('#').next().unwrap_or_default())
+        .and_then(|query| serde_urlencoded::from_str(query).ok())
+        .unwrap_or_default()
+}
@@ -8,18 +8,28 @@ use std::net::SocketAddr;
 mod auth;
 mod components;
 mod config;
+mod constraint;
 mod controllers;
+mod conversion;
 mod crypto;
 mod db_ops;
 mod errors;
+mod export;
+mod file;
 mod filter;
+mod formula;
 mod htmx;
 mod middleware;
 mod models;
+mod number_format;
 mod prop_val;
 mod pw;
+mod rollup;
 mod routes;
+mod search;
 mod session;
+mod template;
+mod view;
 
 /// The Notion Clone entrypoint. Note that I envision this binary some day
 /// becoming a CLI to support the prod backfill operations from our propval
@@ -36,11 +46,15 @@ async fn main() -> Result<()> {
         .layer(from_fn(middleware::html_headers))
         .layer(from_fn(middleware::auth));
 
+    let file_routes =
+        routes::get_file_routes().layer(from_fn(middleware::auth));
+
     let public_routes =
         routes::get_public_routes().layer(from_fn(middleware::html_headers));
 
     let app = Router::new()
         .nest("/", routes)
+        .nest("/", file_routes)
         .nest("/", public_routes)
         .with_state(state);
 
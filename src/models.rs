@@ -1,34 +1,145 @@
 //! Core data-models for the application.
 
-use super::prop_val;
+use super::{number_format::models::NumberFormat, prop_val};
 use anyhow::{bail, Result};
+use chrono::TimeZone;
+use chrono_tz::Tz;
 use serde::{Deserialize, Serialize};
-use sqlx::PgPool;
+use sqlx::{PgPool, Postgres, QueryBuilder};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ValueType {
     Bool,
     Int,
     Float,
+    Str,
+    MultiStr,
     Date,
+    DateTime,
+    Select,
+    Relation,
+    Formula,
+    Rollup,
+    CreatedTime,
+    LastEditedTime,
+    CreatedBy,
+    LastEditedBy,
+    Files,
 }
 
-impl ValueType {
-    pub fn from_int(int: i32) -> Self {
-        match int {
+impl TryFrom<i32> for ValueType {
+    type Error = anyhow::Error;
+
+    fn try_from(int: i32) -> Result<Self> {
+        Ok(match int {
             1 => Self::Bool,
             2 => Self::Int,
             3 => Self::Float,
+            4 => Self::Str,
+            5 => Self::MultiStr,
             6 => Self::Date,
-            _ => panic!("{int} is not a valid ValueType"),
+            7 => Self::DateTime,
+            8 => Self::Select,
+            9 => Self::Relation,
+            10 => Self::Formula,
+            11 => Self::Rollup,
+            12 => Self::CreatedTime,
+            13 => Self::LastEditedTime,
+            14 => Self::CreatedBy,
+            15 => Self::LastEditedBy,
+            16 => Self::Files,
+            _ => bail!("{int} is not a valid ValueType"),
+        })
+    }
+}
+
+impl ValueType {
+    /// Use [ValueType::try_from] for IDs which don't come from the database.
+    pub fn from_int(int: i32) -> Self {
+        match Self::try_from(int) {
+            Ok(ty) => ty,
+            Err(e) => panic!("{e}"),
+        }
+    }
+    pub fn get_int_repr(&self) -> i32 {
+        match self {
+            Self::Bool => 1,
+            Self::Int => 2,
+            Self::Float => 3,
+            Self::Str => 4,
+            Self::MultiStr => 5,
+            Self::Date => 6,
+            Self::DateTime => 7,
+            Self::Select => 8,
+            Self::Relation => 9,
+            Self::Formula => 10,
+            Self::Rollup => 11,
+            Self::CreatedTime => 12,
+            Self::LastEditedTime => 13,
+            Self::CreatedBy => 14,
+            Self::LastEditedBy => 15,
+            Self::Files => 16,
         }
     }
+    /// How the type is described in the UI.
+    pub fn get_display_name(&self) -> &'static str {
+        match self {
+            Self::Bool => "checkbox",
+            Self::Int => "integer",
+            Self::Float => "percent",
+            Self::Str => "text",
+            Self::MultiStr => "tags",
+            Self::Date => "date",
+            Self::DateTime => "date & time",
+            Self::Select => "select",
+            Self::Relation => "relation",
+            Self::Formula => "formula",
+            Self::Rollup => "rollup",
+            Self::CreatedTime => "created time",
+            Self::LastEditedTime => "last edited time",
+            Self::CreatedBy => "created by",
+            Self::LastEditedBy => "last edited by",
+            Self::Files => "files",
+        }
+    }
+    pub fn all() -> [Self; 16] {
+        [
+            Self::Bool,
+            Self::Int,
+            Self::Float,
+            Self::Str,
+            Self::MultiStr,
+            Self::Date,
+            Self::DateTime,
+            Self::Select,
+            Self::Relation,
+            Self::Formula,
+            Self::Rollup,
+            Self::CreatedTime,
+            Self::LastEditedTime,
+            Self::CreatedBy,
+            Self::LastEditedBy,
+            Self::Files,
+        ]
+    }
     pub fn of_value(value: &Value) -> Self {
         match value {
             Value::Int(_) => Self::Int,
             Value::Bool(_) => Self::Bool,
             Value::Date(_) => Self::Date,
             Value::Float(_) => Self::Float,
+            Value::Str(_) => Self::Str,
+            Value::MultiStr(_) => Self::MultiStr,
+            Value::DateTime(_) => Self::DateTime,
+            Value::Select(_) => Self::Select,
+            Value::Relation(_) => Self::Relation,
+            Value::Formula(_) => Self::Formula,
+            Value::Rollup(_) => Self::Rollup,
+            Value::CreatedTime(_) => Self::CreatedTime,
+            Value::LastEditedTime(_) => Self::LastEditedTime,
+            Value::CreatedBy(_) => Self::CreatedBy,
+            Value::LastEditedBy(_) => Self::LastEditedBy,
+            Value::Files(_) => Self::Files,
         }
     }
 }
@@ -38,32 +149,186 @@ pub enum Value {
     Bool(bool),
     Int(i64),
     Float(f64),
+    Str(String),
+    MultiStr(Vec<String>),
     Date(chrono::NaiveDate),
+    /// Stored in UTC; see [Value::localize] for display.
+    DateTime(chrono::DateTime<Tz>),
+    Select(SelectOption),
+    /// Pages linked by a relation prop, ordered by title.
+    Relation(Vec<RelatedPage>),
+    /// The computed result of a formula prop, which is read-only.
+    Formula(Box<Value>),
+    /// An aggregate over the pages linked by a relation prop, which is also
+    /// read-only.
+    Rollup(Box<Value>),
+    /// System props are maintained by the application, and are read-only.
+    CreatedTime(chrono::DateTime<Tz>),
+    LastEditedTime(chrono::DateTime<Tz>),
+    /// Also used as the value of filters on both "created by" and "last
+    /// edited by" props.
+    CreatedBy(User),
+    LastEditedBy(User),
+    /// Uploads attached to the page, oldest first.
+    Files(Vec<StoredFile>),
 }
 
 impl Value {
-    /// This is used for interpolating filter values into SQL queries. For
-    /// now this is safe, because we're using strong data types which do not
-    /// provide a vector for SQL injection attacks. Once we have string filters,
-    /// I'm not sure how this will need to evolve to handle SQL injection
-    /// safety, but I'll punt that problem for now since we only have
-    /// primitive propvals at this point.
-    pub fn as_sql(&self) -> String {
+    /// Push this value onto `query` as a bind parameter, for comparing
+    /// against a propval in a filter. Values are never interpolated into
+    /// the query text. Casts are added where the Rust type does not map
+    /// onto the column type by itself; for example, a `Vec<String>` binds
+    /// as `text[]`, but multi-string propvals are `varchar[]`.
+    pub fn push_bind(&self, query: &mut QueryBuilder<'_, Postgres>) {
         match self {
-            Self::Int(val) => format!("{val}"),
+            Self::Int(val) => {
+                query.push_bind(*val);
+            }
             Self::Bool(val) => {
-                if *val {
-                    "true".into()
-                } else {
-                    "false".into()
-                }
+                query.push_bind(*val);
+            }
+            Self::Float(val) => {
+                query.push_bind(*val);
+            }
+            Self::Str(val) => {
+                query.push_bind(val.clone());
+            }
+            Self::MultiStr(vals) => {
+                query.push_bind(vals.clone());
+                query.push("::varchar[]");
+            }
+            Self::Date(val) => {
+                query.push_bind(*val);
+            }
+            Self::DateTime(val)
+            | Self::CreatedTime(val)
+            | Self::LastEditedTime(val) => {
+                query.push_bind(val.with_timezone(&chrono::Utc));
+            }
+            // Select propvals and filters both store the option ID.
+            Self::Select(option) => {
+                query.push_bind(option.id);
+            }
+            Self::CreatedBy(user) | Self::LastEditedBy(user) => {
+                query.push_bind(user.id);
             }
-            Self::Date(val) => format!(r#"'{val}'"#),
-            Self::Float(val) => format!("{val}"),
+            Self::Formula(val) | Self::Rollup(val) => val.push_bind(query),
+            Self::Relation(pages) => {
+                query.push_bind(pages.iter().map(|p| p.id).collect::<Vec<_>>());
+                query.push("::int[]");
+            }
+            Self::Files(files) => {
+                query.push_bind(files.iter().map(|f| f.id).collect::<Vec<_>>());
+                query.push("::int[]");
+            }
+        }
+    }
+    /// Datetimes come out of the database in UTC. This converts them into
+    /// the user's display timezone; other values are left alone.
+    pub fn localize(&mut self, tz: Tz) {
+        match self {
+            Self::DateTime(val)
+            | Self::CreatedTime(val)
+            | Self::LastEditedTime(val) => *val = val.with_timezone(&tz),
+            Self::Formula(val) | Self::Rollup(val) => val.localize(tz),
+            _ => {}
+        }
+    }
+}
+
+/// Parse the value of a `datetime-local` input, which is a naive datetime in
+/// the user's own timezone. Browsers omit the seconds when they are zero.
+pub fn parse_datetime_local(
+    input: &str,
+    tz: Tz,
+) -> Result<chrono::DateTime<Tz>> {
+    let naive = chrono::NaiveDateTime::parse_from_str(input, "%Y-%m-%dT%H:%M")
+        .or_else(|_| {
+            chrono::NaiveDateTime::parse_from_str(input, "%Y-%m-%dT%H:%M:%S")
+        })?;
+    match tz.from_local_datetime(&naive).earliest() {
+        Some(dt) => Ok(dt),
+        None => bail!("{input} does not exist in {tz}"),
+    }
+}
+
+/// The palette for select options, stored by name in `property_option.color`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelectColor {
+    Gray,
+    Red,
+    Orange,
+    Yellow,
+    Green,
+    Blue,
+    Purple,
+    Pink,
+}
+
+impl SelectColor {
+    pub const ALL: [Self; 8] = [
+        Self::Gray,
+        Self::Red,
+        Self::Orange,
+        Self::Yellow,
+        Self::Green,
+        Self::Blue,
+        Self::Purple,
+        Self::Pink,
+    ];
+    pub fn from_name(name: &str) -> Result<Self> {
+        match Self::ALL.iter().find(|c| c.get_name() == name) {
+            Some(color) => Ok(*color),
+            None => bail!("{name} is not a valid select color"),
+        }
+    }
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Self::Gray => "gray",
+            Self::Red => "red",
+            Self::Orange => "orange",
+            Self::Yellow => "yellow",
+            Self::Green => "green",
+            Self::Blue => "blue",
+            Self::Purple => "purple",
+            Self::Pink => "pink",
         }
     }
 }
 
+/// One of the choices for a select prop.
+#[derive(Debug, Clone)]
+pub struct SelectOption {
+    pub id: i32,
+    pub prop_id: i32,
+    pub name: String,
+    pub color: SelectColor,
+    pub order: i16,
+}
+
+/// An upload attached to a page by a files prop. The contents live in
+/// [crate::file::storage::Storage] under `stored_name`, which is the SHA-256
+/// of the contents; so, identical uploads share one stored copy.
+#[derive(Debug, Clone)]
+pub struct StoredFile {
+    pub id: i32,
+    /// The file name from the uploader's machine.
+    pub name: String,
+    pub stored_name: String,
+    pub mime_type: String,
+    /// In bytes.
+    pub size: i64,
+}
+
+/// A page on the other end of a relation propval; we only carry around what
+/// we need to render a link.
+#[derive(Debug, Clone)]
+pub struct RelatedPage {
+    pub id: i32,
+    pub collection_id: i32,
+    pub title: String,
+}
+
 #[derive(Debug, Clone)]
 pub struct Prop {
     pub id: i32,
@@ -71,6 +336,14 @@ pub struct Prop {
     pub collection_id: i32,
     pub name: String,
     pub order: i16,
+    /// Only used by int and float props.
+    pub number_format: NumberFormat,
+}
+
+#[derive(Debug, Clone)]
+pub struct Collection {
+    pub id: i32,
+    pub name: String,
 }
 
 /// Basically just needed for glue to get the old list page query moved
@@ -90,6 +363,61 @@ pub struct Page {
     pub title: String,
     pub props: Vec<PvOrType>,
     pub content: Option<Content>,
+    /// Only present when the page was loaded from the database; pages built
+    /// from form submissions don't know their key.
+    pub key: Option<PageKey>,
+}
+
+/// A human-readable page ID like `NC-113`, made of the collection's prefix
+/// and the page's number within that collection.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PageKey {
+    pub prefix: String,
+    pub number: i32,
+}
+
+impl PageKey {
+    /// Prefixes are made of letters, so the dash is optional, and so is
+    /// capitalization; `NC-113`, `nc-113`, and `nc113` are all the same key.
+    pub fn parse(key: &str) -> Result<Self> {
+        let digits_start = key
+            .find(|c: char| c.is_ascii_digit())
+            .ok_or_else(|| anyhow::anyhow!("{key} is missing a number"))?;
+        let (prefix, number) = key.split_at(digits_start);
+        let prefix = prefix.strip_suffix('-').unwrap_or(prefix);
+        Self::validate_prefix(prefix)?;
+
+        Ok(Self {
+            prefix: prefix.to_ascii_uppercase(),
+            number: number.parse()?,
+        })
+    }
+    pub fn validate_prefix(prefix: &str) -> Result<()> {
+        if prefix.is_empty() || prefix.len() > 10 {
+            bail!("key prefixes must be between 1 and 10 letters long");
+        }
+        if !prefix.chars().all(|c| c.is_ascii_alphabetic()) {
+            bail!("key prefixes may only contain the letters A-Z");
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for PageKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}-{}", self.prefix, self.number)
+    }
+}
+
+impl Page {
+    /// See [Value::localize].
+    pub fn localize(&mut self, tz: Tz) {
+        for prop in &mut self.props {
+            if let PvOrType::Pv(pv) = prop {
+                pv.value.localize(tz);
+            }
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -103,7 +431,7 @@ pub struct AppState {
     pub db: PgPool,
 }
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub enum SortType {
     Asc,
     Desc,
@@ -132,17 +460,69 @@ impl SortType {
 }
 
 /// If `prop_id` or `type` are `None`, sorting is not currently enabled for
-/// the collection.
-#[derive(Debug, Eq, PartialEq)]
-pub struct CollectionSort {
-    pub collection_id: i32,
+/// the view.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ViewSort {
+    pub view_id: i32,
     pub prop_id: Option<i32>,
     pub r#type: Option<SortType>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct User {
     pub id: i32,
     pub username: String,
     pub email: String,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_value_type_try_from() {
+        for int in 1..=16 {
+            let ty = ValueType::try_from(int).unwrap();
+            assert_eq!(ty.get_int_repr(), int);
+        }
+        assert!(ValueType::try_from(0).is_err());
+        assert!(ValueType::try_from(17).is_err());
+    }
+
+    #[test]
+    fn test_parse_datetime_local() {
+        let tz: Tz = "America/New_York".parse().unwrap();
+        let dt = parse_datetime_local("2024-01-02T13:45", tz).unwrap();
+        assert_eq!(
+            dt.with_timezone(&chrono::Utc).to_rfc3339(),
+            "2024-01-02T18:45:00+00:00"
+        );
+        let dt = parse_datetime_local("2024-07-02T13:45:30", tz).unwrap();
+        assert_eq!(
+            dt.with_timezone(&chrono::Utc).to_rfc3339(),
+            "2024-07-02T17:45:30+00:00"
+        );
+    }
+
+    #[test]
+    fn test_parse_page_key() {
+        let expected = PageKey {
+            prefix: "NC".into(),
+            number: 113,
+        };
+        for key in ["NC-113", "nc-113", "nc113", "Nc113"] {
+            assert_eq!(PageKey::parse(key).unwrap(), expected, "{key}");
+        }
+        assert_eq!(expected.to_string(), "NC-113");
+        for key in ["NC", "113", "-113", "N C-1", "NC-1x", "NC--1"] {
+            assert!(PageKey::parse(key).is_err(), "{key}");
+        }
+    }
+
+    #[test]
+    fn test_parse_datetime_local_rejects_dst_gap() {
+        let tz: Tz = "America/New_York".parse().unwrap();
+        assert!(parse_datetime_local("2024-03-10T02:30", tz).is_err());
+        assert!(parse_datetime_local("yesterday", tz).is_err());
+    }
+}
@@ -0,0 +1,156 @@
+use super::models::{NumberFormat, MAX_DECIMALS};
+use crate::{
+    components::Component,
+    models::{Prop, Value},
+    prop_val::models::PropVal,
+    routes::Route,
+};
+use ammonia::{clean, clean_text};
+
+/// An int or float propval, shown in the format of its prop. The input
+/// still edits the underlying number; saving it replaces this whole
+/// fragment, so that the formatted value stays up to date.
+pub struct NumberCell<'a> {
+    pub propval: &'a PropVal,
+    pub format: &'a NumberFormat,
+}
+impl Component for NumberCell<'_> {
+    fn render(&self) -> String {
+        let value = match self.propval.value {
+            Value::Int(val) => val as f64,
+            Value::Float(val) => val,
+            _ => return self.propval.render(),
+        };
+        let display = match self.format {
+            NumberFormat::Plain => return self.propval.render(),
+            NumberFormat::Progress => ProgressBar {
+                value,
+                text: &self.format.format(value),
+            }
+            .render(),
+            format => {
+                let text = format.format(value);
+                format!(r#"<span class="text-sm">{text}</span>"#)
+            }
+        };
+        let propval = self.propval.render();
+        format!(
+            r#"
+            <div
+                class="flex flex-col items-center gap-1 justify-self-center"
+                hx-target="this"
+                hx-swap="outerHTML"
+            >
+                {display}
+                {propval}
+            </div>
+            "#
+        )
+    }
+}
+
+struct ProgressBar<'a> {
+    value: f64,
+    text: &'a str,
+}
+impl Component for ProgressBar<'_> {
+    fn render(&self) -> String {
+        let text = self.text;
+        let width = (self.value.clamp(0.0, 1.0) * 100.0).round();
+        format!(
+            r#"
+            <div class="flex items-center gap-1" title="{text}">
+                <div class="h-2 w-20 rounded-full bg-slate-200 dark:bg-slate-700">
+                    <div class="h-2 rounded-full bg-blue-500" style="width: {width}%"></div>
+                </div>
+                <span class="text-xs">{text}</span>
+            </div>
+            "#
+        )
+    }
+}
+
+fn get_display_name(name: &str) -> &'static str {
+    match name {
+        "plain" => "Plain",
+        "percent" => "Percent",
+        "currency" => "Currency",
+        "fixed" => "Fixed decimals",
+        "progress" => "Progress bar",
+        _ => "Unknown",
+    }
+}
+
+/// Form for the display format of an int or float prop.
+pub struct NumberFormatForm<'a> {
+    pub prop: &'a Prop,
+    pub error: Option<&'a str>,
+    pub saved: bool,
+}
+impl Component for NumberFormatForm<'_> {
+    fn render(&self) -> String {
+        let prop_name = clean(&self.prop.name);
+        let format_route = Route::PropNumberFormat(Some(self.prop.id));
+        let props_route = Route::CollectionProps(Some(self.prop.collection_id));
+        let format = &self.prop.number_format;
+        let options =
+            NumberFormat::NAMES
+                .iter()
+                .fold(String::new(), |mut acc, name| {
+                    let selected = if *name == format.get_name() {
+                        "selected"
+                    } else {
+                        ""
+                    };
+                    let display_name = get_display_name(name);
+                    acc.push_str(&format!(
+                        r#"<option value="{name}" {selected}>{display_name}</option>"#
+                    ));
+                    acc
+                });
+        let decimals = format.get_decimals();
+        let currency_code =
+            clean_text(format.get_currency_code().unwrap_or_default());
+        let examples = [0.25, 1234.5]
+            .iter()
+            .map(|n| format!("{n} is shown as {}", format.format(*n)))
+            .collect::<Vec<_>>()
+            .join("; ");
+        let examples = clean(&examples);
+        let error = match self.error {
+            Some(error) => {
+                let error = clean(error);
+                format!(
+                    r#"<p class="text-red-600 dark:text-red-400">{error}</p>"#
+                )
+            }
+            None => "".into(),
+        };
+        let saved = if self.saved {
+            r#"<p class="text-green-700 dark:text-green-400">Saved.</p>"#
+        } else {
+            ""
+        };
+        format!(
+            r#"
+            <div class="flex flex-col gap-2 max-w-md" hx-target="this" hx-swap="outerHTML">
+                <a class="link" href="{props_route}">Back</a>
+                <h1 class="text-xl">Number format for {prop_name}</h1>
+                <p class="text-sm">Percent and progress bars treat values as fractions; 1 is 100%. Values are always edited as plain numbers.</p>
+                <form class="flex flex-col gap-2" hx-post="{format_route}">
+                    <label for="number_format">Format</label>
+                    <select class="dark:text-black" id="number_format" name="number_format">{options}</select>
+                    <label for="decimals">Decimals <span class="text-sm text-slate-500 dark:text-slate-400">percent, currency, and fixed decimals</span></label>
+                    <input class="dark:text-black" type="number" min="0" max="{MAX_DECIMALS}" id="decimals" name="decimals" value="{decimals}" />
+                    <label for="currency_code">Currency Code <span class="text-sm text-slate-500 dark:text-slate-400">currency only</span></label>
+                    <input class="dark:text-black" type="text" maxlength="3" id="currency_code" name="currency_code" value="{currency_code}" placeholder="USD" />
+                    <p class="text-sm">{examples}</p>
+                    {error}
+                    {saved}
+                    <button class="dark:bg-slate-700 w-36 dark:text-white dark:hover:bg-slate-600 transition shadow hover:shadow-none rounded p-1 block">Save</button>
+                </form>
+            </div>
+            "#
+        )
+    }
+}
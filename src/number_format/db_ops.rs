@@ -0,0 +1,25 @@
+use super::models::NumberFormat;
+use anyhow::Result;
+use sqlx::{query, PgPool};
+
+pub async fn save_number_format(
+    db: &PgPool,
+    prop_id: i32,
+    format: &NumberFormat,
+) -> Result<()> {
+    query!(
+        "update property set
+            number_format = $1,
+            number_decimals = $2,
+            currency_code = $3
+        where id = $4",
+        format.get_name(),
+        format.get_decimals(),
+        format.get_currency_code(),
+        prop_id
+    )
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
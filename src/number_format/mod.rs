@@ -0,0 +1,11 @@
+//! Display formats for int and float props; say, showing `0.25` as `25%`,
+//! `1234.5` as `$1,234.50`, or as a progress bar. The format is part of
+//! [crate::models::Prop], and is used wherever values are shown as text:
+//! page list cells, filter chips, and CSV exports.
+//!
+//! Formats never change the stored value. Inputs still edit the underlying
+//! `i64` or `f64`, and filters and sorting work on it too.
+
+pub mod components;
+pub mod db_ops;
+pub mod models;
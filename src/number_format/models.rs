@@ -0,0 +1,214 @@
+use anyhow::{bail, Result};
+
+/// The most digits which can be shown after the decimal point.
+pub const MAX_DECIMALS: u8 = 6;
+
+/// How the values of an int or float prop are displayed. This only affects
+/// display; values are stored, edited, filtered, and sorted as plain
+/// numbers.
+///
+/// Percent and progress treat values as fractions, like a spreadsheet does;
+/// `0.25` is shown as `25%`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum NumberFormat {
+    #[default]
+    Plain,
+    Percent {
+        decimals: u8,
+    },
+    /// `code` is an ISO 4217 currency code, like `USD`.
+    Currency {
+        code: String,
+        decimals: u8,
+    },
+    /// A fixed number of decimals, with thousands separators.
+    Fixed {
+        decimals: u8,
+    },
+    /// A bar which fills up from 0 to 1.
+    Progress,
+}
+
+impl NumberFormat {
+    /// Names for the `property.number_format` column, in the order that
+    /// they are offered in the UI.
+    pub const NAMES: [&'static str; 5] =
+        ["plain", "percent", "currency", "fixed", "progress"];
+
+    /// Build a format out of the columns on `property`. The columns are
+    /// checked when the format is saved, so unknown names and bad currency
+    /// codes can only come from editing the database, and fall back to
+    /// [NumberFormat::Plain].
+    pub fn from_columns(
+        name: &str,
+        decimals: i16,
+        currency_code: Option<String>,
+    ) -> Self {
+        Self::parse(name, decimals, currency_code).unwrap_or_default()
+    }
+    /// Build a format out of user input.
+    pub fn parse(
+        name: &str,
+        decimals: i16,
+        currency_code: Option<String>,
+    ) -> Result<Self> {
+        let Ok(decimals) = u8::try_from(decimals) else {
+            bail!("decimals must be between 0 and {MAX_DECIMALS}");
+        };
+        if decimals > MAX_DECIMALS {
+            bail!("decimals must be between 0 and {MAX_DECIMALS}");
+        }
+        Ok(match name {
+            "plain" => Self::Plain,
+            "percent" => Self::Percent { decimals },
+            "currency" => {
+                let code =
+                    currency_code.unwrap_or_default().trim().to_uppercase();
+                if code.len() != 3
+                    || !code.chars().all(|c| c.is_ascii_uppercase())
+                {
+                    bail!("currency codes are three letters, like USD");
+                }
+                Self::Currency { code, decimals }
+            }
+            "fixed" => Self::Fixed { decimals },
+            "progress" => Self::Progress,
+            _ => bail!("{name} is not a number format"),
+        })
+    }
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Self::Plain => "plain",
+            Self::Percent { .. } => "percent",
+            Self::Currency { .. } => "currency",
+            Self::Fixed { .. } => "fixed",
+            Self::Progress => "progress",
+        }
+    }
+    pub fn get_decimals(&self) -> i16 {
+        match self {
+            Self::Percent { decimals }
+            | Self::Currency { decimals, .. }
+            | Self::Fixed { decimals } => (*decimals).into(),
+            Self::Plain | Self::Progress => 0,
+        }
+    }
+    pub fn get_currency_code(&self) -> Option<&str> {
+        match self {
+            Self::Currency { code, .. } => Some(code),
+            _ => None,
+        }
+    }
+    /// `value` as text. Progress bars are shown as a percentage.
+    pub fn format(&self, value: f64) -> String {
+        match self {
+            Self::Plain => value.to_string(),
+            Self::Percent { decimals } => {
+                format!("{}%", group_thousands(value * 100.0, *decimals))
+            }
+            Self::Currency { code, decimals } => {
+                let amount = group_thousands(value, *decimals);
+                let (sign, amount) = match amount.strip_prefix('-') {
+                    Some(amount) => ("-", amount),
+                    None => ("", amount.as_str()),
+                };
+                match currency_symbol(code) {
+                    Some(symbol) => format!("{sign}{symbol}{amount}"),
+                    None => format!("{sign}{code} {amount}"),
+                }
+            }
+            Self::Fixed { decimals } => group_thousands(value, *decimals),
+            Self::Progress => format!("{}%", group_thousands(value * 100.0, 0)),
+        }
+    }
+}
+
+/// The symbol which is shown in place of the currency code, if any.
+fn currency_symbol(code: &str) -> Option<&'static str> {
+    match code {
+        "USD" => Some("$"),
+        "EUR" => Some("€"),
+        "GBP" => Some("£"),
+        "JPY" => Some("¥"),
+        "INR" => Some("₹"),
+        _ => None,
+    }
+}
+
+/// `value` rounded to `decimals`, with commas between groups of thousands.
+fn group_thousands(value: f64, decimals: u8) -> String {
+    if !value.is_finite() {
+        return value.to_string();
+    }
+    let rounded = format!("{:.*}", decimals.into(), value.abs());
+    let (whole, fraction) = match rounded.split_once('.') {
+        Some((whole, fraction)) => (whole, Some(fraction)),
+        None => (rounded.as_str(), None),
+    };
+    let mut grouped = String::new();
+    for (i, digit) in whole.chars().enumerate() {
+        if i > 0 && (whole.len() - i) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    if let Some(fraction) = fraction {
+        grouped.push('.');
+        grouped.push_str(fraction);
+    }
+    // Don't show `-0` for small negative numbers which round to zero.
+    if value < 0.0 && rounded.chars().any(|c| ('1'..='9').contains(&c)) {
+        format!("-{grouped}")
+    } else {
+        grouped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_number_format() {
+        let usd = NumberFormat::Currency {
+            code: "USD".into(),
+            decimals: 2,
+        };
+        let chf = NumberFormat::Currency {
+            code: "CHF".into(),
+            decimals: 0,
+        };
+        let cases = [
+            (NumberFormat::Plain, 1234.5, "1234.5"),
+            (NumberFormat::Plain, 3.0, "3"),
+            (NumberFormat::Percent { decimals: 0 }, 0.25, "25%"),
+            (NumberFormat::Percent { decimals: 1 }, 0.1234, "12.3%"),
+            (usd.clone(), 1234567.891, "$1,234,567.89"),
+            (usd.clone(), -1234.5, "-$1,234.50"),
+            (usd, -0.001, "$0.00"),
+            (chf, 999.6, "CHF 1,000"),
+            (NumberFormat::Fixed { decimals: 3 }, 100.0, "100.000"),
+            (NumberFormat::Fixed { decimals: 0 }, -123456.0, "-123,456"),
+            (NumberFormat::Progress, 0.756, "76%"),
+        ];
+        for (format, value, expected) in cases {
+            assert_eq!(format.format(value), expected, "{format:?} {value}");
+        }
+        assert_eq!(
+            NumberFormat::parse("currency", 2, Some(" eur ".into())).unwrap(),
+            NumberFormat::Currency {
+                code: "EUR".into(),
+                decimals: 2
+            }
+        );
+        assert!(NumberFormat::parse("currency", 2, None).is_err());
+        assert!(NumberFormat::parse("currency", 2, Some("€".into())).is_err());
+        assert!(NumberFormat::parse("fixed", 7, None).is_err());
+        assert!(NumberFormat::parse("fixed", -1, None).is_err());
+        assert!(NumberFormat::parse("scientific", 0, None).is_err());
+        assert_eq!(
+            NumberFormat::from_columns("scientific", 0, None),
+            NumberFormat::Plain
+        );
+    }
+}
@@ -1,13 +1,19 @@
 use super::models;
-use crate::{components::Component, models::Value, routes::Route};
+use crate::{
+    components::Component,
+    file::models::{format_size, is_image},
+    models::{SelectColor, SelectOption, Value},
+    routes::Route,
+};
+use ammonia::clean_text;
 
 impl Component for models::PropVal {
     fn render(&self) -> String {
-        match self.value {
+        match &self.value {
             Value::Bool(val) => {
                 let route =
                     Route::PageBoolProp(Some((self.page_id, self.prop_id)));
-                let checked_state = if val { "checked" } else { "" };
+                let checked_state = if *val { "checked" } else { "" };
                 format!(
                     r#"
                     <input
@@ -51,6 +57,73 @@ impl Component for models::PropVal {
                     "#
                 )
             }
+            Value::Str(val) => {
+                let route =
+                    Route::PageStrProp(Some((self.page_id, self.prop_id)));
+                let val = clean_text(val);
+                format!(
+                    r#"
+                    <input
+                        class="rounded text-sm w-36 justify-self-center"
+                        hx-post="{route}"
+                        name="value"
+                        type="text"
+                        maxlength="511"
+                        value="{val}"
+                    />
+                    "#
+                )
+            }
+            Value::MultiStr(vals) => {
+                let route = Route::PageMultiStrPropTag(Some((
+                    self.page_id,
+                    self.prop_id,
+                )));
+                let vocab_route = Route::PropTagVocab(Some(self.prop_id));
+                let list_id = format!("tags-{}-{}", self.page_id, self.prop_id);
+                let chips = vals.iter().fold(String::new(), |mut acc, tag| {
+                    let tag = clean_text(tag);
+                    acc.push_str(&format!(
+                        r#"
+                        <form
+                            class="flex items-center gap-1 rounded-full px-2 text-xs bg-blue-100 dark:bg-blue-800"
+                            hx-delete="{route}"
+                        >
+                            <input type="hidden" name="value" value="{tag}" />
+                            {tag}
+                            <button aria-label="remove tag">&times;</button>
+                        </form>
+                        "#
+                    ));
+                    acc
+                });
+                format!(
+                    r##"
+                    <div
+                        class="flex flex-wrap items-center gap-1 justify-self-center"
+                        hx-target="this"
+                        hx-swap="outerHTML"
+                    >
+                        {chips}
+                        <form hx-post="{route}">
+                            <input
+                                class="rounded text-xs w-24"
+                                name="value"
+                                type="text"
+                                maxlength="511"
+                                placeholder="add tag"
+                                list="{list_id}"
+                                hx-get="{vocab_route}"
+                                hx-trigger="focus once"
+                                hx-target="#{list_id}"
+                                hx-swap="innerHTML"
+                            />
+                            <datalist id="{list_id}"></datalist>
+                        </form>
+                    </div>
+                    "##
+                )
+            }
             Value::Date(val) => {
                 let route =
                     Route::PageDateProp(Some((self.page_id, self.prop_id)));
@@ -67,6 +140,232 @@ impl Component for models::PropVal {
                     "#
                 )
             }
+            Value::DateTime(val) => {
+                let route =
+                    Route::PageDateTimeProp(Some((self.page_id, self.prop_id)));
+                let tz = val.timezone();
+                let val = val.format("%Y-%m-%dT%H:%M");
+                format!(
+                    r#"
+                    <input
+                        class="rounded text-sm w-48 justify-self-center"
+                        hx-post="{route}"
+                        hx-trigger="input changed delay:1s"
+                        name="value"
+                        type="datetime-local"
+                        title="{tz}"
+                        value="{val}"
+                    />
+                    "#
+                )
+            }
+            Value::Select(option) => {
+                let route =
+                    Route::PageSelectProp(Some((self.page_id, self.prop_id)));
+                let name = clean_text(&option.name);
+                let color = get_select_color_classes(option.color);
+                format!(
+                    r#"
+                    <button
+                        class="rounded-full px-2 text-sm justify-self-center {color}"
+                        hx-get="{route}"
+                        hx-swap="outerHTML"
+                    >{name}</button>
+                    "#
+                )
+            }
+            // Formula and rollup results are read-only.
+            Value::Formula(val) | Value::Rollup(val) => {
+                let text = match val.as_ref() {
+                    Value::Bool(true) => "✓".into(),
+                    Value::Bool(false) => "✗".into(),
+                    Value::Float(val) => format!("{val:.2}"),
+                    Value::Str(val) => clean_text(val),
+                    Value::Int(val) => format!("{val}"),
+                    Value::Date(val) => format!("{val}"),
+                    other => panic!("{other:?} is not a formula result type"),
+                };
+                format!(
+                    r#"<span class="text-sm justify-self-center">{text}</span>"#
+                )
+            }
+            // System props are maintained by the application.
+            Value::CreatedTime(val) | Value::LastEditedTime(val) => {
+                let text = val.format("%Y-%m-%d %H:%M");
+                format!(
+                    r#"<span class="text-sm justify-self-center">{text}</span>"#
+                )
+            }
+            Value::CreatedBy(user) | Value::LastEditedBy(user) => {
+                let text = clean_text(&user.username);
+                format!(
+                    r#"<span class="text-sm justify-self-center">{text}</span>"#
+                )
+            }
+            Value::Files(files) => {
+                let route =
+                    Route::PageFilesProp(Some((self.page_id, self.prop_id)));
+                let chips = files.iter().fold(String::new(), |mut acc, file| {
+                    let file_route = Route::File(Some(file.id));
+                    let file_id = file.id;
+                    let name = clean_text(&file.name);
+                    let size = format_size(file.size);
+                    let link = if is_image(&file.mime_type) {
+                        format!(
+                            r#"
+                            <a href="{file_route}" target="_blank" title="{name} ({size})">
+                                <img class="h-8 w-8 rounded object-cover" src="{file_route}" alt="{name}" />
+                            </a>
+                            "#
+                        )
+                    } else {
+                        format!(
+                            r#"<a class="link max-w-[8rem] truncate" href="{file_route}" hx-boost="false" title="{name} ({size})">{name}</a>"#
+                        )
+                    };
+                    acc.push_str(&format!(
+                        r#"
+                        <span class="flex items-center gap-1 rounded-full px-2 text-xs bg-amber-100 dark:bg-amber-800">
+                            {link}
+                            <button
+                                aria-label="remove file"
+                                hx-delete="{route}?value={file_id}"
+                                hx-confirm="Remove {name}?"
+                            >&times;</button>
+                        </span>
+                        "#
+                    ));
+                    acc
+                });
+                format!(
+                    r#"
+                    <div
+                        class="flex flex-wrap items-center gap-1 justify-self-center"
+                        hx-target="this"
+                        hx-swap="outerHTML"
+                    >
+                        {chips}
+                        <form
+                            hx-post="{route}"
+                            hx-encoding="multipart/form-data"
+                            hx-trigger="change"
+                        >
+                            <label class="link text-xs cursor-pointer">
+                                + file
+                                <input class="hidden" type="file" name="file" multiple />
+                            </label>
+                        </form>
+                    </div>
+                    "#
+                )
+            }
+            Value::Relation(pages) => {
+                let route =
+                    Route::PageRelationProp(Some((self.page_id, self.prop_id)));
+                let options_route =
+                    Route::PropRelationOptions(Some(self.prop_id));
+                let chips = pages.iter().fold(String::new(), |mut acc, page| {
+                    let page_route = Route::Page(Some(page.id));
+                    let target_id = page.id;
+                    let title = clean_text(&page.title);
+                    acc.push_str(&format!(
+                        r#"
+                        <span class="flex items-center gap-1 rounded-full px-2 text-xs bg-emerald-100 dark:bg-emerald-800">
+                            <a class="link max-w-[8rem] truncate" href="{page_route}">{title}</a>
+                            <button
+                                aria-label="remove link"
+                                hx-delete="{route}?value={target_id}"
+                            >&times;</button>
+                        </span>
+                        "#
+                    ));
+                    acc
+                });
+                format!(
+                    r#"
+                    <div
+                        class="flex flex-wrap items-center gap-1 justify-self-center"
+                        hx-target="this"
+                        hx-swap="outerHTML"
+                    >
+                        {chips}
+                        <form hx-post="{route}" hx-trigger="change">
+                            <select
+                                class="rounded text-xs w-24 dark:text-black"
+                                name="value"
+                                hx-get="{options_route}"
+                                hx-trigger="focus once"
+                                hx-target="this"
+                                hx-swap="beforeend"
+                            >
+                                <option value="">+ link</option>
+                            </select>
+                        </form>
+                    </div>
+                    "#
+                )
+            }
         }
     }
 }
+
+/// Tailwind classes for the background and text of a select option chip.
+pub fn get_select_color_classes(color: SelectColor) -> &'static str {
+    match color {
+        SelectColor::Gray => "bg-slate-200 text-slate-900",
+        SelectColor::Red => "bg-red-200 text-red-900",
+        SelectColor::Orange => "bg-orange-200 text-orange-900",
+        SelectColor::Yellow => "bg-yellow-200 text-yellow-900",
+        SelectColor::Green => "bg-green-200 text-green-900",
+        SelectColor::Blue => "bg-blue-200 text-blue-900",
+        SelectColor::Purple => "bg-purple-200 text-purple-900",
+        SelectColor::Pink => "bg-pink-200 text-pink-900",
+    }
+}
+
+/// Dropdown for choosing the option of a select propval. The empty option
+/// clears the propval.
+pub struct SelectPropValForm<'a> {
+    pub page_id: i32,
+    pub prop_id: i32,
+    pub selected: Option<i32>,
+    pub options: &'a [SelectOption],
+}
+impl Component for SelectPropValForm<'_> {
+    fn render(&self) -> String {
+        let route = Route::PageSelectProp(Some((self.page_id, self.prop_id)));
+        let options_route = Route::PropOptions(Some(self.prop_id));
+        let options = self.options.iter().fold(String::new(), |mut acc, o| {
+            let id = o.id;
+            let name = clean_text(&o.name);
+            let selected = if Some(id) == self.selected {
+                "selected"
+            } else {
+                ""
+            };
+            acc.push_str(&format!(
+                r#"<option value="{id}" {selected}>{name}</option>"#
+            ));
+            acc
+        });
+        format!(
+            r#"
+            <div
+                class="flex items-center gap-1 justify-self-center"
+                hx-target="this"
+                hx-swap="outerHTML"
+            >
+                <select
+                    class="rounded text-sm dark:text-black"
+                    hx-post="{route}"
+                    name="value"
+                >
+                    <option value="">--</option>
+                    {options}
+                </select>
+                <a class="link text-xs" href="{options_route}">edit</a>
+            </div>
+            "#
+        )
+    }
+}
@@ -1,12 +1,17 @@
 use super::models;
 use crate::{
-    db_ops::{DbModel, GetPropQuery},
-    models::{Prop, Value, ValueType},
+    constraint,
+    db_ops::{touch_page, DbModel, GetPropQuery},
+    models::{
+        Prop, RelatedPage, SelectColor, SelectOption, StoredFile, Value,
+        ValueType,
+    },
 };
-use anyhow::Result;
+use anyhow::{bail, Result};
 use async_trait::async_trait;
+use chrono_tz::Tz;
 use futures::join;
-use sqlx::{query, query_as, PgPool};
+use sqlx::{query, query_as, PgConnection, PgPool};
 
 pub struct PvGetQuery {
     pub page_id: i32,
@@ -24,6 +29,105 @@ struct Qres<T> {
     value: T,
 }
 
+/// Select propvals are joined with their option, since we need the option's
+/// name and color to display the propval.
+struct QresSelect {
+    page_id: i32,
+    prop_id: i32,
+    option_id: i32,
+    name: String,
+    color: String,
+    order: i16,
+}
+impl QresSelect {
+    fn into_propval(self) -> Result<models::PropVal> {
+        Ok(models::PropVal {
+            page_id: self.page_id,
+            prop_id: self.prop_id,
+            value: Value::Select(SelectOption {
+                id: self.option_id,
+                prop_id: self.prop_id,
+                name: self.name,
+                color: SelectColor::from_name(&self.color)?,
+                order: self.order,
+            }),
+        })
+    }
+}
+
+/// Relation propvals are stored as one row per link, so rows need to be
+/// grouped back into a single propval for each `(page_id, prop_id)`.
+struct QresRelation {
+    page_id: i32,
+    prop_id: i32,
+    target_id: i32,
+    target_collection_id: i32,
+    target_title: String,
+}
+fn group_relations(rows: Vec<QresRelation>) -> Vec<models::PropVal> {
+    let mut propvals: Vec<models::PropVal> = vec![];
+    for row in rows {
+        let page = RelatedPage {
+            id: row.target_id,
+            collection_id: row.target_collection_id,
+            title: row.target_title,
+        };
+        match propvals
+            .iter_mut()
+            .find(|pv| pv.page_id == row.page_id && pv.prop_id == row.prop_id)
+        {
+            Some(models::PropVal {
+                value: Value::Relation(pages),
+                ..
+            }) => pages.push(page),
+            _ => propvals.push(models::PropVal {
+                page_id: row.page_id,
+                prop_id: row.prop_id,
+                value: Value::Relation(vec![page]),
+            }),
+        }
+    }
+    propvals
+}
+
+/// Like relations, files are stored as one row per upload.
+struct QresFile {
+    page_id: i32,
+    prop_id: i32,
+    id: i32,
+    name: String,
+    stored_name: String,
+    mime_type: String,
+    size: i64,
+}
+fn group_files(rows: Vec<QresFile>) -> Vec<models::PropVal> {
+    let mut propvals: Vec<models::PropVal> = vec![];
+    for row in rows {
+        let file = StoredFile {
+            id: row.id,
+            name: row.name,
+            stored_name: row.stored_name,
+            mime_type: row.mime_type,
+            size: row.size,
+        };
+        match propvals
+            .iter_mut()
+            .find(|pv| pv.page_id == row.page_id && pv.prop_id == row.prop_id)
+        {
+            Some(models::PropVal {
+                value: Value::Files(files),
+                ..
+            }) => files.push(file),
+            _ => propvals.push(models::PropVal {
+                page_id: row.page_id,
+                prop_id: row.prop_id,
+                value: Value::Files(vec![file]),
+            }),
+        }
+    }
+    propvals
+}
+
 #[async_trait]
 impl DbModel<PvGetQuery, PvListQuery> for models::PropVal {
     async fn get(db: &PgPool, query: &PvGetQuery) -> Result<Self> {
@@ -39,6 +143,15 @@ impl DbModel<PvGetQuery, PvListQuery> for models::PropVal {
             }
         };
         let value = match val_type {
+            ValueType::Formula | ValueType::Rollup => {
+                bail!("formula and rollup values are computed, not stored")
+            }
+            ValueType::CreatedTime
+            | ValueType::LastEditedTime
+            | ValueType::CreatedBy
+            | ValueType::LastEditedBy => {
+                bail!("system props are maintained by the application")
+            }
             ValueType::Bool => {
                 let value = query_as!(
                     Qres::<bool>,
@@ -78,6 +191,42 @@ impl DbModel<PvGetQuery, PvListQuery> for models::PropVal {
                 .await?;
                 Value::Float(value.value)
             }
+            ValueType::Str => {
+                let value = query_as!(
+                    Qres::<String>,
+                    "select page_id, prop_id, value
+                    from propval_str
+                    where page_id = $1 and prop_id = $2",
+                    query.page_id,
+                    query.prop_id
+                )
+                .fetch_one(db)
+                .await?;
+                Value::Str(value.value)
+            }
+            ValueType::MultiStr => {
+                let value = query_as!(
+                    Qres::<Vec<String>>,
+                    r#"select
+                        m.page_id,
+                        m.prop_id,
+                        coalesce(
+                            array_agg(v.value order by v.value)
+                                filter (where v.value is not null),
+                            '{}'
+                        ) as "value!"
+                    from propval_multistr m
+                    left join propval_multistr__value v
+                        on v.propval_multistr_id = m.id
+                    where m.page_id = $1 and m.prop_id = $2
+                    group by m.page_id, m.prop_id"#,
+                    query.page_id,
+                    query.prop_id
+                )
+                .fetch_one(db)
+                .await?;
+                Value::MultiStr(value.value)
+            }
             ValueType::Date => {
                 let value = query_as!(
                     Qres::<chrono::NaiveDate>,
@@ -91,6 +240,88 @@ impl DbModel<PvGetQuery, PvListQuery> for models::PropVal {
                 .await?;
                 Value::Date(value.value)
             }
+            ValueType::DateTime => {
+                let value = query_as!(
+                    Qres::<chrono::DateTime<chrono::Utc>>,
+                    "select page_id, prop_id, value
+                    from propval_datetime
+                    where page_id = $1 and prop_id = $2",
+                    query.page_id,
+                    query.prop_id
+                )
+                .fetch_one(db)
+                .await?;
+                Value::DateTime(value.value.with_timezone(&Tz::UTC))
+            }
+            ValueType::Select => {
+                return query_as!(
+                    QresSelect,
+                    r#"select
+                        ps.page_id,
+                        ps.prop_id,
+                        po.id option_id,
+                        po.name,
+                        po.color,
+                        po."order"
+                    from propval_select ps
+                    join property_option po on po.id = ps.value
+                    where ps.page_id = $1 and ps.prop_id = $2"#,
+                    query.page_id,
+                    query.prop_id
+                )
+                .fetch_one(db)
+                .await?
+                .into_propval();
+            }
+            ValueType::Relation => {
+                let rows = query_as!(
+                    QresRelation,
+                    "select
+                        r.page_id,
+                        r.prop_id,
+                        t.id target_id,
+                        t.collection_id target_collection_id,
+                        t.title target_title
+                    from propval_relation r
+                    join page t on t.id = r.target_page_id
+                    where r.page_id = $1 and r.prop_id = $2
+                    order by t.title, t.id",
+                    query.page_id,
+                    query.prop_id
+                )
+                .fetch_all(db)
+                .await?;
+                // A relation without any links is the same as a propval which
+                // does not exist.
+                return match group_relations(rows).pop() {
+                    Some(pv) => Ok(pv),
+                    None => Err(sqlx::Error::RowNotFound.into()),
+                };
+            }
+            ValueType::Files => {
+                let rows = query_as!(
+                    QresFile,
+                    "select
+                        page_id,
+                        prop_id,
+                        id,
+                        name,
+                        stored_name,
+                        mime_type,
+                        size
+                    from propval_file
+                    where page_id = $1 and prop_id = $2
+                    order by id",
+                    query.page_id,
+                    query.prop_id
+                )
+                .fetch_all(db)
+                .await?;
+                return match group_files(rows).pop() {
+                    Some(pv) => Ok(pv),
+                    None => Err(sqlx::Error::RowNotFound.into()),
+                };
+            }
         };
         Ok(models::PropVal {
             page_id: query.page_id,
@@ -141,6 +372,44 @@ impl DbModel<PvGetQuery, PvListQuery> for models::PropVal {
         })
         .fetch_all(db);
 
+        let strs = query_as!(
+            Qres::<String>,
+            "select page_id, prop_id, value
+            from propval_str
+            where page_id = ANY($1)",
+            &query.page_ids
+        )
+        .map(|row| models::PropVal {
+            page_id: row.page_id,
+            prop_id: row.prop_id,
+            value: Value::Str(row.value),
+        })
+        .fetch_all(db);
+
+        let multistrs = query_as!(
+            Qres::<Vec<String>>,
+            r#"select
+                m.page_id,
+                m.prop_id,
+                coalesce(
+                    array_agg(v.value order by v.value)
+                        filter (where v.value is not null),
+                    '{}'
+                ) as "value!"
+            from propval_multistr m
+            left join propval_multistr__value v
+                on v.propval_multistr_id = m.id
+            where m.page_id = ANY($1)
+            group by m.page_id, m.prop_id"#,
+            &query.page_ids
+        )
+        .map(|row| models::PropVal {
+            page_id: row.page_id,
+            prop_id: row.prop_id,
+            value: Value::MultiStr(row.value),
+        })
+        .fetch_all(db);
+
         let dates = query_as!(
             Qres::<chrono::NaiveDate>,
             "select page_id, prop_id, value
@@ -155,25 +424,237 @@ impl DbModel<PvGetQuery, PvListQuery> for models::PropVal {
         })
         .fetch_all(db);
 
-        let (bools, ints, floats, dates) = join!(bools, ints, floats, dates);
+        let datetimes = query_as!(
+            Qres::<chrono::DateTime<chrono::Utc>>,
+            "select page_id, prop_id, value
+            from propval_datetime
+            where page_id = ANY($1)",
+            &query.page_ids
+        )
+        .map(|row| models::PropVal {
+            page_id: row.page_id,
+            prop_id: row.prop_id,
+            value: Value::DateTime(row.value.with_timezone(&Tz::UTC)),
+        })
+        .fetch_all(db);
+
+        let selects = query_as!(
+            QresSelect,
+            r#"select
+                ps.page_id,
+                ps.prop_id,
+                po.id option_id,
+                po.name,
+                po.color,
+                po."order"
+            from propval_select ps
+            join property_option po on po.id = ps.value
+            where ps.page_id = ANY($1)"#,
+            &query.page_ids
+        )
+        .fetch_all(db);
+
+        let relations = query_as!(
+            QresRelation,
+            "select
+                r.page_id,
+                r.prop_id,
+                t.id target_id,
+                t.collection_id target_collection_id,
+                t.title target_title
+            from propval_relation r
+            join page t on t.id = r.target_page_id
+            where r.page_id = ANY($1)
+            order by t.title, t.id",
+            &query.page_ids
+        )
+        .fetch_all(db);
+
+        let files = query_as!(
+            QresFile,
+            "select
+                page_id,
+                prop_id,
+                id,
+                name,
+                stored_name,
+                mime_type,
+                size
+            from propval_file
+            where page_id = ANY($1)
+            order by id",
+            &query.page_ids
+        )
+        .fetch_all(db);
+
+        let (
+            bools,
+            ints,
+            floats,
+            strs,
+            multistrs,
+            dates,
+            datetimes,
+            selects,
+            relations,
+            files,
+        ) = join!(
+            bools, ints, floats, strs, multistrs, dates, datetimes, selects,
+            relations, files
+        );
 
         let bools = bools?;
         let ints = ints?;
         let floats = floats?;
+        let strs = strs?;
+        let multistrs = multistrs?;
         let dates = dates?;
+        let datetimes = datetimes?;
+        let selects = selects?
+            .drain(..)
+            .map(|row| row.into_propval())
+            .collect::<Result<Vec<_>>>()?;
+        let relations = group_relations(relations?);
+        let files = group_files(files?);
 
         let mut all_propvals = Vec::with_capacity(
-            bools.len() + ints.len() + floats.len() + dates.len(),
+            bools.len()
+                + ints.len()
+                + floats.len()
+                + strs.len()
+                + multistrs.len()
+                + dates.len()
+                + datetimes.len()
+                + selects.len()
+                + relations.len()
+                + files.len(),
         );
         all_propvals.extend_from_slice(&bools);
         all_propvals.extend_from_slice(&ints);
         all_propvals.extend_from_slice(&floats);
+        all_propvals.extend_from_slice(&strs);
+        all_propvals.extend_from_slice(&multistrs);
         all_propvals.extend_from_slice(&dates);
+        all_propvals.extend_from_slice(&datetimes);
+        all_propvals.extend_from_slice(&selects);
+        all_propvals.extend_from_slice(&relations);
+        all_propvals.extend_from_slice(&files);
 
         Ok(all_propvals)
     }
     async fn save(&self, db: &PgPool) -> Result<()> {
+        let mut tx = db.begin().await?;
+        constraint::db_ops::validate(&mut tx, self).await?;
+        self.save_in(&mut tx).await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+    async fn delete(self, db: &PgPool) -> Result<()> {
+        constraint::db_ops::validate_delete(db, &self).await?;
         match self.value {
+            Value::Formula(_) | Value::Rollup(_) => {
+                bail!("formula and rollup values are computed, not stored")
+            }
+            Value::CreatedTime(_)
+            | Value::LastEditedTime(_)
+            | Value::CreatedBy(_)
+            | Value::LastEditedBy(_) => {
+                bail!("system props are maintained by the application")
+            }
+            Value::Bool(_) => {
+                query!(
+                    "delete from propval_bool where page_id = $1 and prop_id = $2",
+                    self.page_id,
+                    self.prop_id
+                ).execute(db).await?
+            },
+            Value::Int(_) => {
+                query!(
+                    "delete from propval_int where page_id = $1 and prop_id = $2",
+                    self.page_id,
+                    self.prop_id
+                ).execute(db).await?
+            },
+            Value::Float(_) => {
+                query!(
+                    "delete from propval_float where page_id = $1 and prop_id = $2",
+                    self.page_id,
+                    self.prop_id
+                ).execute(db).await?
+            },
+            Value::Str(_) => {
+                query!(
+                    "delete from propval_str where page_id = $1 and prop_id = $2",
+                    self.page_id,
+                    self.prop_id
+                ).execute(db).await?
+            },
+            Value::MultiStr(_) => {
+                query!(
+                    "delete from propval_multistr where page_id = $1 and prop_id = $2",
+                    self.page_id,
+                    self.prop_id
+                ).execute(db).await?
+            },
+            Value::Date(_) => {
+                query!(
+                    "delete from propval_date where page_id = $1 and prop_id = $2",
+                    self.page_id,
+                    self.prop_id
+                ).execute(db).await?
+            },
+            Value::DateTime(_) => {
+                query!(
+                    "delete from propval_datetime where page_id = $1 and prop_id = $2",
+                    self.page_id,
+                    self.prop_id
+                ).execute(db).await?
+            },
+            Value::Select(_) => {
+                query!(
+                    "delete from propval_select where page_id = $1 and prop_id = $2",
+                    self.page_id,
+                    self.prop_id
+                ).execute(db).await?
+            },
+            Value::Relation(_) => {
+                query!(
+                    "delete from propval_relation where page_id = $1 and prop_id = $2",
+                    self.page_id,
+                    self.prop_id
+                ).execute(db).await?
+            },
+            Value::Files(_) => {
+                query!(
+                    "delete from propval_file where page_id = $1 and prop_id = $2",
+                    self.page_id,
+                    self.prop_id
+                ).execute(db).await?
+            },
+        };
+
+        Ok(())
+    }
+}
+
+impl models::PropVal {
+    /// Save the propval as part of a larger transaction, without the
+    /// constraint checks of [DbModel::save].
+    ///
+    /// The prop is locked until the transaction ends; see [lock_prop].
+    pub async fn save_in(&self, conn: &mut PgConnection) -> Result<()> {
+        lock_prop(&mut *conn, self.prop_id).await?;
+        match &self.value {
+            Value::Formula(_) | Value::Rollup(_) => {
+                bail!("formula and rollup values are computed, not stored")
+            }
+            Value::CreatedTime(_)
+            | Value::LastEditedTime(_)
+            | Value::CreatedBy(_)
+            | Value::LastEditedBy(_) => {
+                bail!("system props are maintained by the application")
+            }
             Value::Bool(val) => {
                 query!(
                     "insert into propval_bool (value, page_id, prop_id) values ($1, $2, $3)
@@ -182,7 +663,7 @@ impl DbModel<PvGetQuery, PvListQuery> for models::PropVal {
                     val,
                     self.page_id,
                     self.prop_id
-                ).execute(db).await?
+                ).execute(&mut *conn).await?
             },
             Value::Int(val) => {
                 query!(
@@ -192,7 +673,7 @@ impl DbModel<PvGetQuery, PvListQuery> for models::PropVal {
                     val,
                     self.page_id,
                     self.prop_id
-                ).execute(db).await?
+                ).execute(&mut *conn).await?
             },
             Value::Float(val) => {
                 query!(
@@ -202,7 +683,46 @@ impl DbModel<PvGetQuery, PvListQuery> for models::PropVal {
                     val,
                     self.page_id,
                     self.prop_id
-                ).execute(db).await?
+                ).execute(&mut *conn).await?
+            },
+            Value::Str(val) => {
+                query!(
+                    "insert into propval_str (value, page_id, prop_id) values ($1, $2, $3)
+                    on conflict (page_id, prop_id)
+                    do update set value = $1",
+                    val,
+                    self.page_id,
+                    self.prop_id
+                ).execute(&mut *conn).await?
+            },
+            Value::MultiStr(vals) => {
+                let parent = query!(
+                    "insert into propval_multistr (page_id, prop_id) values ($1, $2)
+                    on conflict (page_id, prop_id)
+                    do update set page_id = $1
+                    returning id",
+                    self.page_id,
+                    self.prop_id
+                ).fetch_one(&mut *conn).await?;
+                query!(
+                    "delete from propval_multistr__value
+                    where propval_multistr_id = $1",
+                    parent.id
+                ).execute(&mut *conn).await?;
+                query!(
+                    "insert into propval_multistr__value (value, propval_multistr_id)
+                    select distinct unnest($1::varchar[]), $2::int",
+                    vals,
+                    parent.id
+                ).execute(&mut *conn).await?;
+                let res = query!(
+                    "insert into propval_multistr__vocab (value, prop_id)
+                    select unnest($1::varchar[]), $2
+                    on conflict do nothing",
+                    vals,
+                    self.prop_id
+                ).execute(&mut *conn).await?;
+                res
             },
             Value::Date(val) => {
                 query!(
@@ -212,13 +732,273 @@ impl DbModel<PvGetQuery, PvListQuery> for models::PropVal {
                     val,
                     self.page_id,
                     self.prop_id
-                ).execute(db).await?
+                ).execute(&mut *conn).await?
+            },
+            Value::DateTime(val) => {
+                query!(
+                    "insert into propval_datetime (value, page_id, prop_id) values ($1, $2, $3)
+                    on conflict (page_id, prop_id)
+                    do update set value = $1",
+                    val.with_timezone(&chrono::Utc),
+                    self.page_id,
+                    self.prop_id
+                ).execute(&mut *conn).await?
+            },
+            Value::Select(option) => {
+                query!(
+                    "insert into propval_select (value, page_id, prop_id) values ($1, $2, $3)
+                    on conflict (page_id, prop_id)
+                    do update set value = $1",
+                    option.id,
+                    self.page_id,
+                    self.prop_id
+                ).execute(&mut *conn).await?
+            },
+            Value::Relation(pages) => {
+                let ids: Vec<i32> = pages.iter().map(|p| p.id).collect();
+                query!(
+                    "delete from propval_relation where page_id = $1 and prop_id = $2",
+                    self.page_id,
+                    self.prop_id
+                ).execute(&mut *conn).await?;
+                let res = query!(
+                    "insert into propval_relation (target_page_id, page_id, prop_id)
+                    select distinct unnest($1::int[]), $2::int, $3::int",
+                    &ids,
+                    self.page_id,
+                    self.prop_id
+                ).execute(&mut *conn).await?;
+                res
+            }
+            // Files are inserted as they are uploaded, by
+            // [crate::file::db_ops::upload], so saving only drops the ones
+            // which are no longer listed.
+            Value::Files(files) => {
+                let ids: Vec<i32> = files.iter().map(|f| f.id).collect();
+                query!(
+                    "delete from propval_file
+                    where page_id = $1 and prop_id = $2 and id != all($3)",
+                    self.page_id,
+                    self.prop_id,
+                    &ids
+                ).execute(&mut *conn).await?
             }
         };
 
         Ok(())
     }
-    async fn delete(self, _db: &PgPool) -> Result<()> {
-        todo!()
+}
+
+/// Lock a prop until the end of the transaction which is changing one of its
+/// values, so that a conversion of the prop can't drop the value; see
+/// [Prop::change_type].
+async fn lock_prop(conn: &mut PgConnection, prop_id: i32) -> Result<()> {
+    query!("select id from property where id = $1 for share", prop_id)
+        .fetch_one(&mut *conn)
+        .await?;
+
+    Ok(())
+}
+
+/// Add one tag to a page. Unlike saving the whole list of tags, this leaves
+/// the other tags alone, so concurrent edits of the same page don't undo
+/// each other.
+pub async fn add_tag(
+    db: &PgPool,
+    page_id: i32,
+    prop_id: i32,
+    tag: &str,
+    user_id: i32,
+) -> Result<()> {
+    let mut tx = db.begin().await?;
+    lock_prop(&mut tx, prop_id).await?;
+    let parent = query!(
+        "insert into propval_multistr (page_id, prop_id) values ($1, $2)
+        on conflict (page_id, prop_id)
+        do update set page_id = $1
+        returning id",
+        page_id,
+        prop_id
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+    let added = query!(
+        "insert into propval_multistr__value (value, propval_multistr_id)
+        values ($1, $2)
+        on conflict do nothing",
+        tag,
+        parent.id
+    )
+    .execute(&mut *tx)
+    .await?
+    .rows_affected()
+        > 0;
+    query!(
+        "insert into propval_multistr__vocab (value, prop_id) values ($1, $2)
+        on conflict do nothing",
+        tag,
+        prop_id
+    )
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+    if added {
+        touch_page(db, page_id, user_id).await?;
+    }
+
+    Ok(())
+}
+
+/// Remove one tag from a page; see [add_tag]. Removing the last tag of a
+/// required prop fails with a [constraint::models::ValidationError].
+pub async fn remove_tag(
+    db: &PgPool,
+    page_id: i32,
+    prop_id: i32,
+    tag: &str,
+    user_id: i32,
+) -> Result<()> {
+    struct Qres {
+        remaining: Option<i64>,
+    }
+    let mut tx = db.begin().await?;
+    lock_prop(&mut tx, prop_id).await?;
+    let removed = query!(
+        "delete from propval_multistr__value v
+        using propval_multistr m
+        where m.id = v.propval_multistr_id
+            and m.page_id = $1 and m.prop_id = $2 and v.value = $3",
+        page_id,
+        prop_id,
+        tag
+    )
+    .execute(&mut *tx)
+    .await?
+    .rows_affected()
+        > 0;
+    let res = query_as!(
+        Qres,
+        "select count(*) remaining
+        from propval_multistr__value v
+        join propval_multistr m on m.id = v.propval_multistr_id
+        where m.page_id = $1 and m.prop_id = $2",
+        page_id,
+        prop_id
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+    if res.remaining == Some(0) {
+        let empty = models::PropVal {
+            page_id,
+            prop_id,
+            value: Value::MultiStr(vec![]),
+        };
+        constraint::db_ops::validate(&mut tx, &empty).await?;
+    }
+    tx.commit().await?;
+    if removed {
+        touch_page(db, page_id, user_id).await?;
+    }
+
+    Ok(())
+}
+
+/// Link one page to another; like [add_tag], the other links are left
+/// alone.
+pub async fn add_relation(
+    db: &PgPool,
+    page_id: i32,
+    prop_id: i32,
+    target_page_id: i32,
+    user_id: i32,
+) -> Result<()> {
+    let mut tx = db.begin().await?;
+    lock_prop(&mut tx, prop_id).await?;
+    let added = query!(
+        "insert into propval_relation (page_id, prop_id, target_page_id)
+        values ($1, $2, $3)
+        on conflict do nothing",
+        page_id,
+        prop_id,
+        target_page_id
+    )
+    .execute(&mut *tx)
+    .await?
+    .rows_affected()
+        > 0;
+    tx.commit().await?;
+    if added {
+        touch_page(db, page_id, user_id).await?;
+    }
+
+    Ok(())
+}
+
+/// Unlink one page from another; see [add_relation]. Removing the last link
+/// of a required prop fails with a [constraint::models::ValidationError].
+pub async fn remove_relation(
+    db: &PgPool,
+    page_id: i32,
+    prop_id: i32,
+    target_page_id: i32,
+    user_id: i32,
+) -> Result<()> {
+    struct Qres {
+        remaining: Option<i64>,
     }
+    let mut tx = db.begin().await?;
+    lock_prop(&mut tx, prop_id).await?;
+    let removed = query!(
+        "delete from propval_relation
+        where page_id = $1 and prop_id = $2 and target_page_id = $3",
+        page_id,
+        prop_id,
+        target_page_id
+    )
+    .execute(&mut *tx)
+    .await?
+    .rows_affected()
+        > 0;
+    let res = query_as!(
+        Qres,
+        "select count(*) remaining from propval_relation
+        where page_id = $1 and prop_id = $2",
+        page_id,
+        prop_id
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+    if res.remaining == Some(0) {
+        let empty = models::PropVal {
+            page_id,
+            prop_id,
+            value: Value::Relation(vec![]),
+        };
+        constraint::db_ops::validate(&mut tx, &empty).await?;
+    }
+    tx.commit().await?;
+    if removed {
+        touch_page(db, page_id, user_id).await?;
+    }
+
+    Ok(())
+}
+
+/// Every tag which has been used for the multi-string prop `prop_id`, in
+/// alphabetical order. Used to populate tag autocomplete.
+pub async fn list_tag_vocab(db: &PgPool, prop_id: i32) -> Result<Vec<String>> {
+    struct Qres {
+        value: String,
+    }
+    let vocab = query_as!(
+        Qres,
+        "select value from propval_multistr__vocab
+        where prop_id = $1
+        order by value",
+        prop_id
+    )
+    .fetch_all(db)
+    .await?;
+
+    Ok(vocab.into_iter().map(|v| v.value).collect())
 }
@@ -5,8 +5,18 @@
 //! - `bool` (UI says, "checkbox")
 //! - `int`
 //! - `float` (UI says, "percent")
+//! - `string` (UI says, "text")
+//! - `multi-string` (UI says, "tags")
 //! - `date`
+//! - `datetime` (UI says, "date & time")
+//! - `select`
+//! - `relation` (links to pages in another collection)
+//! - `files` (uploads; see [crate::file])
+//! - `formula` (computed from other props; read-only, see [crate::formula])
+//! - `rollup` (aggregated over related pages; read-only, see [crate::rollup])
+//! - `created time`, `last edited time`, `created by`, `last edited by`
+//!   (system props stored on the page itself; read-only)
 
-mod components;
-mod db_ops;
+pub mod components;
+pub mod db_ops;
 pub mod models;
@@ -0,0 +1,144 @@
+use super::models::{Aggregate, RelationChoice};
+use crate::{
+    components::Component,
+    formula::models::type_name,
+    models::{Prop, ValueType},
+    routes::Route,
+};
+use ammonia::clean;
+use std::fmt::Write;
+
+/// Form for choosing what a rollup prop aggregates. Changing the relation
+/// re-renders the form with that relation's target props.
+pub struct RollupEditor<'a> {
+    pub prop: &'a Prop,
+    pub relations: &'a [RelationChoice],
+    pub relation_prop_id: Option<i32>,
+    /// Props in the linked collection which can be aggregated.
+    pub targets: &'a [Prop],
+    pub target_prop_id: Option<i32>,
+    pub aggregate: Aggregate,
+    /// The validation error from the last submission, if any.
+    pub error: Option<&'a str>,
+    /// Set after the rollup was saved successfully.
+    pub saved_type: Option<ValueType>,
+}
+impl Component for RollupEditor<'_> {
+    fn render(&self) -> String {
+        let prop_name = clean(&self.prop.name);
+        let rollup_route = Route::PropRollup(Some(self.prop.id));
+        let collection_route = Route::Collection(Some(self.prop.collection_id));
+        let relation_options =
+            self.relations.iter().fold(String::new(), |mut acc, r| {
+                let id = r.prop_id;
+                let selected = if Some(id) == self.relation_prop_id {
+                    "selected"
+                } else {
+                    ""
+                };
+                let name = if r.backlink {
+                    clean(&format!("{} (from {})", r.name, r.collection_name))
+                } else {
+                    clean(&r.name)
+                };
+                let _ = write!(
+                    acc,
+                    r#"<option {selected} value="{id}">{name}</option>"#
+                );
+                acc
+            });
+        let target_options = self.targets.iter().fold(
+            String::from(r#"<option value="">None</option>"#),
+            |mut acc, p| {
+                let id = p.id;
+                let selected = if Some(id) == self.target_prop_id {
+                    "selected"
+                } else {
+                    ""
+                };
+                let name = clean(&p.name);
+                let ty = type_name(p.type_id);
+                let _ = write!(
+                    acc,
+                    r#"<option {selected} value="{id}">{name} ({ty})</option>"#
+                );
+                acc
+            },
+        );
+        let aggregate_options = Aggregate::all().iter().fold(
+            String::new(),
+            |mut acc, aggregate| {
+                let id = aggregate.get_int_repr();
+                let selected = if *aggregate == self.aggregate {
+                    "selected"
+                } else {
+                    ""
+                };
+                let name = aggregate.get_display_name();
+                let _ = write!(
+                    acc,
+                    r#"<option {selected} value="{id}">{name}</option>"#
+                );
+                acc
+            },
+        );
+        let message = match (self.error, self.saved_type) {
+            (Some(error), _) => {
+                let error = clean(error);
+                format!(
+                    r#"<p class="text-red-600 dark:text-red-400">{error}</p>"#
+                )
+            }
+            (None, Some(ty)) => {
+                let ty = type_name(ty);
+                format!(
+                    r#"<p class="text-green-700 dark:text-green-400">Saved; this rollup produces {ty} values.</p>"#
+                )
+            }
+            (None, None) => "".into(),
+        };
+        if self.relations.is_empty() {
+            return format!(
+                r#"
+                <div class="flex flex-col gap-2 max-w-md">
+                    <a class="link" href="{collection_route}">Back</a>
+                    <h1 class="text-xl">Rollup for {prop_name}</h1>
+                    <p>Rollups aggregate over a relation, but there are no relations to or from this collection.</p>
+                </div>
+                "#
+            );
+        }
+        format!(
+            r#"
+            <div class="flex flex-col gap-2 max-w-md" hx-target="this" hx-swap="outerHTML">
+                <a class="link" href="{collection_route}">Back</a>
+                <h1 class="text-xl">Rollup for {prop_name}</h1>
+                <form class="flex flex-col gap-2" hx-post="{rollup_route}">
+                    <label for="relation_prop_id">Relation</label>
+                    <select
+                        class="dark:text-black"
+                        id="relation_prop_id"
+                        name="relation_prop_id"
+                        hx-get="{rollup_route}"
+                        hx-trigger="change"
+                    >{relation_options}</select>
+                    <label for="target_prop_id">Prop</label>
+                    <select
+                        class="dark:text-black"
+                        id="target_prop_id"
+                        name="target_prop_id"
+                    >{target_options}</select>
+                    <label for="aggregate_id">Aggregate</label>
+                    <select
+                        class="dark:text-black"
+                        id="aggregate_id"
+                        name="aggregate_id"
+                    >{aggregate_options}</select>
+                    {message}
+                    <button class="dark:bg-slate-700 w-36 dark:text-white dark:hover:bg-slate-600 transition shadow hover:shadow-none rounded p-1 block">Save</button>
+                </form>
+            </div>
+            "#
+        )
+    }
+}
@@ -0,0 +1,140 @@
+use super::models::{Aggregate, RelationChoice, Rollup};
+use crate::{db_ops::DbModel, models::ValueType};
+use anyhow::Result;
+use async_trait::async_trait;
+use sqlx::{query, query_as, PgPool};
+
+pub struct GetRollupQuery {
+    pub prop_id: i32,
+}
+
+pub struct ListRollupQuery {
+    pub collection_id: i32,
+}
+
+struct Qres {
+    prop_id: i32,
+    relation_prop_id: i32,
+    target_prop_id: Option<i32>,
+    aggregate_id: i32,
+    backlink: bool,
+    target_type_id: Option<i32>,
+}
+impl Qres {
+    fn into_rollup(self) -> Rollup {
+        Rollup {
+            prop_id: self.prop_id,
+            relation_prop_id: self.relation_prop_id,
+            target_prop_id: self.target_prop_id,
+            aggregate: Aggregate::from_int(self.aggregate_id),
+            backlink: self.backlink,
+            target_type: self.target_type_id.map(ValueType::from_int),
+        }
+    }
+}
+
+#[async_trait]
+impl DbModel<GetRollupQuery, ListRollupQuery> for Rollup {
+    async fn get(db: &PgPool, query: &GetRollupQuery) -> Result<Self> {
+        Ok(query_as!(
+            Qres,
+            r#"select
+                r.prop_id,
+                r.relation_prop_id,
+                r.target_prop_id,
+                r.aggregate_id,
+                rel.collection_id <> p.collection_id as "backlink!",
+                t.type_id as "target_type_id?"
+            from property_rollup r
+            join property p on p.id = r.prop_id
+            join property rel on rel.id = r.relation_prop_id
+            left join property t on t.id = r.target_prop_id
+            where r.prop_id = $1"#,
+            query.prop_id
+        )
+        .fetch_one(db)
+        .await?
+        .into_rollup())
+    }
+    async fn list(db: &PgPool, query: &ListRollupQuery) -> Result<Vec<Self>> {
+        Ok(query_as!(
+            Qres,
+            r#"select
+                r.prop_id,
+                r.relation_prop_id,
+                r.target_prop_id,
+                r.aggregate_id,
+                rel.collection_id <> p.collection_id as "backlink!",
+                t.type_id as "target_type_id?"
+            from property_rollup r
+            join property p on p.id = r.prop_id
+            join property rel on rel.id = r.relation_prop_id
+            left join property t on t.id = r.target_prop_id
+            where p.collection_id = $1"#,
+            query.collection_id
+        )
+        .fetch_all(db)
+        .await?
+        .into_iter()
+        .map(|row| row.into_rollup())
+        .collect())
+    }
+    async fn save(&self, db: &PgPool) -> Result<()> {
+        query!(
+            "insert into property_rollup
+                (prop_id, relation_prop_id, target_prop_id, aggregate_id)
+            values ($1, $2, $3, $4)
+            on conflict (prop_id)
+            do update set
+                relation_prop_id = $2,
+                target_prop_id = $3,
+                aggregate_id = $4",
+            self.prop_id,
+            self.relation_prop_id,
+            self.target_prop_id,
+            self.aggregate.get_int_repr()
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+    async fn delete(self, db: &PgPool) -> Result<()> {
+        query!(
+            "delete from property_rollup where prop_id = $1",
+            self.prop_id
+        )
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+}
+
+/// Relation props which a rollup in `collection_id` can aggregate over;
+/// relations belonging to the collection, and relations pointing at it.
+pub async fn list_relation_choices(
+    db: &PgPool,
+    collection_id: i32,
+) -> Result<Vec<RelationChoice>> {
+    Ok(query_as!(
+        RelationChoice,
+        r#"select
+            p.id prop_id,
+            p.name,
+            c.name collection_name,
+            p.collection_id <> $1 as "backlink!",
+            case when p.collection_id = $1
+                then pr.target_collection_id
+                else p.collection_id
+            end as "linked_collection_id!"
+        from property p
+        join property_relation pr on pr.prop_id = p.id
+        join collection c on c.id = p.collection_id
+        where p.collection_id = $1 or pr.target_collection_id = $1
+        order by p.collection_id <> $1, p."order", p.id"#,
+        collection_id
+    )
+    .fetch_all(db)
+    .await?)
+}
@@ -0,0 +1,16 @@
+//! Rollup props aggregate a prop over the pages linked through a relation
+//! prop; for example, the number of tickets in an epic, or the percentage of
+//! them which are completed. Like formulas, rollups are computed as part of
+//! [crate::db_ops::list_pages], so they can be sorted and filtered like any
+//! other numeric prop.
+//!
+//! The relation can either belong to the rollup's collection, or point at
+//! it from another collection. In the second case, the rollup aggregates
+//! over backlinks; an epic can count the tickets which link to it.
+
+pub mod components;
+pub mod db_ops;
+pub mod models;
+mod sql;
+
+pub use sql::compile;
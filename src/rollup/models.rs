@@ -0,0 +1,115 @@
+use crate::models::ValueType;
+use anyhow::{bail, Result};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aggregate {
+    Count,
+    Sum,
+    Average,
+    Min,
+    Max,
+    PercentTrue,
+}
+
+impl Aggregate {
+    pub fn from_int(int: i32) -> Self {
+        match int {
+            1 => Self::Count,
+            2 => Self::Sum,
+            3 => Self::Average,
+            4 => Self::Min,
+            5 => Self::Max,
+            6 => Self::PercentTrue,
+            _ => panic!("{int} is not a valid Aggregate"),
+        }
+    }
+    pub fn get_int_repr(&self) -> i32 {
+        match self {
+            Self::Count => 1,
+            Self::Sum => 2,
+            Self::Average => 3,
+            Self::Min => 4,
+            Self::Max => 5,
+            Self::PercentTrue => 6,
+        }
+    }
+    pub fn get_display_name(&self) -> &'static str {
+        match self {
+            Self::Count => "Count",
+            Self::Sum => "Sum",
+            Self::Average => "Average",
+            Self::Min => "Min",
+            Self::Max => "Max",
+            Self::PercentTrue => "Percent Checked",
+        }
+    }
+    pub fn all() -> [Self; 6] {
+        [
+            Self::Count,
+            Self::Sum,
+            Self::Average,
+            Self::Min,
+            Self::Max,
+            Self::PercentTrue,
+        ]
+    }
+    /// The type of the rollup's values, given the type of the target prop.
+    /// Count ignores the target prop, so it's the only aggregate which works
+    /// without one.
+    pub fn get_result_type(
+        &self,
+        target_type: Option<ValueType>,
+    ) -> Result<ValueType> {
+        Ok(match (self, target_type) {
+            (Self::Count, _) => ValueType::Int,
+            (Self::Sum | Self::Min | Self::Max, Some(ValueType::Int)) => {
+                ValueType::Int
+            }
+            (Self::Sum | Self::Min | Self::Max, Some(ValueType::Float)) => {
+                ValueType::Float
+            }
+            (Self::Average, Some(ValueType::Int | ValueType::Float)) => {
+                ValueType::Float
+            }
+            (Self::PercentTrue, Some(ValueType::Bool)) => ValueType::Float,
+            (Self::PercentTrue, _) => {
+                bail!("percent checked needs a checkbox prop")
+            }
+            (_, None) => {
+                bail!("{} needs a prop to aggregate", self.get_display_name())
+            }
+            (_, Some(_)) => bail!(
+                "{} needs an integer or number prop",
+                self.get_display_name()
+            ),
+        })
+    }
+}
+
+/// A rollup as it's stored on a prop.
+#[derive(Debug, Clone)]
+pub struct Rollup {
+    pub prop_id: i32,
+    pub relation_prop_id: i32,
+    /// Unused by [Aggregate::Count].
+    pub target_prop_id: Option<i32>,
+    pub aggregate: Aggregate,
+    /// True if the relation prop belongs to another collection and points
+    /// at this one. This is loaded from the relation prop; it isn't saved.
+    pub backlink: bool,
+    /// Loaded from the target prop; it isn't saved.
+    pub target_type: Option<ValueType>,
+}
+
+/// A relation prop which a rollup in some collection can aggregate over.
+#[derive(Debug, Clone)]
+pub struct RelationChoice {
+    pub prop_id: i32,
+    pub name: String,
+    /// The collection which the relation prop belongs to.
+    pub collection_name: String,
+    pub backlink: bool,
+    /// The collection whose pages are being aggregated over; this is where
+    /// target props come from.
+    pub linked_collection_id: i32,
+}
@@ -0,0 +1,134 @@
+//! Compiling rollups into scalar subqueries for [crate::db_ops::list_pages],
+//! where the page being listed is `page`.
+
+use super::models::{Aggregate, Rollup};
+use crate::models::ValueType;
+use anyhow::Result;
+
+/// Returns the SQL for a rollup's value and the type of that value.
+pub fn compile(rollup: &Rollup) -> Result<(String, ValueType)> {
+    let result_type = rollup.aggregate.get_result_type(rollup.target_type)?;
+    // `own_col` holds the page being listed, and `linked_col` holds the pages
+    // we are aggregating over.
+    let (own_col, linked_col) = if rollup.backlink {
+        ("target_page_id", "page_id")
+    } else {
+        ("page_id", "target_page_id")
+    };
+    let target_join = match (rollup.aggregate, rollup.target_prop_id) {
+        (Aggregate::Count, _) | (_, None) => String::new(),
+        (_, Some(target_prop_id)) => {
+            let table = match rollup.target_type {
+                Some(ValueType::Bool) => "propval_bool",
+                Some(ValueType::Int) => "propval_int",
+                _ => "propval_float",
+            };
+            format!(
+                "left join {table} t
+                    on t.page_id = r.{linked_col}
+                    and t.prop_id = {target_prop_id}"
+            )
+        }
+    };
+    let aggregate = match (rollup.aggregate, result_type) {
+        (Aggregate::Count, _) => "count(*)".into(),
+        // The sum of bigints is numeric, and might not fit back into a
+        // bigint; sums which don't are empty, rather than an error for the
+        // whole page list.
+        (Aggregate::Sum, ValueType::Int) => format!(
+            "case when sum(t.value) between {} and {}
+                then sum(t.value)::bigint end",
+            i64::MIN,
+            i64::MAX
+        ),
+        (Aggregate::Sum, _) => "sum(t.value)::float8".into(),
+        (Aggregate::Average, _) => "avg(t.value)::float8".into(),
+        (Aggregate::Min, _) => "min(t.value)".into(),
+        (Aggregate::Max, _) => "max(t.value)".into(),
+        // Linked pages with no value count as unchecked, and there is no
+        // percentage at all if there are no linked pages.
+        (Aggregate::PercentTrue, _) => {
+            "(100.0 * count(*) filter (where t.value)
+                / nullif(count(*), 0))::float8"
+                .to_string()
+        }
+    };
+    let relation_prop_id = rollup.relation_prop_id;
+
+    Ok((
+        format!(
+            "(select {aggregate}
+            from propval_relation r
+            {target_join}
+            where r.{own_col} = page.id and r.prop_id = {relation_prop_id})"
+        ),
+        result_type,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rollup(aggregate: Aggregate, target_type: Option<ValueType>) -> Rollup {
+        Rollup {
+            prop_id: 3,
+            relation_prop_id: 1,
+            target_prop_id: target_type.map(|_| 2),
+            aggregate,
+            backlink: true,
+            target_type,
+        }
+    }
+
+    #[test]
+    fn test_compile_result_types() {
+        let cases = [
+            (Aggregate::Count, None, ValueType::Int),
+            (Aggregate::Count, Some(ValueType::Str), ValueType::Int),
+            (Aggregate::Sum, Some(ValueType::Int), ValueType::Int),
+            (Aggregate::Sum, Some(ValueType::Float), ValueType::Float),
+            (Aggregate::Average, Some(ValueType::Int), ValueType::Float),
+            (Aggregate::Max, Some(ValueType::Int), ValueType::Int),
+            (
+                Aggregate::PercentTrue,
+                Some(ValueType::Bool),
+                ValueType::Float,
+            ),
+        ];
+        for (aggregate, target_type, expected) in cases {
+            let (sql, ty) = compile(&rollup(aggregate, target_type)).unwrap();
+            assert_eq!(ty, expected, "{aggregate:?}");
+            assert!(sql.contains("r.target_page_id = page.id"), "{sql}");
+        }
+    }
+
+    #[test]
+    fn test_compile_int_sum_is_checked() {
+        let (sql, _) =
+            compile(&rollup(Aggregate::Sum, Some(ValueType::Int))).unwrap();
+        assert!(
+            sql.contains(
+                "case when sum(t.value) between -9223372036854775808 and \
+                9223372036854775807"
+            ),
+            "{sql}"
+        );
+    }
+
+    #[test]
+    fn test_compile_unsupported_targets() {
+        let cases = [
+            (Aggregate::Sum, None),
+            (Aggregate::Sum, Some(ValueType::Bool)),
+            (Aggregate::Average, Some(ValueType::Date)),
+            (Aggregate::PercentTrue, Some(ValueType::Int)),
+        ];
+        for (aggregate, target_type) in cases {
+            assert!(
+                compile(&rollup(aggregate, target_type)).is_err(),
+                "{aggregate:?} of {target_type:?}"
+            );
+        }
+    }
+}
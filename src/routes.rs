@@ -1,7 +1,10 @@
 //! All possible routes with their params are defined in a big enum.
 
-use super::{controllers, models};
-use axum::routing::{delete, get, post, Router};
+use super::{config, controllers, filter::models::FilterKind, models};
+use axum::{
+    extract::DefaultBodyLimit,
+    routing::{delete, get, post, Router},
+};
 
 /// This enum contains all of the route strings in the application. This
 /// solves several problems.
@@ -20,58 +23,153 @@ use axum::routing::{delete, get, post, Router};
 /// are provided, we'll construct the route with the `:id` template in it
 /// for the Axum router.
 pub enum Route {
+    /// The collection, opened to its default view; see
+    /// [crate::view::db_ops::get_default_view]. Accepts the query params of
+    /// [crate::filter::models::AdHocQuery], like [Route::ViewListPages].
     Collection(Option<i32>),
+    /// The collection, opened to one of its views. The first param is the
+    /// collection ID, and the second is the view ID.
+    CollectionView(Option<(i32, i32)>),
+    /// Has POST, creating a view and redirecting to it.
+    CollectionViews(Option<i32>),
     CollectionPageSubmission(Option<i32>),
     CollectionNewPageForm(Option<i32>),
-    CollectionListPages(Option<i32>),
     CollectionChangePropOrder(Option<i32>),
+    /// Has GET (returning the prop management screen) and POST (creating a
+    /// prop)
+    CollectionProps(Option<i32>),
+    /// Returns the form for creating a prop. Accepts a `type_id` query
+    /// param, since relation props also need a collection to link to.
+    CollectionNewPropForm(Option<i32>),
     CollectionIncrementPropOrder(Option<(i32, i32)>),
     CollectionDecrementPropOrder(Option<(i32, i32)>),
-    CollectionShowFilterToolbar(Option<i32>),
-    CollectionHideFilterToolbar(Option<i32>),
-    CollectionChoosePropForFilter(Option<i32>),
-    CollectionAddFilterButton(Option<i32>),
-    CollectionShowSortToolbar(Option<i32>),
-    CollectionHideSortToolbar(Option<i32>),
-    CollectionSort(Option<i32>),
-    PropNewFilterTypeSelect(Option<i32>),
-    PropNewBoolFilter(Option<i32>),
-    PropNewIntFilter(Option<i32>),
-    PropNewIntRngFilter(Option<i32>),
-    PropNewFloatFilter(Option<i32>),
-    PropNewFloatRngFilter(Option<i32>),
-    PropNewDateFilter(Option<i32>),
-    PropNewDateRngFilter(Option<i32>),
-    FilterBoolChip(Option<i32>),
-    /// Has GET (returning a form), POST (accepting submission), and DELETE
-    FilterBool(Option<i32>),
-    FilterIntChip(Option<i32>),
-    /// Has GET (returning a form), POST (accepting submission), and DELETE
-    FilterInt(Option<i32>),
-    FilterIntRngChip(Option<i32>),
-    /// Has GET (returning a form), POST (accepting submission), and DELETE
-    FilterIntRng(Option<i32>),
-    FilterFloatChip(Option<i32>),
-    FilterFloat(Option<i32>),
-    FilterFloatRngChip(Option<i32>),
-    /// Has GET (returning a form), POST (accepting submission), and DELETE
-    FilterFloatRng(Option<i32>),
-    FilterDateChip(Option<i32>),
-    FilterDate(Option<i32>),
-    FilterDateRngChip(Option<i32>),
-    /// Has GET (returning a form), POST (accepting submission), and DELETE
-    FilterDateRng(Option<i32>),
+    /// Has GET (returning a form) and POST (accepting submission) for the
+    /// prefix of the collection's page keys.
+    CollectionKeyPrefix(Option<i32>),
+    /// Has GET (listing the collection's page templates) and POST (creating
+    /// a template).
+    CollectionTemplates(Option<i32>),
+    /// Has GET (returning the view settings), POST (saving the name and
+    /// layout of the view) and DELETE
+    View(Option<i32>),
+    /// Accepts `page` and `search` query params, along with those of
+    /// [crate::filter::models::AdHocQuery].
+    ViewListPages(Option<i32>),
+    /// Shows the prop's column if it is hidden, or hides it if it is shown.
+    /// Returns the view settings.
+    ViewPropVisibility(Option<(i32, i32)>),
+    ViewIncrementPropOrder(Option<(i32, i32)>),
+    ViewDecrementPropOrder(Option<(i32, i32)>),
+    ViewShowFilterToolbar(Option<i32>),
+    ViewHideFilterToolbar(Option<i32>),
+    ViewChoosePropForFilter(Option<i32>),
+    ViewAddFilterButton(Option<i32>),
+    /// Creates a filter on the titles of the collection's pages. Accepts a
+    /// `group_id` query param, like [Route::ViewNewFilter].
+    ViewNewTitleFilter(Option<i32>),
+    ViewNewFilterTypeSelect(Option<(i32, i32)>),
+    /// Accepts `type_id` and `group_id` query params. The type defaults to
+    /// [FilterKind::get_default_type].
+    ViewNewFilter(Option<(i32, i32, FilterKind)>),
+    /// Saves the filters and sort of the
+    /// [crate::filter::models::AdHocQuery] in the query string to the view,
+    /// then redirects to it.
+    ViewSaveAdHocFilters(Option<i32>),
+    ViewShowSortToolbar(Option<i32>),
+    ViewHideSortToolbar(Option<i32>),
+    ViewSort(Option<i32>),
+    /// Downloads every page in the view as CSV, with its filters, sorting,
+    /// and columns.
+    ViewExport(Option<i32>),
+    /// Has GET (returning the template editor), POST (saving the template)
+    /// and DELETE
+    Template(Option<i32>),
+    /// Has POST (adding an empty subgroup to the group) and DELETE
+    /// (deleting the group, along with everything in it). Both return the
+    /// whole filter toolbar.
+    FilterGroup(Option<i32>),
+    /// Switches the group between matching all and any of its filters.
+    FilterGroupConjunction(Option<i32>),
+    /// Has POST (renaming the prop) and DELETE
+    Prop(Option<i32>),
+    /// Returns a confirmation for deleting the prop, with the number of
+    /// values which would be lost.
+    PropDeleteConfirmation(Option<i32>),
+    /// Has GET (returning the type change form) and POST (changing the
+    /// type). The form accepts a `type_id` query param, and previews the
+    /// conversion to that type.
+    PropType(Option<i32>),
+    /// Has GET (returning the constraint form) and POST (saving the
+    /// constraints).
+    PropConstraints(Option<i32>),
+    /// Has GET (returning a form) and POST (accepting submission) for the
+    /// display format of an int or float prop.
+    PropNumberFormat(Option<i32>),
+    /// Returns `<option>` elements for every tag ever used by a multi-string
+    /// prop, for use inside of a `<datalist>`.
+    PropTagVocab(Option<i32>),
+    /// Has GET (returning the option editor) and POST (adding an option)
+    PropOptions(Option<i32>),
+    /// Returns `<option>` elements for a select prop's options. Accepts a
+    /// `selected` query param.
+    PropOptionList(Option<i32>),
+    /// Has DELETE
+    PropOption(Option<(i32, i32)>),
+    /// Returns `<option>` elements for every page in a relation prop's target
+    /// collection. Accepts a `selected` query param.
+    PropRelationOptions(Option<i32>),
+    /// Has GET (returning the formula editor) and POST (saving the
+    /// expression)
+    PropFormula(Option<i32>),
+    /// Has GET (returning the rollup editor) and POST (saving the rollup).
+    /// The GET accepts a `relation_prop_id` query param, to show the target
+    /// props for a different relation.
+    PropRollup(Option<i32>),
+    FilterChip(Option<(FilterKind, i32)>),
+    /// Has GET (returning a form), POST (accepting submission), and DELETE.
+    /// Also used for filters on the page title.
+    Filter(Option<(FilterKind, i32)>),
+    /// POST toggles whether the filter is enabled, returning its chip.
+    FilterEnabled(Option<(FilterKind, i32)>),
+    /// Returns `<option>` elements for every user. Accepts a `selected` query
+    /// param.
+    UserOptions,
     Page(Option<i32>),
+    /// Redirects to [Route::Page] for the page with a key like `NC-113`.
+    PageByKey(Option<models::PageKey>),
     PageSubmit,
     PageContent(Option<i32>),
     PageBoolProp(Option<(i32, i32)>),
     PageIntProp(Option<(i32, i32)>),
     PageFloatProp(Option<(i32, i32)>),
+    PageStrProp(Option<(i32, i32)>),
+    /// Has POST (add a tag) and DELETE (remove a tag)
+    PageMultiStrPropTag(Option<(i32, i32)>),
     PageDateProp(Option<(i32, i32)>),
+    PageDateTimeProp(Option<(i32, i32)>),
+    /// Has GET (returning a dropdown) and POST (accepting submission). There
+    /// is no `PageNewSelectProp`, since the dropdown works for empty propvals
+    /// too.
+    PageSelectProp(Option<(i32, i32)>),
+    /// Has POST (add a link) and DELETE (remove a link)
+    PageRelationProp(Option<(i32, i32)>),
+    /// Has POST (upload files, as `multipart/form-data`) and DELETE (remove
+    /// a file)
+    PageFilesProp(Option<(i32, i32)>),
+    /// Streams the contents of an uploaded file. See [get_file_routes].
+    File(Option<i32>),
     PageNewBoolProp(Option<(i32, i32)>),
     PageNewIntProp(Option<(i32, i32)>),
     PageNewFloatProp(Option<(i32, i32)>),
+    PageNewStrProp(Option<(i32, i32)>),
     PageNewDateProp(Option<(i32, i32)>),
+    PageNewDateTimeProp(Option<(i32, i32)>),
+    /// Has GET (returning a form) and POST (accepting submission) for the
+    /// timezone used to display and accept datetimes, and the first day of
+    /// the week.
+    UserTimezone,
+    /// Searches pages in every collection. Accepts a `q` query param.
+    Search,
     Root,
     Ping,
     Register,
@@ -88,6 +186,16 @@ impl Route {
                 Some(id) => format!("/collection/{id}"),
                 None => "/collection/:id".into(),
             },
+            Self::CollectionView(params) => match params {
+                Some((collection_id, view_id)) => {
+                    format!("/collection/{collection_id}/view/{view_id}")
+                }
+                None => "/collection/:collection_id/view/:view_id".into(),
+            },
+            Self::CollectionViews(params) => match params {
+                Some(id) => format!("/collection/{id}/views"),
+                None => "/collection/:id/views".into(),
+            },
             Self::CollectionPageSubmission(params) => match params {
                 Some(id) => format!("/collection/{id}"),
                 None => "/collection/:id".into(),
@@ -96,14 +204,18 @@ impl Route {
                 Some(id) => format!("/collection/{id}/new-page"),
                 None => "/collection/:id/new-page".into(),
             },
-            Self::CollectionListPages(params) => match params {
-                Some(id) => format!("/collection/{id}/list-pages"),
-                None => "/collection/:id/list-pages".into(),
-            },
             Self::CollectionChangePropOrder(params) => match params {
                 Some(id) => format!("/collection/{id}/prop-order"),
                 None => "/collection/:id/prop-order".into(),
             },
+            Self::CollectionProps(params) => match params {
+                Some(id) => format!("/collection/{id}/props"),
+                None => "/collection/:id/props".into(),
+            },
+            Self::CollectionNewPropForm(params) => match params {
+                Some(id) => format!("/collection/{id}/new-prop-form"),
+                None => "/collection/:id/new-prop-form".into(),
+            },
             Self::CollectionIncrementPropOrder(params) => match params {
                 Some((collection_id, prop_id)) => {
                     format!("/collection/{collection_id}/prop/{prop_id}/up")
@@ -116,126 +228,177 @@ impl Route {
                 }
                 None => "/collection/:collection_id/prop/:prop_id/down".into(),
             },
-            Route::CollectionShowFilterToolbar(params) => match params {
-                Some(id) => format!("/collection/{id}/show-filter-toolbar"),
-                None => "/collection/:id/show-filter-toolbar".into(),
+            Self::CollectionKeyPrefix(params) => match params {
+                Some(id) => format!("/collection/{id}/key-prefix"),
+                None => "/collection/:id/key-prefix".into(),
             },
-            Self::CollectionHideFilterToolbar(params) => match params {
-                Some(id) => format!("/collection/{id}/hide-sort-toolbar"),
-                None => "/collection/:id/hide-sort-toolbar".into(),
+            Self::CollectionTemplates(params) => match params {
+                Some(id) => format!("/collection/{id}/templates"),
+                None => "/collection/:id/templates".into(),
             },
-            Self::CollectionChoosePropForFilter(params) => match params {
-                Some(id) => format!("/collection/{id}/choose-prop-for-filter"),
-                None => "/collection/:id/choose-prop-for-filter".into(),
+            Self::View(params) => match params {
+                Some(id) => format!("/view/{id}"),
+                None => "/view/:id".into(),
             },
-            Self::CollectionAddFilterButton(params) => match params {
-                Some(id) => format!("/collection/{id}/add-filter-button"),
-                None => "/collection/:id/add-filter-button".into(),
+            Self::ViewListPages(params) => match params {
+                Some(id) => format!("/view/{id}/list-pages"),
+                None => "/view/:id/list-pages".into(),
             },
-            Self::CollectionHideSortToolbar(params) => match params {
-                Some(id) => format!("/collection/{id}/hide-filter-toolbar"),
-                None => "/collection/:id/hide-filter-toolbar".into(),
+            Self::ViewPropVisibility(params) => match params {
+                Some((view_id, prop_id)) => {
+                    format!("/view/{view_id}/prop/{prop_id}/visible")
+                }
+                None => "/view/:view_id/prop/:prop_id/visible".into(),
             },
-            Route::CollectionShowSortToolbar(params) => match params {
-                Some(id) => format!("/collection/{id}/show-sort-toolbar"),
-                None => "/collection/:id/show-sort-toolbar".into(),
+            Self::ViewIncrementPropOrder(params) => match params {
+                Some((view_id, prop_id)) => {
+                    format!("/view/{view_id}/prop/{prop_id}/up")
+                }
+                None => "/view/:view_id/prop/:prop_id/up".into(),
             },
-            Route::CollectionSort(params) => match params {
-                Some(id) => format!("/collection/{id}/sort"),
-                None => "/collection/:id/sort".into(),
+            Self::ViewDecrementPropOrder(params) => match params {
+                Some((view_id, prop_id)) => {
+                    format!("/view/{view_id}/prop/{prop_id}/down")
+                }
+                None => "/view/:view_id/prop/:prop_id/down".into(),
+            },
+            Self::ViewShowFilterToolbar(params) => match params {
+                Some(id) => format!("/view/{id}/show-filter-toolbar"),
+                None => "/view/:id/show-filter-toolbar".into(),
+            },
+            Self::ViewHideFilterToolbar(params) => match params {
+                Some(id) => format!("/view/{id}/hide-filter-toolbar"),
+                None => "/view/:id/hide-filter-toolbar".into(),
+            },
+            Self::ViewChoosePropForFilter(params) => match params {
+                Some(id) => format!("/view/{id}/choose-prop-for-filter"),
+                None => "/view/:id/choose-prop-for-filter".into(),
+            },
+            Self::ViewAddFilterButton(params) => match params {
+                Some(id) => format!("/view/{id}/add-filter-button"),
+                None => "/view/:id/add-filter-button".into(),
+            },
+            Self::ViewNewTitleFilter(params) => match params {
+                Some(id) => format!("/view/{id}/new-title-filter"),
+                None => "/view/:id/new-title-filter".into(),
+            },
+            Self::ViewNewFilterTypeSelect(params) => match params {
+                Some((view_id, prop_id)) => format!(
+                    "/view/{view_id}/prop/{prop_id}/new-filter-type-select"
+                ),
+                None => {
+                    "/view/:view_id/prop/:prop_id/new-filter-type-select".into()
+                }
             },
-            Self::PropNewFilterTypeSelect(params) => match params {
-                Some(id) => format!("/prop/{id}/new-filter-type-select"),
-                None => "/prop/:id/new-filter-type-select".into(),
+            Self::ViewNewFilter(params) => match params {
+                Some((view_id, prop_id, kind)) => {
+                    format!("/view/{view_id}/prop/{prop_id}/new-filter/{kind}")
+                }
+                None => "/view/:view_id/prop/:prop_id/new-filter/:kind".into(),
             },
-            Self::PropNewBoolFilter(params) => match params {
-                Some(id) => format!("/prop/{id}/new-bool-filter"),
-                None => "/prop/:id/new-bool-filter".into(),
+            Self::ViewSaveAdHocFilters(params) => match params {
+                Some(id) => format!("/view/{id}/save-adhoc-filters"),
+                None => "/view/:id/save-adhoc-filters".into(),
             },
-            Self::PropNewIntFilter(params) => match params {
-                Some(id) => format!("/prop/{id}/new-int-filter"),
-                None => "/prop/:id/new-int-filter".into(),
+            Self::ViewShowSortToolbar(params) => match params {
+                Some(id) => format!("/view/{id}/show-sort-toolbar"),
+                None => "/view/:id/show-sort-toolbar".into(),
             },
-            Self::PropNewIntRngFilter(params) => match params {
-                Some(id) => format!("/prop/{id}/new-int-rng-filter"),
-                None => "/prop/:id/new-int-rng-filter".into(),
+            Self::ViewHideSortToolbar(params) => match params {
+                Some(id) => format!("/view/{id}/hide-sort-toolbar"),
+                None => "/view/:id/hide-sort-toolbar".into(),
             },
-            Self::PropNewFloatFilter(params) => match params {
-                Some(id) => format!("/prop/{id}/new-float-filter"),
-                None => "/prop/:id/new-float-filter".into(),
+            Self::ViewSort(params) => match params {
+                Some(id) => format!("/view/{id}/sort"),
+                None => "/view/:id/sort".into(),
             },
-            Self::PropNewFloatRngFilter(params) => match params {
-                Some(id) => format!("/prop/{id}/new-float-rng-filter"),
-                None => "/prop/:id/new-float-rng-filter".into(),
+            Self::ViewExport(params) => match params {
+                Some(id) => format!("/view/{id}/export.csv"),
+                None => "/view/:id/export.csv".into(),
             },
-            Self::PropNewDateFilter(params) => match params {
-                Some(id) => format!("/prop/{id}/new-date-filter"),
-                None => "/prop/:id/new-date-filter".into(),
+            Self::Template(params) => match params {
+                Some(id) => format!("/template/{id}"),
+                None => "/template/:id".into(),
             },
-            Self::PropNewDateRngFilter(params) => match params {
-                Some(id) => format!("/prop/{id}/new-date-rng-filter"),
-                None => "/prop/:id/new-date-rng-filter".into(),
+            Self::FilterGroup(params) => match params {
+                Some(id) => format!("/filter-group/{id}"),
+                None => "/filter-group/:id".into(),
             },
-            Self::FilterBoolChip(params) => match params {
-                Some(id) => format!("/filter/bool/{id}/chip"),
-                None => "/filter/bool/:id/chip".into(),
+            Self::FilterGroupConjunction(params) => match params {
+                Some(id) => format!("/filter-group/{id}/conjunction"),
+                None => "/filter-group/:id/conjunction".into(),
             },
-            Self::FilterBool(params) => match params {
-                Some(id) => format!("/filter/bool/{id}"),
-                None => "/filter/bool/:id".into(),
+            Self::Prop(params) => match params {
+                Some(id) => format!("/prop/{id}"),
+                None => "/prop/:id".into(),
             },
-            Self::FilterIntChip(params) => match params {
-                Some(id) => format!("/filter/int/{id}/chip"),
-                None => "/filter/int/:id/chip".into(),
+            Self::PropDeleteConfirmation(params) => match params {
+                Some(id) => format!("/prop/{id}/delete"),
+                None => "/prop/:id/delete".into(),
             },
-            Self::FilterInt(params) => match params {
-                Some(id) => format!("/filter/int/{id}"),
-                None => "/filter/int/:id".into(),
+            Self::PropType(params) => match params {
+                Some(id) => format!("/prop/{id}/type"),
+                None => "/prop/:id/type".into(),
             },
-            Self::FilterIntRngChip(params) => match params {
-                Some(id) => format!("/filter/int-rng/{id}/chip"),
-                None => "/filter/int-rng/:id/chip".into(),
+            Self::PropConstraints(params) => match params {
+                Some(id) => format!("/prop/{id}/constraints"),
+                None => "/prop/:id/constraints".into(),
             },
-            Self::FilterIntRng(params) => match params {
-                Some(id) => format!("/filter/int-rng/{id}"),
-                None => "/filter/int-rng/:id".into(),
+            Self::PropNumberFormat(params) => match params {
+                Some(id) => format!("/prop/{id}/number-format"),
+                None => "/prop/:id/number-format".into(),
             },
-            Self::FilterFloatChip(params) => match params {
-                Some(id) => format!("/filter/float/{id}/chip"),
-                None => "/filter/float/:id/chip".into(),
+            Self::PropTagVocab(params) => match params {
+                Some(id) => format!("/prop/{id}/tags"),
+                None => "/prop/:id/tags".into(),
             },
-            Self::FilterFloat(params) => match params {
-                Some(id) => format!("/filter/float/{id}"),
-                None => "/filter/float/:id".into(),
+            Self::PropOptions(params) => match params {
+                Some(id) => format!("/prop/{id}/options"),
+                None => "/prop/:id/options".into(),
             },
-            Self::FilterFloatRngChip(params) => match params {
-                Some(id) => format!("/filter/float-rng/{id}/chip"),
-                None => "/filter/float-rng/:id/chip".into(),
+            Self::PropOptionList(params) => match params {
+                Some(id) => format!("/prop/{id}/option-list"),
+                None => "/prop/:id/option-list".into(),
             },
-            Self::FilterFloatRng(params) => match params {
-                Some(id) => format!("/filter/float-rng/{id}"),
-                None => "/filter/float-rng/:id".into(),
+            Self::PropOption(params) => match params {
+                Some((prop_id, option_id)) => {
+                    format!("/prop/{prop_id}/option/{option_id}")
+                }
+                None => "/prop/:prop_id/option/:option_id".into(),
             },
-            Self::FilterDateChip(params) => match params {
-                Some(id) => format!("/filter/date/{id}/chip"),
-                None => "/filter/date/:id/chip".into(),
+            Self::PropRelationOptions(params) => match params {
+                Some(id) => format!("/prop/{id}/relation-options"),
+                None => "/prop/:id/relation-options".into(),
             },
-            Self::FilterDate(params) => match params {
-                Some(id) => format!("/filter/date/{id}"),
-                None => "/filter/date/:id".into(),
+            Self::PropFormula(params) => match params {
+                Some(id) => format!("/prop/{id}/formula"),
+                None => "/prop/:id/formula".into(),
             },
-            Self::FilterDateRngChip(params) => match params {
-                Some(id) => format!("/filter/date-rng/{id}/chip"),
-                None => "/filter/date-rng/:id/chip".into(),
+            Self::PropRollup(params) => match params {
+                Some(id) => format!("/prop/{id}/rollup"),
+                None => "/prop/:id/rollup".into(),
             },
-            Self::FilterDateRng(params) => match params {
-                Some(id) => format!("/filter/date-rng/{id}"),
-                None => "/filter/date-rng/:id".into(),
+            Self::FilterChip(params) => match params {
+                Some((kind, id)) => format!("/filter/{kind}/{id}/chip"),
+                None => "/filter/:kind/:id/chip".into(),
             },
+            Self::Filter(params) => match params {
+                Some((kind, id)) => format!("/filter/{kind}/{id}"),
+                None => "/filter/:kind/:id".into(),
+            },
+            Self::FilterEnabled(params) => match params {
+                Some((kind, id)) => format!("/filter/{kind}/{id}/enabled"),
+                None => "/filter/:kind/:id/enabled".into(),
+            },
+            Self::UserOptions => "/user-options".into(),
             Self::Page(params) => match params {
                 Some(id) => format!("/page/{id}"),
                 None => "/page/:page_id".into(),
             },
+            Self::PageByKey(params) => match params {
+                Some(key) => format!("/p/{key}"),
+                None => "/p/:key".into(),
+            },
             Self::PageSubmit => "/page".into(),
             Self::PageContent(params) => match params {
                 Some(id) => format!("/page/{id}/content"),
@@ -259,12 +422,52 @@ impl Route {
                 }
                 None => "/page/:page_id/prop/:prop_id/float".into(),
             },
+            Self::PageStrProp(params) => match params {
+                Some((page_id, prop_id)) => {
+                    format!("/page/{page_id}/prop/{prop_id}/str")
+                }
+                None => "/page/:page_id/prop/:prop_id/str".into(),
+            },
+            Self::PageMultiStrPropTag(params) => match params {
+                Some((page_id, prop_id)) => {
+                    format!("/page/{page_id}/prop/{prop_id}/tag")
+                }
+                None => "/page/:page_id/prop/:prop_id/tag".into(),
+            },
             Self::PageDateProp(params) => match params {
                 Some((page_id, prop_id)) => {
                     format!("/page/{page_id}/prop/{prop_id}/date")
                 }
                 None => "/page/:page_id/prop/:prop_id/date".into(),
             },
+            Self::PageDateTimeProp(params) => match params {
+                Some((page_id, prop_id)) => {
+                    format!("/page/{page_id}/prop/{prop_id}/datetime")
+                }
+                None => "/page/:page_id/prop/:prop_id/datetime".into(),
+            },
+            Self::PageSelectProp(params) => match params {
+                Some((page_id, prop_id)) => {
+                    format!("/page/{page_id}/prop/{prop_id}/select")
+                }
+                None => "/page/:page_id/prop/:prop_id/select".into(),
+            },
+            Self::PageRelationProp(params) => match params {
+                Some((page_id, prop_id)) => {
+                    format!("/page/{page_id}/prop/{prop_id}/relation")
+                }
+                None => "/page/:page_id/prop/:prop_id/relation".into(),
+            },
+            Self::PageFilesProp(params) => match params {
+                Some((page_id, prop_id)) => {
+                    format!("/page/{page_id}/prop/{prop_id}/files")
+                }
+                None => "/page/:page_id/prop/:prop_id/files".into(),
+            },
+            Self::File(params) => match params {
+                Some(id) => format!("/file/{id}"),
+                None => "/file/:id".into(),
+            },
             Self::PageNewBoolProp(params) => match params {
                 Some((page_id, prop_id)) => {
                     format!("/page/{page_id}/prop/{prop_id}/new-bool")
@@ -283,12 +486,26 @@ impl Route {
                 }
                 None => "/page/:page_id/prop/:prop_id/new-float".into(),
             },
+            Self::PageNewStrProp(params) => match params {
+                Some((page_id, prop_id)) => {
+                    format!("/page/{page_id}/prop/{prop_id}/new-str")
+                }
+                None => "/page/:page_id/prop/:prop_id/new-str".into(),
+            },
             Self::PageNewDateProp(params) => match params {
                 Some((page_id, prop_id)) => {
                     format!("/page/{page_id}/prop/{prop_id}/new-date")
                 }
                 None => "/page/:page_id/prop/:prop_id/new-date".into(),
             },
+            Self::PageNewDateTimeProp(params) => match params {
+                Some((page_id, prop_id)) => {
+                    format!("/page/{page_id}/prop/{prop_id}/new-datetime")
+                }
+                None => "/page/:page_id/prop/:prop_id/new-datetime".into(),
+            },
+            Self::UserTimezone => "/user/timezone".into(),
+            Self::Search => "/search".into(),
             Self::Root => "/".into(),
             Self::Ping => "/ping".into(),
             Self::Register => "/authentication/register".into(),
@@ -314,6 +531,14 @@ pub fn get_protected_routes() -> Router<models::AppState> {
             &Route::Collection(None).as_string(),
             get(controllers::get_collection),
         )
+        .route(
+            &Route::CollectionView(None).as_string(),
+            get(controllers::get_collection_view),
+        )
+        .route(
+            &Route::CollectionViews(None).as_string(),
+            post(controllers::handle_new_view),
+        )
         .route(
             &Route::CollectionPageSubmission(None).as_string(),
             post(controllers::handle_page_submission),
@@ -322,10 +547,6 @@ pub fn get_protected_routes() -> Router<models::AppState> {
             &Route::CollectionNewPageForm(None).as_string(),
             get(controllers::new_page_form),
         )
-        .route(
-            &Route::CollectionListPages(None).as_string(),
-            get(controllers::collection_pages),
-        )
         .route(
             &Route::CollectionChangePropOrder(None).as_string(),
             get(controllers::collection_prop_order),
@@ -339,181 +560,241 @@ pub fn get_protected_routes() -> Router<models::AppState> {
             post(controllers::decrement_prop_order),
         )
         .route(
-            &Route::CollectionShowFilterToolbar(None).as_string(),
+            &Route::CollectionKeyPrefix(None).as_string(),
+            get(controllers::get_key_prefix_form),
+        )
+        .route(
+            &Route::CollectionKeyPrefix(None).as_string(),
+            post(controllers::handle_key_prefix_submit),
+        )
+        .route(
+            &Route::CollectionTemplates(None).as_string(),
+            get(controllers::get_template_list),
+        )
+        .route(
+            &Route::CollectionTemplates(None).as_string(),
+            post(controllers::handle_new_template),
+        )
+        .route(
+            &Route::View(None).as_string(),
+            get(controllers::get_view_settings),
+        )
+        .route(
+            &Route::View(None).as_string(),
+            post(controllers::handle_view_save),
+        )
+        .route(
+            &Route::View(None).as_string(),
+            delete(controllers::delete_view),
+        )
+        .route(
+            &Route::ViewListPages(None).as_string(),
+            get(controllers::view_pages),
+        )
+        .route(
+            &Route::ViewPropVisibility(None).as_string(),
+            post(controllers::toggle_view_prop),
+        )
+        .route(
+            &Route::ViewIncrementPropOrder(None).as_string(),
+            post(controllers::increment_view_prop_order),
+        )
+        .route(
+            &Route::ViewDecrementPropOrder(None).as_string(),
+            post(controllers::decrement_view_prop_order),
+        )
+        .route(
+            &Route::ViewShowFilterToolbar(None).as_string(),
             get(controllers::get_filter_toolbar),
         )
         .route(
-            &Route::CollectionHideFilterToolbar(None).as_string(),
+            &Route::ViewHideFilterToolbar(None).as_string(),
             get(controllers::hide_filter_toolbar),
         )
         .route(
-            &Route::CollectionChoosePropForFilter(None).as_string(),
+            &Route::ViewChoosePropForFilter(None).as_string(),
             get(controllers::choose_prop_for_filter),
         )
         .route(
-            &Route::CollectionAddFilterButton(None).as_string(),
+            &Route::ViewAddFilterButton(None).as_string(),
             get(controllers::get_add_filter_button),
         )
         .route(
-            &Route::CollectionShowSortToolbar(None).as_string(),
+            &Route::ViewNewTitleFilter(None).as_string(),
+            post(controllers::create_new_title_filter),
+        )
+        .route(
+            &Route::ViewNewFilterTypeSelect(None).as_string(),
+            get(controllers::new_filter_type_select),
+        )
+        .route(
+            &Route::ViewNewFilter(None).as_string(),
+            post(controllers::create_new_filter),
+        )
+        .route(
+            &Route::ViewSaveAdHocFilters(None).as_string(),
+            post(controllers::save_adhoc_filters),
+        )
+        .route(
+            &Route::ViewShowSortToolbar(None).as_string(),
             get(controllers::show_sort_toolbar),
         )
         .route(
-            &Route::CollectionHideSortToolbar(None).as_string(),
+            &Route::ViewHideSortToolbar(None).as_string(),
             get(controllers::hide_sort_toolbar),
         )
         .route(
-            &Route::CollectionSort(None).as_string(),
+            &Route::ViewSort(None).as_string(),
             post(controllers::handle_sort_form_submit),
         )
         .route(
-            &Route::PropNewFilterTypeSelect(None).as_string(),
-            get(controllers::new_filter_type_select),
-        )
-        .route(
-            &Route::PropNewBoolFilter(None).as_string(),
-            post(controllers::create_new_bool_filter),
+            &Route::ViewExport(None).as_string(),
+            get(controllers::export_view),
         )
         .route(
-            &Route::PropNewIntFilter(None).as_string(),
-            post(controllers::create_new_int_filter),
+            &Route::Template(None).as_string(),
+            get(controllers::get_template_editor),
         )
         .route(
-            &Route::PropNewIntRngFilter(None).as_string(),
-            post(controllers::create_new_int_rng_filter),
+            &Route::Template(None).as_string(),
+            post(controllers::handle_template_save),
         )
         .route(
-            &Route::PropNewFloatFilter(None).as_string(),
-            post(controllers::create_new_float_filter),
+            &Route::Template(None).as_string(),
+            delete(controllers::delete_template),
         )
         .route(
-            &Route::PropNewFloatRngFilter(None).as_string(),
-            post(controllers::create_new_float_rng_filter),
+            &Route::FilterGroup(None).as_string(),
+            post(controllers::create_filter_group),
         )
         .route(
-            &Route::PropNewDateFilter(None).as_string(),
-            post(controllers::create_new_date_filter),
+            &Route::FilterGroup(None).as_string(),
+            delete(controllers::delete_filter_group),
         )
         .route(
-            &Route::PropNewDateRngFilter(None).as_string(),
-            post(controllers::create_new_date_rng_filter),
+            &Route::FilterGroupConjunction(None).as_string(),
+            post(controllers::toggle_filter_group_conjunction),
         )
         .route(
-            &Route::FilterBoolChip(None).as_string(),
-            get(controllers::get_bool_filter_chip),
+            &Route::PropTagVocab(None).as_string(),
+            get(controllers::get_tag_vocab),
         )
         .route(
-            &Route::FilterBool(None).as_string(),
-            get(controllers::get_bool_filter_form),
+            &Route::PropOptions(None).as_string(),
+            get(controllers::get_option_editor),
         )
         .route(
-            &Route::FilterBool(None).as_string(),
-            post(controllers::handle_bool_form_submit),
+            &Route::PropOptions(None).as_string(),
+            post(controllers::handle_new_option),
         )
         .route(
-            &Route::FilterBool(None).as_string(),
-            delete(controllers::delete_bool_filter),
+            &Route::PropOptionList(None).as_string(),
+            get(controllers::get_option_list),
         )
         .route(
-            &Route::FilterIntChip(None).as_string(),
-            get(controllers::get_int_filter_chip),
+            &Route::PropOption(None).as_string(),
+            delete(controllers::delete_option),
         )
         .route(
-            &Route::FilterInt(None).as_string(),
-            get(controllers::get_int_filter_form),
+            &Route::PropRelationOptions(None).as_string(),
+            get(controllers::get_relation_options),
         )
         .route(
-            &Route::FilterInt(None).as_string(),
-            post(controllers::handle_int_form_submit),
+            &Route::PropFormula(None).as_string(),
+            get(controllers::get_formula_editor),
         )
         .route(
-            &Route::FilterInt(None).as_string(),
-            delete(controllers::delete_int_filter),
+            &Route::PropFormula(None).as_string(),
+            post(controllers::handle_formula_submit),
         )
         .route(
-            &Route::FilterIntRngChip(None).as_string(),
-            get(controllers::get_int_rng_filter_chip),
+            &Route::CollectionProps(None).as_string(),
+            get(controllers::get_prop_manager),
         )
         .route(
-            &Route::FilterIntRng(None).as_string(),
-            get(controllers::get_int_rng_filter_form),
+            &Route::CollectionProps(None).as_string(),
+            post(controllers::handle_new_prop_submit),
         )
         .route(
-            &Route::FilterIntRng(None).as_string(),
-            post(controllers::handle_int_rng_form_submit),
+            &Route::CollectionNewPropForm(None).as_string(),
+            get(controllers::get_new_prop_form),
         )
         .route(
-            &Route::FilterIntRng(None).as_string(),
-            delete(controllers::delete_int_rng_filter),
+            &Route::Prop(None).as_string(),
+            post(controllers::handle_prop_rename),
         )
         .route(
-            &Route::FilterFloat(None).as_string(),
-            get(controllers::get_float_filter_form),
+            &Route::Prop(None).as_string(),
+            delete(controllers::delete_prop),
         )
         .route(
-            &Route::FilterFloat(None).as_string(),
-            post(controllers::handle_float_form_submit),
+            &Route::PropDeleteConfirmation(None).as_string(),
+            get(controllers::get_prop_delete_confirmation),
         )
         .route(
-            &Route::FilterFloat(None).as_string(),
-            delete(controllers::delete_float_filter),
+            &Route::PropType(None).as_string(),
+            get(controllers::get_type_change_form),
         )
         .route(
-            &Route::FilterFloatChip(None).as_string(),
-            get(controllers::get_float_filter_chip),
+            &Route::PropType(None).as_string(),
+            post(controllers::handle_type_change),
         )
         .route(
-            &Route::FilterFloatRng(None).as_string(),
-            get(controllers::get_float_rng_filter_form),
+            &Route::PropConstraints(None).as_string(),
+            get(controllers::get_constraint_form),
         )
         .route(
-            &Route::FilterFloatRng(None).as_string(),
-            post(controllers::handle_float_rng_form_submit),
+            &Route::PropConstraints(None).as_string(),
+            post(controllers::handle_constraint_save),
         )
         .route(
-            &Route::FilterFloatRng(None).as_string(),
-            delete(controllers::delete_float_rng_filter),
+            &Route::PropNumberFormat(None).as_string(),
+            get(controllers::get_number_format_form),
         )
         .route(
-            &Route::FilterFloatRngChip(None).as_string(),
-            get(controllers::get_float_rng_filter_chip),
+            &Route::PropNumberFormat(None).as_string(),
+            post(controllers::handle_number_format_save),
         )
         .route(
-            &Route::FilterDate(None).as_string(),
-            get(controllers::get_date_filter_form),
+            &Route::PropRollup(None).as_string(),
+            get(controllers::get_rollup_editor),
         )
         .route(
-            &Route::FilterDate(None).as_string(),
-            post(controllers::handle_date_form_submit),
+            &Route::PropRollup(None).as_string(),
+            post(controllers::handle_rollup_submit),
         )
         .route(
-            &Route::FilterDate(None).as_string(),
-            delete(controllers::delete_date_filter),
+            &Route::FilterChip(None).as_string(),
+            get(controllers::get_filter_chip),
         )
         .route(
-            &Route::FilterDateChip(None).as_string(),
-            get(controllers::get_date_filter_chip),
+            &Route::Filter(None).as_string(),
+            get(controllers::get_filter_form),
         )
         .route(
-            &Route::FilterDateRng(None).as_string(),
-            get(controllers::get_date_rng_filter_form),
+            &Route::Filter(None).as_string(),
+            post(controllers::handle_filter_form_submit),
         )
         .route(
-            &Route::FilterDateRng(None).as_string(),
-            post(controllers::handle_date_rng_form_submit),
+            &Route::Filter(None).as_string(),
+            delete(controllers::delete_filter),
         )
         .route(
-            &Route::FilterDateRng(None).as_string(),
-            delete(controllers::delete_date_rng_filter),
+            &Route::FilterEnabled(None).as_string(),
+            post(controllers::toggle_filter),
         )
         .route(
-            &Route::FilterDateRngChip(None).as_string(),
-            get(controllers::get_date_rng_filter_chip),
+            &Route::UserOptions.as_string(),
+            get(controllers::get_user_options),
         )
         .route(
             &Route::Page(None).as_string(),
             get(controllers::existing_page_form),
         )
+        .route(
+            &Route::PageByKey(None).as_string(),
+            get(controllers::redirect_page_key),
+        )
         .route(
             &Route::PageSubmit.as_string(),
             post(controllers::save_existing_page_form),
@@ -538,10 +819,51 @@ pub fn get_protected_routes() -> Router<models::AppState> {
             &Route::PageFloatProp(None).as_string(),
             post(controllers::save_pv_float),
         )
+        .route(
+            &Route::PageStrProp(None).as_string(),
+            post(controllers::save_pv_str),
+        )
+        .route(
+            &Route::PageMultiStrPropTag(None).as_string(),
+            post(controllers::add_pv_tag),
+        )
+        .route(
+            &Route::PageMultiStrPropTag(None).as_string(),
+            delete(controllers::remove_pv_tag),
+        )
         .route(
             &Route::PageDateProp(None).as_string(),
             post(controllers::save_pv_date),
         )
+        .route(
+            &Route::PageDateTimeProp(None).as_string(),
+            post(controllers::save_pv_datetime),
+        )
+        .route(
+            &Route::PageSelectProp(None).as_string(),
+            get(controllers::get_select_propval_form),
+        )
+        .route(
+            &Route::PageSelectProp(None).as_string(),
+            post(controllers::save_pv_select),
+        )
+        .route(
+            &Route::PageRelationProp(None).as_string(),
+            post(controllers::add_pv_relation),
+        )
+        .route(
+            &Route::PageRelationProp(None).as_string(),
+            delete(controllers::remove_pv_relation),
+        )
+        .route(
+            &Route::PageFilesProp(None).as_string(),
+            post(controllers::upload_pv_files)
+                .layer(DefaultBodyLimit::max(config::UPLOAD_MAX_BYTES)),
+        )
+        .route(
+            &Route::PageFilesProp(None).as_string(),
+            delete(controllers::remove_pv_file),
+        )
         .route(
             &Route::PageNewBoolProp(None).as_string(),
             get(controllers::new_bool_propval_form),
@@ -554,10 +876,35 @@ pub fn get_protected_routes() -> Router<models::AppState> {
             &Route::PageNewFloatProp(None).as_string(),
             get(controllers::new_float_propval_form),
         )
+        .route(
+            &Route::PageNewStrProp(None).as_string(),
+            get(controllers::new_str_propval_form),
+        )
         .route(
             &Route::PageNewDateProp(None).as_string(),
             get(controllers::new_date_propval_form),
         )
+        .route(
+            &Route::PageNewDateTimeProp(None).as_string(),
+            get(controllers::new_datetime_propval_form),
+        )
+        .route(
+            &Route::UserTimezone.as_string(),
+            get(controllers::get_timezone_form),
+        )
+        .route(
+            &Route::UserTimezone.as_string(),
+            post(controllers::handle_timezone_submit),
+        )
+        .route(&Route::Search.as_string(), get(controllers::search))
+}
+
+/// In [crate::main], these routes are protected like
+/// [get_protected_routes], but skip [crate::middleware::html_headers], since
+/// they serve files with their own content type and caching.
+pub fn get_file_routes() -> Router<models::AppState> {
+    Router::new()
+        .route(&Route::File(None).as_string(), get(controllers::get_file))
 }
 
 /// In [crate::main], these routes are not protected by any authentication, so
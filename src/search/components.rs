@@ -0,0 +1,105 @@
+use super::models::{Headline, SearchResult, MAX_RESULTS};
+use crate::{components::Component, routes::Route};
+use ammonia::clean_text;
+use std::fmt::Write;
+
+/// Text from `ts_headline`, with the matching words marked.
+struct HighlightedText<'a> {
+    headline: &'a Headline,
+}
+impl Component for HighlightedText<'_> {
+    fn render(&self) -> String {
+        self.headline.segments.iter().fold(
+            String::new(),
+            |mut acc, (text, highlighted)| {
+                let text = clean_text(text);
+                if *highlighted {
+                    let _ = write!(
+                        acc,
+                        r#"<mark class="rounded bg-yellow-100 text-black">{text}</mark>"#
+                    );
+                } else {
+                    acc.push_str(&text);
+                }
+                acc
+            },
+        )
+    }
+}
+
+/// Search box for pages in every collection, with its results. `results`
+/// is `None` until something has been searched for.
+pub struct SearchPage<'a> {
+    pub text: &'a str,
+    pub results: Option<&'a [SearchResult]>,
+}
+impl Component for SearchPage<'_> {
+    fn render(&self) -> String {
+        let search_route = Route::Search;
+        let text = clean_text(self.text);
+        let results = match self.results {
+            None => "".to_string(),
+            Some([]) => "<p>No pages match your search.</p>".to_string(),
+            Some(results) => {
+                let items = results.iter().fold(String::new(), |mut acc, r| {
+                    let page_route = Route::Page(Some(r.page_id));
+                    let collection_route =
+                        Route::Collection(Some(r.collection_id));
+                    let key = clean_text(&r.key.to_string());
+                    let collection_name = clean_text(&r.collection_name);
+                    let title = HighlightedText { headline: &r.title }.render();
+                    let snippet = if r.snippet.is_empty() {
+                        "".to_string()
+                    } else {
+                        let snippet =
+                            HighlightedText { headline: &r.snippet }.render();
+                        format!(r#"<p class="text-sm">{snippet}</p>"#)
+                    };
+                    let _ = write!(
+                        acc,
+                        r#"
+                        <li class="flex flex-col gap-1">
+                            <div class="flex gap-2 items-baseline">
+                                <span class="text-sm text-slate-500 dark:text-slate-400 whitespace-nowrap">{key}</span>
+                                <a class="link" href="{page_route}">{title}</a>
+                                <a class="text-xs link" href="{collection_route}">{collection_name}</a>
+                            </div>
+                            {snippet}
+                        </li>
+                        "#
+                    );
+                    acc
+                });
+                let limit_note = if results.len() as i64 >= MAX_RESULTS {
+                    format!(
+                        r#"<p class="text-sm text-slate-500 dark:text-slate-400">Showing the best {MAX_RESULTS} matches.</p>"#
+                    )
+                } else {
+                    "".to_string()
+                };
+                format!(
+                    r#"<ol class="flex flex-col gap-4">{items}</ol>{limit_note}"#
+                )
+            }
+        };
+        format!(
+            r#"
+            <div class="flex flex-col gap-4 max-w-2xl" hx-target="this" hx-swap="outerHTML">
+                <h1 class="text-xl">Search</h1>
+                <form class="flex gap-2" hx-get="{search_route}" hx-push-url="true">
+                    <input
+                        class="dark:text-black grow"
+                        type="search"
+                        name="q"
+                        value="{text}"
+                        placeholder="Words, &quot;a phrase&quot;, or -excluded"
+                        autofocus
+                    />
+                    <button class="dark:bg-slate-700 w-24 dark:text-white dark:hover:bg-slate-600 transition shadow hover:shadow-none rounded p-1 block">Search</button>
+                </form>
+                {results}
+            </div>
+            "#
+        )
+    }
+}
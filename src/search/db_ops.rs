@@ -0,0 +1,67 @@
+use super::models::{
+    get_headline_options, Headline, SearchResult, MAX_RESULTS,
+};
+use crate::models::PageKey;
+use anyhow::Result;
+use sqlx::{query_as, PgPool};
+
+/// Pages in any collection whose title or content matches `text`, best
+/// matches first.
+pub async fn search_pages(
+    db: &PgPool,
+    text: &str,
+) -> Result<Vec<SearchResult>> {
+    struct Qres {
+        id: i32,
+        number: i32,
+        collection_id: i32,
+        collection_name: String,
+        key_prefix: String,
+        title: Option<String>,
+        snippet: Option<String>,
+    }
+    let res = query_as!(
+        Qres,
+        "select
+            p.id,
+            p.number,
+            p.collection_id,
+            c.name collection_name,
+            c.key_prefix,
+            ts_headline('english', p.title, q, $2) title,
+            ts_headline('english', coalesce(pc.content, ''), q, $3) snippet
+        from page p
+        join collection c on c.id = p.collection_id
+        left join page_content pc on pc.page_id = p.id
+        cross join websearch_to_tsquery('english', $1) q
+        where p.search_vector @@ q or pc.search_vector @@ q
+        order by
+            ts_rank(
+                p.search_vector || coalesce(pc.search_vector, ''),
+                q
+            ) desc,
+            p.id
+        limit $4",
+        text,
+        get_headline_options(true),
+        get_headline_options(false),
+        MAX_RESULTS
+    )
+    .fetch_all(db)
+    .await?;
+
+    Ok(res
+        .into_iter()
+        .map(|row| SearchResult {
+            page_id: row.id,
+            key: PageKey {
+                prefix: row.key_prefix,
+                number: row.number,
+            },
+            collection_id: row.collection_id,
+            collection_name: row.collection_name,
+            title: Headline::parse(&row.title.unwrap_or_default()),
+            snippet: Headline::parse(&row.snippet.unwrap_or_default()),
+        })
+        .collect())
+}
@@ -0,0 +1,12 @@
+//! Full-text search over page titles and content, using the `search_vector`
+//! columns on `page` and `page_content`. Search text is parsed with
+//! Postgres' `websearch_to_tsquery`, so it supports `"quoted phrases"`,
+//! `or`, and `-excluded` words.
+//!
+//! Search narrows the page list of a collection alongside its filters (see
+//! [crate::db_ops::list_pages]), and the search page finds pages across every
+//! collection, ranked, with the matching words highlighted.
+
+pub mod components;
+pub mod db_ops;
+pub mod models;
@@ -0,0 +1,123 @@
+use crate::models::PageKey;
+use sqlx::{Postgres, QueryBuilder};
+
+/// `ts_headline` wraps the matching words in these. They are control
+/// characters, so unlike `<b>`, they can't be confused with text from the
+/// page itself.
+pub const HIGHLIGHT_START: char = '\u{2}';
+pub const HIGHLIGHT_END: char = '\u{3}';
+
+/// The most results shown on the search page.
+pub const MAX_RESULTS: i64 = 50;
+
+/// Options for `ts_headline`. Titles are short, so they are highlighted
+/// whole, while content is cut down to a few fragments around the matches.
+pub fn get_headline_options(is_title: bool) -> String {
+    let selectors =
+        format!("StartSel={HIGHLIGHT_START}, StopSel={HIGHLIGHT_END}");
+    if is_title {
+        format!("{selectors}, HighlightAll=true")
+    } else {
+        format!("{selectors}, MaxFragments=2, MaxWords=20, MinWords=8")
+    }
+}
+
+/// Search text, or `None` if there is nothing to search for.
+pub fn normalize(text: Option<&str>) -> Option<&str> {
+    text.map(str::trim).filter(|text| !text.is_empty())
+}
+
+/// Push a condition matching pages whose title or content matches `text`.
+/// Expects `page` to be in scope, as it is in [crate::db_ops::list_pages].
+pub fn push_match_sql(query: &mut QueryBuilder<'_, Postgres>, text: &str) {
+    query.push("(page.search_vector @@ websearch_to_tsquery('english', ");
+    query.push_bind(text.to_string());
+    query.push(
+        ") or exists(
+            select 1 from page_content pc
+            where pc.page_id = page.id
+            and pc.search_vector @@ websearch_to_tsquery('english', ",
+    );
+    query.push_bind(text.to_string());
+    query.push(")))");
+}
+
+/// Text from `ts_headline`, split into runs which are and are not
+/// highlighted.
+#[derive(Debug, PartialEq)]
+pub struct Headline {
+    pub segments: Vec<(String, bool)>,
+}
+
+impl Headline {
+    pub fn parse(text: &str) -> Self {
+        let mut segments = vec![];
+        let mut current = String::new();
+        let mut highlighted = false;
+        for c in text.chars() {
+            let toggles = match c {
+                HIGHLIGHT_START => !highlighted,
+                HIGHLIGHT_END => highlighted,
+                _ => {
+                    current.push(c);
+                    false
+                }
+            };
+            if toggles {
+                if !current.is_empty() {
+                    segments.push((std::mem::take(&mut current), highlighted));
+                }
+                highlighted = !highlighted;
+            }
+        }
+        if !current.is_empty() {
+            segments.push((current, highlighted));
+        }
+        Self { segments }
+    }
+    pub fn is_empty(&self) -> bool {
+        self.segments.iter().all(|(text, _)| text.trim().is_empty())
+    }
+}
+
+/// A page which matches a search, from any collection.
+#[derive(Debug)]
+pub struct SearchResult {
+    pub page_id: i32,
+    pub key: PageKey,
+    pub collection_id: i32,
+    pub collection_name: String,
+    pub title: Headline,
+    pub snippet: Headline,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_headline() {
+        let text = format!(
+            "the {HIGHLIGHT_START}quick{HIGHLIGHT_END} brown \
+            {HIGHLIGHT_START}foxes{HIGHLIGHT_END}"
+        );
+        assert_eq!(
+            Headline::parse(&text).segments,
+            vec![
+                ("the ".to_string(), false),
+                ("quick".to_string(), true),
+                (" brown ".to_string(), false),
+                ("foxes".to_string(), true),
+            ]
+        );
+        // Stray markers don't unbalance the highlighting.
+        let text = format!("{HIGHLIGHT_END}a{HIGHLIGHT_START}b");
+        assert_eq!(
+            Headline::parse(&text).segments,
+            vec![("a".to_string(), false), ("b".to_string(), true)]
+        );
+        assert!(Headline::parse(" ").is_empty());
+        assert_eq!(normalize(Some("  ")), None);
+        assert_eq!(normalize(Some(" fox ")), Some("fox"));
+    }
+}
@@ -36,13 +36,7 @@ impl Session {
         let re = Regex::new(r"session=(.*)").unwrap();
         let captures = re.captures(cookie)?;
         let token = &captures[1];
-        let deserialize_result = Self::deserialize(token);
-
-        if let Ok(session) = deserialize_result {
-            Some(session)
-        } else {
-            None
-        }
+        Self::deserialize(token).ok()
     }
     /// Serialize the session into the provided [HeaderMap].
     pub fn update_headers(&self, mut headers: HeaderMap) -> HeaderMap {
@@ -0,0 +1,173 @@
+use super::models::{is_templatable, PageTemplate};
+use crate::{
+    components::Component,
+    models::{Prop, SelectOption, ValueType},
+    routes::Route,
+};
+use ammonia::{clean, clean_text};
+use std::fmt::Write;
+
+/// The templates of a collection, with a form for adding another.
+pub struct TemplateList<'a> {
+    pub collection_id: i32,
+    pub templates: &'a [PageTemplate],
+    pub error: Option<&'a str>,
+}
+impl Component for TemplateList<'_> {
+    fn render(&self) -> String {
+        let collection_route = Route::Collection(Some(self.collection_id));
+        let templates_route =
+            Route::CollectionTemplates(Some(self.collection_id));
+        let templates =
+            self.templates.iter().fold(String::new(), |mut acc, t| {
+                let name = clean(&t.name);
+                let template_route = Route::Template(Some(t.id));
+                let _ = write!(
+                    acc,
+                    r#"<li><a class="link" href="{template_route}">{name}</a></li>"#
+                );
+                acc
+            });
+        let templates = if templates.is_empty() {
+            "<p>There are no templates yet.</p>".to_string()
+        } else {
+            format!(r#"<ul class="flex flex-col gap-1">{templates}</ul>"#)
+        };
+        let error = match self.error {
+            Some(error) => {
+                let error = clean(error);
+                format!(
+                    r#"<p class="text-red-600 dark:text-red-400">{error}</p>"#
+                )
+            }
+            None => "".into(),
+        };
+        format!(
+            r#"
+            <div class="flex flex-col gap-2 max-w-md" hx-target="this" hx-swap="outerHTML">
+                <a class="link" href="{collection_route}">Back</a>
+                <h1 class="text-xl">Templates</h1>
+                {templates}
+                <form class="flex flex-col gap-2" hx-post="{templates_route}">
+                    <h2 class="text-lg">New Template</h2>
+                    <label for="name">Name</label>
+                    <input class="dark:text-black" required maxlength="255" type="text" id="name" name="name" />
+                    {error}
+                    <button class="dark:bg-slate-700 w-36 dark:text-white dark:hover:bg-slate-600 transition shadow hover:shadow-none rounded p-1 block">Add Template</button>
+                </form>
+            </div>
+            "#
+        )
+    }
+}
+
+/// How values are written for a prop of type `ty`.
+fn placeholder(
+    ty: ValueType,
+    prop_id: i32,
+    options: &[SelectOption],
+) -> String {
+    match ty {
+        ValueType::Bool => "true or false".into(),
+        ValueType::Int => "42".into(),
+        ValueType::Float => "0.5".into(),
+        ValueType::MultiStr => "tag, another tag".into(),
+        ValueType::Date => "2024-01-31".into(),
+        ValueType::DateTime => "2024-01-31 13:00 (UTC)".into(),
+        ValueType::Select => options
+            .iter()
+            .filter(|o| o.prop_id == prop_id)
+            .map(|o| o.name.clone())
+            .collect::<Vec<_>>()
+            .join(", "),
+        _ => "".into(),
+    }
+}
+
+/// Form for a template's name, content, and default propvals. Each default
+/// is written as text, and checked against the prop's type on save.
+pub struct TemplateEditor<'a> {
+    pub template: &'a PageTemplate,
+    pub props: &'a [Prop],
+    pub options: &'a [SelectOption],
+    /// `(prop_id, error)` for defaults which don't parse, or an error about
+    /// the template as a whole with a `prop_id` of `None`.
+    pub errors: &'a [(Option<i32>, String)],
+    pub saved: bool,
+}
+impl Component for TemplateEditor<'_> {
+    fn render(&self) -> String {
+        let template_route = Route::Template(Some(self.template.id));
+        let templates_route =
+            Route::CollectionTemplates(Some(self.template.collection_id));
+        let name = clean_text(&self.template.name);
+        let content = clean_text(&self.template.content);
+        let error_for = |prop_id: Option<i32>| {
+            self.errors
+                .iter()
+                .filter(|(id, _)| *id == prop_id)
+                .fold(String::new(), |mut acc, (_, error)| {
+                    let error = clean(error);
+                    let _ = write!(
+                        acc,
+                        r#"<p class="text-red-600 dark:text-red-400">{error}</p>"#
+                    );
+                    acc
+                })
+        };
+        let fields = self
+            .props
+            .iter()
+            .filter(|p| is_templatable(p.type_id))
+            .fold(String::new(), |mut acc, p| {
+                let id = p.id;
+                let prop_name = clean(&p.name);
+                let type_name = p.type_id.get_display_name();
+                let value =
+                    clean_text(self.template.get_value(id).unwrap_or_default());
+                let placeholder =
+                    clean_text(&placeholder(p.type_id, id, self.options));
+                let error = error_for(Some(id));
+                let _ = write!(
+                    acc,
+                    r#"
+                    <label for="prop_{id}">{prop_name} <span class="text-sm text-slate-500 dark:text-slate-400">{type_name}</span></label>
+                    <input class="dark:text-black" maxlength="511" type="text" id="prop_{id}" name="prop_{id}" value="{value}" placeholder="{placeholder}" />
+                    {error}
+                    "#
+                );
+                acc
+            });
+        let template_error = error_for(None);
+        let saved = if self.saved {
+            r#"<p class="text-green-700 dark:text-green-400">Saved.</p>"#
+        } else {
+            ""
+        };
+        format!(
+            r#"
+            <div class="flex flex-col gap-2 max-w-md" hx-target="this" hx-swap="outerHTML">
+                <a class="link" href="{templates_route}">Back</a>
+                <h1 class="text-xl">Template</h1>
+                <form class="flex flex-col gap-2" hx-post="{template_route}">
+                    <label for="name">Name</label>
+                    <input class="dark:text-black" required maxlength="255" type="text" id="name" name="name" value="{name}" />
+                    <label for="content">Content</label>
+                    <textarea class="dark:text-black" rows="8" id="content" name="content">{content}</textarea>
+                    <h2 class="text-lg">Default Values</h2>
+                    <p class="text-sm">Leave a prop blank to start new pages without a value.</p>
+                    {fields}
+                    {template_error}
+                    {saved}
+                    <button class="dark:bg-slate-700 w-36 dark:text-white dark:hover:bg-slate-600 transition shadow hover:shadow-none rounded p-1 block">Save</button>
+                </form>
+                <button
+                    class="text-xs link self-start"
+                    hx-delete="{template_route}"
+                    hx-confirm="Delete this template? Pages created from it are not affected."
+                >delete template</button>
+            </div>
+            "#
+        )
+    }
+}
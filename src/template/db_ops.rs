@@ -0,0 +1,203 @@
+use super::models::{parse_value, PageTemplate, TemplateValue};
+use crate::{
+    db_ops::{get_prop_set, DbModel, ListSelectOptionQuery},
+    models::{Prop, SelectOption, Value, ValueType},
+};
+use anyhow::Result;
+use async_trait::async_trait;
+use sqlx::{query, query_as, PgPool};
+
+pub struct GetTemplateQuery {
+    pub id: i32,
+}
+
+pub struct ListTemplateQuery {
+    pub collection_id: i32,
+}
+
+struct Qres {
+    id: i32,
+    collection_id: i32,
+    name: String,
+    content: String,
+}
+
+struct QresValue {
+    template_id: i32,
+    prop_id: i32,
+    value: String,
+}
+
+fn into_templates(
+    rows: Vec<Qres>,
+    values: Vec<QresValue>,
+) -> Vec<PageTemplate> {
+    rows.into_iter()
+        .map(|row| PageTemplate {
+            id: row.id,
+            collection_id: row.collection_id,
+            name: row.name,
+            content: row.content,
+            values: values
+                .iter()
+                .filter(|v| v.template_id == row.id)
+                .map(|v| TemplateValue {
+                    prop_id: v.prop_id,
+                    value: v.value.clone(),
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+#[async_trait]
+impl DbModel<GetTemplateQuery, ListTemplateQuery> for PageTemplate {
+    async fn get(db: &PgPool, query: &GetTemplateQuery) -> Result<Self> {
+        let row = query_as!(
+            Qres,
+            "select id, collection_id, name, content
+            from page_template
+            where id = $1",
+            query.id
+        )
+        .fetch_one(db)
+        .await?;
+        let values = query_as!(
+            QresValue,
+            "select template_id, prop_id, value
+            from page_template_propval
+            where template_id = $1",
+            query.id
+        )
+        .fetch_all(db)
+        .await?;
+
+        Ok(into_templates(vec![row], values)
+            .pop()
+            .expect("one row in, one template out"))
+    }
+    async fn list(db: &PgPool, query: &ListTemplateQuery) -> Result<Vec<Self>> {
+        let rows = query_as!(
+            Qres,
+            "select id, collection_id, name, content
+            from page_template
+            where collection_id = $1
+            order by name",
+            query.collection_id
+        )
+        .fetch_all(db)
+        .await?;
+        let values = query_as!(
+            QresValue,
+            "select v.template_id, v.prop_id, v.value
+            from page_template_propval v
+            join page_template t on t.id = v.template_id
+            where t.collection_id = $1",
+            query.collection_id
+        )
+        .fetch_all(db)
+        .await?;
+
+        Ok(into_templates(rows, values))
+    }
+    async fn save(&self, db: &PgPool) -> Result<()> {
+        let (prop_ids, values): (Vec<i32>, Vec<String>) = self
+            .values
+            .iter()
+            .map(|v| (v.prop_id, v.value.clone()))
+            .unzip();
+        let mut tx = db.begin().await?;
+        query!(
+            "update page_template set name = $1, content = $2 where id = $3",
+            self.name,
+            self.content,
+            self.id
+        )
+        .execute(&mut *tx)
+        .await?;
+        query!(
+            "delete from page_template_propval where template_id = $1",
+            self.id
+        )
+        .execute(&mut *tx)
+        .await?;
+        query!(
+            "insert into page_template_propval (template_id, prop_id, value)
+            select $1, unnest($2::int[]), unnest($3::varchar[])",
+            self.id,
+            &prop_ids,
+            &values
+        )
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+    async fn delete(self, db: &PgPool) -> Result<()> {
+        query!("delete from page_template where id = $1", self.id)
+            .execute(db)
+            .await?;
+
+        Ok(())
+    }
+}
+
+pub async fn create_template(
+    db: &PgPool,
+    collection_id: i32,
+    name: &str,
+) -> Result<i32> {
+    struct Qres {
+        id: i32,
+    }
+    Ok(query_as!(
+        Qres,
+        "insert into page_template (collection_id, name) values ($1, $2)
+        returning id",
+        collection_id,
+        name
+    )
+    .fetch_one(db)
+    .await?
+    .id)
+}
+
+/// The options of every select prop in `props`, for parsing template values.
+pub async fn list_select_options(
+    db: &PgPool,
+    props: &[Prop],
+) -> Result<Vec<SelectOption>> {
+    let mut options = vec![];
+    for prop in props.iter().filter(|p| p.type_id == ValueType::Select) {
+        options.extend(
+            SelectOption::list(db, &ListSelectOptionQuery { prop_id: prop.id })
+                .await?,
+        );
+    }
+
+    Ok(options)
+}
+
+impl PageTemplate {
+    /// `(prop, value)` for each propval which pages created from this
+    /// template start with. Values which no longer parse are skipped; say,
+    /// because the prop changed type, or the option was deleted.
+    pub async fn get_defaults(
+        &self,
+        db: &PgPool,
+    ) -> Result<Vec<(Prop, Value)>> {
+        let props = get_prop_set(db, self.collection_id).await?;
+        let options = list_select_options(db, &props).await?;
+
+        Ok(self
+            .values
+            .iter()
+            .filter_map(|v| {
+                let prop = props.iter().find(|p| p.id == v.prop_id)?;
+                let value = parse_value(prop, &v.value, &options).ok()?;
+                Some((prop.clone(), value))
+            })
+            .collect())
+    }
+}
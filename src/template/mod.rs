@@ -0,0 +1,8 @@
+//! Page templates hold starter content and default propvals for the pages
+//! of a collection. A template is picked when a page is created, and
+//! [crate::db_ops::create_page] applies it in the same transaction which
+//! creates the page.
+
+pub mod components;
+pub mod db_ops;
+pub mod models;
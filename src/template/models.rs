@@ -0,0 +1,139 @@
+use crate::{
+    constraint::models::{Constraints, Rule},
+    conversion::models::{coerce, is_convertible},
+    models::{Prop, SelectOption, Value, ValueType},
+};
+
+/// A starting point for new pages in a collection.
+#[derive(Debug, Clone)]
+pub struct PageTemplate {
+    pub id: i32,
+    pub collection_id: i32,
+    pub name: String,
+    /// Markdown which new pages start with.
+    pub content: String,
+    pub values: Vec<TemplateValue>,
+}
+
+impl PageTemplate {
+    pub fn get_value(&self, prop_id: i32) -> Option<&str> {
+        self.values
+            .iter()
+            .find(|v| v.prop_id == prop_id)
+            .map(|v| &v.value[..])
+    }
+}
+
+/// The default value for one prop, written the way a user would type it;
+/// see [parse_value].
+#[derive(Debug, Clone)]
+pub struct TemplateValue {
+    pub prop_id: i32,
+    pub value: String,
+}
+
+/// Only props which store their own values can have defaults. Relations
+/// are left out too, since templates are shared between many pages.
+pub fn is_templatable(ty: ValueType) -> bool {
+    is_convertible(ty)
+}
+
+/// Parse a template value into a value for `prop`. Select values are option
+/// names, which are looked up in `options`. The error explains why the value
+/// doesn't work.
+pub fn parse_value(
+    prop: &Prop,
+    text: &str,
+    options: &[SelectOption],
+) -> Result<Value, String> {
+    if !is_templatable(prop.type_id) {
+        return Err(format!(
+            "{} props cannot have defaults",
+            prop.type_id.get_display_name()
+        ));
+    }
+    match coerce(&Value::Str(text.to_string()), prop.type_id)? {
+        Value::Str(name) if prop.type_id == ValueType::Select => options
+            .iter()
+            .find(|o| o.prop_id == prop.id && o.name == name)
+            .map(|o| Value::Select(o.clone()))
+            .ok_or_else(|| format!(r#"there is no "{name}" option"#)),
+        value => Ok(value),
+    }
+}
+
+/// Check a parsed default against the constraints of its prop. Every page
+/// made from the template would start with the same value, so props whose
+/// values must be unique can't have defaults.
+pub fn check_default(
+    constraints: &Constraints,
+    prop: &Prop,
+    value: &Value,
+) -> Result<(), String> {
+    if constraints.unique && Rule::Unique.applies_to(prop.type_id) {
+        return Err("values must be unique, so there can't be a default".into());
+    }
+    constraints.check(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::SelectColor;
+
+    fn prop(type_id: ValueType) -> Prop {
+        Prop {
+            id: 1,
+            type_id,
+            collection_id: 1,
+            name: "Prop".into(),
+            order: 1,
+            number_format: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_parse_value() {
+        let options = [SelectOption {
+            id: 3,
+            prop_id: 1,
+            name: "Done".into(),
+            color: SelectColor::Green,
+            order: 1,
+        }];
+        let cases = [
+            (ValueType::Bool, "yes", "Bool(true)"),
+            (ValueType::Int, "12", "Int(12)"),
+            (ValueType::MultiStr, "bug, ui", r#"MultiStr(["bug", "ui"])"#),
+        ];
+        for (ty, text, expected) in cases {
+            let value = parse_value(&prop(ty), text, &options).unwrap();
+            assert_eq!(format!("{value:?}"), expected);
+        }
+        let select = parse_value(&prop(ValueType::Select), "Done", &options);
+        assert!(matches!(select, Ok(Value::Select(o)) if o.id == 3));
+        assert!(
+            parse_value(&prop(ValueType::Select), "Nope", &options).is_err()
+        );
+        assert!(parse_value(&prop(ValueType::Date), "soon", &options).is_err());
+        assert!(parse_value(&prop(ValueType::Relation), "1", &options).is_err());
+    }
+
+    #[test]
+    fn test_check_default() {
+        let constraints = Constraints {
+            prop_id: 1,
+            min: Some(1.0),
+            max: Some(10.0),
+            ..Default::default()
+        };
+        let int = prop(ValueType::Int);
+        assert!(check_default(&constraints, &int, &Value::Int(5)).is_ok());
+        assert!(check_default(&constraints, &int, &Value::Int(11)).is_err());
+        let unique = Constraints {
+            unique: true,
+            ..constraints
+        };
+        assert!(check_default(&unique, &int, &Value::Int(5)).is_err());
+    }
+}
@@ -0,0 +1,191 @@
+use super::models::{Layout, View};
+use crate::{
+    components::{ArrowDown, ArrowUp, Component},
+    models::Prop,
+    routes::Route,
+};
+use ammonia::{clean, clean_text};
+use std::fmt::Write;
+
+/// Links to each view of a collection, with a form for adding another.
+pub struct ViewTabs<'a> {
+    pub collection_id: i32,
+    pub active_view_id: i32,
+    pub views: &'a [View],
+}
+impl Component for ViewTabs<'_> {
+    fn render(&self) -> String {
+        let tabs = self.views.iter().fold(String::new(), |mut acc, v| {
+            let name = clean(&v.name);
+            let view_route =
+                Route::CollectionView(Some((self.collection_id, v.id)));
+            let style = if v.id == self.active_view_id {
+                "bg-slate-300 dark:bg-slate-600"
+            } else {
+                "hover:bg-slate-200 dark:hover:bg-slate-700"
+            };
+            let _ = write!(
+                acc,
+                r#"<a class="{style} transition rounded px-2 py-1" href="{view_route}">{name}</a>"#
+            );
+            acc
+        });
+        let new_view_form = NewViewForm {
+            collection_id: self.collection_id,
+            error: None,
+        }
+        .render();
+        format!(
+            r#"
+            <div class="mt-2 flex flex-wrap gap-2 items-center">
+                {tabs}
+                {new_view_form}
+            </div>
+            "#
+        )
+    }
+}
+
+pub struct NewViewForm<'a> {
+    pub collection_id: i32,
+    pub error: Option<&'a str>,
+}
+impl Component for NewViewForm<'_> {
+    fn render(&self) -> String {
+        let views_route = Route::CollectionViews(Some(self.collection_id));
+        let error = match self.error {
+            Some(error) => {
+                let error = clean(error);
+                format!(
+                    r#"<p class="text-sm text-red-600 dark:text-red-400">{error}</p>"#
+                )
+            }
+            None => "".into(),
+        };
+        format!(
+            r#"
+            <form class="flex gap-2 items-center" hx-post="{views_route}">
+                <input class="dark:text-black text-sm rounded" required maxlength="255" type="text" name="name" placeholder="New view" />
+                <button class="text-sm dark:bg-slate-700 dark:text-white dark:hover:bg-slate-600 transition shadow hover:shadow-none rounded p-1">Add View</button>
+                {error}
+            </form>
+            "#
+        )
+    }
+}
+
+/// The name, layout, and columns of a view. `props` are all props of the
+/// collection, in the order of the view's columns, along with whether they
+/// are visible; see [View::arrange].
+pub struct ViewSettings<'a> {
+    pub view: &'a View,
+    pub props: &'a [(&'a Prop, bool)],
+    pub error: Option<&'a str>,
+    pub saved: bool,
+}
+impl Component for ViewSettings<'_> {
+    fn render(&self) -> String {
+        let view_id = self.view.id;
+        let view_route = Route::View(Some(view_id));
+        let collection_view_route =
+            Route::CollectionView(Some((self.view.collection_id, view_id)));
+        let name = clean_text(&self.view.name);
+        let layout_options =
+            Layout::all().iter().fold(String::new(), |mut acc, layout| {
+                let value = layout.get_name();
+                let display_name = layout.get_display_name();
+                let selected = if *layout == self.view.layout {
+                    "selected"
+                } else {
+                    ""
+                };
+                let _ = write!(
+                    acc,
+                    r#"<option {selected} value="{value}">{display_name}</option>"#
+                );
+                acc
+            });
+        let columns =
+            self.props
+                .iter()
+                .fold(String::new(), |mut acc, (prop, visible)| {
+                    let prop_name = clean(&prop.name);
+                    let prop_id = prop.id;
+                    let up = ArrowUp {}.render();
+                    let down = ArrowDown {}.render();
+                    let up_route = Route::ViewIncrementPropOrder(Some((
+                        view_id, prop_id,
+                    )));
+                    let down_route = Route::ViewDecrementPropOrder(Some((
+                        view_id, prop_id,
+                    )));
+                    let visibility_route =
+                        Route::ViewPropVisibility(Some((view_id, prop_id)));
+                    let (checked, name_style) = if *visible {
+                        ("checked", "")
+                    } else {
+                        ("", "opacity-50")
+                    };
+                    let _ = write!(
+                        acc,
+                        r##"
+                        <li class="flex gap-2 items-center">
+                            <input
+                                type="checkbox"
+                                title="Show column"
+                                {checked}
+                                hx-post="{visibility_route}"
+                                hx-sync="closest ol:queue"
+                            />
+                            <span class="w-48 truncate {name_style}">{prop_name}</span>
+                            <a
+                                hx-post="{up_route}"
+                                hx-sync="closest ol:queue">{up}</a>
+                            <a
+                                hx-post="{down_route}"
+                                hx-sync="closest ol:queue">{down}</a>
+                        </li>
+                        "##
+                    );
+                    acc
+                });
+        let error = match self.error {
+            Some(error) => {
+                let error = clean(error);
+                format!(
+                    r#"<p class="text-red-600 dark:text-red-400">{error}</p>"#
+                )
+            }
+            None => "".into(),
+        };
+        let saved = if self.saved {
+            r#"<p class="text-green-700 dark:text-green-400">Saved.</p>"#
+        } else {
+            ""
+        };
+        format!(
+            r#"
+            <div class="flex flex-col gap-2 max-w-md" hx-target="this" hx-swap="outerHTML">
+                <a class="link" href="{collection_view_route}">Back</a>
+                <h1 class="text-xl">View</h1>
+                <form class="flex flex-col gap-2" hx-post="{view_route}">
+                    <label for="name">Name</label>
+                    <input class="dark:text-black" required maxlength="255" type="text" id="name" name="name" value="{name}" />
+                    <label for="layout">Layout</label>
+                    <select class="dark:text-black" id="layout" name="layout">{layout_options}</select>
+                    {error}
+                    {saved}
+                    <button class="dark:bg-slate-700 w-36 dark:text-white dark:hover:bg-slate-600 transition shadow hover:shadow-none rounded p-1 block">Save</button>
+                </form>
+                <h2 class="text-lg">Columns</h2>
+                <ol class="flex flex-col gap-1">{columns}</ol>
+                <button
+                    class="text-xs link self-start"
+                    hx-delete="{view_route}"
+                    hx-confirm="Delete this view? Its filters and sort will be deleted too; pages are not affected."
+                >delete view</button>
+            </div>
+            "#
+        )
+    }
+}
@@ -0,0 +1,200 @@
+use super::models::{Layout, View, ViewProp};
+use crate::db_ops::DbModel;
+use anyhow::Result;
+use async_trait::async_trait;
+use sqlx::{query, query_as, PgPool};
+
+pub struct GetViewQuery {
+    pub id: i32,
+}
+
+pub struct ListViewQuery {
+    pub collection_id: i32,
+}
+
+struct Qres {
+    id: i32,
+    collection_id: i32,
+    name: String,
+    layout: String,
+}
+
+struct QresProp {
+    view_id: i32,
+    prop_id: i32,
+    visible: bool,
+}
+
+/// `props` should be in the order of their columns.
+fn into_views(rows: Vec<Qres>, props: Vec<QresProp>) -> Result<Vec<View>> {
+    rows.into_iter()
+        .map(|row| {
+            Ok(View {
+                id: row.id,
+                collection_id: row.collection_id,
+                name: row.name,
+                layout: Layout::from_name(&row.layout)?,
+                props: props
+                    .iter()
+                    .filter(|p| p.view_id == row.id)
+                    .map(|p| ViewProp {
+                        prop_id: p.prop_id,
+                        visible: p.visible,
+                    })
+                    .collect(),
+            })
+        })
+        .collect()
+}
+
+#[async_trait]
+impl DbModel<GetViewQuery, ListViewQuery> for View {
+    async fn get(db: &PgPool, query: &GetViewQuery) -> Result<Self> {
+        let row = query_as!(
+            Qres,
+            "select id, collection_id, name, layout from view where id = $1",
+            query.id
+        )
+        .fetch_one(db)
+        .await?;
+        let props = query_as!(
+            QresProp,
+            r#"select view_id, prop_id, visible
+            from view_prop
+            where view_id = $1
+            order by "order""#,
+            query.id
+        )
+        .fetch_all(db)
+        .await?;
+
+        Ok(into_views(vec![row], props)?
+            .pop()
+            .expect("one row in, one view out"))
+    }
+    async fn list(db: &PgPool, query: &ListViewQuery) -> Result<Vec<Self>> {
+        let rows = query_as!(
+            Qres,
+            "select id, collection_id, name, layout
+            from view
+            where collection_id = $1
+            order by id",
+            query.collection_id
+        )
+        .fetch_all(db)
+        .await?;
+        let props = query_as!(
+            QresProp,
+            r#"select vp.view_id, vp.prop_id, vp.visible
+            from view_prop vp
+            join view v on v.id = vp.view_id
+            where v.collection_id = $1
+            order by vp."order""#,
+            query.collection_id
+        )
+        .fetch_all(db)
+        .await?;
+
+        into_views(rows, props)
+    }
+    /// Saves the name, layout, and columns of the view. Its sort is saved
+    /// through [crate::models::ViewSort].
+    async fn save(&self, db: &PgPool) -> Result<()> {
+        let (prop_ids, visible): (Vec<i32>, Vec<bool>) =
+            self.props.iter().map(|vp| (vp.prop_id, vp.visible)).unzip();
+        let mut tx = db.begin().await?;
+        query!(
+            "update view set name = $1, layout = $2 where id = $3",
+            self.name,
+            self.layout.get_name(),
+            self.id
+        )
+        .execute(&mut *tx)
+        .await?;
+        query!("delete from view_prop where view_id = $1", self.id)
+            .execute(&mut *tx)
+            .await?;
+        query!(
+            r#"insert into view_prop (view_id, prop_id, visible, "order")
+            select $1, p.prop_id, p.visible, p.ord
+            from unnest($2::int[], $3::boolean[])
+                with ordinality as p(prop_id, visible, ord)"#,
+            self.id,
+            &prop_ids,
+            &visible
+        )
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+    /// Filters and filter groups of the view are deleted by cascade.
+    async fn delete(self, db: &PgPool) -> Result<()> {
+        query!("delete from view where id = $1", self.id)
+            .execute(db)
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// Create a view, along with its root filter group; see
+/// [crate::filter::db_ops::get_root_group].
+pub async fn create_view(
+    db: &PgPool,
+    collection_id: i32,
+    name: &str,
+) -> Result<i32> {
+    struct Qres {
+        id: i32,
+    }
+    Ok(query_as!(
+        Qres,
+        r#"with new_view as (
+            insert into view (collection_id, name) values ($1, $2)
+            returning id
+        ), root_group as (
+            insert into filter_group (view_id) select id from new_view
+        )
+        select id as "id!" from new_view"#,
+        collection_id,
+        name
+    )
+    .fetch_one(db)
+    .await?
+    .id)
+}
+
+/// The view which a collection opens to; that is, its first view. One is
+/// created, like [create_view] does, if the collection doesn't have any
+/// views yet. Concurrent first loads of a collection may both try to create
+/// it, so the loser's insert does nothing.
+pub async fn get_default_view(db: &PgPool, collection_id: i32) -> Result<View> {
+    struct Qres {
+        id: i32,
+    }
+    query!(
+        "with new_view as (
+            insert into view (collection_id, name)
+            select $1, 'All Pages'
+            where not exists (select 1 from view where collection_id = $1)
+            on conflict (collection_id, name) do nothing
+            returning id
+        )
+        insert into filter_group (view_id) select id from new_view",
+        collection_id
+    )
+    .execute(db)
+    .await?;
+    let id = query_as!(
+        Qres,
+        "select id from view where collection_id = $1 order by id limit 1",
+        collection_id
+    )
+    .fetch_one(db)
+    .await?
+    .id;
+
+    View::get(db, &GetViewQuery { id }).await
+}
@@ -0,0 +1,8 @@
+//! Views are saved ways of looking at the pages of a collection. Each view
+//! has its own filters (see [crate::filter]), sort, columns, and layout, and
+//! [crate::db_ops::list_pages] lists the pages of a view. Every collection
+//! has at least one view; see [db_ops::get_default_view].
+
+pub mod components;
+pub mod db_ops;
+pub mod models;
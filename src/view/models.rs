@@ -0,0 +1,211 @@
+use crate::models::Prop;
+use anyhow::{bail, Result};
+
+/// How the pages of a view are laid out. Stored by name in `view.layout`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layout {
+    /// A row for each page, with a column for each visible prop.
+    Table,
+    /// Just the key and title of each page.
+    List,
+}
+
+impl Layout {
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name {
+            "table" => Ok(Self::Table),
+            "list" => Ok(Self::List),
+            _ => bail!("{name} is not a valid layout"),
+        }
+    }
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Self::Table => "table",
+            Self::List => "list",
+        }
+    }
+    pub fn get_display_name(&self) -> &'static str {
+        match self {
+            Self::Table => "Table",
+            Self::List => "List",
+        }
+    }
+    pub fn all() -> [Self; 2] {
+        [Self::Table, Self::List]
+    }
+}
+
+/// Whether a prop is shown as a column of a view. The position of each
+/// [ViewProp] in [View::props] is the position of its column.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ViewProp {
+    pub prop_id: i32,
+    pub visible: bool,
+}
+
+/// A saved way of looking at the pages of a collection. The view's sort is
+/// a [crate::models::ViewSort], and its filters are in the filter tree of
+/// the view; see [crate::filter::db_ops::get_filter_tree].
+#[derive(Debug, Clone)]
+pub struct View {
+    pub id: i32,
+    pub collection_id: i32,
+    pub name: String,
+    pub layout: Layout,
+    /// Props missing from here are visible, after the others, in the
+    /// collection's prop order; see [View::arrange].
+    pub props: Vec<ViewProp>,
+}
+
+impl View {
+    /// Each of the collection's `props`, in the order of the view's
+    /// columns, and whether it is visible. `props` should be in the
+    /// collection's prop order.
+    pub fn arrange<'a>(&self, props: &'a [Prop]) -> Vec<(&'a Prop, bool)> {
+        let mut arranged: Vec<(&Prop, bool)> = self
+            .props
+            .iter()
+            .filter_map(|vp| {
+                let prop = props.iter().find(|p| p.id == vp.prop_id)?;
+                Some((prop, vp.visible))
+            })
+            .collect();
+        for prop in props {
+            if !self.props.iter().any(|vp| vp.prop_id == prop.id) {
+                arranged.push((prop, true));
+            }
+        }
+
+        arranged
+    }
+    /// The columns of the view; see [View::arrange].
+    pub fn get_visible_props(&self, props: &[Prop]) -> Vec<Prop> {
+        self.arrange(props)
+            .into_iter()
+            .filter(|(_, visible)| *visible)
+            .map(|(prop, _)| prop.clone())
+            .collect()
+    }
+    /// Swap the column of `prop_id` with the one before it, or after it if
+    /// `forward` is true. Nothing happens at either end. Every prop ends up
+    /// in [View::props], so that the order stays put as props are added.
+    pub fn move_prop(&mut self, props: &[Prop], prop_id: i32, forward: bool) {
+        let mut arranged = self.get_view_props(props);
+        if let Some(i) = arranged.iter().position(|vp| vp.prop_id == prop_id) {
+            let other = if forward {
+                i.checked_add(1)
+            } else {
+                i.checked_sub(1)
+            };
+            if let Some(other) = other.filter(|o| *o < arranged.len()) {
+                arranged.swap(i, other);
+            }
+        }
+        self.props = arranged;
+    }
+    /// Show the column of `prop_id` if it is hidden, or hide it if it is
+    /// shown.
+    pub fn toggle_prop(&mut self, props: &[Prop], prop_id: i32) {
+        let mut arranged = self.get_view_props(props);
+        if let Some(vp) = arranged.iter_mut().find(|vp| vp.prop_id == prop_id) {
+            vp.visible = !vp.visible;
+        }
+        self.props = arranged;
+    }
+    fn get_view_props(&self, props: &[Prop]) -> Vec<ViewProp> {
+        self.arrange(props)
+            .into_iter()
+            .map(|(prop, visible)| ViewProp {
+                prop_id: prop.id,
+                visible,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ValueType;
+
+    fn prop(id: i32) -> Prop {
+        Prop {
+            id,
+            type_id: ValueType::Int,
+            collection_id: 1,
+            name: format!("Prop {id}"),
+            order: id as i16,
+            number_format: Default::default(),
+        }
+    }
+
+    fn ids(arranged: &[(&Prop, bool)]) -> Vec<(i32, bool)> {
+        arranged.iter().map(|(p, v)| (p.id, *v)).collect()
+    }
+
+    #[test]
+    fn test_arrange_props() {
+        let props = [prop(1), prop(2), prop(3), prop(4)];
+        let mut view = View {
+            id: 1,
+            collection_id: 1,
+            name: "Mine".into(),
+            layout: Layout::Table,
+            props: vec![],
+        };
+        assert_eq!(
+            ids(&view.arrange(&props)),
+            [(1, true), (2, true), (3, true), (4, true)]
+        );
+
+        // Props which were deleted are skipped, and new ones are added to
+        // the end.
+        view.props = vec![
+            ViewProp {
+                prop_id: 3,
+                visible: true,
+            },
+            ViewProp {
+                prop_id: 9,
+                visible: true,
+            },
+            ViewProp {
+                prop_id: 1,
+                visible: false,
+            },
+        ];
+        assert_eq!(
+            ids(&view.arrange(&props)),
+            [(3, true), (1, false), (2, true), (4, true)]
+        );
+        let visible: Vec<i32> = view
+            .get_visible_props(&props)
+            .iter()
+            .map(|p| p.id)
+            .collect();
+        assert_eq!(visible, [3, 2, 4]);
+
+        view.move_prop(&props, 2, false);
+        assert_eq!(
+            ids(&view.arrange(&props)),
+            [(3, true), (2, true), (1, false), (4, true)]
+        );
+        view.move_prop(&props, 4, true);
+        view.move_prop(&props, 3, false);
+        view.toggle_prop(&props, 1);
+        view.toggle_prop(&props, 4);
+        assert_eq!(
+            ids(&view.arrange(&props)),
+            [(3, true), (2, true), (1, true), (4, false)]
+        );
+        assert_eq!(view.props.len(), 4);
+    }
+
+    #[test]
+    fn test_layout_names() {
+        for layout in Layout::all() {
+            assert_eq!(Layout::from_name(layout.get_name()).unwrap(), layout);
+        }
+        assert!(Layout::from_name("board").is_err());
+    }
+}